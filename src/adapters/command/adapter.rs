@@ -2,21 +2,30 @@
 
 use std::{
     collections::HashMap,
-    io::{BufRead, BufReader, Read},
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::process::CommandExt,
     path::{Path, PathBuf},
     process::{Command, Stdio},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 use serde_json::Value;
 
 use crate::core::{
     adapter::{
-        Adapter, AdapterError, AdapterInfo, HealthStatus, ProgressEvent, ProgressSender, Result,
+        Adapter, AdapterError, AdapterInfo, CancelToken, HealthStatus, ProgressEvent,
+        ProgressSender, Result,
     },
     capabilities::Capabilities,
     config::{AdapterConfig, ConfigField, ConfigFieldType, ConfigSchema, ConfigValue},
-    package::{InstallResult, InstalledPackage, Package, PackageDetail, Update},
+    doctor::Fault,
+    package::{
+        Advisory, InstallResult, InstalledPackage, Package, PackageDetail, ReleaseNote, Severity,
+        Update,
+    },
     privilege::PackageMode,
     profile::Profile,
     repository::Repository,
@@ -25,10 +34,14 @@ use crate::views::manifest::{ManifestApplyReport, ManifestDiff, ManifestEntry};
 
 use super::{
     manifest::{
-        self, CommandManifest, Format, OP_ADD_REPO, OP_APPLY, OP_APPLY_CHECK, OP_APPLY_PRUNE,
-        OP_DEFAULT_CONFIG, OP_INFO, OP_INFO_INSTALLED, OP_INSTALL, OP_LIST, OP_LIST_INSTALLED,
-        OP_LIST_REPOS, OP_LIST_UPDATES, OP_PATHS, OP_REMOVE, OP_REMOVE_REPO, OP_SEARCH,
-        OP_SET_REPO_ENABLED, OP_SYNC, OP_UPDATE, OP_UPDATE_ALL, Op, Setting, SettingKind,
+        self, CommandManifest, Format, OP_ADD_REPO, OP_ADVISORIES, OP_APPLY, OP_APPLY_CHECK,
+        OP_APPLY_PRUNE, OP_CACHE_SIZE, OP_CHANGELOG, OP_CLEAN, OP_CLEAN_ALL, OP_CREATE_PROFILE,
+        OP_DEFAULT_CONFIG, OP_DELETE_PROFILE, OP_DEPENDS, OP_DOWNLOAD, OP_FILES, OP_INFO,
+        OP_INFO_INSTALLED, OP_INSTALL, OP_INSTALL_VERSION, OP_LIST, OP_LIST_INSTALLED,
+        OP_LIST_ORPHANS, OP_LIST_PROFILES, OP_LIST_REPOS, OP_LIST_UPDATES, OP_OWNER, OP_PATHS,
+        OP_PIN, OP_REMOVE, OP_REMOVE_ORPHANS, OP_REMOVE_REPO, OP_REPAIR, OP_REQUIRED_BY, OP_SEARCH,
+        OP_SET_REPO_ENABLED, OP_SWITCH_PROFILE, OP_SYNC, OP_UNPIN, OP_UPDATE, OP_UPDATE_ALL,
+        OP_VERIFY, Op, Setting, SettingKind,
     },
     output, version,
 };
@@ -47,12 +60,15 @@ pub struct CommandAdapter {
     system_program: Option<PathBuf>,
     info: AdapterInfo,
     capabilities: Capabilities,
+    /// Held while a manager that runs alone is changing something, so a
+    /// second change waits for the first instead of tripping over its lock.
+    turn: tokio::sync::Mutex<()>,
 }
 
 impl CommandAdapter {
     /// Build an adapter from a manifest, refusing a manager that is missing or
     /// too old to speak the interface the manifest describes.
-    pub fn new(manifest: CommandManifest, source: Option<PathBuf>) -> Result<Self> {
+    pub fn new(mut manifest: CommandManifest, source: Option<PathBuf>) -> Result<Self> {
         let user_program = which::which(&manifest.detect.command).ok();
 
         // A system mode naming no command of its own is the same binary with
@@ -65,7 +81,7 @@ impl CommandAdapter {
             None => None,
         };
 
-        let program = match (manifest.system_only, &user_program, &system_program) {
+        let program = match (manifest.system_only(), &user_program, &system_program) {
             // Nothing installed for any scope this manager works in.
             (_, None, None) | (true, _, None) => {
                 return Err(AdapterError::PluginError(missing(&manifest)));
@@ -94,6 +110,9 @@ impl CommandAdapter {
             }
         }
 
+        // Which way each operation is run depends on the version found, and
+        // with it what the manager is offered as able to do.
+        manifest.choose_ops(found.as_deref());
         let capabilities =
             scoped_capabilities(&manifest, user_program.is_some(), system_program.is_some());
         let info = AdapterInfo {
@@ -109,6 +128,8 @@ impl CommandAdapter {
             plugin_path: source,
             description: manifest.description.clone(),
             icon: manifest.icon.clone(),
+            deprecations: manifest.deprecations.clone(),
+            inherited: manifest.inherited.clone(),
         };
 
         Ok(Self {
@@ -117,6 +138,7 @@ impl CommandAdapter {
             system_program,
             info,
             capabilities,
+            turn: tokio::sync::Mutex::new(()),
         })
     }
 
@@ -162,7 +184,7 @@ impl CommandAdapter {
     /// goes before the operation's own arguments, and whether it needs
     /// privileges the person running aeris does not have.
     fn invocation(&self, mode: PackageMode) -> Result<(PathBuf, Vec<String>, bool)> {
-        let system = self.manifest.system_only || mode == PackageMode::System;
+        let system = self.manifest.system_only() || mode == PackageMode::System;
 
         if !system {
             let program = self
@@ -193,26 +215,60 @@ impl CommandAdapter {
             .ok_or(AdapterError::NotSupported)
     }
 
+    /// Wait until this manager is free to be changed, for one the manifest
+    /// says runs alone. Any other goes straight ahead.
+    ///
+    /// Only what changes packages waits. Reading is left alone, since a
+    /// manager that locks for an install still lets itself be searched.
+    async fn take_turn(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        if self.manifest.exclusive {
+            Some(self.turn.lock().await)
+        } else {
+            None
+        }
+    }
+
     fn op(&self, name: &str) -> Result<&Op> {
         self.manifest.op(name).ok_or(AdapterError::NotSupported)
     }
 
     /// Run an operation, returning everything it printed.
+    ///
+    /// A run that fails once it has been cancelled failed because it was, and
+    /// says so whatever the manager made of being stopped.
     async fn run(
         &self,
         op_name: &str,
         values: Values,
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         package_id: String,
         mode: PackageMode,
     ) -> Result<Ran> {
+        let cancelled = |progress: Option<ProgressSender>, package_id: String| {
+            if let Some(sender) = progress {
+                let _ = sender.send(ProgressEvent::Cancelled {
+                    adapter_id: self.info.id.clone(),
+                    package_id,
+                });
+            }
+            Err(AdapterError::Cancelled)
+        };
+        if cancel.is_cancelled() {
+            return cancelled(progress, package_id);
+        }
+
         let op = self.op(op_name)?.clone();
         let op_name = op_name.to_string();
         let manifest = self.manifest.clone();
         let (program, before, elevate) = self.invocation(mode)?;
         let adapter_id = self.info.id.clone();
+        // Kept back to report a cancellation with, since the run takes both.
+        let reporting = progress.clone();
+        let reported = package_id.clone();
+        let watching = cancel.clone();
 
-        tokio::task::spawn_blocking(move || {
+        let ran = tokio::task::spawn_blocking(move || {
             let mut args = before;
             args.extend(fill_args(&op, &values)?);
             let context = progress.map(|sender| Progress {
@@ -221,7 +277,7 @@ impl CommandAdapter {
                 package_id,
                 map: op.progress.clone(),
                 format: op.output.format,
-                pattern: op.pattern.clone(),
+                pattern: op.output.pattern.clone(),
             });
 
             let elevate = op.elevate.unwrap_or(elevate);
@@ -233,6 +289,7 @@ impl CommandAdapter {
                     manifest.strip_ansi,
                     manifest.failure_pattern.as_deref(),
                     context.as_ref(),
+                    &watching,
                     elevate,
                 );
             }
@@ -252,10 +309,16 @@ impl CommandAdapter {
                 manifest.strip_ansi,
                 manifest.failure_pattern.as_deref(),
                 context.as_ref(),
+                &watching,
             )
         })
         .await
-        .map_err(|e| AdapterError::Other(format!("could not wait for the run: {e}")))?
+        .map_err(|e| AdapterError::Other(format!("could not wait for the run: {e}")))?;
+
+        match ran {
+            Err(_) if cancel.is_cancelled() => cancelled(reporting, reported),
+            ran => ran,
+        }
     }
 
     /// Fill in what an installed listing left out, for a manager that names
@@ -316,7 +379,16 @@ impl CommandAdapter {
 
     /// Read the records a query operation printed.
     async fn query(&self, op_name: &str, values: Values, mode: PackageMode) -> Result<Vec<Value>> {
-        let ran = self.run(op_name, values, None, String::new(), mode).await?;
+        let ran = self
+            .run(
+                op_name,
+                values,
+                None,
+                &CancelToken::default(),
+                String::new(),
+                mode,
+            )
+            .await?;
 
         // A question that goes unanswered is a failure however the manager
         // exited, and what it complained about says more than the empty
@@ -369,6 +441,63 @@ impl CommandAdapter {
         })
     }
 
+    /// The packages an operation naming one package answers with, such as
+    /// what it needs or what needs it.
+    async fn related(
+        &self,
+        op_name: &str,
+        package_id: &str,
+        mode: PackageMode,
+    ) -> Result<Vec<Package>> {
+        let values = Values::from([
+            ("selector".into(), package_id.to_string()),
+            ("name".into(), package_id.to_string()),
+        ]);
+        let records = self.query(op_name, values, mode).await?;
+        let fields = &self.op(op_name)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| self.to_package(record, fields))
+            .collect())
+    }
+
+    /// Run an operation over each package for its effect alone, failing with
+    /// the first package that did not go.
+    async fn run_each(&self, op_name: &str, packages: &[Package], mode: PackageMode) -> Result<()> {
+        let results = self
+            .run_over(op_name, packages, None, &CancelToken::default(), mode)
+            .await?;
+
+        match results.iter().find(|r| !r.success) {
+            Some(failed) => {
+                Err(AdapterError::Other(failed.error.clone().unwrap_or_else(
+                    || format!("could not {op_name} {}", failed.package_name),
+                )))
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn advisories(&self, mode: PackageMode) -> Result<Vec<Advisory>> {
+        let records = self.query(OP_ADVISORIES, Values::new(), mode).await?;
+        let fields = &self.op(OP_ADVISORIES)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| {
+                Some(Advisory {
+                    id: output::text(record, fields, "id")?,
+                    package_id: output::text(record, fields, "package")?,
+                    fixed_version: output::text(record, fields, "fixed_version"),
+                    severity: output::text(record, fields, "severity")
+                        .as_deref()
+                        .and_then(Severity::parse),
+                })
+            })
+            .collect())
+    }
+
     /// Where the manager keeps its files, read without waiting.
     ///
     /// A frontend needs these while it is still starting up, before there is
@@ -381,6 +510,7 @@ impl CommandAdapter {
             false,
             self.manifest.failure_pattern.as_deref(),
             None,
+            &CancelToken::default(),
             false,
         )?
         .printed;
@@ -417,6 +547,108 @@ impl CommandAdapter {
             .map_err(|e| AdapterError::ParseError(format!("could not read {path}: {e}")))
     }
 
+    /// Change the manager's configuration file where it stands, leaving
+    /// whatever else it holds, comments included, as it was written.
+    async fn edit_config(
+        &self,
+        change: impl FnOnce(&mut toml_edit::DocumentMut) -> Result<()>,
+    ) -> Result<()> {
+        use toml_edit::DocumentMut;
+
+        let path = self.file("config").await?;
+
+        // Only some of what a configuration file holds is described here, so
+        // writing one from nothing would leave out whatever the manager needs
+        // but never offered. Ask it for a whole one first.
+        if !Path::new(&path).exists() && self.op(OP_DEFAULT_CONFIG).is_ok() {
+            self.run(
+                OP_DEFAULT_CONFIG,
+                Values::new(),
+                None,
+                &CancelToken::default(),
+                String::new(),
+                PackageMode::User,
+            )
+            .await?;
+        }
+
+        let mut document = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| text.parse::<DocumentMut>().ok())
+            .unwrap_or_default();
+
+        change(&mut document)?;
+
+        if let Some(parent) = Path::new(&path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AdapterError::Other(e.to_string()))?;
+        }
+
+        std::fs::write(&path, document.to_string()).map_err(|e| AdapterError::Other(e.to_string()))
+    }
+
+    /// The profiles the manager names, by its own operation where it has one
+    /// and from its configuration file where not.
+    async fn named_profiles(&self) -> Result<Vec<Profile>> {
+        if self.op(OP_LIST_PROFILES).is_ok() {
+            let records = self
+                .query(OP_LIST_PROFILES, Values::new(), PackageMode::User)
+                .await?;
+            let fields = &self.op(OP_LIST_PROFILES)?.fields;
+            return Ok(records
+                .iter()
+                .filter_map(|record| {
+                    let name = output::text(record, fields, "name")?;
+                    Some(Profile {
+                        id: name.clone(),
+                        is_active: output::flag(record, fields, "active").unwrap_or(false),
+                        name,
+                        package_count: 0,
+                    })
+                })
+                .collect());
+        }
+
+        let document = self.read_config().await?;
+
+        let Some(profiles) = document.get("profile").and_then(toml::Value::as_table) else {
+            return Ok(Vec::new());
+        };
+
+        let active = document
+            .get("default_profile")
+            .and_then(toml::Value::as_str)
+            .unwrap_or("default");
+
+        Ok(profiles
+            .keys()
+            .map(|name| Profile {
+                id: name.clone(),
+                name: name.clone(),
+                is_active: name == active,
+                package_count: 0,
+            })
+            .collect())
+    }
+
+    /// Run a profile operation on the profile named, where the manifest
+    /// declares one, answering `None` where it leaves it to the file.
+    async fn run_on_profile(&self, op_name: &str, profile_id: &str) -> Option<Result<()>> {
+        self.op(op_name).ok()?;
+        let values = Values::from([("profile".into(), profile_id.to_string())]);
+        Some(
+            self.run(
+                op_name,
+                values,
+                None,
+                &CancelToken::default(),
+                String::new(),
+                PackageMode::User,
+            )
+            .await
+            .map(|_| ()),
+        )
+    }
+
     /// The values an operation's arguments are filled from for one package.
     fn values_for(&self, package: &Package) -> Values {
         Values::from([
@@ -433,9 +665,11 @@ impl CommandAdapter {
         op_name: &str,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<Vec<InstallResult>> {
         let op = self.op(op_name)?;
+        let _turn = self.take_turn().await;
 
         if !takes_a_package(op) {
             // Progress goes to whoever was asked about, or nowhere at all.
@@ -444,7 +678,7 @@ impl CommandAdapter {
                 _ => String::new(),
             };
 
-            self.run(op_name, Values::new(), progress, addressed, mode)
+            self.run(op_name, Values::new(), progress, cancel, addressed, mode)
                 .await?;
             return Ok(Vec::new());
         }
@@ -463,27 +697,35 @@ impl CommandAdapter {
                     op_name,
                     self.values_for(package),
                     progress.clone(),
+                    cancel,
                     package.id.clone(),
                     mode,
                 )
                 .await;
 
+            // A cancelled run has already said so, and once one is cancelled
+            // the rest are too, each without being started.
             if let Some(sender) = &progress {
-                let _ = sender.send(match &outcome {
-                    Ok(_) => ProgressEvent::Completed {
+                let event = match &outcome {
+                    Ok(_) => Some(ProgressEvent::Completed {
                         adapter_id: self.info.id.clone(),
                         package_id: package.id.clone(),
-                    },
-                    Err(e) => ProgressEvent::Failed {
+                    }),
+                    Err(AdapterError::Cancelled) => None,
+                    Err(e) => Some(ProgressEvent::Failed {
                         adapter_id: self.info.id.clone(),
                         package_id: package.id.clone(),
                         error: e.to_string(),
-                    },
-                });
+                    }),
+                };
+                if let Some(event) = event {
+                    let _ = sender.send(event);
+                }
             }
 
             results.push(InstallResult {
                 package_name: package.name.clone(),
+                adapter_id: self.info.id.clone(),
                 package_id: package.id.clone(),
                 version: package.version.clone(),
                 success: outcome.is_ok(),
@@ -549,6 +791,29 @@ impl Adapter for CommandAdapter {
             .filter_map(|extra| Some((extra.label.clone(), output::value(record, &extra.field)?)))
             .collect();
 
+        // Asked separately, so a manager that cannot answer these still
+        // shows everything else about the package.
+        let mut depends = Vec::new();
+        let mut required_by = Vec::new();
+        if self.manifest.op(OP_DEPENDS).is_some() {
+            match self.depends(package_id, PackageMode::User).await {
+                Ok(found) => depends = found,
+                Err(e) => log::warn!(
+                    "{} could not say what {package_id} needs: {e}",
+                    self.info.id
+                ),
+            }
+        }
+        if self.manifest.op(OP_REQUIRED_BY).is_some() {
+            match self.required_by(package_id, PackageMode::User).await {
+                Ok(found) => required_by = found,
+                Err(e) => log::warn!(
+                    "{} could not say what needs {package_id}: {e}",
+                    self.info.id
+                ),
+            }
+        }
+
         Ok(PackageDetail {
             package,
             pkg_type: output::text(record, fields, "pkg_type"),
@@ -556,27 +821,134 @@ impl Adapter for CommandAdapter {
             build_date: output::text(record, fields, "build_date"),
             download_url: output::text(record, fields, "download_url"),
             extra,
+            depends,
+            required_by,
         })
     }
 
+    async fn depends(&self, package_id: &str, mode: PackageMode) -> Result<Vec<Package>> {
+        self.related(OP_DEPENDS, package_id, mode).await
+    }
+
+    async fn required_by(&self, package_id: &str, mode: PackageMode) -> Result<Vec<Package>> {
+        self.related(OP_REQUIRED_BY, package_id, mode).await
+    }
+
+    async fn changelog(
+        &self,
+        package: &Package,
+        from: &str,
+        to: &str,
+        mode: PackageMode,
+    ) -> Result<Vec<ReleaseNote>> {
+        let mut values = self.values_for(package);
+        values.insert("from".into(), from.to_string());
+        values.insert("to".into(), to.to_string());
+        let records = self.query(OP_CHANGELOG, values, mode).await?;
+        let fields = &self.op(OP_CHANGELOG)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| {
+                let version = output::text(record, fields, "version");
+                let text = output::text(record, fields, "text").unwrap_or_default();
+                // A version on its own is a heading, which is worth keeping.
+                (version.is_some() || !text.trim().is_empty()).then(|| ReleaseNote {
+                    version,
+                    date: output::text(record, fields, "date"),
+                    text,
+                })
+            })
+            .collect())
+    }
+
+    async fn download(
+        &self,
+        packages: &[Package],
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<()> {
+        let results = self
+            .run_over(OP_DOWNLOAD, packages, progress, cancel, mode)
+            .await?;
+        match crate::core::package::failure_among(&results) {
+            Some(why) => Err(AdapterError::Other(why)),
+            None => Ok(()),
+        }
+    }
+
+    async fn pin(&self, packages: &[Package], mode: PackageMode) -> Result<()> {
+        self.run_each(OP_PIN, packages, mode).await
+    }
+
+    async fn unpin(&self, packages: &[Package], mode: PackageMode) -> Result<()> {
+        self.run_each(OP_UNPIN, packages, mode).await
+    }
+
+    async fn files(&self, package_id: &str, mode: PackageMode) -> Result<Vec<String>> {
+        let values = Values::from([
+            ("selector".into(), package_id.to_string()),
+            ("name".into(), package_id.to_string()),
+        ]);
+        let records = self.query(OP_FILES, values, mode).await?;
+        let fields = &self.op(OP_FILES)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| output::text(record, fields, "path"))
+            .collect())
+    }
+
+    async fn owner(&self, path: &str, mode: PackageMode) -> Result<Vec<Package>> {
+        let values = Values::from([("path".into(), path.to_string())]);
+        let records = self.query(OP_OWNER, values, mode).await?;
+        let fields = &self.op(OP_OWNER)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| self.to_package(record, fields))
+            .collect())
+    }
+
     async fn install(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        self.run_over(OP_INSTALL, packages, progress, cancel, mode)
+            .await
+    }
+
+    async fn install_version(
+        &self,
+        packages: &[Package],
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<Vec<InstallResult>> {
-        self.run_over(OP_INSTALL, packages, progress, mode).await
+        if !self.capabilities.can_install_version {
+            return Err(AdapterError::NotSupported);
+        }
+        self.run_over(OP_INSTALL_VERSION, packages, progress, cancel, mode)
+            .await
     }
 
     async fn remove(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<()> {
-        let results = self.run_over(OP_REMOVE, packages, progress, mode).await?;
+        let results = self
+            .run_over(OP_REMOVE, packages, progress, cancel, mode)
+            .await?;
 
         match results.iter().find(|r| !r.success) {
+            Some(_) if cancel.is_cancelled() => Err(AdapterError::Cancelled),
             Some(failed) => {
                 Err(AdapterError::Other(failed.error.clone().unwrap_or_else(
                     || format!("could not remove {}", failed.package_name),
@@ -586,18 +958,52 @@ impl Adapter for CommandAdapter {
         }
     }
 
+    async fn remove_orphans(
+        &self,
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<()> {
+        let _turn = self.take_turn().await;
+        self.run(
+            OP_REMOVE_ORPHANS,
+            Values::new(),
+            progress,
+            cancel,
+            String::new(),
+            mode,
+        )
+        .await?;
+        Ok(())
+    }
+
     async fn update(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<Vec<InstallResult>> {
-        self.run_over(OP_UPDATE, packages, progress, mode).await
+        self.run_over(OP_UPDATE, packages, progress, cancel, mode)
+            .await
     }
 
-    async fn update_all(&self, progress: Option<ProgressSender>, mode: PackageMode) -> Result<()> {
-        self.run(OP_UPDATE_ALL, Values::new(), progress, String::new(), mode)
-            .await?;
+    async fn update_all(
+        &self,
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<()> {
+        let _turn = self.take_turn().await;
+        self.run(
+            OP_UPDATE_ALL,
+            Values::new(),
+            progress,
+            cancel,
+            String::new(),
+            mode,
+        )
+        .await?;
         Ok(())
     }
 
@@ -616,7 +1022,7 @@ impl Adapter for CommandAdapter {
                     install_size: output::number(record, fields, "size").unwrap_or(0),
                     install_path: output::text(record, fields, "path"),
                     pinned: output::flag(record, fields, "pinned").unwrap_or(false),
-                    auto_installed: false,
+                    auto_installed: output::flag(record, fields, "auto").unwrap_or(false),
                     is_healthy: output::flag(record, fields, "healthy").unwrap_or(true),
                     profile: output::text(record, fields, "profile"),
                     package,
@@ -629,11 +1035,52 @@ impl Adapter for CommandAdapter {
         Ok(listed)
     }
 
+    async fn verify(&self, mode: PackageMode) -> Result<Vec<Fault>> {
+        let records = self.query(OP_VERIFY, Values::new(), mode).await?;
+        let fields = &self.op(OP_VERIFY)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| {
+                Some(Fault {
+                    package: self.to_package(record, fields)?,
+                    problem: output::text(record, fields, "problem")
+                        .unwrap_or_else(|| "The manager found something wrong".into()),
+                })
+            })
+            .collect())
+    }
+
+    async fn repair(
+        &self,
+        packages: &[Package],
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        self.run_over(OP_REPAIR, packages, progress, cancel, mode)
+            .await
+    }
+
+    async fn list_orphans(&self, mode: PackageMode) -> Result<Vec<Package>> {
+        let records = self.query(OP_LIST_ORPHANS, Values::new(), mode).await?;
+        let fields = &self.op(OP_LIST_ORPHANS)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| {
+                let mut package = self.to_package(record, fields)?;
+                package.installed = true;
+                Some(package)
+            })
+            .collect())
+    }
+
     async fn list_updates(&self, mode: PackageMode) -> Result<Vec<Update>> {
         let records = self.query(OP_LIST_UPDATES, Values::new(), mode).await?;
         let fields = &self.op(OP_LIST_UPDATES)?.fields;
 
-        Ok(records
+        let mut updates = records
             .iter()
             .filter_map(|record| {
                 let package = self.to_package(record, fields)?;
@@ -641,23 +1088,44 @@ impl Adapter for CommandAdapter {
                     .unwrap_or_else(|| package.version.clone());
                 let new_version = output::text(record, fields, "new_version")?;
 
+                let severity = output::text(record, fields, "severity")
+                    .as_deref()
+                    .and_then(Severity::parse);
                 Some(Update {
                     current_version: current,
                     new_version,
                     download_size: output::number(record, fields, "size"),
-                    is_security: false,
-                    changelog_url: None,
+                    // A grade is only given to a security fix, so one implies
+                    // the other for a manager that reports only the grade.
+                    is_security: output::flag(record, fields, "is_security")
+                        .unwrap_or(severity.is_some()),
+                    severity,
+                    advisories: Vec::new(),
+                    changelog_url: output::text(record, fields, "changelog_url"),
                     package,
                 })
             })
-            .collect())
+            .collect::<Vec<_>>();
+
+        if self.manifest.op(OP_ADVISORIES).is_some() {
+            // The updates stand on their own; without the advisories they are
+            // only less well described.
+            match self.advisories(mode).await {
+                Ok(advisories) => join_advisories(&mut updates, advisories),
+                Err(e) => log::warn!("{} could not list its advisories: {e}", self.info.id),
+            }
+        }
+
+        Ok(updates)
     }
 
     async fn sync(&self, progress: Option<ProgressSender>) -> Result<()> {
+        let _turn = self.take_turn().await;
         self.run(
             OP_SYNC,
             Values::new(),
             progress,
+            &CancelToken::default(),
             String::new(),
             PackageMode::User,
         )
@@ -683,20 +1151,65 @@ impl Adapter for CommandAdapter {
             message: format!("{} {}", self.manifest.detect.command, self.info.version),
             package_count: count,
             repo_count: None,
-            cache_size: None,
+            cache_size: if self.capabilities.has_cache_size {
+                self.cache_size().await.ok()
+            } else {
+                None
+            },
         })
     }
 
-    async fn list_repositories(&self) -> Result<Vec<Repository>> {
-        let records = self
-            .query(OP_LIST_REPOS, Values::new(), PackageMode::User)
-            .await?;
-        let fields = &self.op(OP_LIST_REPOS)?.fields;
+    async fn cache_size(&self) -> Result<u64> {
+        if self.op(OP_CACHE_SIZE).is_ok() {
+            let records = self
+                .query(OP_CACHE_SIZE, Values::new(), PackageMode::User)
+                .await?;
+            let fields = &self.op(OP_CACHE_SIZE)?.fields;
+            return records
+                .first()
+                .and_then(|record| output::number(record, fields, "size"))
+                .ok_or_else(|| AdapterError::ParseError("the manager reported no size".into()));
+        }
 
-        Ok(records
-            .iter()
-            .filter_map(|record| {
-                Some(Repository {
+        // Walking a large cache takes a while, and would hold up whatever
+        // else this thread is running meanwhile.
+        let cache = self.file("cache").await?;
+        tokio::task::spawn_blocking(move || crate::core::cache::size_of(Path::new(&cache)))
+            .await
+            .map_err(|e| AdapterError::Other(e.to_string()))
+    }
+
+    async fn clean_cache(&self, all: bool, progress: Option<ProgressSender>) -> Result<()> {
+        let op_name = if all { OP_CLEAN_ALL } else { OP_CLEAN };
+        // A manager that works only for everyone keeps its cache there too.
+        let mode = if self.capabilities.works_in(PackageMode::User) {
+            PackageMode::User
+        } else {
+            PackageMode::System
+        };
+        let _turn = self.take_turn().await;
+        self.run(
+            op_name,
+            Values::new(),
+            progress,
+            &CancelToken::default(),
+            String::new(),
+            mode,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn list_repositories(&self) -> Result<Vec<Repository>> {
+        let records = self
+            .query(OP_LIST_REPOS, Values::new(), PackageMode::User)
+            .await?;
+        let fields = &self.op(OP_LIST_REPOS)?.fields;
+
+        Ok(records
+            .iter()
+            .filter_map(|record| {
+                Some(Repository {
                     name: output::text(record, fields, "name")?,
                     url: output::text(record, fields, "url").unwrap_or_default(),
                     enabled: output::flag(record, fields, "enabled").unwrap_or(true),
@@ -711,10 +1224,12 @@ impl Adapter for CommandAdapter {
             ("name".into(), repo.name.clone()),
             ("url".into(), repo.url.clone()),
         ]);
+        let _turn = self.take_turn().await;
         self.run(
             OP_ADD_REPO,
             values,
             None,
+            &CancelToken::default(),
             repo.name.clone(),
             PackageMode::User,
         )
@@ -725,10 +1240,12 @@ impl Adapter for CommandAdapter {
 
     async fn remove_repository(&self, repo_name: &str) -> Result<()> {
         let values = Values::from([("name".into(), repo_name.to_string())]);
+        let _turn = self.take_turn().await;
         self.run(
             OP_REMOVE_REPO,
             values,
             None,
+            &CancelToken::default(),
             repo_name.to_string(),
             PackageMode::User,
         )
@@ -742,8 +1259,16 @@ impl Adapter for CommandAdapter {
             ("name".into(), name.to_string()),
             ("enabled".into(), enabled.to_string()),
         ]);
-        self.run(OP_SET_REPO_ENABLED, values, None, name.to_string(), mode)
-            .await?;
+        let _turn = self.take_turn().await;
+        self.run(
+            OP_SET_REPO_ENABLED,
+            values,
+            None,
+            &CancelToken::default(),
+            name.to_string(),
+            mode,
+        )
+        .await?;
 
         Ok(())
     }
@@ -817,66 +1342,142 @@ impl Adapter for CommandAdapter {
     }
 
     async fn set_config(&self, config: &AdapterConfig) -> Result<()> {
-        use toml_edit::DocumentMut;
-
-        let path = self.file("config").await?;
+        self.edit_config(|document| {
+            // Only what the manifest declares, so a setting soar has and this
+            // frontend does not know about is left as the user wrote it.
+            for setting in &self.manifest.config {
+                let Some(value) = config.values.get(&setting.key) else {
+                    continue;
+                };
+                write_setting(document, &setting.key, value);
+            }
+            Ok(())
+        })
+        .await
+    }
 
-        // Only some of what a configuration file holds is described here, so
-        // writing one from nothing would leave out whatever the manager needs
-        // but never offered. Ask it for a whole one first.
-        if !Path::new(&path).exists() && self.op(OP_DEFAULT_CONFIG).is_ok() {
-            self.run(
-                OP_DEFAULT_CONFIG,
-                Values::new(),
-                None,
-                String::new(),
-                PackageMode::User,
-            )
-            .await?;
+    async fn list_profiles(&self) -> Result<Vec<Profile>> {
+        let mut profiles = self.named_profiles().await?;
+
+        // Counted from what is installed, since no manager keeps a tally. A
+        // listing that fails costs the counts and not the profiles.
+        let caps = self.capabilities;
+        if caps.can_list && !profiles.is_empty() {
+            let mode = if caps.works_in(PackageMode::User) {
+                PackageMode::User
+            } else {
+                PackageMode::System
+            };
+            match self.list_installed(mode).await {
+                Ok(installed) => {
+                    for held in &installed {
+                        let Some(ref in_profile) = held.profile else {
+                            continue;
+                        };
+                        if let Some(profile) = profiles.iter_mut().find(|p| &p.id == in_profile) {
+                            profile.package_count += 1;
+                        }
+                    }
+                }
+                Err(e) => log::warn!("{} could not count its profiles: {e}", self.info.id),
+            }
         }
 
-        let mut document = std::fs::read_to_string(&path)
-            .ok()
-            .and_then(|text| text.parse::<DocumentMut>().ok())
-            .unwrap_or_default();
+        Ok(profiles)
+    }
 
-        // Only what the manifest declares, so a setting soar has and this
-        // frontend does not know about is left as the user wrote it.
-        for setting in &self.manifest.config {
-            let Some(value) = config.values.get(&setting.key) else {
-                continue;
-            };
-            write_setting(&mut document, &setting.key, value);
-        }
+    async fn active_profile(&self) -> Result<Profile> {
+        self.list_profiles()
+            .await?
+            .into_iter()
+            .find(|p| p.is_active)
+            .ok_or_else(|| AdapterError::Other("no profile is active".into()))
+    }
 
-        if let Some(parent) = Path::new(&path).parent() {
-            std::fs::create_dir_all(parent).map_err(|e| AdapterError::Other(e.to_string()))?;
+    async fn switch_profile(&self, profile_id: &str) -> Result<()> {
+        if let Some(ran) = self.run_on_profile(OP_SWITCH_PROFILE, profile_id).await {
+            return ran;
+        }
+        if !self.manifest.profiles_in_config {
+            return Err(AdapterError::NotSupported);
         }
 
-        std::fs::write(&path, document.to_string()).map_err(|e| AdapterError::Other(e.to_string()))
+        self.edit_config(|document| {
+            if !has_profile(document, profile_id) {
+                return Err(AdapterError::Other(format!(
+                    "there is no profile named {profile_id}"
+                )));
+            }
+            document["default_profile"] = toml_edit::value(profile_id);
+            Ok(())
+        })
+        .await
     }
 
-    async fn list_profiles(&self) -> Result<Vec<Profile>> {
-        let document = self.read_config().await?;
+    async fn create_profile(&self, profile_id: &str) -> Result<()> {
+        let profile_id = profile_id.trim();
+        if profile_id.is_empty() {
+            return Err(AdapterError::Other("a profile needs a name".into()));
+        }
+        if let Some(ran) = self.run_on_profile(OP_CREATE_PROFILE, profile_id).await {
+            return ran;
+        }
+        if !self.manifest.profiles_in_config {
+            return Err(AdapterError::NotSupported);
+        }
 
-        let Some(profiles) = document.get("profile").and_then(toml::Value::as_table) else {
-            return Ok(Vec::new());
-        };
+        self.edit_config(|document| {
+            if has_profile(document, profile_id) {
+                return Err(AdapterError::Other(format!(
+                    "there is already a profile named {profile_id}"
+                )));
+            }
+            let profiles = document
+                .entry("profile")
+                .or_insert_with(|| {
+                    let mut table = toml_edit::Table::new();
+                    // Written as `[profile.<name>]` alone, the way a person
+                    // writes them, rather than with an empty `[profile]`.
+                    table.set_implicit(true);
+                    toml_edit::Item::Table(table)
+                })
+                .as_table_mut()
+                .ok_or_else(|| AdapterError::Other("profile is not a table".into()))?;
+            profiles.insert(profile_id, toml_edit::Item::Table(toml_edit::Table::new()));
+            Ok(())
+        })
+        .await
+    }
 
-        let active = document
-            .get("default_profile")
-            .and_then(toml::Value::as_str)
-            .unwrap_or("default");
+    async fn delete_profile(&self, profile_id: &str) -> Result<()> {
+        if self.manifest.profiles_in_config || self.op(OP_DELETE_PROFILE).is_ok() {
+            let active = self.named_profiles().await?;
+            if active.iter().any(|p| p.id == profile_id && p.is_active) {
+                return Err(AdapterError::Other(format!(
+                    "{profile_id} is the active profile; switch to another first"
+                )));
+            }
+        }
+        if let Some(ran) = self.run_on_profile(OP_DELETE_PROFILE, profile_id).await {
+            return ran;
+        }
+        if !self.manifest.profiles_in_config {
+            return Err(AdapterError::NotSupported);
+        }
 
-        Ok(profiles
-            .keys()
-            .map(|name| Profile {
-                id: name.clone(),
-                name: name.clone(),
-                is_active: name == active,
-                package_count: 0,
-            })
-            .collect())
+        self.edit_config(|document| {
+            let removed = document
+                .get_mut("profile")
+                .and_then(toml_edit::Item::as_table_mut)
+                .and_then(|profiles| profiles.remove(profile_id));
+            match removed {
+                Some(_) => Ok(()),
+                None => Err(AdapterError::Other(format!(
+                    "there is no profile named {profile_id}"
+                ))),
+            }
+        })
+        .await
     }
 
     async fn declarative_diff(&self) -> Result<ManifestDiff> {
@@ -903,11 +1504,13 @@ impl Adapter for CommandAdapter {
         progress: Option<ProgressSender>,
     ) -> Result<ManifestApplyReport> {
         let op_name = if prune { OP_APPLY_PRUNE } else { OP_APPLY };
+        let _turn = self.take_turn().await;
         let printed = self
             .run(
                 op_name,
                 Values::new(),
                 progress,
+                &CancelToken::default(),
                 String::new(),
                 PackageMode::User,
             )
@@ -958,6 +1561,14 @@ fn to_value(value: &toml::Value) -> Option<ConfigValue> {
     }
 }
 
+/// Whether a configuration file has a `[profile.<name>]` table by this name.
+fn has_profile(document: &toml_edit::DocumentMut, profile_id: &str) -> bool {
+    document
+        .get("profile")
+        .and_then(toml_edit::Item::as_table)
+        .is_some_and(|profiles| profiles.contains_key(profile_id))
+}
+
 fn write_setting(document: &mut toml_edit::DocumentMut, key: &str, value: &ConfigValue) {
     match value {
         ConfigValue::Bool(v) => document[key] = toml_edit::value(*v),
@@ -1080,12 +1691,24 @@ fn scoped_capabilities(
     Capabilities {
         can_search: has(OP_SEARCH),
         can_install: has(OP_INSTALL),
+        // One that does not name the version installs whatever is newest,
+        // which is the opposite of going back.
+        can_install_version: manifest
+            .op(OP_INSTALL_VERSION)
+            .is_some_and(|op| op.args.iter().any(|arg| arg.contains("{version}"))),
         can_remove: has(OP_REMOVE),
+        can_list_orphans: has(OP_LIST_ORPHANS),
+        // Removing them in one go without being able to say beforehand
+        // which they are would be asking for a removal sight unseen.
+        can_remove_orphans: has(OP_REMOVE_ORPHANS) && has(OP_LIST_ORPHANS),
         can_update: has(OP_UPDATE) || has(OP_UPDATE_ALL),
         can_update_one: manifest.op(OP_UPDATE).is_some_and(takes_a_package),
         can_update_all: has(OP_UPDATE_ALL),
         can_list: has(OP_LIST) || has(OP_LIST_INSTALLED),
         can_list_updates: has(OP_LIST_UPDATES),
+        can_download: has(OP_DOWNLOAD),
+        // Pinning with no way back would strand a package for good.
+        can_pin: has(OP_PIN) && has(OP_UNPIN),
         can_sync: has(OP_SYNC),
         // Running a package means finding the commands it put on the path, so
         // a manager has to say both where the package went and where it links
@@ -1097,17 +1720,35 @@ fn scoped_capabilities(
         can_add_repo: has(OP_ADD_REPO),
         can_remove_repo: has(OP_REMOVE_REPO),
         can_list_repos: has(OP_LIST_REPOS),
+        has_profiles: has(OP_LIST_PROFILES) || manifest.profiles_in_config,
+        // Making one and doing away with one each need an operation of their
+        // own or the file to write, and offering only half would strand what
+        // was made.
+        can_manage_profiles: manifest.profiles_in_config
+            || (has(OP_CREATE_PROFILE) && has(OP_DELETE_PROFILE)),
         supports_declarative: has(OP_APPLY),
         has_package_detail: has(OP_INFO),
+        has_dependencies: has(OP_DEPENDS) || has(OP_REQUIRED_BY),
+        has_changelog: has(OP_CHANGELOG),
+        can_list_files: has(OP_FILES),
+        can_find_owner: has(OP_OWNER),
+        can_verify: has(OP_VERIFY),
+        can_repair: has(OP_REPAIR),
+        has_cache_size: has(OP_CACHE_SIZE)
+            || manifest
+                .op(OP_PATHS)
+                .is_some_and(|op| op.fields.contains_key("cache")),
+        can_clean_cache: has(OP_CLEAN),
+        can_clean_whole_cache: has(OP_CLEAN_ALL),
         has_size_info: manifest
             .ops
             .values()
             .any(|op| op.fields.contains_key("size")),
         // A scope is offered only when this manager works that way and the
         // binary it works with is here.
-        supports_user_packages: !manifest.system_only && user_available,
+        supports_user_packages: !manifest.system_only() && user_available,
         supports_system_packages: manifest.system.is_some() && system_available,
-        ..Default::default()
+        exclusive: manifest.exclusive,
     }
 }
 
@@ -1271,6 +1912,7 @@ fn run_on_terminal(
     strip_ansi: bool,
     failure: Option<&str>,
     progress: Option<&Progress>,
+    cancel: &CancelToken,
 ) -> Result<Ran> {
     let pty = portable_pty::native_pty_system()
         .openpty(portable_pty::PtySize {
@@ -1298,6 +1940,12 @@ fn run_on_terminal(
     // finished: our own copy would hold the terminal open past its exit.
     drop(pty.slave);
 
+    // A manager on a terminal of its own leads a session of its own, so its
+    // process group is everything it started.
+    let _watch = child
+        .process_id()
+        .map(|group| watch(cancel, move |over| stop_group(group, over)));
+
     let mut reader = pty
         .master
         .try_clone_reader()
@@ -1438,6 +2086,7 @@ fn run(
     strip_ansi: bool,
     failure: Option<&str>,
     progress: Option<&Progress>,
+    cancel: &CancelToken,
     elevate: bool,
 ) -> Result<Ran> {
     let mut base = Command::new(program);
@@ -1461,13 +2110,19 @@ fn run(
         elevator = asked;
     }
 
+    // In a group of its own, so that stopping it reaches whatever it started
+    // and nothing else.
     let mut child = base
+        .process_group(0)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| AdapterError::Other(format!("could not run {}: {e}", program.display())))?;
 
+    let group = child.id();
+    let _watch = watch(cancel, move |over| stop_group(group, over));
+
     // Drained on its own thread: a manager writing more diagnostics than the
     // pipe holds would otherwise block while we are still reading stdout.
     let mut errors = child.stderr.take();
@@ -1499,6 +2154,14 @@ fn run(
 
             printed.push_str(&line);
             printed.push('\n');
+
+            // sudo hands a signal on to what it raised, but pkexec and doas
+            // become it, and nobody may signal root. Nobody reading is what
+            // reaches one of those: its next line is written to a closed
+            // pipe, which ends it.
+            if cancel.is_cancelled() {
+                break;
+            }
         }
     }
 
@@ -1544,6 +2207,71 @@ fn run(
     })
 }
 
+/// How often a run looks to see whether it has been cancelled.
+const CANCEL_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How long a manager asked to stop has to do so before it is made to.
+const STOP_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Marks the run it watches as over once it goes out of scope.
+struct Watch(Arc<AtomicBool>);
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Watch a run for as long as it lasts, and stop it if it is cancelled.
+///
+/// The run itself is blocked on the manager, reading what it writes, and
+/// cannot look up to check. `stop` is handed whether the run has ended, so
+/// it can tell when it no longer has anything to stop.
+fn watch(cancel: &CancelToken, stop: impl FnOnce(&AtomicBool) + Send + 'static) -> Watch {
+    let over = Arc::new(AtomicBool::new(false));
+    let watching = over.clone();
+    let cancel = cancel.clone();
+
+    std::thread::spawn(move || {
+        while !watching.load(Ordering::SeqCst) {
+            if cancel.is_cancelled() {
+                stop(&watching);
+                return;
+            }
+            std::thread::sleep(CANCEL_POLL);
+        }
+    });
+
+    Watch(over)
+}
+
+/// Ask a process group to stop, and make it once it has had the chance.
+fn stop_group(group: u32, over: &AtomicBool) {
+    signal_group(group, "TERM");
+
+    let asked = std::time::Instant::now();
+    while !over.load(Ordering::SeqCst) && asked.elapsed() < STOP_GRACE {
+        std::thread::sleep(CANCEL_POLL);
+    }
+    if !over.load(Ordering::SeqCst) {
+        signal_group(group, "KILL");
+    }
+}
+
+/// Signal every process in a group. The standard library can only signal
+/// the one child it started, and a manager downloading through curl would
+/// leave curl running.
+fn signal_group(group: u32, signal: &str) {
+    let _ = Command::new("kill")
+        .arg(format!("-{signal}"))
+        .arg("--")
+        .arg(format!("-{group}"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
 /// Keeps the tail of what a failed run complained about.
 fn last_lines(text: &str) -> String {
     // A manager that prints a stack trace puts it after the thing that went
@@ -1661,9 +2389,14 @@ impl<'a> Reporter<'a> {
 
                 Some(Value::Object(record))
             }
-            // Neither is whole until the run ends, so they say nothing while
-            // the work is still going.
-            Format::Json | Format::KeyValue => None,
+            // None is whole until the run ends, so they say nothing while the
+            // work is still going.
+            Format::Json
+            | Format::KeyValue
+            | Format::KeyValueBlocks
+            | Format::Columns
+            | Format::Csv
+            | Format::Tsv => None,
         }
     }
 
@@ -1721,6 +2454,39 @@ impl<'a> Reporter<'a> {
     }
 }
 
+/// Mark each update that fixes an advisory as a security fix, graded by the
+/// worst advisory it fixes.
+///
+/// An update fixes an advisory when it reaches the fixed version from below
+/// it. One already past the fixed version was never affected, and one that
+/// stops short leaves the problem where it was.
+fn join_advisories(updates: &mut [Update], advisories: Vec<Advisory>) {
+    for update in updates.iter_mut() {
+        let fixed: Vec<&Advisory> = advisories
+            .iter()
+            .filter(|advisory| advisory.package_id == update.package.id)
+            .filter(|advisory| match advisory.fixed_version.as_deref() {
+                Some(fixed) => {
+                    version::at_least(&update.new_version, fixed)
+                        && !version::at_least(&update.current_version, fixed)
+                }
+                None => true,
+            })
+            .collect();
+        if fixed.is_empty() {
+            continue;
+        }
+
+        update.is_security = true;
+        update.severity = fixed
+            .iter()
+            .filter_map(|advisory| advisory.severity)
+            .chain(update.severity)
+            .max();
+        update.advisories.extend(fixed.into_iter().cloned());
+    }
+}
+
 fn detect_version(program: &Path, manifest: &CommandManifest) -> Option<String> {
     if manifest.detect.version.is_empty() {
         return None;
@@ -1792,11 +2558,14 @@ progress = { event = "type", current = "current", total = "total", message = "pk
                 plugin_path: None,
                 description: String::new(),
                 icon: None,
+                deprecations: Vec::new(),
+                inherited: Vec::new(),
             },
             manifest: Arc::new(manifest),
             user_program: Some(PathBuf::from("demo")),
             system_program: None,
             capabilities,
+            turn: tokio::sync::Mutex::new(()),
         }
     }
 
@@ -1810,6 +2579,45 @@ progress = { event = "type", current = "current", total = "total", message = "pk
         assert!(capabilities.has_size_info);
     }
 
+    #[test]
+    fn pinning_is_only_offered_with_a_way_to_unpin() {
+        let pin_only = manifest(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "demo"
+
+[ops.pin]
+args = ["hold", "{selector}"]
+output = { format = "lines" }
+"#,
+        );
+        assert!(!capabilities_from(&pin_only).can_pin);
+
+        let both = manifest(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "demo"
+
+[ops.pin]
+args = ["hold", "{selector}"]
+output = { format = "lines" }
+
+[ops.unpin]
+args = ["unhold", "{selector}"]
+output = { format = "lines" }
+"#,
+        );
+        assert!(capabilities_from(&both).can_pin);
+    }
+
     #[test]
     fn naming_a_package_is_what_makes_an_update_about_one() {
         let one = manifest(
@@ -1852,6 +2660,43 @@ output = { format = "lines" }
         assert!(caps.can_update_all);
     }
 
+    #[test]
+    fn installing_a_chosen_version_needs_the_version_named() {
+        let named = manifest(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "demo"
+
+[ops.install_version]
+args = ["install", "{selector}={version}"]
+output = { format = "lines" }
+"#,
+        );
+        assert!(capabilities_from(&named).can_install_version);
+
+        // Whatever is newest, which is no way back at all.
+        let unnamed = manifest(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "demo"
+
+[ops.install_version]
+args = ["install", "{selector}"]
+output = { format = "lines" }
+"#,
+        );
+        assert!(!capabilities_from(&unnamed).can_install_version);
+        assert!(!capabilities_from(&manifest(DEMO)).can_install_version);
+    }
+
     #[test]
     fn running_needs_both_a_bin_directory_and_a_package_path() {
         let both = manifest(
@@ -2203,6 +3048,65 @@ case "$1" in
     echo "description: about $2"
     echo "size: 1.5 MB"
     ;;
+  files)
+    echo "/usr/bin/$2"
+    echo "/usr/share/man/man1/$2.1"
+    ;;
+  owner)
+    basename "$2"
+    ;;
+  depends)
+    echo "openssl >=3"
+    echo "zlib"
+    ;;
+  required-by)
+    echo "curl"
+    echo "git"
+    ;;
+  updates)
+    echo "curl 8.4.0 8.5.0"
+    echo "jq 1.6 1.7"
+    echo "zlib 1.3 1.3.1 high"
+    ;;
+  changelog)
+    echo "{\"version\":\"$4\",\"date\":\"2024-05-01\",\"text\":\"$2 is faster\"}"
+    echo "{\"version\":\"$3\"}"
+    echo "{\"text\":\"\"}"
+    ;;
+  advisories)
+    echo "CVE-2023-38545 curl 8.4.0 critical"
+    echo "CVE-2023-46218 curl 8.5.0 medium"
+    echo "CVE-2024-0001 curl 9.0.0 high"
+    ;;
+  cache-paths)
+    printf '{"cache":"%s.cache"}\n' "$0"
+    ;;
+  cache-size)
+    echo '{"size":"2 KiB"}'
+    ;;
+  verify)
+    echo "curl: /usr/bin/curl does not match its checksum"
+    echo "jq: /usr/share/man/man1/jq.1 is missing"
+    ;;
+  installed-auto)
+    echo '{"items":[{"name":"fd","auto":false},{"name":"libfoo","auto":true},{"name":"libbar","auto":true}]}'
+    ;;
+  orphans)
+    echo "libfoo"
+    echo "libbar"
+    ;;
+  remove-one)
+    echo "$2" >> "$0.removed"
+    ;;
+  remove-orphans)
+    echo "every orphan" >> "$0.removed"
+    ;;
+  installed-profiles)
+    echo "fd default"
+    echo "rg default"
+    echo "bat work"
+    echo "jq"
+    ;;
   says-no)
     # Complains and exits as though all was well, the way some managers do.
     echo "SKULL ERROR: \"$2\" is not in the database"
@@ -2224,6 +3128,13 @@ case "$1" in
     echo "it went wrong" >&2
     exit 1
     ;;
+  lingers)
+    # Hands the work to a child and waits on it, so stopping the manager
+    # alone would leave the child running.
+    sleep 30 &
+    echo "started $2"
+    wait
+    ;;
 esac
 "#,
         )
@@ -2322,6 +3233,35 @@ fields = {{ config = "config", packages_config = "packages_config" }}
         assert!(err.to_string().contains("1.2.0"), "{err}");
     }
 
+    #[test]
+    fn each_operation_is_run_the_way_the_version_found_takes_it() {
+        let program = fake_manager("versioned");
+        let text = manifest_for(&program, "1.0.0")
+            + r#"
+[[ops.update]]
+when_version = "<1.0"
+args = ["refresh", "{selector}"]
+output = { format = "lines" }
+
+[[ops.update]]
+when_version = ">=1.0, <2.0"
+args = ["upgrade", "{selector}"]
+output = { format = "lines" }
+
+[[ops.list_updates]]
+when_version = ">=2.0"
+args = ["outdated"]
+output = { format = "json" }
+"#;
+        let adapter = CommandAdapter::new(manifest(&text), None).expect("should accept");
+
+        let update = adapter.manifest.op(OP_UPDATE).expect("1.2.0 has an update");
+        assert_eq!(update.args[0], "upgrade");
+        // Only from 2.0 can it say what has updates, and it is 1.2.0.
+        assert!(!adapter.capabilities().can_list_updates);
+        assert!(adapter.capabilities().can_update_one);
+    }
+
     #[test]
     fn a_manager_is_searched_and_installed_through_its_manifest() {
         let program = fake_manager("whole-path");
@@ -2337,8 +3277,13 @@ fields = {{ config = "config", packages_config = "packages_config" }}
         assert_eq!(found[0].size, Some(10));
 
         let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
-        let results = block_on(adapter.install(&found, Some(sender), PackageMode::User))
-            .expect("should install");
+        let results = block_on(adapter.install(
+            &found,
+            Some(sender),
+            &CancelToken::default(),
+            PackageMode::User,
+        ))
+        .expect("should install");
         assert!(results[0].success, "{:?}", results[0].error);
 
         let mut reported = Vec::new();
@@ -2585,6 +3530,7 @@ fields = {{ config = "config", packages_config = "packages_config" }}
             OP_LIST_UPDATES,
             Values::new(),
             None,
+            &CancelToken::default(),
             String::new(),
             PackageMode::System,
         )) else {
@@ -2606,6 +3552,7 @@ fields = {{ config = "config", packages_config = "packages_config" }}
             OP_LIST_UPDATES,
             Values::new(),
             None,
+            &CancelToken::default(),
             String::new(),
             PackageMode::System,
         ))
@@ -2659,12 +3606,12 @@ fields = {{ config = "config", packages_config = "packages_config" }}
         // `quiet` is not a case the fake handles, so it says nothing at all.
         let op = adapter.manifest.op(OP_SEARCH).unwrap().clone();
         let mut manifest = (*adapter.manifest).clone();
-        manifest.ops.insert(
+        manifest.variants.insert(
             OP_SEARCH.to_string(),
-            Op {
+            vec![Op {
                 args: vec!["quiet".into()],
                 ..op
-            },
+            }],
         );
         let adapter = CommandAdapter::new(manifest, None).expect("should accept");
 
@@ -2674,6 +3621,37 @@ fields = {{ config = "config", packages_config = "packages_config" }}
         assert!(err.to_string().contains("answered nothing"), "{err}");
     }
 
+    #[test]
+    fn a_cancelled_run_stops_reading_so_a_manager_out_of_reach_still_ends() {
+        // Shrugging off the signal stands in for being root, where it would
+        // not arrive at all.
+        let args: Vec<String> = [
+            "-c",
+            "trap '' TERM; while :; do echo working; sleep 0.1; done",
+        ]
+        .map(String::from)
+        .to_vec();
+        let cancel = CancelToken::default();
+        let cancelling = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            cancelling.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let ran = run(
+            Path::new("/bin/sh"),
+            &args,
+            false,
+            None,
+            None,
+            &cancel,
+            false,
+        );
+        assert!(ran.is_err());
+        assert!(started.elapsed() < STOP_GRACE, "{:?}", started.elapsed());
+    }
+
     #[test]
     fn an_operation_that_needs_a_terminal_is_given_one() {
         let program = fake_manager("terminal");
@@ -2681,9 +3659,21 @@ fields = {{ config = "config", packages_config = "packages_config" }}
 
         // That it fails on a pipe is what makes the terminal the thing under
         // test rather than an ornament.
-        assert!(run(&program, &args, false, None, None, false).is_err());
+        assert!(
+            run(
+                &program,
+                &args,
+                false,
+                None,
+                None,
+                &CancelToken::default(),
+                false
+            )
+            .is_err()
+        );
 
-        let ran = run_on_terminal(&program, &args, false, None, None).expect("should answer");
+        let ran = run_on_terminal(&program, &args, false, None, None, &CancelToken::default())
+            .expect("should answer");
         assert!(
             ran.printed.contains("answered on a terminal"),
             "{}",
@@ -2733,6 +3723,181 @@ fields = {{ name = "name", version = "version", description = "description", siz
         }
     }
 
+    #[test]
+    fn what_a_package_needs_and_what_needs_it_are_read_as_packages() {
+        let program = fake_manager("dependencies");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+selector = ["{{name}}"]
+
+[detect]
+command = "{}"
+
+[ops.depends]
+args = ["depends", "{{selector}}"]
+output = {{ format = "lines" }}
+pattern = "^(?P<name>\\S+)(?: (?P<version>\\S+))?$"
+fields = {{ name = "name", version = "version" }}
+
+[ops.required_by]
+args = ["required-by", "{{selector}}"]
+output = {{ format = "lines" }}
+pattern = "^(?P<name>\\S+)$"
+fields = {{ name = "name" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+        assert!(adapter.capabilities().has_dependencies);
+        assert!(!adapter.capabilities().has_package_detail);
+
+        let needs = block_on(adapter.depends("curl", PackageMode::User)).expect("should answer");
+        let named: Vec<(&str, &str)> = needs
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str()))
+            .collect();
+        assert_eq!(named, [("openssl", ">=3"), ("zlib", "")]);
+        assert!(needs.iter().all(|p| p.adapter_id == "demo"));
+
+        let needed_by =
+            block_on(adapter.required_by("openssl", PackageMode::User)).expect("should answer");
+        assert_eq!(needed_by.len(), 2);
+        assert_eq!(needed_by[1].id, "git");
+    }
+
+    #[test]
+    fn release_notes_are_read_between_the_versions_named() {
+        let program = fake_manager("changelog");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "{}"
+
+[ops.changelog]
+args = ["changelog", "{{selector}}", "{{from}}", "{{to}}"]
+output = {{ format = "ndjson" }}
+fields = {{ version = "version", date = "date", text = "text" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+        assert!(adapter.capabilities().has_changelog);
+
+        let fd = Package::named("demo", "fd", "fd", "9.0");
+        let notes = block_on(adapter.changelog(&fd, "9.0", "10.0", PackageMode::User))
+            .expect("should answer");
+
+        // A version with nothing under it still heads the notes; a record
+        // with neither says nothing worth a line.
+        assert_eq!(
+            notes,
+            [
+                ReleaseNote {
+                    version: Some("10.0".into()),
+                    date: Some("2024-05-01".into()),
+                    text: "fd is faster".into(),
+                },
+                ReleaseNote {
+                    version: Some("9.0".into()),
+                    date: None,
+                    text: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn advisories_mark_the_updates_that_fix_them() {
+        let program = fake_manager("advisories");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "{}"
+
+[ops.list_updates]
+args = ["updates"]
+output = {{ format = "lines" }}
+pattern = "^(?P<name>\\S+) (?P<from>\\S+) (?P<to>\\S+)(?: (?P<severity>\\S+))?$"
+fields = {{ name = "name", current_version = "from", new_version = "to", severity = "severity" }}
+
+[ops.advisories]
+args = ["advisories"]
+output = {{ format = "lines" }}
+pattern = "^(?P<id>\\S+) (?P<package>\\S+) (?P<fixed>\\S+) (?P<severity>\\S+)$"
+fields = {{ id = "id", package = "package", fixed_version = "fixed", severity = "severity" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+
+        let updates = block_on(adapter.list_updates(PackageMode::User)).expect("should answer");
+        let curl = updates.iter().find(|u| u.package.id == "curl").unwrap();
+        let jq = updates.iter().find(|u| u.package.id == "jq").unwrap();
+        let zlib = updates.iter().find(|u| u.package.id == "zlib").unwrap();
+
+        // 8.4.0 was already past the first fix, and 8.5.0 stops short of 9.0.0,
+        // so only the middle advisory is this update's to claim.
+        assert!(curl.is_security);
+        assert_eq!(curl.severity, Some(Severity::Medium));
+        let fixed: Vec<&str> = curl.advisories.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(fixed, ["CVE-2023-46218"]);
+
+        assert!(!jq.is_security);
+        assert!(zlib.is_security);
+        assert_eq!(zlib.severity, Some(Severity::High));
+    }
+
+    #[test]
+    fn a_package_s_files_and_a_file_s_owner_are_asked_of_the_manager() {
+        let program = fake_manager("files");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+selector = ["{{name}}"]
+
+[detect]
+command = "{}"
+
+[ops.files]
+args = ["files", "{{selector}}"]
+output = {{ format = "lines" }}
+pattern = "^(?P<path>/.+)$"
+fields = {{ path = "path" }}
+
+[ops.owner]
+args = ["owner", "{{path}}"]
+output = {{ format = "lines" }}
+pattern = "^(?P<name>\\S+)$"
+fields = {{ name = "name" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+        assert!(adapter.capabilities().can_list_files);
+        assert!(adapter.capabilities().can_find_owner);
+
+        let files = block_on(adapter.files("fd", PackageMode::User)).expect("should list");
+        assert_eq!(files, ["/usr/bin/fd", "/usr/share/man/man1/fd.1"]);
+
+        let owners =
+            block_on(adapter.owner("/usr/bin/bat", PackageMode::User)).expect("should answer");
+        assert_eq!(owners.len(), 1);
+        assert_eq!(owners[0].name, "bat");
+    }
+
     #[test]
     fn a_manager_that_fails_in_words_has_failed() {
         let program = fake_manager("says-no");
@@ -2772,8 +3937,9 @@ output = {{ format = "lines" }}
         };
 
         // The command exited cleanly, so only what it said can give it away.
-        let results = block_on(adapter.install(&[package], None, PackageMode::User))
-            .expect("the call itself goes through");
+        let results =
+            block_on(adapter.install(&[package], None, &CancelToken::default(), PackageMode::User))
+                .expect("the call itself goes through");
         assert_eq!(results.len(), 1);
         assert!(!results[0].success, "{:?}", results[0]);
 
@@ -2890,6 +4056,7 @@ output = {{ format = "lines" }}
             false,
             None,
             Some(&progress),
+            &CancelToken::default(),
         )
         .expect("should get through");
 
@@ -2929,6 +4096,7 @@ output = {{ format = "lines" }}
             false,
             None,
             Some(&progress),
+            &CancelToken::default(),
         ) else {
             panic!("should not wait forever on an answer that cannot come");
         };
@@ -2958,7 +4126,305 @@ output = {{ format = "lines" }}
         };
 
         let err =
-            block_on(adapter.remove(&[package], None, PackageMode::User)).expect_err("should fail");
+            block_on(adapter.remove(&[package], None, &CancelToken::default(), PackageMode::User))
+                .expect_err("should fail");
         assert!(err.to_string().contains("it went wrong"), "{err}");
     }
+
+    #[test]
+    fn a_cancelled_install_stops_the_manager_and_what_it_started() {
+        let program = fake_manager("lingers");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+selector = ["{{name}}"]
+
+[detect]
+command = "{}"
+
+[ops.install]
+args = ["lingers", "{{selector}}"]
+output = {{ format = "lines" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+
+        let package = Package {
+            id: "cat".into(),
+            name: "cat".into(),
+            version: String::new(),
+            adapter_id: "demo".into(),
+            description: None,
+            size: None,
+            homepage: None,
+            license: None,
+            installed: false,
+            update_available: false,
+            category: None,
+            tags: Vec::new(),
+            icon_url: None,
+        };
+
+        let cancel = CancelToken::default();
+        let stopping = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            stopping.cancel();
+        });
+
+        let (sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+        let started = std::time::Instant::now();
+        let results =
+            block_on(adapter.install(&[package], Some(sender), &cancel, PackageMode::User))
+                .expect("a cancelled install still answers for its package");
+
+        // Well inside the half minute the child would have kept it waiting.
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert!(!results[0].success);
+        assert_eq!(results[0].error.as_deref(), Some("Cancelled"));
+        let mut said_so = false;
+        while let Ok(event) = events.try_recv() {
+            said_so |= matches!(event, ProgressEvent::Cancelled { .. });
+        }
+        assert!(said_so, "should say it was cancelled");
+    }
+
+    #[test]
+    fn profiles_kept_in_the_configuration_file_are_written_there() {
+        let program = fake_manager("profiles");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+profiles_in_config = true
+
+[detect]
+command = "{}"
+
+[ops.list_installed]
+args = ["installed-profiles"]
+output = {{ format = "lines" }}
+pattern = '^(?P<name>\S+)(?: (?P<profile>\S+))?$'
+fields = {{ name = "name", profile = "profile" }}
+
+[ops.paths]
+args = ["paths"]
+output = {{ format = "json" }}
+fields = {{ config = "config" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+        assert!(adapter.capabilities().has_profiles);
+        assert!(adapter.capabilities().can_manage_profiles);
+
+        let path = format!("{}.config.toml", program.display());
+        std::fs::write(
+            &path,
+            "# mine\ndefault_profile = \"default\"\n\n[profile.default]\nroot_path = \"~/.soar\"\n",
+        )
+        .expect("should write");
+
+        block_on(adapter.create_profile("work")).expect("should create");
+        assert!(block_on(adapter.create_profile("work")).is_err());
+        block_on(adapter.switch_profile("work")).expect("should switch");
+        assert!(block_on(adapter.switch_profile("nowhere")).is_err());
+
+        let profiles = block_on(adapter.list_profiles()).expect("should list");
+        let count = |id: &str| {
+            let profile = profiles
+                .iter()
+                .find(|p| p.id == id)
+                .expect("should be listed");
+            (profile.is_active, profile.package_count)
+        };
+        assert_eq!(count("default"), (false, 2));
+        assert_eq!(count("work"), (true, 1));
+
+        // The one in use stays; any other can go.
+        assert!(block_on(adapter.delete_profile("work")).is_err());
+        block_on(adapter.switch_profile("default")).expect("should switch back");
+        block_on(adapter.delete_profile("work")).expect("should delete");
+
+        let written = std::fs::read_to_string(&path).expect("should read");
+        assert!(written.contains("# mine"), "{written}");
+        assert!(written.contains("root_path"), "{written}");
+        assert!(!written.contains("work"), "{written}");
+        assert_eq!(
+            block_on(adapter.active_profile())
+                .expect("should answer")
+                .id,
+            "default"
+        );
+    }
+
+    #[test]
+    fn what_a_manager_finds_wrong_is_told_package_by_package() {
+        let program = fake_manager("verify");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "{}"
+
+[ops.verify]
+args = ["verify"]
+output = {{ format = "lines" }}
+pattern = '^(?P<name>[^:]+): (?P<problem>.+)$'
+fields = {{ name = "name", problem = "problem" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+        assert!(adapter.capabilities().can_verify);
+        assert!(!adapter.capabilities().can_repair);
+
+        let faults = block_on(adapter.verify(PackageMode::User)).expect("should check");
+        let told: Vec<(&str, &str)> = faults
+            .iter()
+            .map(|f| (f.package.name.as_str(), f.problem.as_str()))
+            .collect();
+        assert_eq!(
+            told,
+            vec![
+                ("curl", "/usr/bin/curl does not match its checksum"),
+                ("jq", "/usr/share/man/man1/jq.1 is missing"),
+            ]
+        );
+    }
+
+    #[test]
+    fn orphans_are_removed_by_name_unless_the_managers_own_command_is_asked_for() {
+        let program = fake_manager("orphans");
+        let manifest = manifest(&format!(
+            r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+selector = ["{{name}}"]
+
+[detect]
+command = "{}"
+
+[ops.list_installed]
+args = ["installed-auto"]
+output = {{ format = "json", select = "$.items[*]" }}
+fields = {{ name = "name", auto = "auto" }}
+
+[ops.list_orphans]
+args = ["orphans"]
+output = {{ format = "lines" }}
+pattern = '^(?P<name>\S+)$'
+fields = {{ name = "name" }}
+
+[ops.remove]
+args = ["remove-one", "{{selector}}"]
+output = {{ format = "lines" }}
+
+[ops.remove_orphans]
+args = ["remove-orphans"]
+output = {{ format = "lines" }}
+"#,
+            program.display()
+        ));
+        let adapter = CommandAdapter::new(manifest, None).expect("should accept");
+        assert!(adapter.capabilities().can_list_orphans);
+        assert!(adapter.capabilities().can_remove_orphans);
+
+        let installed = block_on(adapter.list_installed(PackageMode::User)).expect("should list");
+        let automatic: Vec<&str> = installed
+            .iter()
+            .filter(|p| p.auto_installed)
+            .map(|p| p.package.name.as_str())
+            .collect();
+        assert_eq!(automatic, vec!["libfoo", "libbar"]);
+
+        let orphans = block_on(adapter.list_orphans(PackageMode::User)).expect("should list");
+        assert_eq!(orphans.len(), 2);
+
+        let cancel = CancelToken::default();
+        block_on(adapter.remove(&orphans[..1], None, &cancel, PackageMode::User))
+            .expect("should remove one");
+        block_on(adapter.remove(&orphans, None, &cancel, PackageMode::User))
+            .expect("should remove them by name");
+        block_on(adapter.remove_orphans(None, &cancel, PackageMode::User))
+            .expect("should remove them all");
+
+        let removed = std::fs::read_to_string(format!("{}.removed", program.display()))
+            .expect("should have run");
+        assert_eq!(removed, "libfoo\nlibfoo\nlibbar\nevery orphan\n");
+    }
+
+    #[test]
+    fn a_cache_is_measured_by_the_manager_or_else_where_it_says_it_is() {
+        let program = fake_manager("cache");
+        let with = |ops: &str| {
+            manifest(&format!(
+                r#"
+schema_version = 1
+id = "demo"
+name = "Demo"
+
+[detect]
+command = "{}"
+{ops}"#,
+                program.display()
+            ))
+        };
+
+        let measured = CommandAdapter::new(
+            with(
+                r#"
+[ops.paths]
+args = ["cache-paths"]
+output = { format = "json" }
+fields = { cache = "cache" }
+"#,
+            ),
+            None,
+        )
+        .expect("should accept");
+        assert!(measured.capabilities().has_cache_size);
+        assert!(!measured.capabilities().can_clean_cache);
+
+        let cache = PathBuf::from(format!("{}.cache", program.display()));
+        std::fs::create_dir_all(cache.join("pkgs")).unwrap();
+        std::fs::write(cache.join("pkgs").join("fd.tar.zst"), [0u8; 300]).unwrap();
+        assert_eq!(
+            block_on(measured.cache_size()).expect("should measure"),
+            300
+        );
+
+        let told = CommandAdapter::new(
+            with(
+                r#"
+[ops.cache_size]
+args = ["cache-size"]
+output = { format = "json" }
+fields = { size = "size" }
+
+[ops.clean]
+args = ["--version"]
+output = { format = "lines" }
+"#,
+            ),
+            None,
+        )
+        .expect("should accept");
+        assert!(told.capabilities().can_clean_cache);
+        assert!(!told.capabilities().can_clean_whole_cache);
+        assert_eq!(block_on(told.cache_size()).expect("should answer"), 2048);
+        block_on(told.clean_cache(false, None)).expect("should clean");
+        assert!(matches!(
+            block_on(told.clean_cache(true, None)),
+            Err(AdapterError::NotSupported)
+        ));
+    }
 }
@@ -0,0 +1,265 @@
+//! Manifests that build on another with `extends`.
+//!
+//! Two managers that differ in a handful of arguments, the way apt and nala
+//! do, should not need two whole manifests kept in step by hand. One names
+//! the other with `extends` and writes only what differs: any table it gives
+//! replaces the parent's outright, except `ops`, where each operation it
+//! gives replaces only the parent's operation of that name.
+//!
+//! A parent is read the way its child is, upgraded from an older schema and
+//! resolved through its own `extends`, so a chain works as far as it goes
+//! and a circle is refused rather than followed for ever.
+
+use std::collections::BTreeMap;
+
+use toml::{Table, Value};
+
+use super::migrate;
+
+/// What belongs to a manifest alone and is never taken from its parent: which
+/// adapter it is, and which revision of it.
+const OWN: &[&str] = &["id", "version", "schema_version"];
+
+/// A manifest with everything it extends folded in.
+pub struct Resolved {
+    pub table: Table,
+    /// What the upgrade of this manifest, not of its parents, had to change.
+    pub deprecations: Vec<String>,
+    /// Each field taken from another manifest, with the id of the one that
+    /// wrote it. An operation is named as `ops.<name>`.
+    pub inherited: Vec<(String, String)>,
+}
+
+/// Read `text`, folding in whatever it extends. `parent` answers the text of
+/// the manifest with the id asked for.
+pub fn resolve(
+    text: &str,
+    parent: &dyn Fn(&str) -> Result<String, String>,
+) -> Result<Resolved, String> {
+    read(text, parent, &mut Vec::new())
+}
+
+fn read(
+    text: &str,
+    parent: &dyn Fn(&str) -> Result<String, String>,
+    chain: &mut Vec<String>,
+) -> Result<Resolved, String> {
+    let mut doc: toml_edit::DocumentMut = text
+        .parse()
+        .map_err(|e| format!("could not read manifest: {e}"))?;
+    let deprecations = migrate::upgrade(&mut doc)?;
+    let mut table: Table =
+        toml::from_str(&doc.to_string()).map_err(|e| format!("could not read manifest: {e}"))?;
+
+    let Some(extends) = table.remove("extends") else {
+        return Ok(Resolved {
+            table,
+            deprecations,
+            inherited: Vec::new(),
+        });
+    };
+    let Value::String(parent_id) = extends else {
+        return Err("extends names the adapter by its id".into());
+    };
+
+    let id = table
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    chain.push(id);
+    if chain.contains(&parent_id) {
+        return Err(format!(
+            "extends goes round in a circle: {} and back to {parent_id}",
+            chain.join(" to ")
+        ));
+    }
+
+    let parent_text = parent(&parent_id).map_err(|e| format!("extends {parent_id}: {e}"))?;
+    let Resolved {
+        table: mut merged,
+        inherited: handed_down,
+        ..
+    } = read(&parent_text, parent, chain)?;
+
+    // Where each field of the parent came from, before the child has its say:
+    // the parent itself, or whatever the parent took it from in turn.
+    let handed_down: BTreeMap<String, String> = handed_down.into_iter().collect();
+    let mut origin: BTreeMap<String, String> = fields(&merged)
+        .into_iter()
+        .map(|field| {
+            let from = handed_down
+                .get(&field)
+                .cloned()
+                .unwrap_or_else(|| parent_id.clone());
+            (field, from)
+        })
+        .collect();
+    for key in OWN {
+        merged.remove(*key);
+    }
+
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("ops", Value::Table(ops)) => {
+                let mut inherited_ops = match merged.remove("ops") {
+                    Some(Value::Table(ops)) => ops,
+                    _ => Table::new(),
+                };
+                for (name, op) in ops {
+                    origin.remove(&format!("ops.{name}"));
+                    inherited_ops.insert(name, op);
+                }
+                merged.insert(key, Value::Table(inherited_ops));
+            }
+            (_, value) => {
+                origin.remove(&key);
+                merged.insert(key, value);
+            }
+        }
+    }
+    chain.pop();
+
+    Ok(Resolved {
+        table: merged,
+        deprecations,
+        inherited: origin.into_iter().collect(),
+    })
+}
+
+/// The fields a manifest can hand down, with each operation its own.
+fn fields(table: &Table) -> Vec<String> {
+    let mut fields = Vec::new();
+    for (key, value) in table {
+        if OWN.contains(&key.as_str()) {
+            continue;
+        }
+        match (key.as_str(), value) {
+            ("ops", Value::Table(ops)) => {
+                fields.extend(ops.keys().map(|name| format!("ops.{name}")));
+            }
+            _ => fields.push(key.clone()),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APT: &str = r#"
+schema_version = 2
+id = "apt"
+name = "APT"
+version = "3"
+
+[detect]
+command = "apt"
+
+[system]
+elevate = true
+
+[ops.search]
+args = ["search", "{query}"]
+output = { format = "lines", pattern = '^(?P<name>\S+)$' }
+fields = { name = "name" }
+
+[ops.install]
+args = ["install", "-y", "{selector}"]
+output = { format = "lines" }
+"#;
+
+    const NALA: &str = r#"
+schema_version = 2
+id = "nala"
+name = "Nala"
+extends = "apt"
+
+[detect]
+command = "nala"
+
+[ops.install]
+args = ["install", "--assume-yes", "{selector}"]
+output = { format = "lines" }
+"#;
+
+    fn only(id: &str, text: &'static str) -> impl Fn(&str) -> Result<String, String> {
+        let id = id.to_string();
+        move |asked: &str| match asked == id {
+            true => Ok(text.to_string()),
+            false => Err(format!("{asked} is not installed")),
+        }
+    }
+
+    #[test]
+    fn a_manifest_takes_what_it_does_not_write_from_the_one_it_extends() {
+        let resolved = resolve(NALA, &only("apt", APT)).expect("should resolve");
+        let table = resolved.table;
+
+        assert_eq!(table["id"].as_str(), Some("nala"));
+        assert_eq!(table["detect"]["command"].as_str(), Some("nala"));
+        assert!(table.get("version").is_none());
+        assert_eq!(table["ops"]["search"]["args"][0].as_str(), Some("search"));
+        assert_eq!(
+            table["ops"]["install"]["args"][1].as_str(),
+            Some("--assume-yes")
+        );
+        assert_eq!(
+            resolved.inherited,
+            vec![
+                ("ops.search".to_string(), "apt".to_string()),
+                ("system".to_string(), "apt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn what_a_parent_took_from_its_own_parent_is_credited_to_that_one() {
+        let grandchild = r#"
+schema_version = 2
+id = "nala-nightly"
+name = "Nala Nightly"
+extends = "nala"
+"#;
+        let lookup = |asked: &str| match asked {
+            "apt" => Ok(APT.to_string()),
+            "nala" => Ok(NALA.to_string()),
+            other => Err(format!("{other} is not installed")),
+        };
+        let resolved = resolve(grandchild, &lookup).expect("should resolve");
+        let from: BTreeMap<String, String> = resolved.inherited.into_iter().collect();
+
+        assert_eq!(from["detect"], "nala");
+        assert_eq!(from["ops.install"], "nala");
+        assert_eq!(from["ops.search"], "apt");
+        assert!(!from.contains_key("name"));
+    }
+
+    #[test]
+    fn manifests_extending_one_another_in_a_circle_are_refused() {
+        let paru = r#"
+schema_version = 2
+id = "paru"
+name = "Paru"
+extends = "yay"
+"#;
+        let yay = r#"
+schema_version = 2
+id = "yay"
+name = "Yay"
+extends = "paru"
+"#;
+        let lookup = |asked: &str| match asked {
+            "paru" => Ok(paru.to_string()),
+            "yay" => Ok(yay.to_string()),
+            other => Err(format!("{other} is not installed")),
+        };
+
+        let Err(err) = resolve(paru, &lookup) else {
+            panic!("should refuse a circle");
+        };
+        assert!(err.contains("circle"), "{err}");
+        assert!(resolve(NALA, &only("dpkg", APT)).is_err());
+    }
+}
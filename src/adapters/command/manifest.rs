@@ -10,25 +10,63 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use serde::Deserialize;
+use super::version;
+
+use serde::{Deserialize, Deserializer, de::Error as _};
 
 /// The schema version this build reads.
 ///
 /// A manifest declaring a newer one is refused rather than read in part: the
-/// fields it gained are exactly the ones that would be ignored in silence.
-pub const SCHEMA_VERSION: u32 = 1;
+/// fields it gained are exactly the ones that would be ignored in silence. An
+/// older one is upgraded as it is read; see [`super::migrate`].
+pub const SCHEMA_VERSION: u32 = 2;
 
 pub const OP_LIST: &str = "list";
 pub const OP_LIST_INSTALLED: &str = "list_installed";
 pub const OP_LIST_UPDATES: &str = "list_updates";
+/// Published security advisories, one record each naming the package, the
+/// advisory and the version that fixes it. Joined onto the updates listed, so
+/// a manager whose update listing cannot tell a fix apart can still be told.
+pub const OP_ADVISORIES: &str = "advisories";
 pub const OP_SEARCH: &str = "search";
 pub const OP_INFO: &str = "info";
 /// Detail about a package as installed, for a manager whose installed listing
 /// is thinner than what it knows.
 pub const OP_INFO_INSTALLED: &str = "info_installed";
+/// The release notes for a package between `{from}` and `{to}`, a record an
+/// entry with its `text`, and the `version` and `date` where there are some.
+pub const OP_CHANGELOG: &str = "changelog";
+/// What a package needs, one record a package.
+pub const OP_DEPENDS: &str = "depends";
+/// What installed packages need the one named, one record a package. What
+/// makes a removal worth a second thought.
+pub const OP_REQUIRED_BY: &str = "required_by";
+/// The files a package put down, one record a file under `path`.
+pub const OP_FILES: &str = "files";
+/// Check the installed packages over, one record for each thing wrong, naming
+/// the package and what the `problem` is.
+pub const OP_VERIFY: &str = "verify";
+/// Put a broken package right.
+pub const OP_REPAIR: &str = "repair";
+/// Which package a file belongs to, named by `{path}`.
+pub const OP_OWNER: &str = "owner";
 pub const OP_INSTALL: &str = "install";
 pub const OP_REMOVE: &str = "remove";
+/// The packages installed only for others that nothing needs any more, one
+/// record a package.
+pub const OP_LIST_ORPHANS: &str = "list_orphans";
+/// Remove every package `list_orphans` names, in the manager's own way.
+pub const OP_REMOVE_ORPHANS: &str = "remove_orphans";
+/// Install one particular version of a package, named by `{version}`. What
+/// going back to a version held before an update or a removal is made of.
+pub const OP_INSTALL_VERSION: &str = "install_version";
+/// Keep a package where it is, so an update passes it by.
+pub const OP_PIN: &str = "pin";
+pub const OP_UNPIN: &str = "unpin";
 pub const OP_UPDATE: &str = "update";
+/// Fetch what an update would install without installing it, so applying it
+/// later costs no waiting on the network.
+pub const OP_DOWNLOAD: &str = "download";
 /// Update everything the manager holds, for a manager that offers that as a
 /// command of its own rather than as an update naming no package.
 pub const OP_UPDATE_ALL: &str = "update_all";
@@ -38,6 +76,20 @@ pub const OP_ADD_REPO: &str = "add_repo";
 pub const OP_REMOVE_REPO: &str = "remove_repo";
 pub const OP_SET_REPO_ENABLED: &str = "set_repo_enabled";
 pub const OP_PATHS: &str = "paths";
+/// How much room the manager's cache takes, one record with its `size`. A
+/// manager without one is measured from the `cache` its `paths` names.
+pub const OP_CACHE_SIZE: &str = "cache_size";
+/// Clear what the cache no longer needs.
+pub const OP_CLEAN: &str = "clean";
+/// Clear the cache outright.
+pub const OP_CLEAN_ALL: &str = "clean_all";
+/// The manager's profiles, one record each with its `name` and whether it is
+/// the `active` one.
+pub const OP_LIST_PROFILES: &str = "list_profiles";
+/// Make the profile named by `{profile}` the one packages go to.
+pub const OP_SWITCH_PROFILE: &str = "switch_profile";
+pub const OP_CREATE_PROFILE: &str = "create_profile";
+pub const OP_DELETE_PROFILE: &str = "delete_profile";
 pub const OP_DEFAULT_CONFIG: &str = "default_config";
 pub const OP_APPLY: &str = "apply";
 pub const OP_APPLY_PRUNE: &str = "apply_prune";
@@ -77,16 +129,38 @@ pub struct CommandManifest {
     /// person running it. Absent means it cannot, and the mode is not
     /// offered.
     pub system: Option<SystemMode>,
-    /// Set when every operation acts system wide, as it does for a manager
-    /// that has no per-user notion at all.
+    /// Set when the manager cannot run twice at once, as with one that locks
+    /// everything it holds for as long as it works. Whatever changes packages
+    /// then waits its turn rather than failing on the lock.
     #[serde(default)]
-    pub system_only: bool,
+    pub exclusive: bool,
+    /// Set when the manager keeps its profiles as `[profile.<name>]` tables in
+    /// its configuration file and picks one with `default_profile`. Profiles
+    /// are then listed, switched, created and deleted by reading and writing
+    /// that file, wherever an operation does not say otherwise.
+    #[serde(default)]
+    pub profiles_in_config: bool,
     /// The settings the manager can be configured with, named as they appear
     /// in its own configuration file.
     #[serde(default)]
     pub config: Vec<Setting>,
-    #[serde(default)]
+    /// Every way of running each operation, for a manager whose arguments
+    /// changed between releases. Written as `[ops.<name>]` for an operation
+    /// with one, or as `[[ops.<name>]]` entries with their `when_version`.
+    #[serde(default, rename = "ops", deserialize_with = "one_or_many")]
+    pub variants: HashMap<String, Vec<Op>>,
+    /// The operations as they apply to the version of the manager found,
+    /// chosen from [`Self::variants`] by [`Self::choose_ops`].
+    #[serde(skip)]
     pub ops: HashMap<String, Op>,
+    /// What had to change for this manifest to be read, when it was written
+    /// for an older schema. Empty for one that is current.
+    #[serde(skip)]
+    pub deprecations: Vec<String>,
+    /// The fields taken from the manifest this one `extends`, each with the
+    /// id of the manifest that wrote it. See [`super::inherit`].
+    #[serde(skip)]
+    pub inherited: Vec<(String, String)>,
 }
 
 /// One setting, described well enough to offer and to write back.
@@ -134,6 +208,10 @@ pub struct SystemMode {
     /// Whether this needs privileges the person running aeris does not have.
     #[serde(default)]
     pub elevate: bool,
+    /// Set when every operation acts system wide, as it does for a manager
+    /// that has no per-user notion at all.
+    #[serde(default)]
+    pub only: bool,
 }
 
 /// What decides whether the manager is usable at all.
@@ -150,6 +228,10 @@ pub struct Detect {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Op {
+    /// The versions of the manager this applies to, such as `>=0.13, <0.15`.
+    /// Absent means every version.
+    #[serde(default)]
+    pub when_version: Option<String>,
     pub args: Vec<String>,
     pub output: Output,
     /// Maps a field aeris knows to the field the manager reports it under.
@@ -158,9 +240,6 @@ pub struct Op {
     /// Maps the parts of a progress event, for a streaming operation.
     #[serde(default)]
     pub progress: HashMap<String, String>,
-    /// Named captures pulled from each line, for the `lines` format.
-    #[serde(default)]
-    pub pattern: Option<String>,
     /// Anything else worth showing, in the order it should be shown. Lets a
     /// manager surface what it knows without aeris having a name for it.
     #[serde(default)]
@@ -195,10 +274,15 @@ pub struct Output {
     /// Lines to drop before reading, for a manager that prints a header.
     #[serde(default)]
     pub skip_header: usize,
-    /// What separates a name from its value, for the `keyvalue` format.
-    /// Defaults to a colon.
+    /// What separates a name from its value, for the `keyvalue` and
+    /// `keyvalue_blocks` formats, where it defaults to a colon. For `csv` it
+    /// is what separates one value from the next instead, a comma unless
+    /// said otherwise.
     #[serde(default)]
     pub separator: Option<String>,
+    /// Named captures pulled from each line, for the `lines` format.
+    #[serde(default)]
+    pub pattern: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
@@ -213,6 +297,18 @@ pub enum Format {
     /// Plain text describing one thing, a name and a value to a line. What a
     /// manager prints when asked about a single package.
     KeyValue,
+    /// Blocks of names and values, one record a block, with a blank line
+    /// between them. What `apt show` or `pacman -Qi` print when asked about
+    /// more than one package.
+    #[serde(rename = "keyvalue_blocks")]
+    KeyValueBlocks,
+    /// A table lined up in columns under a header naming each, the way
+    /// `flatpak list` or `snap list` print.
+    Columns,
+    /// Values separated by commas, with a header naming them.
+    Csv,
+    /// Values separated by tabs, with a header naming them.
+    Tsv,
 }
 
 impl CommandManifest {
@@ -220,6 +316,34 @@ impl CommandManifest {
         self.ops.get(name)
     }
 
+    /// Settle each operation on the first of its variants that applies to
+    /// `version`. Where the version is not known only a variant without a
+    /// `when_version` applies, since nothing else can be vouched for, and an
+    /// operation with none that applies is treated as absent.
+    pub fn choose_ops(&mut self, version: Option<&str>) {
+        self.ops = self
+            .variants
+            .iter()
+            .filter_map(|(name, variants)| {
+                let chosen = variants
+                    .iter()
+                    .find(|op| match (&op.when_version, version) {
+                        (None, _) => true,
+                        (Some(range), Some(found)) => {
+                            version::within(found, range).unwrap_or(false)
+                        }
+                        (Some(_), None) => false,
+                    })?;
+                Some((name.clone(), chosen.clone()))
+            })
+            .collect();
+    }
+
+    /// Whether every operation acts system wide.
+    pub fn system_only(&self) -> bool {
+        self.system.as_ref().is_some_and(|system| system.only)
+    }
+
     fn validate(&self) -> Result<(), String> {
         if self.schema_version == 0 || self.schema_version > SCHEMA_VERSION {
             return Err(format!(
@@ -237,11 +361,25 @@ impl CommandManifest {
             return Err("manifest has no detect.command".into());
         }
 
-        for (name, op) in &self.ops {
+        for (name, op) in self
+            .variants
+            .iter()
+            .flat_map(|(name, variants)| variants.iter().map(move |op| (name, op)))
+        {
+            if let Some(range) = &op.when_version {
+                // Whether it holds for any version does not matter here, only
+                // that it can be read.
+                version::within("0", range)
+                    .map_err(|e| format!("operation {name} has an unreadable when_version: {e}"))?;
+            }
+            if let Some(path) = &op.output.select {
+                super::output::parse_path(path)
+                    .map_err(|e| format!("operation {name} has an unreadable select: {e}"))?;
+            }
             if op.args.is_empty() {
                 return Err(format!("operation {name} runs no arguments"));
             }
-            if let Some(pattern) = &op.pattern {
+            if let Some(pattern) = &op.output.pattern {
                 regex::Regex::new(pattern)
                     .map_err(|e| format!("operation {name} has an unreadable pattern: {e}"))?;
             } else if op.output.format == Format::Lines && !op.fields.is_empty() {
@@ -256,13 +394,51 @@ impl CommandManifest {
     }
 }
 
+/// Read a manifest, upgrading it first when it was written for an older
+/// schema. What the upgrade changed is kept on it, to be shown to whoever
+/// looks after it. A manifest it extends is looked for on disk.
 pub fn parse(text: &str) -> Result<CommandManifest, String> {
-    let manifest: CommandManifest =
-        toml::from_str(text).map_err(|e| format!("could not read manifest: {e}"))?;
+    parse_extending(text, &|id| {
+        find(id).ok_or_else(|| format!("no manifest for {id} is installed"))
+    })
+}
+
+/// Read a manifest, asking `parent` for the text of any it extends.
+pub fn parse_extending(
+    text: &str,
+    parent: &dyn Fn(&str) -> Result<String, String>,
+) -> Result<CommandManifest, String> {
+    let resolved = super::inherit::resolve(text, parent)?;
+
+    let mut manifest: CommandManifest = toml::Value::Table(resolved.table)
+        .try_into()
+        .map_err(|e| format!("could not read manifest: {e}"))?;
+    manifest.deprecations = resolved.deprecations;
+    manifest.inherited = resolved.inherited;
     manifest.validate()?;
+    manifest.choose_ops(None);
     Ok(manifest)
 }
 
+/// Read an operation written once as a table, or as a list of variants.
+fn one_or_many<'de, D>(deserializer: D) -> Result<HashMap<String, Vec<Op>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let written: HashMap<String, toml::Value> = HashMap::deserialize(deserializer)?;
+    written
+        .into_iter()
+        .map(|(name, value)| {
+            let variants = match value {
+                toml::Value::Array(_) => value.try_into::<Vec<Op>>(),
+                _ => value.try_into::<Op>().map(|op| vec![op]),
+            }
+            .map_err(|e| D::Error::custom(format!("operation {name}: {e}")))?;
+            Ok((name, variants))
+        })
+        .collect()
+}
+
 pub fn load(path: &Path) -> Result<CommandManifest, String> {
     let text = std::fs::read_to_string(path)
         .map_err(|e| format!("could not read {}: {e}", path.display()))?;
@@ -284,6 +460,36 @@ pub fn search_paths() -> Vec<PathBuf> {
     paths
 }
 
+/// The text of the manifest on disk with this id, found where [`discover`]
+/// would find it first.
+pub fn find(id: &str) -> Option<String> {
+    for dir in search_paths() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        // Sorted, so which of two manifests claiming one id is found does not
+        // depend on the order the directory lists them in.
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let names_it = toml::from_str::<toml::Table>(&text)
+                .ok()
+                .is_some_and(|table| table.get("id").and_then(toml::Value::as_str) == Some(id));
+            if names_it {
+                return Some(text);
+            }
+        }
+    }
+    None
+}
+
 /// Read every manifest found on disk, keeping the first of any repeated id.
 pub fn discover() -> Vec<(PathBuf, CommandManifest)> {
     let mut found: Vec<(PathBuf, CommandManifest)> = Vec::new();
@@ -324,7 +530,7 @@ mod tests {
     use super::*;
 
     const MINIMAL: &str = r#"
-schema_version = 1
+schema_version = 2
 id = "demo"
 name = "Demo"
 
@@ -347,9 +553,9 @@ fields = { name = "name" }
 
     #[test]
     fn a_newer_schema_is_refused() {
-        let text = MINIMAL.replace("schema_version = 1", "schema_version = 2");
+        let text = MINIMAL.replace("schema_version = 2", "schema_version = 3");
         let err = parse(&text).expect_err("should refuse");
-        assert!(err.contains("schema 2"), "{err}");
+        assert!(err.contains("schema 3"), "{err}");
     }
 
     #[test]
@@ -378,12 +584,18 @@ output = { format = "lines" }"#,
         parse(&text).expect("should accept");
     }
 
+    #[test]
+    fn an_unreadable_select_is_refused() {
+        let text = MINIMAL.replace("$.items[*]", "$.items[?(@.installed==)]");
+        let err = parse(&text).expect_err("should refuse");
+        assert!(err.contains("unreadable select"), "{err}");
+    }
+
     #[test]
     fn an_unreadable_pattern_is_refused() {
         let text = MINIMAL.replace(
             r#"output = { format = "json", select = "$.items[*]" }"#,
-            r#"output = { format = "lines" }
-pattern = "(?P<name>""#,
+            r#"output = { format = "lines", pattern = "(?P<name>" }"#,
         );
         let err = parse(&text).expect_err("should refuse");
         assert!(err.contains("unreadable pattern"), "{err}");
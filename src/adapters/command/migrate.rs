@@ -0,0 +1,221 @@
+//! Bringing a manifest written for an older schema up to the one this build
+//! reads.
+//!
+//! The upgrade works on the document rather than on what serde makes of it,
+//! so one step serves both ends: reading, where the upgraded form only lives
+//! in memory, and `aeris manifest upgrade`, where it is written back with
+//! every comment and blank line where its author left them.
+//!
+//! What changed in schema 2:
+//!
+//! - `pattern` belongs to how output is read, so it moved from the operation
+//!   into its `output`.
+//! - `system_only` said something about acting system wide, so it moved into
+//!   `[system]` as `only`.
+
+use std::path::Path;
+
+use toml_edit::{DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+use super::manifest::{self, SCHEMA_VERSION};
+
+/// Bring `doc` up to [`SCHEMA_VERSION`] in place, answering what had to
+/// change, a sentence each. Nothing is answered for a manifest that was
+/// already current.
+///
+/// A schema newer than this build reads is refused rather than read in part,
+/// as is one that declares none.
+pub fn upgrade(doc: &mut DocumentMut) -> Result<Vec<String>, String> {
+    let declared = doc
+        .get("schema_version")
+        .and_then(Item::as_integer)
+        .ok_or("manifest declares no schema_version")?;
+    if declared < 1 || declared > i64::from(SCHEMA_VERSION) {
+        return Err(format!(
+            "manifest declares schema {declared}, and this aeris reads up to {SCHEMA_VERSION}"
+        ));
+    }
+
+    let mut changes = Vec::new();
+    if declared == 1 {
+        changes.push(format!(
+            "Written for schema 1, which is read as schema {SCHEMA_VERSION}"
+        ));
+        changes.extend(from_1(doc));
+        set_schema(doc, 2);
+    }
+    Ok(changes)
+}
+
+/// Rewrite the manifest at `path` in the current schema, answering what
+/// changed. The file is only touched when something did and the result
+/// reads as a manifest, so a failed upgrade leaves it as it was.
+pub fn upgrade_file(path: &Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("could not read {}: {e}", path.display()))?;
+    let mut doc: DocumentMut = text
+        .parse()
+        .map_err(|e| format!("could not read manifest: {e}"))?;
+
+    let changes = upgrade(&mut doc)?;
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+    let upgraded = doc.to_string();
+    manifest::parse(&upgraded)?;
+
+    // Written beside it and moved over, so nothing is ever left half written.
+    let mut written = path.as_os_str().to_owned();
+    written.push(".upgrading");
+    std::fs::write(&written, upgraded)
+        .map_err(|e| format!("could not write {}: {e}", path.display()))?;
+    std::fs::rename(&written, path)
+        .map_err(|e| format!("could not write {}: {e}", path.display()))?;
+    Ok(changes)
+}
+
+fn from_1(doc: &mut DocumentMut) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if let Some(only) = doc.remove("system_only") {
+        changes.push("`system_only` is now `only` under `[system]`".into());
+        if only.as_bool() == Some(true) {
+            let system = doc
+                .entry("system")
+                .or_insert_with(|| Item::Table(Table::new()));
+            if let Some(system) = system.as_table_like_mut() {
+                system.insert("only", toml_edit::value(true));
+            }
+        }
+    }
+
+    let mut moved = Vec::new();
+    if let Some(ops) = doc.get_mut("ops").and_then(Item::as_table_like_mut) {
+        for (name, op) in ops.iter_mut() {
+            let Some(op) = op.as_table_like_mut() else {
+                continue;
+            };
+            let Some(Item::Value(mut pattern)) = op.remove("pattern") else {
+                continue;
+            };
+            // The spacing around `pattern = ` does not suit it once it sits
+            // inside an inline table, so it is left to be laid out afresh.
+            pattern.decor_mut().clear();
+            output_of(op).insert("pattern", Item::Value(pattern));
+            moved.push(name.get().to_string());
+        }
+    }
+    if !moved.is_empty() {
+        moved.sort();
+        changes.push(format!(
+            "`pattern` has moved into `output` (in {})",
+            moved.join(", ")
+        ));
+    }
+
+    changes
+}
+
+/// The `output` of an operation, made an inline table if it was missing so
+/// the pattern has somewhere to go.
+fn output_of(op: &mut dyn TableLike) -> &mut dyn TableLike {
+    if op.get("output").and_then(Item::as_table_like).is_none() {
+        op.insert(
+            "output",
+            Item::Value(Value::InlineTable(InlineTable::new())),
+        );
+    }
+    op.get_mut("output")
+        .and_then(Item::as_table_like_mut)
+        .expect("output was just made a table")
+}
+
+/// Change the declared schema, keeping any comment written after it.
+fn set_schema(doc: &mut DocumentMut, version: i64) {
+    if let Some(declared) = doc.get_mut("schema_version").and_then(Item::as_value_mut) {
+        let decor = declared.decor().clone();
+        *declared = Value::from(version);
+        *declared.decor_mut() = decor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_1: &str = r#"# Demo's manifest, kept by hand.
+schema_version = 1 # bumped when the schema is
+id = "demo"
+name = "Demo"
+system_only = true
+
+[detect]
+command = "demo"
+
+[system]
+elevate = true
+
+# Lines, since demo has no JSON to give.
+[ops.list_installed]
+args = ["list"]
+output = { format = "lines" }
+pattern = '^(?P<name>\S+) (?P<version>\S+)$'
+fields = { name = "name", version = "version" }
+"#;
+
+    #[test]
+    fn a_schema_1_manifest_is_read_as_schema_2_and_says_what_changed() {
+        let manifest = manifest::parse(SCHEMA_1).expect("should read");
+        let op = manifest.op(manifest::OP_LIST_INSTALLED).unwrap();
+
+        assert_eq!(manifest.schema_version, 2);
+        assert!(manifest.system_only());
+        assert!(
+            op.output
+                .pattern
+                .as_deref()
+                .is_some_and(|p| p.contains("name"))
+        );
+        assert_eq!(
+            manifest.deprecations.len(),
+            3,
+            "{:?}",
+            manifest.deprecations
+        );
+        assert!(manifest.deprecations[2].contains("list_installed"));
+    }
+
+    #[test]
+    fn upgrading_keeps_every_comment_and_is_done_once() {
+        let mut doc: DocumentMut = SCHEMA_1.parse().unwrap();
+        upgrade(&mut doc).unwrap();
+        let upgraded = doc.to_string();
+
+        assert!(upgraded.contains("schema_version = 2 # bumped when the schema is"));
+        assert!(upgraded.contains("# Demo's manifest, kept by hand."));
+        assert!(upgraded.contains("# Lines, since demo has no JSON to give."));
+        assert!(!upgraded.contains("system_only"));
+        assert!(upgraded.contains("only = true"));
+
+        let read = manifest::parse(&upgraded).expect("should read");
+        assert!(read.deprecations.is_empty());
+        assert!(upgrade(&mut doc).unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_file_is_rewritten_only_when_it_was_old() {
+        let dir = std::env::temp_dir().join(format!("aeris-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("demo.toml");
+        std::fs::write(&path, SCHEMA_1).unwrap();
+
+        let first = upgrade_file(&path);
+        let second = upgrade_file(&path);
+        let written = std::fs::read_to_string(&path).unwrap_or_default();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(!first.unwrap().is_empty());
+        assert!(second.unwrap().is_empty());
+        assert!(written.contains("schema_version = 2"));
+    }
+}
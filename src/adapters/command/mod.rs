@@ -8,7 +8,9 @@
 //! input rather than leaving the window waiting on an answer it cannot give.
 
 pub mod adapter;
+pub mod inherit;
 pub mod manifest;
+pub mod migrate;
 pub mod output;
 pub mod version;
 
@@ -52,10 +52,207 @@ pub fn strip_ansi(text: &str) -> String {
     out
 }
 
-/// Pick the records out of a JSON document.
+/// One step of a path into a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// A field of an object, written `.name` or `['name']`.
+    Field(String),
+    /// An element of an array, written `[2]`, or `[-1]` to count from the
+    /// end.
+    Index(i64),
+    /// Every element of an array or value of an object, written `[*]` or
+    /// `.*`. What a manager keying its records by name needs.
+    Each,
+    /// A node and everything beneath it, written `..` before the step to take
+    /// from all of them, as in `$..packages`.
+    Descend,
+    /// The elements or values a test holds for, as in
+    /// `[?(@.installed==true)]`.
+    Filter(Filter),
+}
+
+/// What a filter asks of each element: that a field is there and set, or
+/// that it is, or is not, some value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    /// The field under `@`, empty for the element itself.
+    field: Vec<String>,
+    /// Whether it should equal the value or differ from it. Nothing means the
+    /// field only has to be there and be neither `null` nor `false`.
+    test: Option<(bool, Value)>,
+}
+
+impl Filter {
+    fn holds(&self, element: &Value) -> bool {
+        let found = self
+            .field
+            .iter()
+            .try_fold(element, |value, name| value.get(name.as_str()));
+        match &self.test {
+            None => found.is_some_and(|value| !matches!(value, Value::Null | Value::Bool(false))),
+            Some((equal, wanted)) => (found == Some(wanted)) == *equal,
+        }
+    }
+}
+
+/// Read a path into a JSON document, such as `$.repos[*].packages[*]`.
+///
+/// This is the part of JSONPath a manifest has use for: fields, indices,
+/// `[*]` and `.*` for every element or value, `..` to look at every depth,
+/// and filters comparing one field with `==` or `!=`. The leading `$` may be
+/// left off.
+pub fn parse_path(path: &str) -> Result<Vec<Step>, String> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut steps = Vec::new();
+
+    while !rest.is_empty() {
+        let (step, after) = if let Some(after) = rest.strip_prefix("..") {
+            steps.push(Step::Descend);
+            match after {
+                "" => return Err("`..` has to be followed by what to look for".into()),
+                _ if after.starts_with('[') => bracketed(&after[1..])?,
+                _ => dotted(after)?,
+            }
+        } else if let Some(after) = rest.strip_prefix('.') {
+            dotted(after)?
+        } else if let Some(after) = rest.strip_prefix('[') {
+            bracketed(after)?
+        } else if steps.is_empty() {
+            // A name may open the path with no dot before it: `items[*]`.
+            dotted(rest)?
+        } else {
+            return Err(format!(
+                "`{rest}` does not follow on from what comes before"
+            ));
+        };
+        steps.push(step);
+        rest = after;
+    }
+
+    Ok(steps)
+}
+
+/// A step written after a dot: a name, or `*`.
+fn dotted(rest: &str) -> Result<(Step, &str), String> {
+    let end = rest.find(['.', '[']).unwrap_or(rest.len());
+    let (name, after) = rest.split_at(end);
+    match name {
+        "" => Err("a `.` has to be followed by a name".into()),
+        "*" => Ok((Step::Each, after)),
+        name => Ok((Step::Field(name.to_string()), after)),
+    }
+}
+
+/// A step written in brackets, with the opening one already read.
+fn bracketed(rest: &str) -> Result<(Step, &str), String> {
+    if let Some(test) = rest.strip_prefix("?(") {
+        let end = test.find(")]").ok_or("a filter is closed with `)]`")?;
+        return Ok((Step::Filter(filter(&test[..end])?), &test[end + 2..]));
+    }
+
+    let end = rest.find(']').ok_or("a `[` is never closed")?;
+    let (inside, after) = (rest[..end].trim(), &rest[end + 1..]);
+    let quoted = ['\'', '"']
+        .into_iter()
+        .find_map(|quote| inside.strip_prefix(quote)?.strip_suffix(quote));
+
+    let step =
+        match (inside, quoted) {
+            ("*", _) => Step::Each,
+            (_, Some(name)) => Step::Field(name.to_string()),
+            (index, None) => Step::Index(index.parse().map_err(|_| {
+                format!("`[{index}]` is not an index, `*`, a quoted name or a filter")
+            })?),
+        };
+    Ok((step, after))
+}
+
+/// What is inside `[?( )]`: `@` or a field under it, and perhaps a
+/// comparison with a value.
+fn filter(test: &str) -> Result<Filter, String> {
+    let (left, compared) = match test.split_once("==") {
+        Some((left, right)) => (left, Some((true, right))),
+        None => match test.split_once("!=") {
+            Some((left, right)) => (left, Some((false, right))),
+            None => (test, None),
+        },
+    };
+
+    let left = left.trim();
+    let field = left
+        .strip_prefix('@')
+        .ok_or_else(|| format!("a filter tests a field of `@`, not `{left}`"))?
+        .split('.')
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let test = match compared {
+        Some((equal, right)) => Some((equal, literal(right.trim())?)),
+        None => None,
+    };
+    Ok(Filter { field, test })
+}
+
+/// A value a filter compares with, written as JSON or as a string in single
+/// quotes.
+fn literal(text: &str) -> Result<Value, String> {
+    if let Some(quoted) = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        return Ok(Value::String(quoted.to_string()));
+    }
+    serde_json::from_str(text).map_err(|_| format!("a filter cannot compare with `{text}`"))
+}
+
+/// Take one step from every node reached so far.
+fn apply<'a>(step: &Step, nodes: Vec<&'a Value>) -> Vec<&'a Value> {
+    let mut reached = Vec::new();
+    for node in nodes {
+        match step {
+            Step::Field(name) => reached.extend(node.get(name.as_str())),
+            Step::Index(index) => {
+                if let Value::Array(items) = node {
+                    let at = match *index < 0 {
+                        true => items.len().checked_sub(index.unsigned_abs() as usize),
+                        false => Some(*index as usize),
+                    };
+                    reached.extend(at.and_then(|at| items.get(at)));
+                }
+            }
+            Step::Each => reached.extend(children(node)),
+            Step::Descend => descend(node, &mut reached),
+            Step::Filter(filter) => reached.extend(
+                children(node)
+                    .into_iter()
+                    .filter(|child| filter.holds(child)),
+            ),
+        }
+    }
+    reached
+}
+
+fn children(node: &Value) -> Vec<&Value> {
+    match node {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(fields) => fields.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The node, then everything beneath it, depth first.
+fn descend<'a>(node: &'a Value, reached: &mut Vec<&'a Value>) {
+    reached.push(node);
+    for child in children(node) {
+        descend(child, reached);
+    }
+}
+
+/// Pick the records out of a JSON document, through a path as
+/// [`parse_path`] reads it. With no path, a document that is a list is a
+/// record an element, and anything else is one record.
 ///
-/// Only what a manifest needs is understood: field access and a trailing
-/// `[*]`, so `$.items[*]` and `$[*]` both work and nothing else is promised.
+/// A path that cannot be read picks nothing. Manifests have theirs checked
+/// when read, so that is never the first anyone hears of it.
 pub fn select<'a>(root: &'a Value, path: Option<&str>) -> Vec<&'a Value> {
     let Some(path) = path else {
         return match root {
@@ -64,21 +261,11 @@ pub fn select<'a>(root: &'a Value, path: Option<&str>) -> Vec<&'a Value> {
         };
     };
 
-    let trimmed = path.trim().trim_start_matches('$');
-    let wants_each = trimmed.ends_with("[*]");
-    let trimmed = trimmed.trim_end_matches("[*]");
-
-    let mut current = root;
-    for segment in trimmed.split('.').filter(|s| !s.is_empty()) {
-        match current.get(segment) {
-            Some(next) => current = next,
-            None => return Vec::new(),
-        }
-    }
-
-    match current {
-        Value::Array(items) if wants_each => items.iter().collect(),
-        other => vec![other],
+    match parse_path(path) {
+        Ok(steps) => steps
+            .iter()
+            .fold(vec![root], |nodes, step| apply(step, nodes)),
+        Err(_) => Vec::new(),
     }
 }
 
@@ -106,9 +293,32 @@ pub fn records(op: &Op, stdout: &str, strip: bool) -> Result<Vec<Value>, String>
             .filter(|line| !line.trim().is_empty())
             .filter_map(|line| serde_json::from_str(line).ok())
             .collect()),
-        Format::KeyValue => Ok(key_value(op, stdout).into_iter().collect()),
+        Format::KeyValue => Ok(key_value(op, stdout.lines().skip(op.output.skip_header))
+            .into_iter()
+            .collect()),
+        Format::KeyValueBlocks => Ok(blocks(stdout.lines().skip(op.output.skip_header))
+            .into_iter()
+            .filter_map(|block| key_value(op, block.into_iter()))
+            .collect()),
+        Format::Columns => Ok(columns(stdout.lines().skip(op.output.skip_header))),
+        Format::Csv => {
+            let separator = op
+                .output
+                .separator
+                .as_deref()
+                .and_then(|written| written.chars().next())
+                .unwrap_or(',');
+            Ok(tabled(delimited(&after_header(op, stdout), separator)))
+        }
+        Format::Tsv => Ok(tabled(
+            after_header(op, stdout)
+                .lines()
+                .map(|line| line.split('\t').map(str::to_string).collect())
+                .collect(),
+        )),
         Format::Lines => {
             let pattern = op
+                .output
                 .pattern
                 .as_deref()
                 .ok_or_else(|| "the operation reads lines but has no pattern".to_string())?;
@@ -128,11 +338,11 @@ pub fn records(op: &Op, stdout: &str, strip: bool) -> Result<Vec<Value>, String>
 ///
 /// A name given more than once, as a manager listing several dependencies
 /// would, is kept as a list rather than the last one winning.
-fn key_value(op: &Op, printed: &str) -> Option<Value> {
+fn key_value<'a>(op: &Op, lines: impl Iterator<Item = &'a str>) -> Option<Value> {
     let separator = op.output.separator.as_deref().unwrap_or(":");
     let mut record = Map::new();
 
-    for line in printed.lines().skip(op.output.skip_header) {
+    for line in lines {
         let Some((name, value)) = line.split_once(separator) else {
             continue;
         };
@@ -163,6 +373,156 @@ fn key_value(op: &Op, printed: &str) -> Option<Value> {
     (!record.is_empty()).then_some(Value::Object(record))
 }
 
+/// The lines in each run of them between blank ones.
+fn blocks<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<Vec<&'a str>> {
+    let mut blocks = vec![Vec::new()];
+    for line in lines {
+        match line.trim().is_empty() {
+            true if blocks.last().is_some_and(|block| !block.is_empty()) => blocks.push(Vec::new()),
+            true => {}
+            false => blocks.last_mut().expect("there is always one").push(line),
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+    blocks
+}
+
+/// What is left once the lines the manifest says to skip are gone.
+fn after_header(op: &Op, printed: &str) -> String {
+    printed
+        .lines()
+        .skip(op.output.skip_header)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read a table lined up in columns, each named by the header above it.
+///
+/// A column starts where its name does. Names are told apart by two spaces
+/// or a tab between them, so one of two words such as `Last Updated` stays
+/// whole, unless the header has no gap that wide anywhere, in which case a
+/// single space separates them. A line of nothing but dashes or equals signs
+/// under the header is a rule, not a record.
+fn columns<'a>(mut lines: impl Iterator<Item = &'a str>) -> Vec<Value> {
+    let Some(header) = lines.find(|line| !line.trim().is_empty()) else {
+        return Vec::new();
+    };
+    let header: Vec<char> = header.chars().collect();
+    let wide = header.windows(2).any(|pair| pair == [' ', ' ']) || header.contains(&'\t');
+    let gap = |at: usize| match wide {
+        true => header[at] == '\t' || header[at..].starts_with(&[' ', ' ']),
+        false => header[at].is_whitespace(),
+    };
+
+    // Where each name starts, found as the first letter after a gap.
+    let mut starts = Vec::new();
+    let mut in_gap = true;
+    for (at, letter) in header.iter().enumerate() {
+        if letter.is_whitespace() && (gap(at) || in_gap) {
+            in_gap = true;
+        } else if !letter.is_whitespace() {
+            if in_gap {
+                starts.push(at);
+            }
+            in_gap = false;
+        }
+    }
+    let names: Vec<String> = starts
+        .iter()
+        .enumerate()
+        .map(|(index, &start)| {
+            let end = starts.get(index + 1).copied().unwrap_or(header.len());
+            header[start..end]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .collect();
+
+    lines
+        .filter(|line| {
+            let line = line.trim();
+            !line.is_empty() && !line.chars().all(|c| matches!(c, '-' | '=' | ' '))
+        })
+        .filter_map(|line| {
+            let line: Vec<char> = line.chars().collect();
+            let mut record = Map::new();
+            for (index, name) in names.iter().enumerate() {
+                let start = starts[index].min(line.len());
+                let end = match starts.get(index + 1) {
+                    Some(&next) => next.min(line.len()),
+                    // The last column runs to the end of the line, however
+                    // far past its name that goes.
+                    None => line.len(),
+                };
+                let value = line[start..end].iter().collect::<String>();
+                let value = value.trim();
+                if !value.is_empty() {
+                    record.insert(name.clone(), Value::String(value.to_string()));
+                }
+            }
+            (!record.is_empty()).then_some(Value::Object(record))
+        })
+        .collect()
+}
+
+/// Split separated values into rows, honouring double quotes: a quoted value
+/// may hold the separator, a line break, or a quote written twice.
+fn delimited(text: &str, separator: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                value.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if value.is_empty() => quoted = true,
+            c if c == separator && !quoted => row.push(std::mem::take(&mut value)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut value));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => value.push(c),
+        }
+    }
+    if !value.is_empty() || !row.is_empty() {
+        row.push(value);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Records from rows whose first is the header naming each value. Blank rows
+/// and empty values are passed over, as they are in every other format.
+fn tabled(rows: Vec<Vec<String>>) -> Vec<Value> {
+    let mut rows = rows
+        .into_iter()
+        .filter(|row| row.iter().any(|value| !value.trim().is_empty()));
+    let Some(header) = rows.next() else {
+        return Vec::new();
+    };
+    let header: Vec<String> = header.iter().map(|name| name.trim().to_string()).collect();
+
+    rows.filter_map(|row| {
+        let record: Map<String, Value> = header
+            .iter()
+            .zip(row)
+            .filter(|(name, value)| !name.is_empty() && !value.trim().is_empty())
+            .map(|(name, value)| (name.clone(), Value::String(value.trim().to_string())))
+            .collect();
+        (!record.is_empty()).then_some(Value::Object(record))
+    })
+    .collect()
+}
+
 fn captures(pattern: &regex::Regex, line: &str) -> Option<Value> {
     let found = pattern.captures(line)?;
     let mut record = Map::new();
@@ -325,6 +685,84 @@ output = { format = "json", select = "$.missing[*]" }"#);
         assert!(found.is_empty());
     }
 
+    fn picked(document: &str, path: &str) -> Vec<Value> {
+        let root: Value = serde_json::from_str(document).unwrap();
+        select(&root, Some(path)).into_iter().cloned().collect()
+    }
+
+    const REPOS: &str = r#"{"repos":[
+        {"name":"main","packages":[{"name":"a","installed":true},{"name":"b","installed":false}]},
+        {"name":"extra","packages":[{"name":"c","installed":true}]}
+    ]}"#;
+
+    #[test]
+    fn wildcards_nest() {
+        let found = picked(REPOS, "$.repos[*].packages[*]");
+        let names: Vec<&str> = found.iter().filter_map(|p| p["name"].as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn records_keyed_by_name_are_read_through_their_values() {
+        let found = picked(r#"{"fd":{"version":"9"},"rg":{"version":"14"}}"#, "$.*");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1]["version"], "14");
+    }
+
+    #[test]
+    fn an_index_picks_one_counting_from_either_end() {
+        assert_eq!(picked(REPOS, "$.repos[1].name"), vec![Value::from("extra")]);
+        assert_eq!(
+            picked(REPOS, "$.repos[-1].name"),
+            vec![Value::from("extra")]
+        );
+        assert_eq!(
+            picked(REPOS, "$['repos'][0]['name']"),
+            vec![Value::from("main")]
+        );
+        assert!(picked(REPOS, "$.repos[5]").is_empty());
+        assert!(picked(REPOS, "$.repos[-5]").is_empty());
+    }
+
+    #[test]
+    fn descent_finds_a_field_at_any_depth() {
+        let found = picked(REPOS, "$..packages[*]");
+        assert_eq!(found.len(), 3);
+        let names = picked(r#"{"a":{"name":"x","b":[{"name":"y"}]}}"#, "$..name");
+        assert_eq!(names, vec![Value::from("x"), Value::from("y")]);
+    }
+
+    #[test]
+    fn a_filter_keeps_only_what_it_holds_for() {
+        let installed = picked(REPOS, "$.repos[*].packages[?(@.installed==true)]");
+        let names: Vec<&str> = installed
+            .iter()
+            .filter_map(|p| p["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "c"]);
+
+        let others = picked(REPOS, "$..packages[?(@.name != 'a')].name");
+        assert_eq!(others, vec![Value::from("b"), Value::from("c")]);
+        assert_eq!(picked(REPOS, "$.repos[?(@.name==\"extra\")]").len(), 1);
+        assert_eq!(
+            picked(REPOS, "$.repos[0].packages[?(@.installed)]").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn a_path_that_cannot_be_read_says_why() {
+        assert!(parse_path("$.items[*]").is_ok());
+        assert!(parse_path("items[*]").is_ok());
+        assert!(parse_path("$.items[").is_err());
+        assert!(parse_path("$.items[x]").is_err());
+        assert!(parse_path("$..").is_err());
+        assert!(parse_path("$.a..").is_err());
+        assert!(parse_path("$.[?(@.a==true]").is_err());
+        assert!(parse_path("$.a[?(b==1)]").is_err());
+        assert!(parse_path("$.a[?(@.b==nope)]").is_err());
+    }
+
     #[test]
     fn a_stream_reads_a_record_a_line() {
         let op = op(r#"args = ["x"]
@@ -448,6 +886,104 @@ output = { format = "keyvalue" }"#);
         assert!(records(&op, "no names here\n", false).unwrap().is_empty());
     }
 
+    #[test]
+    fn each_block_between_blank_lines_is_a_record_of_its_own() {
+        let op = op(r#"args = ["x"]
+output = { format = "keyvalue_blocks" }"#);
+
+        // As `pacman -Qi` prints two packages, trailing blank lines and all.
+        let printed = concat!(
+            "Name            : bash\n",
+            "Version         : 5.2.037-1\n",
+            "Depends On      : readline  glibc\n",
+            "\n",
+            "\n",
+            "Name            : zsh\n",
+            "Version         : 5.9-5\n",
+            "\n"
+        );
+
+        let found = records(&op, printed, false).expect("should read");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0]["Name"], "bash");
+        assert_eq!(found[1]["Version"], "5.9-5");
+    }
+
+    #[test]
+    fn columns_are_read_under_the_names_heading_them() {
+        let op = op(r#"args = ["x"]
+output = { format = "columns" }"#);
+
+        // Two spaces or more between columns, so `Last Updated` is one name
+        // and a value with a space in it stays whole.
+        let printed = concat!(
+            "Name          Version   Last Updated  Notes\n",
+            "------------  --------  ------------  -----\n",
+            "GNU Image     2.10.38   2024-05-02    -\n",
+            "fd            9.0.0                   classic, held\n"
+        );
+
+        let found = records(&op, printed, false).expect("should read");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0]["Name"], "GNU Image");
+        assert_eq!(found[0]["Last Updated"], "2024-05-02");
+        assert!(found[1].get("Last Updated").is_none());
+        assert_eq!(found[1]["Notes"], "classic, held");
+    }
+
+    #[test]
+    fn a_header_with_single_spaces_splits_on_them() {
+        let op = op(r#"args = ["x"]
+output = { format = "columns", skip_header = 1 }"#);
+
+        let printed = "Listing...\nname version\nfd   9.0.0\nrg   14.1.0\n";
+        let found = records(&op, printed, false).expect("should read");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1]["name"], "rg");
+        assert_eq!(found[1]["version"], "14.1.0");
+    }
+
+    #[test]
+    fn comma_separated_values_are_read_with_their_quotes() {
+        let op = op(r#"args = ["x"]
+output = { format = "csv" }"#);
+
+        let printed = concat!(
+            "name,version,description\r\n",
+            "fd,9.0.0,\"Simple, fast \"\"find\"\"\"\r\n",
+            "\r\n",
+            "rg,14.1.0,\"Line one\nline two\"\r\n",
+            "bare,,\n"
+        );
+
+        let found = records(&op, printed, false).expect("should read");
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0]["description"], "Simple, fast \"find\"");
+        assert_eq!(found[1]["description"], "Line one\nline two");
+        assert!(found[2].get("version").is_none());
+    }
+
+    #[test]
+    fn a_separator_other_than_a_comma_can_be_named() {
+        let op = op(r#"args = ["x"]
+output = { format = "csv", separator = ";" }"#);
+
+        let found = records(&op, "name;version\nfd;9.0.0\n", false).expect("should read");
+        assert_eq!(found[0]["version"], "9.0.0");
+    }
+
+    #[test]
+    fn tab_separated_values_are_read_under_their_header() {
+        let op = op(r#"args = ["x"]
+output = { format = "tsv", skip_header = 1 }"#);
+
+        let printed = "# generated\nname\tversion\tbranch\nfd\t9.0.0\tstable\nrg\t\tbeta\n";
+        let found = records(&op, printed, false).expect("should read");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0]["branch"], "stable");
+        assert!(found[1].get("version").is_none());
+    }
+
     #[test]
     fn a_template_takes_the_first_form_it_can_complete() {
         let values = HashMap::from([
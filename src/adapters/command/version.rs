@@ -56,6 +56,37 @@ pub fn at_least(found: &str, required: &str) -> bool {
     true
 }
 
+/// Whether `found` falls within `range`, written as comparisons joined by
+/// commas, such as `>=0.13, <0.15`. Each of `>=`, `>`, `<=`, `<` and `=` is
+/// understood, and a bare version means `=`. Every comparison has to hold.
+///
+/// The whole range is read before any of it is compared, so one written
+/// wrongly is refused whatever version it is asked about.
+pub fn within(found: &str, range: &str) -> Result<bool, String> {
+    let bounds = range
+        .split(',')
+        .map(|bound| {
+            let bound = bound.trim();
+            let (op, wanted) = ["<=", ">=", "<", ">", "="]
+                .into_iter()
+                .find_map(|op| bound.strip_prefix(op).map(|rest| (op, rest.trim())))
+                .unwrap_or(("=", bound));
+            match wanted.is_empty() {
+                true => Err(format!("`{range}` compares against no version")),
+                false => Ok((op, wanted)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(bounds.into_iter().all(|(op, wanted)| match op {
+        ">=" => at_least(found, wanted),
+        ">" => !at_least(wanted, found),
+        "<=" => at_least(wanted, found),
+        "<" => !at_least(found, wanted),
+        _ => at_least(found, wanted) && at_least(wanted, found),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,4 +113,16 @@ mod tests {
         assert!(at_least("0.13.0.1", "0.13.0"));
         assert!(!at_least("0.13", "0.13.1"));
     }
+
+    #[test]
+    fn a_range_holds_only_where_every_bound_does() {
+        assert_eq!(within("0.13.2", ">=0.13, <0.15"), Ok(true));
+        assert_eq!(within("0.15.0", ">=0.13, <0.15"), Ok(false));
+        assert_eq!(within("0.12.9", ">=0.13"), Ok(false));
+        assert_eq!(within("2.0", "<=2.0"), Ok(true));
+        assert_eq!(within("2.0", ">2.0"), Ok(false));
+        assert_eq!(within("1.4", "1.4"), Ok(true));
+        assert!(within("1.4", ">=").is_err());
+        assert!(within("0.1", ">=1.0, <").is_err());
+    }
 }
@@ -8,11 +8,19 @@ use std::time::{Duration, Instant};
 use gpui::*;
 
 use crate::{
-    adapters::command::{self, CommandAdapter},
+    adapters::command::CommandAdapter,
     config::AerisConfig,
     core::{
-        adapter::Adapter, adapter_manager::AdapterManager, package::failure_among,
-        privilege::PackageMode, registry::PluginEntry,
+        adapter::{Adapter, CancelToken},
+        adapter_manager::{AdapterManager, Loaded, load_managers, pinned_in},
+        history::{self, Action, Outcome},
+        inventory::{self, ImportPlan, Inventory},
+        ownership,
+        package::{InstallResult, failure_among},
+        privilege::PackageMode,
+        queue::{JOBS_PER_MANAGER, Job, JobId, JobKind, JobQueue, JobState, named},
+        registry::PluginEntry,
+        shadowing::{self, list_package_binaries},
     },
     styles, theme, views,
 };
@@ -68,6 +76,10 @@ pub enum View {
     Browse,
     Installed,
     Updates,
+    Jobs,
+    History,
+    DiskUsage,
+    Doctor,
     AdapterInfo,
     Manifest,
     Settings,
@@ -89,6 +101,10 @@ impl std::fmt::Display for View {
             View::Browse => write!(f, "Browse"),
             View::Installed => write!(f, "Installed"),
             View::Updates => write!(f, "Updates"),
+            View::Jobs => write!(f, "Jobs"),
+            View::History => write!(f, "History"),
+            View::DiskUsage => write!(f, "Disk Usage"),
+            View::Doctor => write!(f, "Doctor"),
             View::AdapterInfo => write!(f, "Adapters"),
             View::Manifest => write!(f, "Manifest"),
             View::Settings => write!(f, "Settings"),
@@ -96,24 +112,6 @@ impl std::fmt::Display for View {
     }
 }
 
-pub enum OperationType {
-    Install,
-    Remove,
-    Update,
-    UpdateAll,
-}
-
-impl std::fmt::Display for OperationType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            OperationType::Install => write!(f, "Installing"),
-            OperationType::Remove => write!(f, "Removing"),
-            OperationType::Update => write!(f, "Updating"),
-            OperationType::UpdateAll => write!(f, "Updating all"),
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum OperationStatus {
     Starting,
@@ -127,6 +125,8 @@ pub enum OperationStatus {
     Working(String),
     Completed,
     Failed(String),
+    /// Stopped on request, which is neither of the other two endings.
+    Cancelled,
 }
 
 impl OperationStatus {
@@ -146,6 +146,7 @@ impl OperationStatus {
             OperationStatus::Working(phase) => phase.clone(),
             OperationStatus::Completed => "Completed".into(),
             OperationStatus::Failed(e) => format!("Failed: {e}"),
+            OperationStatus::Cancelled => "Cancelled".into(),
         }
     }
 
@@ -157,7 +158,7 @@ impl OperationStatus {
     pub fn is_finished(&self) -> bool {
         matches!(
             self,
-            OperationStatus::Completed | OperationStatus::Failed(_)
+            OperationStatus::Completed | OperationStatus::Failed(_) | OperationStatus::Cancelled
         )
     }
 
@@ -184,12 +185,6 @@ impl OperationStatus {
     }
 }
 
-pub struct ActiveOperation {
-    pub operation_type: OperationType,
-    pub package_name: String,
-    pub status: OperationStatus,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ToastLevel {
     Success,
@@ -222,44 +217,6 @@ pub struct RunningProcess {
     pub child: std::process::Child,
 }
 
-/// Find user-runnable binaries for a package: symlinks in `bin_path` whose
-/// canonicalized target lives inside `install_path`. Avoids launching
-/// internal helpers/libraries inside the install dir that happen to be
-/// marked executable but aren't meant to be invoked directly.
-pub(crate) fn list_package_binaries(
-    install_path: &std::path::Path,
-    bin_path: &std::path::Path,
-) -> Vec<std::path::PathBuf> {
-    let mut out = Vec::new();
-    let canonical_install = match std::fs::canonicalize(install_path) {
-        Ok(p) => p,
-        Err(_) => return out,
-    };
-    let read = match std::fs::read_dir(bin_path) {
-        Ok(r) => r,
-        Err(_) => return out,
-    };
-    for entry in read.flatten() {
-        let path = entry.path();
-        let symlink_meta = match path.symlink_metadata() {
-            Ok(m) => m,
-            Err(_) => continue,
-        };
-        if !symlink_meta.file_type().is_symlink() {
-            continue;
-        }
-        let canonical_target = match std::fs::canonicalize(&path) {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
-        if canonical_target.starts_with(&canonical_install) {
-            out.push(path);
-        }
-    }
-    out.sort();
-    out
-}
-
 /// Order results by how well they answer the query rather than by which
 /// manager happened to be asked first.
 ///
@@ -337,21 +294,45 @@ async fn held_by(adapter: &dyn Adapter, mode: PackageMode) -> HashMap<String, (S
         .unwrap_or_default()
 }
 
-/// The packages whose version moved, named so they can be reported.
-fn changed_between(
+/// A package whose version moved, and where it moved from and to.
+struct Moved {
+    id: String,
+    name: String,
+    was: Option<String>,
+    now: String,
+}
+
+/// The packages whose version moved, in name order.
+fn moved_between(
     before: &HashMap<String, (String, String)>,
     after: &HashMap<String, (String, String)>,
-) -> Vec<String> {
-    let mut moved: Vec<String> = after
+) -> Vec<Moved> {
+    let mut moved: Vec<Moved> = after
         .iter()
         .filter(|(id, (_, now))| before.get(*id).is_none_or(|(_, was)| was != now))
-        .map(|(_, (name, _))| name.clone())
+        .map(|(id, (name, now))| Moved {
+            id: id.clone(),
+            name: name.clone(),
+            was: before.get(id).map(|(_, was)| was.clone()),
+            now: now.clone(),
+        })
         .collect();
 
-    moved.sort();
+    moved.sort_by(|a, b| a.name.cmp(&b.name));
     moved
 }
 
+/// The packages whose version moved, named so they can be reported.
+fn changed_between(
+    before: &HashMap<String, (String, String)>,
+    after: &HashMap<String, (String, String)>,
+) -> Vec<String> {
+    moved_between(before, after)
+        .into_iter()
+        .map(|moved| moved.name)
+        .collect()
+}
+
 /// What to say once a manager has updated everything it holds.
 ///
 /// It reports nothing about the packages it touched, so the only honest
@@ -376,18 +357,6 @@ fn wholesale_outcome(adapter_name: &str, changed: &[String]) -> (ToastLevel, Str
     }
 }
 
-/// The version a manager holds for a package right now, or nothing when it
-/// cannot say.
-async fn version_of(adapter: &dyn Adapter, package_id: &str, mode: PackageMode) -> Option<String> {
-    adapter
-        .list_installed(mode)
-        .await
-        .ok()?
-        .into_iter()
-        .find(|installed| installed.package.id == package_id)
-        .map(|installed| installed.package.version)
-}
-
 /// What to say once an update has run without complaining.
 ///
 /// Exiting cleanly is not the same as having changed anything: a manager can
@@ -403,6 +372,123 @@ fn update_outcome(pkg_name: &str, was: &str, now: Option<String>) -> (ToastLevel
     }
 }
 
+/// What came back for each package, and what went wrong among them, from a
+/// task that may not have answered at all.
+fn settled(
+    result: std::result::Result<
+        crate::core::adapter::Result<Vec<InstallResult>>,
+        tokio::task::JoinError,
+    >,
+) -> (Vec<InstallResult>, Option<String>) {
+    match result {
+        Ok(Ok(results)) => {
+            let failure = failure_among(&results);
+            (results, failure)
+        }
+        Ok(Err(e)) => (Vec::new(), Some(e.to_string())),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    }
+}
+
+/// Whether one package went through, or why not.
+fn went(
+    results: &[InstallResult],
+    package: &crate::core::package::Package,
+    failure: &Option<String>,
+) -> std::result::Result<(), String> {
+    match results
+        .iter()
+        .find(|r| r.adapter_id == package.adapter_id && r.package_id == package.id)
+    {
+        Some(result) if result.success => Ok(()),
+        Some(result) => Err(result
+            .error
+            .clone()
+            .unwrap_or_else(|| "it gave no reason".to_string())),
+        // Nothing came back for it, so whatever stopped the rest stopped it.
+        None => Err(failure
+            .clone()
+            .unwrap_or_else(|| "it gave no reason".to_string())),
+    }
+}
+
+/// How a job ended, from what went wrong in it and whether it was asked to
+/// stop. Work that was stopped fails on the way out, and saying so would
+/// report a cancel as a fault.
+fn ended_with(failure: Option<String>, cancel: &CancelToken) -> JobState {
+    match failure {
+        Some(_) if cancel.is_cancelled() => JobState::Cancelled,
+        Some(why) => JobState::Failed(why),
+        None => JobState::Finished,
+    }
+}
+
+/// What putting an exported file back would do, in a line to confirm.
+fn import_summary(plan: &ImportPlan) -> String {
+    let mut said = match plan.install.len() {
+        0 => "Nothing to install.".to_string(),
+        1 => format!("Install {}?", plan.install[0].0.name),
+        count => format!("Install {count} packages?"),
+    };
+    if !plan.present.is_empty() {
+        said.push_str(&format!(" {} already here.", plan.present.len()));
+    }
+    if !plan.pin.is_empty() {
+        said.push_str(&format!(" {} to be pinned again.", plan.pin.len()));
+    }
+    for (held, why) in plan.unkept.iter().take(5) {
+        said.push_str(&format!(" {} {why}.", held.name));
+    }
+    if plan.unkept.len() > 5 {
+        said.push_str(&format!(
+            " {} more are not kept as they were.",
+            plan.unkept.len() - 5
+        ));
+    }
+    if !plan.missing.is_empty() {
+        let named: Vec<String> = plan
+            .missing
+            .iter()
+            .take(5)
+            .map(|(held, why)| format!("{} ({why})", held.name))
+            .collect();
+        let more = match plan.missing.len() {
+            n if n > 5 => format!(" and {} more", n - 5),
+            _ => String::new(),
+        };
+        said.push_str(&format!(" Cannot put back: {}{more}.", named.join(", ")));
+    }
+    said
+}
+
+/// What a question about removing these packages is known by, so an answer
+/// about what needs them is only shown against the question it was for.
+fn removal_key(packages: &[crate::core::package::Package]) -> String {
+    packages
+        .iter()
+        .map(|p| crate::core::adapter::progress_key(&p.adapter_id, &p.id))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// How one package's part in a change ended, for the journal.
+fn outcome_of(went: std::result::Result<(), String>, cancel: &CancelToken) -> Outcome {
+    match went {
+        Ok(()) => Outcome::Succeeded,
+        Err(_) if cancel.is_cancelled() => Outcome::Cancelled,
+        Err(why) => Outcome::Failed(why),
+    }
+}
+
+/// How a whole job ended, for the journal.
+fn job_outcome(state: &JobState) -> Outcome {
+    match state {
+        JobState::Failed(why) => Outcome::Failed(why.clone()),
+        JobState::Cancelled => Outcome::Cancelled,
+        _ => Outcome::Succeeded,
+    }
+}
+
 /// Take a manager in or out of the set a search is narrowed to.
 ///
 /// No manager picked and every manager picked both mean the same thing, so the
@@ -523,7 +609,34 @@ pub struct AdapterViewState {
     pub profiles_by_adapter: HashMap<String, Vec<crate::core::profile::Profile>>,
     pub profiles_loading: HashMap<String, bool>,
     pub profiles_error: HashMap<String, String>,
-    pub switching_profile: Option<String>,
+    /// The profile being switched to, made or deleted, and which of those.
+    pub changing_profile: Option<(String, ProfileChange)>,
+    /// How much room each manager's cache takes, or why it could not say.
+    pub cache_sizes: HashMap<String, Result<u64, String>>,
+    pub cache_measuring: HashSet<String>,
+    /// Managers whose cache is being cleaned, or waiting to be.
+    pub cleaning: HashSet<String>,
+    /// Aeris's own caches, once measured.
+    pub own_caches: Option<Vec<crate::core::cache::OwnCache>>,
+    pub own_caches_clearing: bool,
+}
+
+/// What is being done to a manager's profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileChange {
+    Switch,
+    Create,
+    Delete,
+}
+
+impl ProfileChange {
+    pub fn doing(&self) -> &'static str {
+        match self {
+            ProfileChange::Switch => "switching...",
+            ProfileChange::Create => "creating...",
+            ProfileChange::Delete => "deleting...",
+        }
+    }
 }
 
 pub struct App {
@@ -574,6 +687,9 @@ pub struct App {
     pub(crate) adapter_manager: AdapterManager,
     pub(crate) adapter_view: AdapterViewState,
     pub(crate) confirm_dialog: Option<ConfirmAction>,
+    /// What needs the packages a removal is being confirmed for, by the
+    /// progress keys of those packages, once their managers have said.
+    pub(crate) removal_needed_by: Option<(String, Vec<crate::core::package::Package>)>,
     /// What a manager stopped to ask, and the way back to it.
     pub(crate) question: Option<Question>,
     pub(crate) answer_input: Entity<crate::components::TextInput>,
@@ -581,9 +697,11 @@ pub struct App {
     /// Running processes launched via Run, keyed by package unique_key.
     pub(crate) running_processes: HashMap<String, Vec<RunningProcess>>,
     next_run_id: u64,
-    active_operation: Option<ActiveOperation>,
-    package_progress: HashMap<String, OperationStatus>,
-    next_operation_id: u64,
+    /// Every change asked for, waiting, under way or ended, in the order
+    /// they were asked for.
+    pub(crate) jobs: JobQueue,
+    /// How to stop each job under way, for the list of jobs to offer.
+    job_cancels: HashMap<JobId, CancelToken>,
     toasts: Vec<Toast>,
     next_toast_id: u64,
     /// Latest BatchProgress event from any adapter: (adapter_id, completed, total, failed).
@@ -593,6 +711,9 @@ pub struct App {
     pub(crate) output_log: HashMap<String, std::collections::VecDeque<String>>,
     /// The operation whose output is open, if any.
     pub(crate) open_log: Option<String>,
+    /// How to stop each operation under way, keyed the same way as progress.
+    /// Every package in a batch holds the batch's one token.
+    cancels: HashMap<String, CancelToken>,
     /// Holds the asked text at its end, where the question itself is.
     question_scroll: ScrollHandle,
     /// Keeps the open output log on its newest line.
@@ -608,9 +729,17 @@ pub struct App {
     pub(crate) updates_state: views::updates::UpdatesState,
     pub(crate) settings_state: views::settings::SettingsState,
     pub(crate) manifest_state: views::manifest::ManifestState,
+    pub(crate) history_state: views::history::HistoryState,
+    pub(crate) disk_usage_state: views::disk_usage::DiskUsageState,
+    pub(crate) doctor_state: views::doctor::DoctorState,
 
     // Text input entities
     pub(crate) search_input: Entity<crate::components::TextInput>,
+    pub(crate) history_package_input: Entity<crate::components::TextInput>,
+    pub(crate) history_from_input: Entity<crate::components::TextInput>,
+    pub(crate) history_until_input: Entity<crate::components::TextInput>,
+    pub(crate) owner_path_input: Entity<crate::components::TextInput>,
+    pub(crate) profile_name_input: Entity<crate::components::TextInput>,
 
     /// Focus handle so the root div can receive app-level key actions
     /// (Escape, Enter) when no other element is focused.
@@ -739,21 +868,6 @@ fn spawn_manifest_watcher(
     )
 }
 
-/// Register an adapter unless one already answers to its id.
-///
-/// Ids decide where settings are kept, so the one registered first keeps the
-/// name rather than being replaced by whatever was discovered later.
-fn register_new(manager: &mut AdapterManager, adapter: Arc<dyn Adapter>) {
-    let id = adapter.info().id.clone();
-
-    if manager.get_adapter(&id).is_some() {
-        log::warn!("Ignoring a second adapter named {id}");
-        return;
-    }
-
-    manager.register(adapter);
-}
-
 impl App {
     pub fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
         let aeris_config = AerisConfig::load();
@@ -761,78 +875,24 @@ impl App {
         let selected_theme = aeris_config.theme();
         let startup_view = aeris_config.startup_view();
 
-        let mut adapter_manager = AdapterManager::new();
-        let mut soar_problem = None;
-        let mut paths = HashMap::new();
-
-        // Turned off means left alone: no looking for it, and nothing said
-        // about not finding it.
-        let adapter: Option<Arc<dyn Adapter>> = if aeris_config.is_adapter_disabled(SOAR_ID) {
-            None
-        } else {
-            // Soar describes itself, so aeris drives whichever one is
-            // installed rather than the one it was built against.
-            match CommandAdapter::from_command(SOAR_ID, command::DESCRIBE_ARGS)
-                .map(CommandAdapter::as_builtin)
-            {
-                Ok(soar) => {
-                    log::info!("Driving soar {}", soar.info().version);
-                    paths = soar.file_paths().unwrap_or_else(|e| {
-                        log::warn!("soar did not say where its files are: {e}");
-                        HashMap::new()
-                    });
-
-                    let adapter: Arc<dyn Adapter> = Arc::new(soar);
-                    adapter_manager.register(adapter.clone());
-                    Some(adapter)
-                }
-                // Said on the adapters page instead of thrown as a message,
-                // so it can be read once and turned off rather than met at
-                // every start.
-                Err(e) => {
-                    log::warn!("Soar is unavailable: {e}");
-                    soar_problem = Some(match &e {
-                        crate::core::adapter::AdapterError::PluginError(said) => said.clone(),
-                        other => other.to_string(),
-                    });
-                    None
-                }
-            }
+        // Why soar could not be driven is said on the adapters page instead
+        // of thrown as a message, so it can be read once and turned off
+        // rather than met at every start.
+        let Loaded {
+            manager: adapter_manager,
+            soar,
+            soar_problem,
+        } = load_managers(&aeris_config);
+        let paths = match &soar {
+            Some(soar) => soar.file_paths().unwrap_or_else(|e| {
+                log::warn!("soar did not say where its files are: {e}");
+                HashMap::new()
+            }),
+            None => HashMap::new(),
         };
+        let adapter: Option<Arc<dyn Adapter>> = soar.map(|soar| soar as Arc<dyn Adapter>);
 
-        for result in crate::adapters::command::load_all() {
-            match result {
-                Ok(manifest_adapter) => {
-                    log::info!("Loaded adapter: {}", manifest_adapter.info().id);
-                    register_new(&mut adapter_manager, Arc::new(manifest_adapter));
-                }
-                Err(e) => log::warn!("Failed to load adapter: {e}"),
-            }
-        }
-
-        let disabled: std::collections::HashSet<String> =
-            aeris_config.disabled_adapters.iter().cloned().collect();
-        adapter_manager.set_disabled(disabled);
-
-        // Whichever scope the adapters actually work in. A manager that only
-        // ever acts system wide would otherwise have its packages counted and
-        // labelled as the user's, and one that is turned off should have no
-        // say at all: what is left on decides what opens.
-        let default_mode = if adapter_manager
-            .enabled_adapters()
-            .iter()
-            .any(|info| info.capabilities.supports_user_packages)
-        {
-            PackageMode::User
-        } else if adapter_manager
-            .enabled_adapters()
-            .iter()
-            .any(|info| info.capabilities.supports_system_packages)
-        {
-            PackageMode::System
-        } else {
-            PackageMode::User
-        };
+        let default_mode = adapter_manager.preferred_mode();
         let (progress_sender, progress_receiver) = tokio::sync::mpsc::unbounded_channel();
 
         let settings_state = match adapter.as_ref() {
@@ -842,6 +902,16 @@ impl App {
 
         let search_input = cx.new(|cx| crate::components::TextInput::new(cx, "Search packages..."));
         let answer_input = cx.new(|cx| crate::components::TextInput::new(cx, "Your answer..."));
+        let history_package_input =
+            cx.new(|cx| crate::components::TextInput::new(cx, "Package..."));
+        let history_from_input =
+            cx.new(|cx| crate::components::TextInput::new(cx, "From YYYY-MM-DD"));
+        let history_until_input =
+            cx.new(|cx| crate::components::TextInput::new(cx, "Until YYYY-MM-DD"));
+        let owner_path_input =
+            cx.new(|cx| crate::components::TextInput::new(cx, "Which package owns a path?"));
+        let profile_name_input =
+            cx.new(|cx| crate::components::TextInput::new(cx, "New profile name"));
 
         let (manifest_watcher_rx, manifest_watcher) =
             spawn_manifest_watcher(paths.get("packages_config").map(std::path::Path::new));
@@ -920,6 +990,30 @@ impl App {
         )
         .detach();
 
+        // Whatever was waiting when aeris last closed picks up where it was,
+        // once there is an app to run it.
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        let waiting: Vec<JobKind> = app
+                            .jobs
+                            .jobs()
+                            .iter()
+                            .filter(|job| job.state == JobState::Queued)
+                            .map(|job| job.kind.clone())
+                            .collect();
+                        for kind in &waiting {
+                            app.show_waiting(kind);
+                        }
+                        app.run_ready_jobs(cx);
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+
         // Poll for progress events periodically
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
@@ -955,19 +1049,20 @@ impl App {
             adapter_manager,
             adapter_view: AdapterViewState::default(),
             confirm_dialog: None,
+            removal_needed_by: None,
             question: None,
             answer_input,
             run_picker: None,
             running_processes: HashMap::new(),
             next_run_id: 1,
-            active_operation: None,
-            package_progress: HashMap::new(),
-            next_operation_id: 1,
+            jobs: JobQueue::open(JobQueue::default_path()),
+            job_cancels: HashMap::new(),
             toasts: Vec::new(),
             next_toast_id: 1,
             batch_progress: None,
             output_log: HashMap::new(),
             open_log: None,
+            cancels: HashMap::new(),
             question_scroll: ScrollHandle::new(),
             output_scroll: ScrollHandle::new(),
             progress_sender,
@@ -979,7 +1074,15 @@ impl App {
             updates_state: views::updates::UpdatesState::default(),
             settings_state,
             manifest_state: views::manifest::ManifestState::default(),
+            history_state: views::history::HistoryState::default(),
+            disk_usage_state: views::disk_usage::DiskUsageState::default(),
+            doctor_state: views::doctor::DoctorState::default(),
             search_input,
+            history_package_input,
+            history_from_input,
+            history_until_input,
+            owner_path_input,
+            profile_name_input,
             focus_handle: cx.focus_handle(),
             pending_settings_edit_focus: false,
             manifest_watcher_rx,
@@ -1165,9 +1268,10 @@ impl App {
 
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let (all_packages, updatable_adapters) = crate::tokio_spawn(async move {
+                let (all_packages, updatable_adapters, orphans) = crate::tokio_spawn(async move {
                     let mut all_packages = Vec::new();
                     let mut updatable_adapters = std::collections::HashSet::new();
+                    let mut orphans = Vec::new();
 
                     for adapter in &manager_adapters {
                         match adapter.list_installed(mode).await {
@@ -1178,8 +1282,14 @@ impl App {
                         if caps.can_update && caps.can_update_one && !caps.can_list_updates {
                             updatable_adapters.insert(adapter.info().id.clone());
                         }
+                        if caps.can_list_orphans {
+                            match adapter.list_orphans(mode).await {
+                                Ok(found) => orphans.extend(found),
+                                Err(e) => log::warn!("List orphans failed: {e}"),
+                            }
+                        }
                     }
-                    (all_packages, updatable_adapters)
+                    (all_packages, updatable_adapters, orphans)
                 })
                 .await
                 .unwrap_or_default();
@@ -1192,6 +1302,12 @@ impl App {
                         app.installed_state.loaded = true;
                         app.installed_state.result_version += 1;
                         app.installed_state.updatable_adapters = updatable_adapters;
+                        app.installed_state.orphans = orphans;
+                        // What was removed or installed since may have
+                        // settled a conflict or started one.
+                        if app.installed_state.conflicts_checked {
+                            app.check_conflicts(cx);
+                        }
 
                         cx.notify();
                     })
@@ -1331,104 +1447,13 @@ impl App {
     }
 
     pub fn apply_manifest(&mut self, prune: bool, cx: &mut Context<Self>) {
-        use crate::manifest_file::ManifestLoadError;
-        use views::manifest::ManifestStatus;
-
         let Some(adapter_id) = self.adapter.as_ref().map(|a| a.info().id.clone()) else {
             return;
         };
-        let seed_keys: Vec<String> = match &self.manifest_state.status {
-            ManifestStatus::Loaded(diff) => {
-                let mut keys: Vec<String> = diff
-                    .to_install
-                    .iter()
-                    .chain(diff.to_update.iter())
-                    .map(|e| crate::core::adapter::package_key(&adapter_id, &e.name))
-                    .collect();
-                if prune {
-                    keys.extend(
-                        diff.to_remove
-                            .iter()
-                            .map(|e| crate::core::adapter::package_key(&adapter_id, &e.name)),
-                    );
-                }
-                keys
-            }
-            _ => Vec::new(),
-        };
-        for key in seed_keys {
-            self.record_progress(key, OperationStatus::Starting);
-        }
 
-        self.manifest_state.applying = true;
         self.manifest_state.apply_error = None;
         self.manifest_state.last_report = None;
-
-        let Some(adapter) = self.adapter.clone() else {
-            return;
-        };
-        let progress = self.progress_sender.clone();
-
-        cx.spawn(
-            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let result = crate::tokio_spawn(async move {
-                    adapter
-                        .declarative_apply(prune, Some(progress))
-                        .await
-                        .map_err(|e| ManifestLoadError::Other(e.to_string()))
-                })
-                .await;
-
-                let _ = cx.update(|cx| {
-                    this.update(cx, |app, cx| {
-                        app.manifest_state.applying = false;
-                        match result {
-                            Ok(Ok(report)) => {
-                                app.manifest_state.last_report = Some(report);
-                                let msg = format!(
-                                    "Manifest applied: {} installed, {} updated, {} removed",
-                                    report.installed, report.updated, report.removed
-                                );
-                                if report.failed > 0 {
-                                    app.add_toast(
-                                        ToastLevel::Error,
-                                        format!("{msg}, {} failed", report.failed),
-                                    );
-                                } else {
-                                    app.add_toast(ToastLevel::Success, msg);
-                                }
-                            }
-                            Ok(Err(err)) => {
-                                let msg = match err {
-                                    ManifestLoadError::FileMissing => {
-                                        "Manifest file is missing".to_string()
-                                    }
-                                    ManifestLoadError::Parse(e) | ManifestLoadError::Other(e) => e,
-                                };
-                                app.manifest_state.apply_error = Some(msg.clone());
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Manifest apply failed: {msg}"),
-                                );
-                            }
-                            Err(e) => {
-                                let msg = format!("{e}");
-                                app.manifest_state.apply_error = Some(msg.clone());
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Manifest apply failed: {msg}"),
-                                );
-                            }
-                        }
-                        app.installed_state.loaded = false;
-                        app.updates_state.checked = false;
-                        app.load_manifest_diff(cx);
-                        cx.notify();
-                    })
-                });
-            },
-        )
-        .detach();
+        self.enqueue(JobKind::Apply { adapter_id, prune }, self.current_mode, cx);
     }
 
     pub fn open_manifest_add(&mut self, cx: &mut Context<Self>) {
@@ -1638,15 +1663,23 @@ impl App {
 
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let (all_updates, limits) = crate::tokio_spawn(async move {
+                let (all_updates, limits, pinned) = crate::tokio_spawn(async move {
                     let mut all_updates = Vec::new();
                     let mut limits: Vec<views::updates::ManagerLimit> = Vec::new();
+                    let mut pinned = HashSet::new();
 
                     for adapter in &manager_adapters {
                         let caps = adapter.capabilities();
                         if caps.can_list_updates {
                             match adapter.list_updates(mode).await {
-                                Ok(updates) => all_updates.extend(updates),
+                                Ok(updates) => {
+                                    // Only worth asking a manager that has
+                                    // something on offer.
+                                    if !updates.is_empty() {
+                                        pinned.extend(pinned_in(adapter.as_ref(), mode).await);
+                                    }
+                                    all_updates.extend(updates);
+                                }
                                 Err(e) => log::warn!("Check updates failed: {e}"),
                             }
                         }
@@ -1668,14 +1701,17 @@ impl App {
                             can_update_all: caps.can_update_all,
                         });
                     }
-                    (all_updates, limits)
+                    (all_updates, limits, pinned)
                 })
                 .await
                 .unwrap_or_default();
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
+                        let mut all_updates = all_updates;
+                        crate::core::package::security_first(&mut all_updates);
                         app.updates_state.updates = all_updates;
+                        app.updates_state.pinned = pinned;
                         app.updates_state.loading = false;
                         app.updates_state.checked = true;
                         app.updates_state.limits = limits;
@@ -1692,81 +1728,84 @@ impl App {
         if self.updates_state.updates.is_empty() {
             return;
         }
-        self.updates_state.updating = Some("__all__".to_string());
         let packages: Vec<_> = self
             .updates_state
             .updates
             .iter()
             .map(|u| u.package.clone())
             .collect();
-        let mode = self.current_mode;
-        let progress_sender = self.progress_sender.clone();
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
-            .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
+        let packages = self.hold_back_pinned(packages);
+        if packages.is_empty() {
+            return;
+        }
+        self.enqueue(JobKind::Update(packages), self.current_mode, cx);
+    }
+
+    /// Open an update's changelog, reading it the first time, or close it.
+    pub fn toggle_changelog(
+        &mut self,
+        update: &crate::core::package::Update,
+        cx: &mut Context<Self>,
+    ) {
+        let key = crate::core::adapter::package_key(&update.package.adapter_id, &update.package.id);
+        self.remeasure_update(&key);
+        if !self.updates_state.changelog_open.insert(key.clone()) {
+            self.updates_state.changelog_open.remove(&key);
+            cx.notify();
+            return;
+        }
+        cx.notify();
+
+        let Some(adapter) = self.adapter_manager.get_adapter(&update.package.adapter_id) else {
+            return;
+        };
+        if !adapter.capabilities().has_changelog
+            || self.updates_state.changelogs.contains_key(&key)
+            || !self.updates_state.changelog_loading.insert(key.clone())
+        {
+            return;
+        }
 
-        let count = packages.len();
+        let mode = self.current_mode;
+        let update = update.clone();
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in &packages {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg.clone());
-                    }
+                let notes = crate::tokio_spawn(async move {
+                    crate::core::changelog::notes_for(adapter.as_ref(), &update, mode).await
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
 
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .update(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(results) => {
-                                    // Answering is not the same as
-                                    // having worked: each package
-                                    // carries its own outcome.
-                                    if let Some(why) = failure_among(&results) {
-                                        log::error!("Update failed for {adapter_id}: {why}");
-                                        errors.push(why);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Update failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
-                })
-                .await
-                .unwrap_or_default();
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.updates_state.changelog_loading.remove(&key);
+                        app.remeasure_update(&key);
+                        app.updates_state.changelogs.insert(key, notes);
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Open the log of unattended update runs, reading it afresh, or close it.
+    pub fn toggle_unattended_log(&mut self, cx: &mut Context<Self>) {
+        self.updates_state.unattended_open = !self.updates_state.unattended_open;
+        cx.notify();
+        if !self.updates_state.unattended_open {
+            return;
+        }
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let runs = crate::tokio_spawn(async move { crate::core::unattended::read_log() })
+                    .await
+                    .unwrap_or_default();
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
-                        app.updates_state.updating = None;
-                        app.updates_state.updates.clear();
-                        app.updates_state.result_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(ToastLevel::Success, format!("Updated {count} packages"));
-                        } else {
-                            for err in &errors {
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to update: {err}"),
-                                );
-                            }
-                        }
-                        app.installed_state.loaded = false;
+                        app.updates_state.unattended_runs = runs;
                         cx.notify();
                     })
                 });
@@ -1775,12 +1814,61 @@ impl App {
         .detach();
     }
 
-    pub fn update_selected(&mut self, cx: &mut Context<Self>) {
-        if self.updates_state.selected.is_empty() {
+    /// The list keeps the height each row had, so a row that grows has to be
+    /// measured again. Only that row is, which keeps the list where it was
+    /// scrolled to.
+    fn remeasure_update(&self, key: &str) {
+        let found = self.updates_state.updates.iter().position(|u| {
+            crate::core::adapter::package_key(&u.package.adapter_id, &u.package.id) == key
+        });
+        if let Some(idx) = found {
+            self.updates_list.splice(idx..idx + 1, 1);
+        }
+    }
+
+    /// Update only what fixes a security problem, leaving routine bumps for
+    /// when there is time to look at them.
+    pub fn update_security(&mut self, cx: &mut Context<Self>) {
+        let packages: Vec<_> = self
+            .updates_state
+            .updates
+            .iter()
+            .filter(|u| u.is_security)
+            .map(|u| u.package.clone())
+            .collect();
+        let packages = self.hold_back_pinned(packages);
+        if packages.is_empty() {
             return;
         }
-        self.updates_state.updating = Some("__batch__".to_string());
-        let selected = self.updates_state.selected.clone();
+        self.enqueue(JobKind::Update(packages), self.current_mode, cx);
+    }
+
+    /// Leave out the pinned packages, saying how many were left.
+    fn hold_back_pinned(
+        &mut self,
+        packages: Vec<crate::core::package::Package>,
+    ) -> Vec<crate::core::package::Package> {
+        let (held, free): (Vec<_>, Vec<_>) = packages.into_iter().partition(|p| {
+            self.updates_state
+                .pinned
+                .contains(&crate::core::adapter::package_key(&p.adapter_id, &p.id))
+        });
+        match held.as_slice() {
+            [] => {}
+            [one] => self.add_toast(
+                ToastLevel::Info,
+                format!("Held back {}, which is pinned", one.name),
+            ),
+            _ => self.add_toast(
+                ToastLevel::Info,
+                format!("Held back {} pinned packages", held.len()),
+            ),
+        }
+        free
+    }
+
+    pub fn update_selected(&mut self, cx: &mut Context<Self>) {
+        let selected = std::mem::take(&mut self.updates_state.selected);
         let packages: Vec<_> = self
             .updates_state
             .updates
@@ -1793,97 +1881,15 @@ impl App {
             })
             .map(|u| u.package.clone())
             .collect();
-        for pkg in &packages {
-            let key = crate::core::adapter::progress_key(&pkg.adapter_id, &pkg.id);
-            self.updates_state
-                .package_progress
-                .insert(key, OperationStatus::Starting);
+        let packages = self.hold_back_pinned(packages);
+        if packages.is_empty() {
+            return;
         }
-        let mode = self.current_mode;
-        let progress_sender = self.progress_sender.clone();
-
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
-            .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
-
-        let count = packages.len();
-        cx.spawn(
-            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in &packages {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg.clone());
-                    }
-
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .update(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(results) => {
-                                    // Answering is not the same as
-                                    // having worked: each package
-                                    // carries its own outcome.
-                                    if let Some(why) = failure_among(&results) {
-                                        log::error!(
-                                            "Update selected failed for {adapter_id}: {why}"
-                                        );
-                                        errors.push(why);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Update selected failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
-                })
-                .await
-                .unwrap_or_default();
-
-                let _ = cx.update(|cx| {
-                    this.update(cx, |app, cx| {
-                        app.updates_state.updating = None;
-                        app.updates_state.selected.clear();
-                        app.updates_state.result_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(ToastLevel::Success, format!("Updated {count} packages"));
-                        } else {
-                            for err in &errors {
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to update: {err}"),
-                                );
-                            }
-                        }
-                        app.installed_state.loaded = false;
-                        cx.notify();
-                    })
-                });
-            },
-        )
-        .detach();
+        self.enqueue(JobKind::Update(packages), self.current_mode, cx);
     }
 
     pub fn install_selected_browse(&mut self, cx: &mut Context<Self>) {
-        if self.browse_state.selected.is_empty() {
-            return;
-        }
-        self.browse_state.installing = Some("__batch__".to_string());
-        let selected = self.browse_state.selected.clone();
+        let selected = std::mem::take(&mut self.browse_state.selected);
         let packages: Vec<_> = self
             .browse_state
             .search_results
@@ -1894,260 +1900,55 @@ impl App {
             })
             .cloned()
             .collect();
-        let package_ids: Vec<String> = packages.iter().map(|p| p.id.clone()).collect();
-        let progress_keys: Vec<String> = packages
-            .iter()
-            .map(|p| crate::core::adapter::progress_key(&p.adapter_id, &p.id))
-            .collect();
-        for key in &progress_keys {
-            self.browse_state
-                .package_progress
-                .insert(key.clone(), OperationStatus::Starting);
+        if packages.is_empty() {
+            return;
         }
-        let mode = self.current_mode;
-        let progress_sender = self.progress_sender.clone();
+        self.enqueue(JobKind::Install(packages), self.current_mode, cx);
+    }
 
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
+    /// The packages picked in the Installed view.
+    fn selected_installed(&self) -> Vec<crate::core::package::Package> {
+        self.installed_state
+            .packages
             .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
-
-        cx.spawn(
-            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in &packages {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg.clone());
-                    }
-
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .install(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(results) => {
-                                    if let Some(why) = failure_among(&results) {
-                                        log::error!(
-                                            "Install selected failed for {adapter_id}: {why}"
-                                        );
-                                        errors.push(why);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Install selected failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
-                })
-                .await
-                .unwrap_or_default();
-
-                let _ = cx.update(|cx| {
-                    this.update(cx, |app, cx| {
-                        app.browse_state.installing = None;
-                        app.browse_state.selected.clear();
-
-                        // Only what actually went through is marked, and the
-                        // reason is worth more than the count when it did not.
-                        if errors.is_empty() {
-                            for p in &mut app.browse_state.search_results {
-                                if package_ids.contains(&p.id) {
-                                    p.installed = true;
-                                }
-                            }
-                            app.add_toast(
-                                ToastLevel::Success,
-                                format!("Installed {} packages", package_ids.len()),
-                            );
-                        } else {
-                            app.add_toast(
-                                ToastLevel::Error,
-                                format!("Install failed. {}", errors.join("; ")),
-                            );
-                        }
+            .filter(|p| self.installed_state.selected.contains(&p.unique_key()))
+            .map(|p| p.package.clone())
+            .collect()
+    }
 
-                        for key in &progress_keys {
-                            app.browse_state.package_progress.remove(key);
-                        }
-                        app.browse_state.result_version += 1;
-                        app.installed_state.loaded = false;
-                        cx.notify();
-                    })
-                });
-            },
-        )
-        .detach();
+    /// Ask before removing what is picked in the Installed view, and say
+    /// what needs any of it once the managers have answered.
+    pub(crate) fn ask_to_remove_selected(&mut self, cx: &mut Context<Self>) {
+        let packages = self.selected_installed();
+        self.confirm_dialog = Some(ConfirmAction::BatchRemoveInstalled {
+            count: packages.len(),
+        });
+        self.look_for_needed_by(packages, self.current_mode, cx);
     }
 
     pub fn remove_selected_installed(&mut self, cx: &mut Context<Self>) {
-        if self.installed_state.selected.is_empty() {
+        let packages = self.selected_installed();
+        self.installed_state.selected.clear();
+        if packages.is_empty() {
             return;
         }
-        self.installed_state.removing = Some("__batch__".to_string());
-        let selected = self.installed_state.selected.clone();
-        let packages: Vec<_> = self
-            .installed_state
-            .packages
-            .iter()
-            .filter(|p| selected.contains(&p.unique_key()))
-            .map(|p| p.package.clone())
-            .collect();
-        let progress_keys: Vec<String> = packages
-            .iter()
-            .map(|p| crate::core::adapter::progress_key(&p.adapter_id, &p.id))
-            .collect();
-        for key in &progress_keys {
-            self.installed_state
-                .package_progress
-                .insert(key.clone(), OperationStatus::Starting);
-        }
-        let count = packages.len();
-        let mode = self.current_mode;
-        let progress_sender = self.progress_sender.clone();
-
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
-            .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
-
-        cx.spawn(
-            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in &packages {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg.clone());
-                    }
-
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .remove(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(_) => log::info!("Removed selected packages for {adapter_id}"),
-                                Err(e) => {
-                                    log::error!("Remove selected failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
-                })
-                .await
-                .unwrap_or_default();
-
-                let _ = cx.update(|cx| {
-                    this.update(cx, |app, cx| {
-                        app.installed_state.removing = None;
-                        app.installed_state.selected.clear();
-                        for key in &progress_keys {
-                            app.installed_state.package_progress.remove(key);
-                        }
-                        app.installed_state.result_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(ToastLevel::Success, format!("Removed {count} packages"));
-                        } else {
-                            for err in &errors {
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to remove: {err}"),
-                                );
-                            }
-                        }
-                        app.load_installed(cx);
-                    })
-                });
-            },
-        )
-        .detach();
+        self.enqueue(JobKind::Remove(packages), self.current_mode, cx);
     }
 
     pub fn sync_all_repos(&mut self, cx: &mut Context<Self>) {
         if self.adapter_view.syncing.is_some() {
             return;
         }
-        self.adapter_view.syncing = Some("__all__".to_string());
         self.adapter_view.sync_error = None;
 
-        let progress_sender = self.progress_sender.clone();
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
+        let adapters: Vec<String> = self
             .adapter_manager
             .list_adapters()
             .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
+            .filter(|info| info.capabilities.can_sync)
+            .map(|info| info.id.clone())
             .collect();
-
-        cx.spawn(
-            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut errors: Vec<(String, String)> = Vec::new();
-                    for adapter in &manager_adapters {
-                        if adapter.capabilities().can_sync {
-                            match adapter.sync(Some(progress_sender.clone())).await {
-                                Ok(_) => log::info!("Synced {}", adapter.info().id),
-                                Err(e) => {
-                                    log::warn!("Sync failed for {}: {e}", adapter.info().id);
-                                    errors.push((adapter.info().id.clone(), format!("{e}")));
-                                }
-                            }
-                        }
-                    }
-                    errors
-                })
-                .await
-                .unwrap_or_default();
-
-                let _ = cx.update(|cx| {
-                    this.update(cx, |app, cx| {
-                        app.adapter_view.syncing = None;
-                        app.adapter_view.repos_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(ToastLevel::Success, "Repositories synced".into());
-                            app.adapter_view.sync_error = None;
-                        } else {
-                            for (adapter_id, err) in &errors {
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Sync failed for {adapter_id}: {err}"),
-                                );
-                            }
-                            app.adapter_view.sync_error = Some(
-                                errors
-                                    .iter()
-                                    .map(|(id, e)| format!("{id}: {e}"))
-                                    .collect::<Vec<_>>()
-                                    .join("; "),
-                            );
-                        }
-                        cx.notify();
-                    })
-                });
-            },
-        )
-        .detach();
+        self.enqueue(JobKind::Sync(adapters), self.current_mode, cx);
     }
 
     pub fn load_repos(&mut self, cx: &mut Context<Self>) {
@@ -2248,6 +2049,8 @@ impl App {
                     plugin_path: None,
                     description: "Fast package manager for static binaries".to_string(),
                     icon: None,
+                    deprecations: Vec::new(),
+                    inherited: Vec::new(),
                 },
                 reason,
             ));
@@ -2276,6 +2079,8 @@ impl App {
                             plugin_path: Some(path),
                             description: manifest.description,
                             icon: manifest.icon,
+                            deprecations: manifest.deprecations,
+                            inherited: manifest.inherited,
                         },
                         reason,
                     )
@@ -2298,7 +2103,7 @@ impl App {
         let name = manifest.name.clone();
         match CommandAdapter::new(manifest, Some(path)) {
             Ok(adapter) => {
-                register_new(&mut self.adapter_manager, Arc::new(adapter));
+                self.adapter_manager.register_new(Arc::new(adapter));
                 self.add_toast(ToastLevel::Success, format!("{name} is ready"));
             }
             Err(e) => {
@@ -2393,13 +2198,15 @@ impl App {
         let id = entry.id.clone();
         let name = entry.name.clone();
         self.adapter_view.installing_plugin = Some(id.clone());
+        let offered = self.adapter_view.registry_plugins.clone();
 
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let fetched = crate::core::registry::download_plugin(&entry).and_then(|path| {
-                    let manifest = crate::adapters::command::manifest::load(&path)?;
-                    Ok((path, manifest))
-                });
+                let fetched =
+                    crate::core::registry::download_plugin(&entry, &offered).and_then(|path| {
+                        let manifest = crate::adapters::command::manifest::load(&path)?;
+                        Ok((path, manifest))
+                    });
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
@@ -2409,7 +2216,7 @@ impl App {
                             Ok((path, manifest)) => {
                                 match CommandAdapter::new(manifest, Some(path)) {
                                     Ok(adapter) => {
-                                        register_new(&mut app.adapter_manager, Arc::new(adapter));
+                                        app.adapter_manager.register_new(Arc::new(adapter));
                                         app.add_toast(ToastLevel::Success, format!("Added {name}"));
                                     }
                                     // The manifest is sound but the manager it
@@ -2640,46 +2447,99 @@ impl App {
         adapter_id: &str,
         profile_id: &str,
         cx: &mut Context<Self>,
+    ) {
+        self.change_profile(adapter_id, profile_id, ProfileChange::Switch, cx);
+    }
+
+    /// Make a profile named as typed on the Adapters page.
+    pub fn create_profile(&mut self, adapter_id: &str, cx: &mut Context<Self>) {
+        let name = self
+            .profile_name_input
+            .read(cx)
+            .content()
+            .trim()
+            .to_string();
+        if name.is_empty() {
+            return;
+        }
+        self.profile_name_input
+            .update(cx, |input, cx| input.set_content(String::new(), cx));
+        self.change_profile(adapter_id, &name, ProfileChange::Create, cx);
+    }
+
+    pub fn delete_profile(&mut self, adapter_id: &str, profile_id: &str, cx: &mut Context<Self>) {
+        self.change_profile(adapter_id, profile_id, ProfileChange::Delete, cx);
+    }
+
+    fn change_profile(
+        &mut self,
+        adapter_id: &str,
+        profile_id: &str,
+        change: ProfileChange,
+        cx: &mut Context<Self>,
     ) {
         let adapter = match self.adapter_manager.get_adapter(adapter_id) {
             Some(a) => a,
             None => return,
         };
-        if !adapter.capabilities().has_profiles {
+        let caps = adapter.capabilities();
+        let allowed = match change {
+            ProfileChange::Switch => caps.has_profiles,
+            ProfileChange::Create | ProfileChange::Delete => caps.can_manage_profiles,
+        };
+        if !allowed || self.adapter_view.changing_profile.is_some() {
             return;
         }
-        self.adapter_view.switching_profile = Some(profile_id.to_string());
+        self.adapter_view.changing_profile = Some((profile_id.to_string(), change));
         let aid = adapter_id.to_string();
         let pid = profile_id.to_string();
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let result =
-                    crate::tokio_spawn(
-                        async move { adapter.switch_profile(&pid).await.map(|_| pid) },
-                    )
-                    .await
-                    .unwrap_or_else(|e| {
-                        Err(crate::core::adapter::AdapterError::Other(format!("{e}")))
-                    });
+                let result = crate::tokio_spawn(async move {
+                    let done = match change {
+                        ProfileChange::Switch => adapter.switch_profile(&pid).await,
+                        ProfileChange::Create => adapter.create_profile(&pid).await,
+                        ProfileChange::Delete => adapter.delete_profile(&pid).await,
+                    };
+                    done.map(|_| pid)
+                })
+                .await
+                .unwrap_or_else(|e| Err(crate::core::adapter::AdapterError::Other(format!("{e}"))));
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
-                        app.adapter_view.switching_profile = None;
-                        match result {
-                            Ok(switched_to) => {
-                                app.add_toast(
-                                    ToastLevel::Success,
-                                    format!("Switched to profile {switched_to}"),
-                                );
+                        app.adapter_view.changing_profile = None;
+                        match (result, change) {
+                            (Ok(named), _) => {
+                                let said = match change {
+                                    ProfileChange::Switch => format!("Switched to profile {named}"),
+                                    ProfileChange::Create => format!("Created profile {named}"),
+                                    ProfileChange::Delete => format!("Deleted profile {named}"),
+                                };
+                                app.add_toast(ToastLevel::Success, said);
                                 app.load_profiles(&aid, cx);
-                                // Profile change affects installed packages location
-                                app.installed_state.loaded = false;
+                                if change == ProfileChange::Switch {
+                                    // Profile change affects installed packages location
+                                    app.installed_state.loaded = false;
+                                }
                             }
-                            Err(e) => {
+                            (Err(e), ProfileChange::Switch) => {
                                 app.add_toast(
                                     ToastLevel::Error,
                                     format!("Failed to switch profile: {e}"),
                                 );
                             }
+                            (Err(e), ProfileChange::Create) => {
+                                app.add_toast(
+                                    ToastLevel::Error,
+                                    format!("Failed to create profile: {e}"),
+                                );
+                            }
+                            (Err(e), ProfileChange::Delete) => {
+                                app.add_toast(
+                                    ToastLevel::Error,
+                                    format!("Failed to delete profile: {e}"),
+                                );
+                            }
                         }
                         cx.notify();
                     })
@@ -3109,6 +2969,291 @@ impl App {
         .detach();
     }
 
+    /// Open an installed package beside the list, and ask its manager what it
+    /// needs and what needs it.
+    pub(crate) fn open_installed_detail(
+        &mut self,
+        pkg: crate::core::package::InstalledPackage,
+        cx: &mut Context<Self>,
+    ) {
+        let adapter = self.adapter_manager.get_adapter(&pkg.package.adapter_id);
+        let asks = adapter
+            .as_ref()
+            .is_some_and(|a| a.capabilities().has_dependencies);
+        let error = (!asks).then(|| {
+            format!(
+                "{} does not say what a package needs",
+                adapter
+                    .as_ref()
+                    .map(|a| a.info().name.clone())
+                    .unwrap_or_else(|| pkg.package.adapter_id.clone())
+            )
+        });
+        let key = pkg.unique_key();
+        let package_id = pkg.package.id.clone();
+        self.installed_state.detail = Some(views::installed::InstalledDetail {
+            package: pkg,
+            depends: Vec::new(),
+            required_by: Vec::new(),
+            loading: asks,
+            error,
+            files: None,
+            files_loading: false,
+            folded: HashSet::new(),
+        });
+        let (Some(adapter), true) = (adapter, asks) else {
+            return;
+        };
+
+        let mode = self.current_mode;
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    let depends = adapter.depends(&package_id, mode).await;
+                    let required_by = adapter.required_by(&package_id, mode).await;
+                    (depends, required_by)
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        // Another package may have been opened meanwhile.
+                        let Some(detail) = app
+                            .installed_state
+                            .detail
+                            .as_mut()
+                            .filter(|detail| detail.package.unique_key() == key)
+                        else {
+                            return;
+                        };
+                        detail.loading = false;
+                        match result {
+                            // A manager may answer only one of the two, which
+                            // is still worth showing.
+                            Ok((depends, required_by)) => {
+                                if depends.is_err() && required_by.is_err() {
+                                    detail.error = depends.err().map(|e| e.to_string());
+                                } else {
+                                    detail.depends = depends.unwrap_or_default();
+                                    detail.required_by = required_by.unwrap_or_default();
+                                }
+                            }
+                            Err(e) => detail.error = Some(e.to_string()),
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Read the files of the installed package open beside the list.
+    pub(crate) fn load_installed_files(&mut self, cx: &mut Context<Self>) {
+        let Some(detail) = self.installed_state.detail.as_mut() else {
+            return;
+        };
+        let Some(adapter) = self
+            .adapter_manager
+            .get_adapter(&detail.package.package.adapter_id)
+        else {
+            return;
+        };
+        detail.files_loading = true;
+        let held = detail.package.clone();
+        let key = held.unique_key();
+        let mode = self.current_mode;
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    ownership::files_of(adapter.as_ref(), &held, mode).await
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        let Some(detail) = app
+                            .installed_state
+                            .detail
+                            .as_mut()
+                            .filter(|detail| detail.package.unique_key() == key)
+                        else {
+                            return;
+                        };
+                        detail.files_loading = false;
+                        detail.files = Some(result);
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    pub(crate) fn toggle_file_fold(&mut self, path: &str, cx: &mut Context<Self>) {
+        if let Some(detail) = self.installed_state.detail.as_mut()
+            && !detail.folded.remove(path)
+        {
+            detail.folded.insert(path.to_string());
+        }
+        cx.notify();
+    }
+
+    /// Ask every enabled manager which package the path typed in belongs to.
+    pub(crate) fn find_owner(&mut self, cx: &mut Context<Self>) {
+        let typed = self.owner_path_input.read(cx).content().trim().to_string();
+        if typed.is_empty() || self.installed_state.owner_searching {
+            return;
+        }
+        let path = std::path::PathBuf::from(shellexpand::tilde(&typed).to_string());
+
+        self.installed_state.owner_asked = Some(typed);
+        self.installed_state.owner_searching = true;
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result =
+                    crate::tokio_spawn(async move { ownership::owners_of(&manager, &path).await })
+                        .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.installed_state.owner_searching = false;
+                        match result {
+                            Ok((owners, problems)) => {
+                                app.installed_state.owners = owners;
+                                app.installed_state.owner_problems = problems;
+                            }
+                            Err(e) => {
+                                app.installed_state.owners.clear();
+                                app.installed_state.owner_problems = vec![e.to_string()];
+                            }
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Open a package named somewhere other than a listing: as something
+    /// another needs or is needed by, or as what owns a file.
+    ///
+    /// From the installed list a package that is held opens there, with what
+    /// is known about it as installed; anything else opens in Browse, where
+    /// it can be installed.
+    pub(crate) fn open_related(
+        &mut self,
+        mut pkg: crate::core::package::Package,
+        cx: &mut Context<Self>,
+    ) {
+        let held = self
+            .installed_state
+            .packages
+            .iter()
+            .find(|held| {
+                held.package.adapter_id == pkg.adapter_id
+                    && (held.package.id == pkg.id || held.package.name == pkg.name)
+            })
+            .cloned();
+
+        if self.current_view == View::Installed
+            && let Some(held) = held
+        {
+            self.open_installed_detail(held, cx);
+            return;
+        }
+
+        pkg.installed = held.is_some();
+        self.navigate_to(View::Browse, cx);
+        self.browse_state.selected_package = Some(pkg.clone());
+        self.load_package_detail(pkg, cx);
+    }
+
+    /// Ask before removing a package, and say what needs it once its manager
+    /// has answered. The question is put straight away rather than waiting
+    /// on that, and gains the warning when it comes.
+    pub(crate) fn ask_to_remove(
+        &mut self,
+        pkg: crate::core::package::Package,
+        mode: PackageMode,
+        cx: &mut Context<Self>,
+    ) {
+        self.confirm_dialog = Some(ConfirmAction::RemoveInstalled {
+            pkg: pkg.clone(),
+            mode,
+        });
+        self.look_for_needed_by(vec![pkg], mode, cx);
+    }
+
+    /// Ask the managers what needs any of the packages a removal is being
+    /// confirmed for, for the question to gain the warning when they answer.
+    fn look_for_needed_by(
+        &mut self,
+        packages: Vec<crate::core::package::Package>,
+        mode: PackageMode,
+        cx: &mut Context<Self>,
+    ) {
+        self.removal_needed_by = None;
+        let key = removal_key(&packages);
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result =
+                    crate::tokio_spawn(async move { manager.needed_by(&packages, mode).await })
+                        .await;
+
+                let Ok(needed_by) = result else {
+                    return;
+                };
+                if needed_by.is_empty() {
+                    return;
+                }
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.removal_needed_by = Some((key, needed_by));
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// What a removal would leave without something it needs, as a sentence
+    /// to follow the question.
+    fn needed_by_warning(&self, packages: &[crate::core::package::Package]) -> String {
+        let key = removal_key(packages);
+        let Some((_, needed_by)) = self
+            .removal_needed_by
+            .as_ref()
+            .filter(|(asked, _)| *asked == key)
+        else {
+            return String::new();
+        };
+
+        let it = match packages {
+            [_] => "it",
+            _ => "some of them",
+        };
+        let names: Vec<&str> = needed_by.iter().map(|p| p.name.as_str()).collect();
+        match names.as_slice() {
+            [] => String::new(),
+            [one] => format!(" {one} needs {it} and may stop working."),
+            [first, second] => format!(" {first} and {second} need {it} and may stop working."),
+            [first, second, third] => {
+                format!(" {first}, {second} and {third} need {it} and may stop working.")
+            }
+            [first, second, rest @ ..] => format!(
+                " {first}, {second} and {} more need {it} and may stop working.",
+                rest.len()
+            ),
+        }
+    }
+
     /// Handle the Escape key. Closes the topmost overlay or clears selection.
     pub(crate) fn handle_escape(&mut self, cx: &mut Context<Self>) {
         // Dropping the way back tells the manager nobody is going to answer,
@@ -3149,6 +3294,11 @@ impl App {
             self.browse_state.selected_package = None;
             self.browse_state.selected_detail = None;
             cx.notify();
+            return;
+        }
+        if self.installed_state.detail.is_some() {
+            self.installed_state.detail = None;
+            cx.notify();
         }
     }
 
@@ -3217,6 +3367,27 @@ impl App {
         self.output_log.remove(key);
     }
 
+    /// The last of what a manager wrote for one operation, to keep with it.
+    fn output_tail(&self, key: &str) -> Vec<String> {
+        self.output_log
+            .get(key)
+            .map(|lines| {
+                let skipped = lines.len().saturating_sub(history::OUTPUT_KEPT);
+                lines.iter().skip(skipped).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add changes to the journal, and to the history on screen once it has
+    /// been read. Failing to write one is logged rather than shown: the
+    /// change itself went as it went.
+    fn journal(&mut self, entries: Vec<history::Entry>) {
+        history::record(&entries);
+        if self.history_state.loaded {
+            self.history_state.entries.extend(entries);
+        }
+    }
+
     /// Show or hide what a manager wrote for one operation.
     pub fn toggle_output_log(&mut self, key: &str, cx: &mut Context<Self>) {
         if self.open_log.as_deref() == Some(key) {
@@ -3235,6 +3406,55 @@ impl App {
         self.updates_state.package_progress.remove(key);
     }
 
+    /// A token to stop work about to start, offered on the card of each
+    /// package it is about until the work is over.
+    fn cancel_token_for<'a>(&mut self, keys: impl IntoIterator<Item = &'a String>) -> CancelToken {
+        let cancel = CancelToken::default();
+        for key in keys {
+            self.cancels.insert(key.clone(), cancel.clone());
+        }
+        cancel
+    }
+
+    /// Stop offering to cancel work that is over. Only its own token goes, so
+    /// a package started again in the meantime keeps the newer one.
+    fn finished_with(&mut self, cancel: &CancelToken) {
+        self.cancels.retain(|_, held| !held.same_as(cancel));
+    }
+
+    pub fn can_cancel(&self, key: &str) -> bool {
+        self.cancels
+            .get(key)
+            .is_some_and(|cancel| !cancel.is_cancelled())
+    }
+
+    /// Stop the work a card is showing, along with whatever was started with
+    /// it.
+    pub(crate) fn cancel_operation(&mut self, key: &str, cx: &mut Context<Self>) {
+        let Some(cancel) = self.cancels.get(key).cloned() else {
+            return;
+        };
+        cancel.cancel();
+
+        // A manager waiting on an answer would sit out the whole window
+        // before it noticed, so the question goes with it.
+        let asking = self.question.as_ref().map(|question| {
+            crate::core::adapter::progress_key(&question.adapter_id, &question.package_id)
+        });
+        if asking
+            .and_then(|asking| self.cancels.get(&asking))
+            .is_some_and(|held| held.same_as(&cancel))
+        {
+            self.question = None;
+        }
+
+        self.record_progress(
+            key.to_string(),
+            OperationStatus::Working("Cancelling\u{2026}".into()),
+        );
+        cx.notify();
+    }
+
     fn drain_progress(&mut self, cx: &mut Context<Self>) {
         use crate::core::adapter::{ProgressEvent, progress_key};
 
@@ -3300,6 +3520,13 @@ impl App {
                     let key = progress_key(&adapter_id, &package_id);
                     self.record_progress(key, OperationStatus::Failed(error));
                 }
+                ProgressEvent::Cancelled {
+                    adapter_id,
+                    package_id,
+                } => {
+                    let key = progress_key(&adapter_id, &package_id);
+                    self.record_progress(key, OperationStatus::Cancelled);
+                }
                 ProgressEvent::Status {
                     adapter_id,
                     package_id,
@@ -3614,7 +3841,12 @@ impl Render for App {
                     format!("Install {}?{}", pkg.name, mode_suffix(mode))
                 }
                 ConfirmAction::Remove(pkg, mode) => {
-                    format!("Remove {}?{}", pkg.name, mode_suffix(mode))
+                    format!(
+                        "Remove {}?{}{}",
+                        pkg.name,
+                        mode_suffix(mode),
+                        self.needed_by_warning(std::slice::from_ref(pkg))
+                    )
                 }
                 ConfirmAction::Update(pkg, mode) => {
                     format!("Update {}?{}", pkg.name, mode_suffix(mode))
@@ -3632,18 +3864,29 @@ impl Render for App {
                     format!("Install {} packages?{}", pkgs.len(), mode_suffix(mode))
                 }
                 ConfirmAction::BatchRemove(pkgs, mode) => {
-                    format!("Remove {} packages?{}", pkgs.len(), mode_suffix(mode))
+                    format!(
+                        "Remove {} packages?{}{}",
+                        pkgs.len(),
+                        mode_suffix(mode),
+                        self.needed_by_warning(pkgs)
+                    )
                 }
                 ConfirmAction::BatchUpdate(pkgs, mode) => {
                     format!("Update {} packages?{}", pkgs.len(), mode_suffix(mode))
                 }
                 ConfirmAction::RemoveInstalled { pkg, mode, .. } => {
-                    format!("Remove {}?{}", pkg.name, mode_suffix(mode))
+                    format!(
+                        "Remove {}?{}{}",
+                        pkg.name,
+                        mode_suffix(mode),
+                        self.needed_by_warning(std::slice::from_ref(pkg))
+                    )
                 }
                 ConfirmAction::BatchRemoveInstalled { count } => {
                     format!(
-                        "Remove {count} packages?{}",
-                        mode_suffix(&self.current_mode)
+                        "Remove {count} packages?{}{}",
+                        mode_suffix(&self.current_mode),
+                        self.needed_by_warning(&self.selected_installed())
                     )
                 }
                 ConfirmAction::ApplyManifest { remove_names, .. } => {
@@ -3673,6 +3916,23 @@ impl Render for App {
                 ConfirmAction::ImportInstalledManifest => {
                     "Replace the manifest with your currently installed packages?".to_string()
                 }
+                ConfirmAction::ImportInventory(plan) => import_summary(plan),
+                ConfirmAction::CleanCache {
+                    adapter_name,
+                    all: false,
+                    ..
+                } => format!("Clean the {adapter_name} cache of what it no longer needs?"),
+                ConfirmAction::CleanCache {
+                    adapter_name,
+                    all: true,
+                    ..
+                } => format!(
+                    "Clear the whole {adapter_name} cache? Anything it needs again is downloaded again."
+                ),
+                ConfirmAction::ClearOwnCaches => {
+                    "Clear the icons, registries and release notes aeris keeps? They are fetched again as needed."
+                        .to_string()
+                }
             };
 
             let confirm_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
@@ -4491,6 +4751,10 @@ impl App {
             (View::Browse, "Browse"),
             (View::Installed, "Installed"),
             (View::Updates, "Updates"),
+            (View::Jobs, "Jobs"),
+            (View::History, "History"),
+            (View::DiskUsage, "Disk Usage"),
+            (View::Doctor, "Doctor"),
             (View::AdapterInfo, "Adapters"),
         ];
         if self
@@ -4592,20 +4856,35 @@ impl App {
             PackageMode::System => "System",
         };
 
-        // Active operation indicator
-        let op_indicator = if let Some(ref op) = self.active_operation {
-            Some(
-                div()
-                    .px(px(styles::spacing::MD))
-                    .py(px(styles::spacing::XXS))
-                    .rounded(px(styles::radius::FULL))
-                    .bg(theme.warning.opacity(0.2))
-                    .text_size(px(styles::font_size::CAPTION))
-                    .child(format!("{}: {}", op.operation_type, op.status.label())),
-            )
-        } else {
-            None
-        };
+        // What the queue is doing, for as long as it is doing anything.
+        let (running, queued) = self.jobs.counts();
+        let op_indicator = (running + queued > 0).then(|| {
+            let label = match (running, queued) {
+                (running, 0) => format!("{running} running"),
+                (0, queued) => format!("{queued} queued"),
+                (running, queued) => format!("{running} running, {queued} queued"),
+            };
+            let open_jobs = cx.listener(|app, _: &ClickEvent, _window, cx| {
+                app.current_view = View::Jobs;
+                cx.notify();
+            });
+
+            div()
+                .id("jobs-indicator")
+                .px(px(styles::spacing::MD))
+                .py(px(styles::spacing::XXS))
+                .rounded(px(styles::radius::FULL))
+                .bg(theme.warning.opacity(0.2))
+                .text_size(px(styles::font_size::CAPTION))
+                .cursor_pointer()
+                // The header drags the window, so a control has to keep its
+                // own press to itself.
+                .on_mouse_down(MouseButton::Left, |_, _, cx| {
+                    cx.stop_propagation();
+                })
+                .on_click(open_jobs)
+                .child(label)
+        });
 
         let mut header = div()
             .id("app-header")
@@ -4742,6 +5021,10 @@ impl App {
             View::Browse => wrapper.child(self.render_browse(theme, cx)),
             View::Installed => wrapper.child(self.render_installed(theme, cx)),
             View::Updates => wrapper.child(self.render_updates(theme, cx)),
+            View::Jobs => wrapper.child(self.render_jobs(theme, cx)),
+            View::History => wrapper.child(self.render_history(theme, cx)),
+            View::DiskUsage => wrapper.child(self.render_disk_usage(theme, cx)),
+            View::Doctor => wrapper.child(self.render_doctor(theme, cx)),
             View::AdapterInfo => wrapper.child(self.render_adapter_info(theme, cx)),
             View::Manifest => wrapper.child(self.render_manifest(theme, cx)),
             View::Settings => wrapper.child(self.render_settings(theme, cx)),
@@ -4753,11 +5036,11 @@ impl App {
             ConfirmAction::Install(pkg, mode) => {
                 self.install_package(pkg, mode, cx);
             }
-            ConfirmAction::Remove(pkg, mode) => {
-                self.remove_package(pkg, mode, cx);
+            ConfirmAction::Remove(pkg, mode) | ConfirmAction::RemoveInstalled { pkg, mode } => {
+                self.enqueue(JobKind::Remove(vec![pkg]), mode, cx);
             }
             ConfirmAction::Update(pkg, mode) => {
-                self.update_package(pkg, mode, cx);
+                self.enqueue(JobKind::Update(vec![pkg]), mode, cx);
             }
             ConfirmAction::UpdateAll(_mode) => {
                 self.update_all(cx);
@@ -4767,23 +5050,23 @@ impl App {
                 adapter_name,
                 mode,
             } => {
-                self.update_everything_in(adapter_id, adapter_name, mode, cx);
+                self.enqueue(
+                    JobKind::UpdateAll {
+                        adapter_id,
+                        adapter_name,
+                    },
+                    mode,
+                    cx,
+                );
             }
             ConfirmAction::BatchInstall(pkgs, mode) => {
-                self.batch_install(pkgs, mode, cx);
+                self.enqueue(JobKind::Install(pkgs), mode, cx);
             }
             ConfirmAction::BatchRemove(pkgs, mode) => {
-                self.batch_remove(pkgs, mode, cx);
+                self.enqueue(JobKind::Remove(pkgs), mode, cx);
             }
             ConfirmAction::BatchUpdate(pkgs, mode) => {
-                self.batch_update(pkgs, mode, cx);
-            }
-            ConfirmAction::RemoveInstalled {
-                pkg,
-                unique_key,
-                mode,
-            } => {
-                self.remove_installed_package(pkg, unique_key, mode, cx);
+                self.enqueue(JobKind::Update(pkgs), mode, cx);
             }
             ConfirmAction::BatchRemoveInstalled { .. } => {
                 self.remove_selected_installed(cx);
@@ -4797,8 +5080,196 @@ impl App {
             ConfirmAction::ImportInstalledManifest => {
                 self.import_installed_into_manifest(cx);
             }
-        }
-    }
+            ConfirmAction::ImportInventory(plan) => {
+                for (mode, packages) in plan.batches() {
+                    let pin = plan.pins(mode);
+                    self.enqueue(JobKind::Import { packages, pin }, mode, cx);
+                }
+                for (mode, packages) in plan.repins() {
+                    self.enqueue(JobKind::Pin(packages), mode, cx);
+                }
+            }
+            ConfirmAction::CleanCache {
+                adapter_id,
+                adapter_name,
+                all,
+            } => {
+                self.enqueue(
+                    JobKind::Clean {
+                        adapter_id,
+                        adapter_name,
+                        all,
+                    },
+                    self.current_mode,
+                    cx,
+                );
+            }
+            ConfirmAction::ClearOwnCaches => self.clear_own_caches(cx),
+        }
+    }
+
+    /// Work out which commands more than one manager puts on `PATH`.
+    pub(crate) fn check_conflicts(&mut self, cx: &mut Context<Self>) {
+        if self.installed_state.conflicts_checking {
+            return;
+        }
+        self.installed_state.conflicts_checking = true;
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result =
+                    crate::tokio_spawn(async move { shadowing::gather(&manager).await }).await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.installed_state.conflicts_checking = false;
+                        app.installed_state.conflicts_checked = true;
+                        match result {
+                            Ok((conflicts, problems)) => {
+                                app.installed_state.conflicts = conflicts;
+                                app.installed_state.conflict_problems = problems;
+                            }
+                            Err(e) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Could not check for conflicts: {e}"),
+                            ),
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Remove every copy of a command that never runs, leaving the one that
+    /// does. A copy nobody here owns is left alone.
+    pub(crate) fn remove_losing_copies(&mut self, command: &str, cx: &mut Context<Self>) {
+        let Some(conflict) = self
+            .installed_state
+            .conflicts
+            .iter()
+            .find(|c| c.command == command)
+        else {
+            return;
+        };
+
+        let mut by_mode: Vec<(PackageMode, Vec<crate::core::package::Package>)> = Vec::new();
+        for owner in conflict.losers() {
+            let package = owner.package.package.clone();
+            match by_mode.iter_mut().find(|(mode, _)| *mode == owner.mode) {
+                Some((_, packages)) => packages.push(package),
+                None => by_mode.push((owner.mode, vec![package])),
+            }
+        }
+        for (mode, packages) in by_mode {
+            self.enqueue(JobKind::Remove(packages), mode, cx);
+        }
+    }
+
+    /// Write down what every manager holds, in both scopes, to a file picked
+    /// for it.
+    pub(crate) fn export_installed(&mut self, cx: &mut Context<Self>) {
+        if self.installed_state.exporting {
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("aeris-packages.toml")
+            .add_filter("TOML", &["toml"])
+            .save_file()
+        else {
+            return;
+        };
+
+        self.installed_state.exporting = true;
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    let (inventory, problems) = Inventory::gather(&manager).await;
+                    inventory
+                        .write(&path)
+                        .map(|()| (inventory.packages.len(), problems, path))
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.installed_state.exporting = false;
+                        match result {
+                            Ok(Ok((count, problems, path))) => {
+                                let mut said =
+                                    format!("Exported {count} packages to {}", path.display());
+                                if !problems.is_empty() {
+                                    said.push_str(&format!(
+                                        ", leaving out {}",
+                                        problems.join("; ")
+                                    ));
+                                }
+                                app.add_toast(ToastLevel::Success, said);
+                            }
+                            Ok(Err(e)) => {
+                                app.add_toast(ToastLevel::Error, format!("Export failed: {e}"))
+                            }
+                            Err(e) => {
+                                app.add_toast(ToastLevel::Error, format!("Export failed: {e}"))
+                            }
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Read an exported file and say what putting it back would do, before
+    /// anything is installed.
+    pub(crate) fn import_installed(&mut self, cx: &mut Context<Self>) {
+        if self.installed_state.importing {
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("TOML", &["toml"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        self.installed_state.importing = true;
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    let inventory = Inventory::read(&path)?;
+                    Ok::<_, String>(ImportPlan::for_inventory(&inventory, &manager).await)
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.installed_state.importing = false;
+                        match result {
+                            Ok(Ok(plan)) if plan.install.is_empty() && plan.pin.is_empty() => {
+                                app.add_toast(ToastLevel::Info, import_summary(&plan))
+                            }
+                            Ok(Ok(plan)) => {
+                                app.confirm_dialog = Some(ConfirmAction::ImportInventory(plan))
+                            }
+                            Ok(Err(e)) => {
+                                app.add_toast(ToastLevel::Error, format!("Import failed: {e}"))
+                            }
+                            Err(e) => {
+                                app.add_toast(ToastLevel::Error, format!("Import failed: {e}"))
+                            }
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
 
     /// Record what a package's state became, everywhere browse shows it.
     ///
@@ -4822,173 +5293,914 @@ impl App {
         self.browse_state.package_progress.remove(&key);
     }
 
-    pub(crate) fn install_package(
+    pub(crate) fn install_package(
+        &mut self,
+        pkg: crate::core::package::Package,
+        mode: PackageMode,
+        cx: &mut Context<Self>,
+    ) {
+        self.enqueue(JobKind::Install(vec![pkg]), mode, cx);
+    }
+
+    /// Line a change up behind whatever is already under way, starting it at
+    /// once when nothing it needs is busy.
+    pub(crate) fn enqueue(&mut self, kind: JobKind, mode: PackageMode, cx: &mut Context<Self>) {
+        let what = kind.describe();
+        self.show_waiting(&kind);
+        let id = self.jobs.push(kind, mode);
+        self.run_ready_jobs(cx);
+
+        if self
+            .jobs
+            .get(id)
+            .is_some_and(|job| job.state == JobState::Queued)
+        {
+            self.add_toast(
+                ToastLevel::Info,
+                format!("{what} will start once the work ahead of it is done"),
+            );
+        }
+        cx.notify();
+    }
+
+    /// Say on everything a job is about that it is waiting its turn, so work
+    /// lined up does not look forgotten.
+    fn show_waiting(&mut self, kind: &JobKind) {
+        for key in kind.progress_keys() {
+            self.record_progress(key, OperationStatus::Working("Queued".into()));
+        }
+        match kind {
+            JobKind::Sync(_) => self.adapter_view.syncing = Some("__all__".to_string()),
+            JobKind::Apply { .. } => self.manifest_state.applying = true,
+            JobKind::Clean { adapter_id, .. } => {
+                self.adapter_view.cleaning.insert(adapter_id.clone());
+            }
+            JobKind::Repair(_) => self.doctor_state.repairing.extend(kind.progress_keys()),
+            _ => {}
+        }
+    }
+
+    /// Take back what [`Self::show_waiting`] said, for a job that will not
+    /// run after all.
+    fn forget_waiting(&mut self, kind: &JobKind) {
+        for key in kind.progress_keys() {
+            self.clear_progress(&key);
+        }
+        match kind {
+            JobKind::Sync(_) => self.adapter_view.syncing = None,
+            JobKind::Apply { .. } => self.manifest_state.applying = false,
+            JobKind::Clean { adapter_id, .. } => {
+                self.adapter_view.cleaning.remove(adapter_id);
+            }
+            JobKind::Repair(_) => {
+                for key in kind.progress_keys() {
+                    self.doctor_state.repairing.remove(&key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Start every waiting job whose managers have room for it.
+    fn run_ready_jobs(&mut self, cx: &mut Context<Self>) {
+        let manager = &self.adapter_manager;
+        let ready = self
+            .jobs
+            .take_ready(|adapter_id| match manager.get_adapter(adapter_id) {
+                Some(adapter) if adapter.capabilities().exclusive => 1,
+                _ => JOBS_PER_MANAGER,
+            });
+
+        for Job { id, kind, mode, .. } in ready {
+            match kind {
+                JobKind::Install(packages) => self.run_install(id, packages, Vec::new(), mode, cx),
+                JobKind::Import { packages, pin } => self.run_install(id, packages, pin, mode, cx),
+                JobKind::Remove(packages) => self.run_remove(id, packages, mode, cx),
+                JobKind::Update(packages) => self.run_update(id, packages, mode, cx),
+                JobKind::Revert(packages) => self.run_revert(id, packages, mode, cx),
+                JobKind::Pin(packages) => self.run_pin(id, packages, true, mode, cx),
+                JobKind::Unpin(packages) => self.run_pin(id, packages, false, mode, cx),
+                JobKind::Repair(packages) => self.run_repair(id, packages, mode, cx),
+                JobKind::UpdateAll {
+                    adapter_id,
+                    adapter_name,
+                } => self.run_update_everything_in(id, adapter_id, adapter_name, mode, cx),
+                JobKind::Sync(adapters) => self.run_sync(id, adapters, cx),
+                JobKind::Clean {
+                    adapter_id,
+                    adapter_name,
+                    all,
+                } => self.run_clean(id, adapter_id, adapter_name, all, cx),
+                JobKind::Apply { adapter_id, prune } => {
+                    self.run_apply(id, adapter_id, prune, mode, cx)
+                }
+            }
+        }
+    }
+
+    /// Record how a job ended and hand its managers whatever was waiting on
+    /// them.
+    fn job_ended(&mut self, id: JobId, state: JobState, cx: &mut Context<Self>) {
+        self.jobs.end(id, state);
+        self.job_cancels.remove(&id);
+        self.run_ready_jobs(cx);
+        cx.notify();
+    }
+
+    pub(crate) fn shift_job(&mut self, id: JobId, earlier: bool, cx: &mut Context<Self>) {
+        if self.jobs.shift(id, earlier) {
+            cx.notify();
+        }
+    }
+
+    pub(crate) fn discard_job(&mut self, id: JobId, cx: &mut Context<Self>) {
+        let Some(kind) = self.jobs.get(id).map(|job| job.kind.clone()) else {
+            return;
+        };
+        if self.jobs.discard(id) {
+            self.forget_waiting(&kind);
+            cx.notify();
+        }
+    }
+
+    pub(crate) fn retry_job(&mut self, id: JobId, cx: &mut Context<Self>) {
+        let Some(kind) = self.jobs.get(id).map(|job| job.kind.clone()) else {
+            return;
+        };
+        if self.jobs.retry(id) {
+            self.show_waiting(&kind);
+            self.run_ready_jobs(cx);
+            cx.notify();
+        }
+    }
+
+    pub(crate) fn clear_ended_jobs(&mut self, cx: &mut Context<Self>) {
+        self.jobs.clear_ended();
+        cx.notify();
+    }
+
+    pub fn can_cancel_job(&self, id: JobId) -> bool {
+        self.job_cancels
+            .get(&id)
+            .is_some_and(|cancel| !cancel.is_cancelled())
+    }
+
+    /// Stop a running job the way its own cards would, so a question it is
+    /// waiting on goes with it.
+    pub(crate) fn cancel_job(&mut self, id: JobId, cx: &mut Context<Self>) {
+        let Some(cancel) = self.job_cancels.get(&id).cloned() else {
+            return;
+        };
+        let shown_on = self
+            .cancels
+            .iter()
+            .find(|(_, held)| held.same_as(&cancel))
+            .map(|(key, _)| key.clone());
+
+        match shown_on {
+            Some(key) => self.cancel_operation(&key, cx),
+            None => {
+                cancel.cancel();
+                cx.notify();
+            }
+        }
+    }
+
+    /// A token for a job about to start, offered on each of its cards and in
+    /// the list of jobs until the job is over.
+    fn job_cancel_token(&mut self, job: JobId, keys: &[String]) -> CancelToken {
+        let cancel = self.cancel_token_for(keys);
+        self.job_cancels.insert(job, cancel.clone());
+        cancel
+    }
+
+    /// Install the packages, then pin again those of `pin` that went in.
+    fn run_install(
+        &mut self,
+        job: JobId,
+        packages: Vec<crate::core::package::Package>,
+        pin: Vec<crate::core::package::Package>,
+        mode: PackageMode,
+        cx: &mut Context<Self>,
+    ) {
+        let keys = JobKind::Install(packages.clone()).progress_keys();
+        for key in &keys {
+            self.start_output_log(key);
+            self.record_progress(key.clone(), OperationStatus::Starting);
+        }
+        self.browse_state.installing = Some(match keys.as_slice() {
+            [one] => one.clone(),
+            _ => "__batch__".to_string(),
+        });
+
+        let cancel = self.job_cancel_token(job, &keys);
+        let stopping = cancel.clone();
+        let progress_sender = self.progress_sender.clone();
+        let manager = self.adapter_manager.clone();
+        let what = named(&packages);
+        let asked = packages.clone();
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    let installed = manager
+                        .install(&packages, Some(progress_sender), &stopping, mode)
+                        .await;
+                    let pinned = match &installed {
+                        Ok(results) => inventory::pin_again(&manager, &pin, results, mode).await,
+                        Err(_) => None,
+                    };
+                    (installed, pinned)
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.browse_state.installing = None;
+                        app.finished_with(&cancel);
+
+                        let (result, pinned) = match result {
+                            Ok((installed, pinned)) => (Ok(installed), pinned),
+                            Err(e) => (Err(e), None),
+                        };
+                        // An install that answered still has to say it
+                        // worked: the manager reports each package, and a
+                        // failed one comes back this way too.
+                        let (results, failure) = settled(result);
+                        let mut changes = Vec::new();
+                        for (package, key) in asked.iter().zip(&keys) {
+                            let went = went(&results, package, &failure);
+                            let now = match &went {
+                                Ok(()) => results
+                                    .iter()
+                                    .find(|r| {
+                                        r.adapter_id == package.adapter_id
+                                            && r.package_id == package.id
+                                    })
+                                    .map(|r| r.version.clone())
+                                    .filter(|v| !v.is_empty())
+                                    .or_else(|| Some(package.version.clone())),
+                                Err(_) => None,
+                            };
+                            let mut change = history::Entry::ended(
+                                Action::Install,
+                                &package.adapter_id,
+                                mode,
+                                outcome_of(went.clone(), &cancel),
+                            )
+                            .of(&package.id, &package.name)
+                            .moved(None, now);
+                            change.output = app.output_tail(key);
+                            changes.push(change);
+
+                            match went {
+                                Ok(()) => {
+                                    app.mark_installed(&package.adapter_id, &package.id, true);
+                                    app.clear_progress(key);
+                                }
+                                Err(_) if cancel.is_cancelled() => app.clear_progress(key),
+                                Err(why) => {
+                                    app.record_progress(key.clone(), OperationStatus::Failed(why))
+                                }
+                            }
+                        }
+
+                        app.journal(changes);
+                        if let Some(pinned) = pinned {
+                            app.pinned_again(pinned, &asked, mode);
+                        }
+
+                        let ended = ended_with(failure, &cancel);
+                        match &ended {
+                            JobState::Cancelled => app
+                                .add_toast(ToastLevel::Info, format!("Stopped installing {what}")),
+                            JobState::Failed(why) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Failed to install {what}. {why}"),
+                            ),
+                            _ => app.add_toast(ToastLevel::Success, format!("Installed {what}")),
+                        }
+                        app.browse_state.result_version += 1;
+                        app.installed_state.loaded = false;
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Record pinning again what an import put back, and say if it did not
+    /// take, since the install itself went through.
+    fn pinned_again(
+        &mut self,
+        pinned: crate::core::adapter::Result<Vec<InstallResult>>,
+        asked: &[crate::core::package::Package],
+        mode: PackageMode,
+    ) {
+        let (results, failure) = match pinned {
+            Ok(results) => {
+                let failure = failure_among(&results);
+                (results, failure)
+            }
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        let changes = asked
+            .iter()
+            .filter(|package| {
+                results
+                    .iter()
+                    .any(|r| r.adapter_id == package.adapter_id && r.package_id == package.id)
+            })
+            .map(|package| {
+                let went = went(&results, package, &failure);
+                history::Entry::ended(
+                    Action::Pin,
+                    &package.adapter_id,
+                    mode,
+                    outcome_of(went, &CancelToken::default()),
+                )
+                .of(&package.id, &package.name)
+            })
+            .collect();
+        self.journal(changes);
+        if let Some(why) = failure {
+            self.add_toast(
+                ToastLevel::Error,
+                format!("Installed, but could not pin again: {why}"),
+            );
+        }
+    }
+
+    /// Why a change in the history cannot be undone here, or nothing when
+    /// it can. Said up front, so a manager that has no way to install a
+    /// chosen version is not asked to and left to fail.
+    pub(crate) fn revert_blocker(&self, entry: &history::Entry) -> Option<String> {
+        let Some(adapter) = self.adapter_manager.get_adapter(&entry.adapter_id) else {
+            return Some(format!("{} is not loaded", entry.adapter_id));
+        };
+        let name = &adapter.info().name;
+        let capabilities = adapter.capabilities();
+
+        if !capabilities.can_install_version {
+            Some(format!("{name} cannot install a chosen version"))
+        } else if !capabilities.works_in(entry.mode) {
+            Some(format!("{name} does not work with {} packages", entry.mode))
+        } else {
+            None
+        }
+    }
+
+    /// Put back the version a change in the history took away.
+    pub(crate) fn revert(&mut self, entry: &history::Entry, cx: &mut Context<Self>) {
+        let (Some(version), Some(id)) = (entry.reverts_to(), &entry.package_id) else {
+            return;
+        };
+        if let Some(why) = self.revert_blocker(entry) {
+            self.add_toast(ToastLevel::Error, format!("Cannot revert: {why}"));
+            return;
+        }
+
+        let name = entry.package_name.as_deref().unwrap_or(id);
+        let package = crate::core::package::Package::named(&entry.adapter_id, id, name, version);
+        self.enqueue(JobKind::Revert(vec![package]), entry.mode, cx);
+    }
+
+    fn run_revert(
+        &mut self,
+        job: JobId,
+        packages: Vec<crate::core::package::Package>,
+        mode: PackageMode,
+        cx: &mut Context<Self>,
+    ) {
+        let keys = JobKind::Revert(packages.clone()).progress_keys();
+        for key in &keys {
+            self.start_output_log(key);
+            self.record_progress(key.clone(), OperationStatus::Starting);
+        }
+
+        let cancel = self.job_cancel_token(job, &keys);
+        let stopping = cancel.clone();
+        let progress_sender = self.progress_sender.clone();
+        let manager = self.adapter_manager.clone();
+        let what = named(&packages);
+        let asked = packages.clone();
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    // What is held now is what the journal says it went back
+                    // from, which need not be what the change being undone
+                    // left behind.
+                    let mut held: HashMap<String, HashMap<String, (String, String)>> =
+                        HashMap::new();
+                    for package in &packages {
+                        if held.contains_key(&package.adapter_id) {
+                            continue;
+                        }
+                        if let Some(adapter) = manager.get_adapter(&package.adapter_id) {
+                            let now = held_by(adapter.as_ref(), mode).await;
+                            held.insert(package.adapter_id.clone(), now);
+                        }
+                    }
+
+                    let outcome = manager
+                        .install_version(&packages, Some(progress_sender), &stopping, mode)
+                        .await;
+                    (outcome, held)
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.finished_with(&cancel);
+
+                        let (result, held) = match result {
+                            Ok((outcome, held)) => (Ok(outcome), held),
+                            Err(e) => (Err(e), HashMap::new()),
+                        };
+                        let (results, failure) = settled(result);
+                        let mut changes = Vec::new();
+                        for (package, key) in asked.iter().zip(&keys) {
+                            let went = went(&results, package, &failure);
+                            let was = held
+                                .get(&package.adapter_id)
+                                .and_then(|held| held.get(&package.id))
+                                .map(|(_, version)| version.clone());
+                            let now = match &went {
+                                Ok(()) => Some(package.version.clone()),
+                                Err(_) => was.clone(),
+                            };
+                            if went.is_ok() {
+                                app.mark_installed(&package.adapter_id, &package.id, true);
+                            }
+                            let mut change = history::Entry::ended(
+                                Action::Revert,
+                                &package.adapter_id,
+                                mode,
+                                outcome_of(went, &cancel),
+                            )
+                            .of(&package.id, &package.name)
+                            .moved(was, now);
+                            change.output = app.output_tail(key);
+                            changes.push(change);
+                            app.clear_progress(key);
+                        }
+                        app.journal(changes);
+
+                        let ended = ended_with(failure, &cancel);
+                        match &ended {
+                            JobState::Cancelled => app.add_toast(
+                                ToastLevel::Info,
+                                format!("Stopped putting back {what}"),
+                            ),
+                            JobState::Failed(why) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Failed to put back {what}. {why}"),
+                            ),
+                            _ => app.add_toast(
+                                ToastLevel::Success,
+                                match asked.as_slice() {
+                                    [one] => format!("Put {} back to {}", one.name, one.version),
+                                    _ => format!("Put back {what}"),
+                                },
+                            ),
+                        }
+                        app.browse_state.result_version += 1;
+                        app.installed_state.loaded = false;
+                        app.updates_state.checked = false;
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Pin the packages where they are, or let them go again.
+    fn run_pin(
+        &mut self,
+        job: JobId,
+        packages: Vec<crate::core::package::Package>,
+        pin: bool,
+        mode: PackageMode,
+        cx: &mut Context<Self>,
+    ) {
+        let keys = if pin {
+            JobKind::Pin(packages.clone())
+        } else {
+            JobKind::Unpin(packages.clone())
+        }
+        .progress_keys();
+        for key in &keys {
+            self.start_output_log(key);
+            self.record_progress(key.clone(), OperationStatus::Starting);
+        }
+
+        let cancel = self.job_cancel_token(job, &keys);
+        let manager = self.adapter_manager.clone();
+        let what = named(&packages);
+        let asked = packages.clone();
+        let (action, verb) = if pin {
+            (Action::Pin, "pin")
+        } else {
+            (Action::Unpin, "unpin")
+        };
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    if pin {
+                        manager.pin(&packages, mode).await
+                    } else {
+                        manager.unpin(&packages, mode).await
+                    }
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.finished_with(&cancel);
+
+                        let (results, failure) = settled(result);
+                        let mut changes = Vec::new();
+                        for (package, key) in asked.iter().zip(&keys) {
+                            let went = went(&results, package, &failure);
+                            let mut change = history::Entry::ended(
+                                action,
+                                &package.adapter_id,
+                                mode,
+                                outcome_of(went, &cancel),
+                            )
+                            .of(&package.id, &package.name);
+                            change.output = app.output_tail(key);
+                            changes.push(change);
+                            app.clear_progress(key);
+                        }
+                        app.journal(changes);
+
+                        let ended = ended_with(failure, &cancel);
+                        match &ended {
+                            JobState::Failed(why) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Failed to {verb} {what}. {why}"),
+                            ),
+                            _ if pin => app.add_toast(
+                                ToastLevel::Success,
+                                format!("Pinned {what}; updates will pass it by"),
+                            ),
+                            _ => app.add_toast(ToastLevel::Success, format!("Unpinned {what}")),
+                        }
+                        app.installed_state.loaded = false;
+                        app.updates_state.checked = false;
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    fn run_repair(
         &mut self,
-        pkg: crate::core::package::Package,
+        job: JobId,
+        packages: Vec<crate::core::package::Package>,
         mode: PackageMode,
         cx: &mut Context<Self>,
     ) {
-        let package_id = pkg.id.clone();
-        let pkg_name = pkg.name.clone();
-        let adapter_id = pkg.adapter_id.clone();
-        let progress_key = crate::core::adapter::progress_key(&pkg.adapter_id, &pkg.id);
-        self.browse_state.installing = Some(progress_key.clone());
-        self.browse_state
-            .package_progress
-            .insert(progress_key.clone(), OperationStatus::Starting);
+        let keys = JobKind::Repair(packages.clone()).progress_keys();
+        for key in &keys {
+            self.start_output_log(key);
+            self.record_progress(key.clone(), OperationStatus::Starting);
+        }
+
+        let cancel = self.job_cancel_token(job, &keys);
+        let stopping = cancel.clone();
         let progress_sender = self.progress_sender.clone();
-        let adapter = self.adapter_manager.get_adapter(&pkg.adapter_id);
+        let manager = self.adapter_manager.clone();
+        let what = named(&packages);
+        let asked = packages.clone();
 
-        if let Some(adapter) = adapter {
-            cx.spawn(
-                async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                    let result = crate::tokio_spawn(async move {
-                        adapter.install(&[pkg], Some(progress_sender), mode).await
-                    })
-                    .await;
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    manager
+                        .repair(&packages, Some(progress_sender), &stopping, mode)
+                        .await
+                })
+                .await;
 
-                    let _ = cx.update(|cx| {
-                        this.update(cx, |app, cx| {
-                            app.browse_state.installing = None;
-                            match result {
-                                // An install that answered still has to say it
-                                // worked: the manager reports each package,
-                                // and a failed one comes back this way too.
-                                Ok(Ok(results)) => match failure_among(&results) {
-                                    Some(why) => {
-                                        app.browse_state.package_progress.insert(
-                                            progress_key.clone(),
-                                            OperationStatus::Failed(why.clone()),
-                                        );
-                                        app.add_toast(
-                                            ToastLevel::Error,
-                                            format!("Failed to install {pkg_name}. {why}"),
-                                        );
-                                    }
-                                    None => {
-                                        app.mark_installed(&adapter_id, &package_id, true);
-                                        app.browse_state.package_progress.remove(&progress_key);
-                                        app.add_toast(
-                                            ToastLevel::Success,
-                                            format!("Installed {pkg_name}"),
-                                        );
-                                        // Refresh installed list
-                                        app.installed_state.loaded = false;
-                                    }
-                                },
-                                Ok(Err(e)) => {
-                                    app.browse_state.package_progress.insert(
-                                        progress_key.clone(),
-                                        OperationStatus::Failed(format!("{e}")),
-                                    );
-                                    app.add_toast(
-                                        ToastLevel::Error,
-                                        format!("Failed to install {pkg_name}: {e}"),
-                                    );
-                                }
-                                Err(e) => {
-                                    app.browse_state.package_progress.insert(
-                                        progress_key.clone(),
-                                        OperationStatus::Failed(format!("{e}")),
-                                    );
-                                }
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.finished_with(&cancel);
+
+                        let (results, failure) = settled(result);
+                        let mut changes = Vec::new();
+                        for (package, key) in asked.iter().zip(&keys) {
+                            let went = went(&results, package, &failure);
+                            let mut change = history::Entry::ended(
+                                Action::Repair,
+                                &package.adapter_id,
+                                mode,
+                                outcome_of(went, &cancel),
+                            )
+                            .of(&package.id, &package.name);
+                            change.output = app.output_tail(key);
+                            changes.push(change);
+                            app.clear_progress(key);
+                            app.doctor_state.repairing.remove(key);
+                        }
+                        app.journal(changes);
+
+                        let ended = ended_with(failure, &cancel);
+                        match &ended {
+                            JobState::Cancelled => {
+                                app.add_toast(ToastLevel::Info, format!("Stopped repairing {what}"))
                             }
-                            app.browse_state.result_version += 1;
-                            cx.notify();
-                        })
-                    });
-                },
-            )
-            .detach();
-        }
+                            JobState::Failed(why) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Failed to repair {what}. {why}"),
+                            ),
+                            _ => app.add_toast(ToastLevel::Success, format!("Repaired {what}")),
+                        }
+                        // Whether it took is for a fresh look to say.
+                        app.doctor_state.loaded = false;
+                        app.installed_state.loaded = false;
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
     }
 
-    pub(crate) fn remove_package(
+    fn run_remove(
         &mut self,
-        pkg: crate::core::package::Package,
+        job: JobId,
+        packages: Vec<crate::core::package::Package>,
         mode: PackageMode,
         cx: &mut Context<Self>,
     ) {
-        self.remove_installed_package(pkg.clone(), pkg.id.clone(), mode, cx);
+        let keys = JobKind::Remove(packages.clone()).progress_keys();
+        for key in &keys {
+            self.start_output_log(key);
+            self.record_progress(key.clone(), OperationStatus::Starting);
+        }
+        // One package is named down to its version, so a second install of
+        // it does not also say it is on its way out.
+        self.installed_state.removing = Some(match packages.as_slice() {
+            [one] => format!(
+                "{}@{}",
+                crate::core::adapter::package_key(&one.adapter_id, &one.id),
+                one.version
+            ),
+            _ => "__batch__".to_string(),
+        });
+
+        let cancel = self.job_cancel_token(job, &keys);
+        let stopping = cancel.clone();
+        let progress_sender = self.progress_sender.clone();
+        let manager = self.adapter_manager.clone();
+        let what = named(&packages);
+        let asked = packages.clone();
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    manager
+                        .remove(&packages, Some(progress_sender), &stopping, mode)
+                        .await
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.installed_state.removing = None;
+                        app.finished_with(&cancel);
+
+                        let (results, failure) = settled(result);
+                        let mut changes = Vec::new();
+                        for (package, key) in asked.iter().zip(&keys) {
+                            let went = went(&results, package, &failure);
+                            // One that did not go is still there as it was.
+                            let now = went.is_err().then(|| package.version.clone());
+                            if went.is_ok() {
+                                app.mark_installed(&package.adapter_id, &package.id, false);
+                            }
+                            let mut change = history::Entry::ended(
+                                Action::Remove,
+                                &package.adapter_id,
+                                mode,
+                                outcome_of(went, &cancel),
+                            )
+                            .of(&package.id, &package.name)
+                            .moved(Some(package.version.clone()), now);
+                            change.output = app.output_tail(key);
+                            changes.push(change);
+                            app.clear_progress(key);
+                        }
+                        app.journal(changes);
+
+                        let ended = ended_with(failure, &cancel);
+                        match &ended {
+                            JobState::Cancelled => {
+                                app.add_toast(ToastLevel::Info, format!("Stopped removing {what}"))
+                            }
+                            JobState::Failed(why) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Failed to remove {what}. {why}"),
+                            ),
+                            _ => app.add_toast(ToastLevel::Success, format!("Removed {what}")),
+                        }
+                        app.installed_state.result_version += 1;
+                        app.load_installed(cx);
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
     }
 
-    /// Remove from installed view — uses unique_key so duplicate package names
-    /// don't cause the wrong card to show "Removing…".
-    pub(crate) fn remove_installed_package(
+    fn run_update(
         &mut self,
-        pkg: crate::core::package::Package,
-        unique_key: String,
+        job: JobId,
+        packages: Vec<crate::core::package::Package>,
         mode: PackageMode,
         cx: &mut Context<Self>,
     ) {
-        let pkg_name = pkg.name.clone();
-        let package_id = pkg.id.clone();
-        let adapter_id = pkg.adapter_id.clone();
-        let progress_key = crate::core::adapter::progress_key(&pkg.adapter_id, &pkg.id);
-        self.installed_state.removing = Some(unique_key);
-        self.installed_state
-            .package_progress
-            .insert(progress_key.clone(), OperationStatus::Starting);
+        // Stands in until the manager says something of its own.
+        let keys = JobKind::Update(packages.clone()).progress_keys();
+        for key in &keys {
+            self.start_output_log(key);
+            self.record_progress(key.clone(), OperationStatus::Starting);
+        }
+        let updating = match keys.as_slice() {
+            [one] => one.clone(),
+            _ => "__batch__".to_string(),
+        };
+        self.updates_state.updating = Some(updating.clone());
+        self.installed_state.updating = Some(updating);
+
+        let cancel = self.job_cancel_token(job, &keys);
+        let stopping = cancel.clone();
         let progress_sender = self.progress_sender.clone();
-        let adapter = self.adapter_manager.get_adapter(&pkg.adapter_id);
+        let manager = self.adapter_manager.clone();
+        let what = named(&packages);
+        let was = match packages.as_slice() {
+            [one] => Some(one.version.clone()),
+            _ => None,
+        };
+        let asked = packages.clone();
+        cx.notify();
 
-        if let Some(adapter) = adapter {
-            cx.spawn(
-                async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                    let result = crate::tokio_spawn(async move {
-                        adapter.remove(&[pkg], Some(progress_sender), mode).await
-                    })
-                    .await;
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let result = crate::tokio_spawn(async move {
+                    let (outcome, held_back) = match manager
+                        .update(&packages, Some(progress_sender), &stopping, mode)
+                        .await
+                    {
+                        Ok(updated) => (Ok(updated.results), updated.held_back),
+                        Err(e) => (Err(e), Vec::new()),
+                    };
 
-                    let _ = cx.update(|cx| {
-                        this.update(cx, |app, cx| {
-                            app.installed_state.removing = None;
-                            app.installed_state.package_progress.remove(&progress_key);
-                            match result {
-                                Ok(Ok(_)) => {
-                                    app.mark_installed(&adapter_id, &package_id, false);
-                                    app.add_toast(
-                                        ToastLevel::Success,
-                                        format!("Removed {pkg_name}"),
-                                    );
-                                }
-                                Ok(Err(e)) => {
-                                    app.add_toast(
-                                        ToastLevel::Error,
-                                        format!("Failed to remove {pkg_name}: {e}"),
-                                    );
-                                }
-                                Err(e) => {
-                                    app.add_toast(
-                                        ToastLevel::Error,
-                                        format!("Failed to remove {pkg_name}: {e}"),
-                                    );
-                                }
+                    // Each manager is looked at again, since exiting cleanly
+                    // is not the same as having moved.
+                    let mut now: HashMap<String, HashMap<String, (String, String)>> =
+                        HashMap::new();
+                    if outcome.is_ok() {
+                        for package in &packages {
+                            if now.contains_key(&package.adapter_id) {
+                                continue;
                             }
-                            app.installed_state.result_version += 1;
-                            app.load_installed(cx);
-                        })
-                    });
-                },
-            )
-            .detach();
-        }
+                            if let Some(adapter) = manager.get_adapter(&package.adapter_id) {
+                                let held = held_by(adapter.as_ref(), mode).await;
+                                now.insert(package.adapter_id.clone(), held);
+                            }
+                        }
+                    }
+
+                    (outcome, now, held_back)
+                })
+                .await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.updates_state.updating = None;
+                        app.installed_state.updating = None;
+                        app.finished_with(&cancel);
+
+                        let (result, held, held_back) = match result {
+                            Ok((outcome, held, held_back)) => (Ok(outcome), held, held_back),
+                            Err(e) => (Err(e), HashMap::new(), Vec::new()),
+                        };
+                        let (results, failure) = settled(result);
+                        // A pinned package left alone was not updated, but
+                        // nothing went wrong with it either, so it is not
+                        // recorded as though it had been tried.
+                        let pinned: HashSet<String> = held_back
+                            .iter()
+                            .map(|p| crate::core::adapter::package_key(&p.adapter_id, &p.id))
+                            .collect();
+                        let tried = |package: &&crate::core::package::Package| {
+                            !pinned.contains(&crate::core::adapter::package_key(
+                                &package.adapter_id,
+                                &package.id,
+                            ))
+                        };
+                        // The version held now, or nothing when the manager
+                        // will not say.
+                        let now_of = |package: &crate::core::package::Package| {
+                            held.get(&package.adapter_id)
+                                .and_then(|held| held.get(&package.id))
+                                .map(|(_, version)| version.clone())
+                        };
+                        let changes: Vec<history::Entry> = asked
+                            .iter()
+                            .zip(&keys)
+                            .filter(|(package, _)| tried(package))
+                            .map(|(package, key)| {
+                                let went = went(&results, package, &failure);
+                                let mut change = history::Entry::ended(
+                                    Action::Update,
+                                    &package.adapter_id,
+                                    mode,
+                                    outcome_of(went, &cancel),
+                                )
+                                .of(&package.id, &package.name)
+                                .moved(Some(package.version.clone()), now_of(package));
+                                change.output = app.output_tail(key);
+                                change
+                            })
+                            .collect();
+                        let now = asked.first().and_then(now_of);
+                        app.journal(changes);
+                        let moved: HashSet<String> = asked
+                            .iter()
+                            .filter(tried)
+                            .filter(|p| went(&results, p, &failure).is_ok())
+                            .map(|p| crate::core::adapter::package_key(&p.adapter_id, &p.id))
+                            .collect();
+                        app.updates_state.updates.retain(|u| {
+                            !moved.contains(&crate::core::adapter::package_key(
+                                &u.package.adapter_id,
+                                &u.package.id,
+                            ))
+                        });
+                        for key in &keys {
+                            app.clear_progress(key);
+                        }
+
+                        let ended = ended_with(failure, &cancel);
+                        // Everything held back leaves nothing else to say.
+                        match (&ended, was) {
+                            _ if held_back.len() == asked.len() => {}
+                            (JobState::Cancelled, _) => {
+                                app.add_toast(ToastLevel::Info, format!("Stopped updating {what}"))
+                            }
+                            (JobState::Failed(why), _) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Failed to update {what}. {why}"),
+                            ),
+                            (_, Some(was)) => {
+                                let (level, said) = update_outcome(&what, &was, now);
+                                app.add_toast(level, said);
+                            }
+                            _ => app.add_toast(ToastLevel::Success, format!("Updated {what}")),
+                        }
+                        if !held_back.is_empty() {
+                            app.add_toast(
+                                ToastLevel::Info,
+                                format!("Held back {}, as pinned", named(&held_back)),
+                            );
+                        }
+                        app.updates_state.result_version += 1;
+                        app.installed_state.result_version += 1;
+                        app.installed_state.loaded = false;
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
     }
 
     /// Hand a manager that cannot be pointed at one package the only update
     /// it understands.
-    fn update_everything_in(
+    fn run_update_everything_in(
         &mut self,
+        job: JobId,
         adapter_id: String,
         adapter_name: String,
         mode: PackageMode,
         cx: &mut Context<Self>,
     ) {
         let Some(adapter) = self.adapter_manager.get_adapter(&adapter_id) else {
-            self.add_toast(
-                ToastLevel::Error,
-                format!("Cannot update: {adapter_id} is not loaded"),
-            );
+            let why = format!("{adapter_id} is not loaded");
+            self.add_toast(ToastLevel::Error, format!("Cannot update: {why}"));
+            self.clear_progress(&crate::core::adapter::manager_progress_key(&adapter_id));
+            self.job_ended(job, JobState::Failed(why), cx);
             return;
         };
 
@@ -4999,6 +6211,8 @@ impl App {
         let progress_key = crate::core::adapter::manager_progress_key(&adapter_id);
         self.start_output_log(&progress_key);
         self.record_progress(progress_key.clone(), OperationStatus::Starting);
+        let cancel = self.job_cancel_token(job, std::slice::from_ref(&progress_key));
+        let stopping = cancel.clone();
         cx.notify();
 
         cx.spawn(
@@ -5008,13 +6222,15 @@ impl App {
                     // so the answer has to come from what it held either side
                     // of the run.
                     let before = held_by(adapter.as_ref(), mode).await;
-                    let outcome = adapter.update_all(Some(progress_sender), mode).await;
-                    let changed = match &outcome {
-                        Ok(()) => changed_between(&before, &held_by(adapter.as_ref(), mode).await),
-                        Err(_) => Vec::new(),
+                    let outcome = adapter
+                        .update_all(Some(progress_sender), &stopping, mode)
+                        .await;
+                    let after = match &outcome {
+                        Ok(()) => held_by(adapter.as_ref(), mode).await,
+                        Err(_) => before.clone(),
                     };
 
-                    (outcome, changed)
+                    (outcome, before, after)
                 })
                 .await;
 
@@ -5022,24 +6238,131 @@ impl App {
                     this.update(cx, |app, cx| {
                         app.updates_state.updating = None;
                         app.clear_progress(&progress_key);
+                        app.finished_with(&cancel);
                         app.updates_state.result_version += 1;
                         app.installed_state.result_version += 1;
-                        match result {
-                            Ok((Ok(()), changed)) => {
+                        let moved = match &result {
+                            Ok((_, before, after)) => moved_between(before, after),
+                            Err(_) => Vec::new(),
+                        };
+                        let mut changes: Vec<history::Entry> = moved
+                            .iter()
+                            .map(|m| {
+                                history::Entry::ended(
+                                    Action::UpdateAll,
+                                    &adapter_id,
+                                    mode,
+                                    Outcome::Succeeded,
+                                )
+                                .of(&m.id, &m.name)
+                                .moved(m.was.clone(), Some(m.now.clone()))
+                            })
+                            .collect();
+                        let ended = match result {
+                            Ok((Ok(()), before, after)) => {
+                                let changed = changed_between(&before, &after);
                                 let (level, said) = wholesale_outcome(&adapter_name, &changed);
                                 app.add_toast(level, said);
+                                JobState::Finished
                             }
-                            Ok((Err(e), _)) => app.add_toast(
-                                ToastLevel::Error,
-                                format!("Failed to update {adapter_name}: {e}"),
-                            ),
-                            Err(e) => app.add_toast(
-                                ToastLevel::Error,
-                                format!("Failed to update {adapter_name}: {e}"),
-                            ),
+                            Ok((Err(_), ..)) if cancel.is_cancelled() => {
+                                app.add_toast(
+                                    ToastLevel::Info,
+                                    format!("Stopped updating {adapter_name}"),
+                                );
+                                JobState::Cancelled
+                            }
+                            Ok((Err(e), ..)) => {
+                                app.add_toast(
+                                    ToastLevel::Error,
+                                    format!("Failed to update {adapter_name}: {e}"),
+                                );
+                                JobState::Failed(e.to_string())
+                            }
+                            Err(e) => {
+                                app.add_toast(
+                                    ToastLevel::Error,
+                                    format!("Failed to update {adapter_name}: {e}"),
+                                );
+                                JobState::Failed(e.to_string())
+                            }
+                        };
+                        // A run that moved nothing, or did not finish, is still
+                        // a change someone asked for, so it is kept against
+                        // the manager as a whole.
+                        if changes.is_empty() {
+                            changes.push(history::Entry::ended(
+                                Action::UpdateAll,
+                                &adapter_id,
+                                mode,
+                                job_outcome(&ended),
+                            ));
+                        }
+                        let output = app.output_tail(&progress_key);
+                        for change in &mut changes {
+                            change.output = output.clone();
                         }
+                        app.journal(changes);
                         app.installed_state.loaded = false;
-                        cx.notify();
+                        app.job_ended(job, ended, cx);
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    fn run_sync(&mut self, job: JobId, adapters: Vec<String>, cx: &mut Context<Self>) {
+        self.adapter_view.syncing = Some("__all__".to_string());
+
+        let progress_sender = self.progress_sender.clone();
+        let manager_adapters: Vec<Arc<dyn Adapter>> = adapters
+            .iter()
+            .filter_map(|id| self.adapter_manager.get_adapter(id))
+            .collect();
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let errors = crate::tokio_spawn(async move {
+                    let mut errors: Vec<(String, String)> = Vec::new();
+                    for adapter in &manager_adapters {
+                        match adapter.sync(Some(progress_sender.clone())).await {
+                            Ok(_) => log::info!("Synced {}", adapter.info().id),
+                            Err(e) => {
+                                log::warn!("Sync failed for {}: {e}", adapter.info().id);
+                                errors.push((adapter.info().id.clone(), format!("{e}")));
+                            }
+                        }
+                    }
+                    errors
+                })
+                .await
+                .unwrap_or_default();
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.adapter_view.syncing = None;
+                        app.adapter_view.repos_version += 1;
+                        let ended = if errors.is_empty() {
+                            app.add_toast(ToastLevel::Success, "Repositories synced".into());
+                            app.adapter_view.sync_error = None;
+                            JobState::Finished
+                        } else {
+                            for (adapter_id, err) in &errors {
+                                app.add_toast(
+                                    ToastLevel::Error,
+                                    format!("Sync failed for {adapter_id}: {err}"),
+                                );
+                            }
+                            let why = errors
+                                .iter()
+                                .map(|(id, e)| format!("{id}: {e}"))
+                                .collect::<Vec<_>>()
+                                .join("; ");
+                            app.adapter_view.sync_error = Some(why.clone());
+                            JobState::Failed(why)
+                        };
+                        app.job_ended(job, ended, cx);
                     })
                 });
             },
@@ -5047,82 +6370,76 @@ impl App {
         .detach();
     }
 
-    fn update_package(
+    fn run_clean(
         &mut self,
-        pkg: crate::core::package::Package,
-        mode: PackageMode,
+        job: JobId,
+        adapter_id: String,
+        adapter_name: String,
+        all: bool,
         cx: &mut Context<Self>,
-    ) {
-        let pkg_name = pkg.name.clone();
-        let progress_sender = self.progress_sender.clone();
-        let Some(adapter) = self.adapter_manager.get_adapter(&pkg.adapter_id) else {
-            self.add_toast(
-                ToastLevel::Error,
-                format!("Cannot update {pkg_name}: {} is not loaded", pkg.adapter_id),
+    ) {
+        let Some(adapter) = self.adapter_manager.get_adapter(&adapter_id) else {
+            self.adapter_view.cleaning.remove(&adapter_id);
+            self.job_ended(
+                job,
+                JobState::Failed(format!("{adapter_id} is not loaded")),
+                cx,
             );
             return;
         };
+        self.adapter_view.cleaning.insert(adapter_id.clone());
+        let before = self
+            .adapter_view
+            .cache_sizes
+            .get(&adapter_id)
+            .and_then(|size| size.as_ref().ok().copied());
 
-        // Stands in until the manager says something of its own.
-        let progress_key = crate::core::adapter::progress_key(&pkg.adapter_id, &pkg.id);
-
-        self.updates_state.updating = Some(progress_key.clone());
-        self.installed_state.updating = Some(progress_key.clone());
-
-        self.start_output_log(&progress_key);
-        self.record_progress(progress_key.clone(), OperationStatus::Starting);
-        cx.notify();
-
-        let pkg_id = pkg.id.clone();
-        let was = pkg.version.clone();
-
+        let progress_sender = self.progress_sender.clone();
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
                 let result = crate::tokio_spawn(async move {
-                    let outcome = adapter.update(&[pkg], Some(progress_sender), mode).await;
-
-                    let now = match &outcome {
-                        Ok(_) => version_of(adapter.as_ref(), &pkg_id, mode).await,
-                        Err(_) => None,
-                    };
-
-                    (outcome, now)
+                    adapter.clean_cache(all, Some(progress_sender)).await?;
+                    Ok::<_, crate::core::adapter::AdapterError>(adapter.cache_size().await.ok())
                 })
-                .await;
+                .await
+                .unwrap_or_else(|e| Err(crate::core::adapter::AdapterError::Other(e.to_string())));
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
-                        app.updates_state.updating = None;
-                        app.installed_state.updating = None;
-                        app.clear_progress(&progress_key);
-                        app.updates_state.result_version += 1;
-                        app.installed_state.result_version += 1;
-                        match result {
-                            Ok((Ok(results), now)) => match failure_among(&results) {
-                                Some(why) => app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to update {pkg_name}. {why}"),
-                                ),
-                                None => {
-                                    let (level, said) = update_outcome(&pkg_name, &was, now);
-                                    app.add_toast(level, said);
-                                }
-                            },
-                            Ok((Err(e), _)) => {
+                        app.adapter_view.cleaning.remove(&adapter_id);
+                        let ended = match result {
+                            Ok(after) => {
+                                let freed = match (before, after) {
+                                    (Some(before), Some(after)) if before > after => format!(
+                                        ", freeing {}",
+                                        views::browse::format_bytes_pub(before - after)
+                                    ),
+                                    _ => String::new(),
+                                };
                                 app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to update {pkg_name}: {e}"),
+                                    ToastLevel::Success,
+                                    format!("Cleaned the {adapter_name} cache{freed}"),
                                 );
+                                match after {
+                                    Some(size) => {
+                                        app.adapter_view.cache_sizes.insert(adapter_id, Ok(size));
+                                    }
+                                    None => {
+                                        app.adapter_view.cache_sizes.remove(&adapter_id);
+                                    }
+                                }
+                                JobState::Finished
                             }
                             Err(e) => {
+                                let why = e.to_string();
                                 app.add_toast(
                                     ToastLevel::Error,
-                                    format!("Failed to update {pkg_name}: {e}"),
+                                    format!("Could not clean the {adapter_name} cache: {why}"),
                                 );
+                                JobState::Failed(why)
                             }
-                        }
-                        app.installed_state.loaded = false;
-                        cx.notify();
+                        };
+                        app.job_ended(job, ended, cx);
                     })
                 });
             },
@@ -5130,82 +6447,55 @@ impl App {
         .detach();
     }
 
-    fn batch_install(
-        &mut self,
-        pkgs: Vec<crate::core::package::Package>,
-        mode: PackageMode,
-        cx: &mut Context<Self>,
-    ) {
-        let count = pkgs.len();
-        self.browse_state.installing = Some("__batch__".to_string());
-        let progress_sender = self.progress_sender.clone();
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
-            .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
+    /// Ask a manager how much room its cache takes.
+    pub fn measure_cache(&mut self, adapter_id: &str, cx: &mut Context<Self>) {
+        let Some(adapter) = self.adapter_manager.get_adapter(adapter_id) else {
+            return;
+        };
+        if !adapter.capabilities().has_cache_size
+            || !self
+                .adapter_view
+                .cache_measuring
+                .insert(adapter_id.to_string())
+        {
+            return;
+        }
 
+        let aid = adapter_id.to_string();
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in pkgs {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg);
-                    }
+                let size = crate::tokio_spawn(async move { adapter.cache_size().await })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|size| size.map_err(|e| e.to_string()));
 
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .install(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(results) => {
-                                    // Answering is not the same as
-                                    // having worked: each package
-                                    // carries its own outcome.
-                                    if let Some(why) = failure_among(&results) {
-                                        log::error!("Batch install failed for {adapter_id}: {why}");
-                                        errors.push(why);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Batch install failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.adapter_view.cache_measuring.remove(&aid);
+                        app.adapter_view.cache_sizes.insert(aid, size);
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Measure the caches aeris keeps for itself.
+    pub fn measure_own_caches(&mut self, cx: &mut Context<Self>) {
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let measured = crate::tokio_spawn(async move {
+                    tokio::task::spawn_blocking(crate::core::cache::measure_own)
+                        .await
+                        .unwrap_or_default()
                 })
                 .await
                 .unwrap_or_default();
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
-                        app.browse_state.installing = None;
-                        app.browse_state.result_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(
-                                ToastLevel::Success,
-                                format!("Installed {count} packages"),
-                            );
-                        } else {
-                            for err in &errors {
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to install: {err}"),
-                                );
-                            }
-                        }
-                        app.installed_state.loaded = false;
+                        app.adapter_view.own_caches = Some(measured);
                         cx.notify();
                     })
                 });
@@ -5214,71 +6504,40 @@ impl App {
         .detach();
     }
 
-    fn batch_remove(
-        &mut self,
-        pkgs: Vec<crate::core::package::Package>,
-        mode: PackageMode,
-        cx: &mut Context<Self>,
-    ) {
-        self.installed_state.removing = Some("__batch__".to_string());
-        let progress_sender = self.progress_sender.clone();
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
-            .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
+    fn clear_own_caches(&mut self, cx: &mut Context<Self>) {
+        if self.adapter_view.own_caches_clearing {
+            return;
+        }
+        self.adapter_view.own_caches_clearing = true;
+        cx.notify();
 
-        let count = pkgs.len();
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in pkgs {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg);
-                    }
-
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .remove(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(_) => log::info!("Batch remove completed for {adapter_id}"),
-                                Err(e) => {
-                                    log::error!("Batch remove failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
+                let cleared = crate::tokio_spawn(async move {
+                    tokio::task::spawn_blocking(crate::core::cache::clear_own)
+                        .await
+                        .unwrap_or_else(|e| Err(e.to_string()))
                 })
                 .await
-                .unwrap_or_default();
+                .unwrap_or_else(|e| Err(e.to_string()));
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
-                        app.installed_state.removing = None;
-                        app.installed_state.result_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(ToastLevel::Success, format!("Removed {count} packages"));
-                        } else {
-                            for err in &errors {
-                                app.add_toast(
-                                    ToastLevel::Error,
-                                    format!("Failed to remove: {err}"),
-                                );
-                            }
+                        app.adapter_view.own_caches_clearing = false;
+                        match cleared {
+                            Ok(freed) => app.add_toast(
+                                ToastLevel::Success,
+                                format!(
+                                    "Cleared aeris's caches, freeing {}",
+                                    views::browse::format_bytes_pub(freed)
+                                ),
+                            ),
+                            Err(e) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Could not clear everything: {e}"),
+                            ),
                         }
-                        app.load_installed(cx);
+                        app.measure_own_caches(cx);
                     })
                 });
             },
@@ -5286,80 +6545,126 @@ impl App {
         .detach();
     }
 
-    fn batch_update(
+    fn run_apply(
         &mut self,
-        pkgs: Vec<crate::core::package::Package>,
+        job: JobId,
+        adapter_id: String,
+        prune: bool,
         mode: PackageMode,
         cx: &mut Context<Self>,
     ) {
-        self.updates_state.updating = Some("__batch__".to_string());
-        let progress_sender = self.progress_sender.clone();
-        let manager_adapters: Vec<Arc<dyn Adapter>> = self
-            .adapter_manager
-            .list_adapters()
-            .iter()
-            .filter_map(|info| self.adapter_manager.get_adapter(&info.id))
-            .collect();
+        use crate::manifest_file::ManifestLoadError;
+        use views::manifest::ManifestStatus;
+
+        let Some(adapter) = self.adapter_manager.get_adapter(&adapter_id) else {
+            let why = format!("{adapter_id} is not loaded");
+            self.manifest_state.applying = false;
+            self.manifest_state.apply_error = Some(why.clone());
+            self.journal(vec![history::Entry::ended(
+                Action::Apply,
+                &adapter_id,
+                mode,
+                Outcome::Failed(why.clone()),
+            )]);
+            self.job_ended(job, JobState::Failed(why), cx);
+            return;
+        };
+
+        let seed_keys: Vec<String> = match &self.manifest_state.status {
+            ManifestStatus::Loaded(diff) => {
+                let mut keys: Vec<String> = diff
+                    .to_install
+                    .iter()
+                    .chain(diff.to_update.iter())
+                    .map(|e| crate::core::adapter::package_key(&adapter_id, &e.name))
+                    .collect();
+                if prune {
+                    keys.extend(
+                        diff.to_remove
+                            .iter()
+                            .map(|e| crate::core::adapter::package_key(&adapter_id, &e.name)),
+                    );
+                }
+                keys
+            }
+            _ => Vec::new(),
+        };
+        for key in seed_keys {
+            self.record_progress(key, OperationStatus::Starting);
+        }
+
+        self.manifest_state.applying = true;
+        let progress = self.progress_sender.clone();
+        let manager_key = crate::core::adapter::manager_progress_key(&adapter_id);
+        self.start_output_log(&manager_key);
 
-        let count = pkgs.len();
         cx.spawn(
             async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
-                let errors = crate::tokio_spawn(async move {
-                    let mut by_adapter: HashMap<String, Vec<crate::core::package::Package>> =
-                        HashMap::new();
-                    for pkg in pkgs {
-                        by_adapter
-                            .entry(pkg.adapter_id.clone())
-                            .or_default()
-                            .push(pkg);
-                    }
-
-                    let mut errors: Vec<String> = Vec::new();
-                    for (adapter_id, pkgs) in by_adapter {
-                        if let Some(adapter) =
-                            manager_adapters.iter().find(|a| a.info().id == adapter_id)
-                        {
-                            match adapter
-                                .update(&pkgs, Some(progress_sender.clone()), mode)
-                                .await
-                            {
-                                Ok(results) => {
-                                    // Answering is not the same as
-                                    // having worked: each package
-                                    // carries its own outcome.
-                                    if let Some(why) = failure_among(&results) {
-                                        log::error!("Batch update failed for {adapter_id}: {why}");
-                                        errors.push(why);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Batch update failed for {adapter_id}: {e}");
-                                    errors.push(format!("{e}"));
-                                }
-                            }
-                        }
-                    }
-                    errors
+                let result = crate::tokio_spawn(async move {
+                    adapter
+                        .declarative_apply(prune, Some(progress))
+                        .await
+                        .map_err(|e| ManifestLoadError::Other(e.to_string()))
                 })
-                .await
-                .unwrap_or_default();
+                .await;
 
                 let _ = cx.update(|cx| {
                     this.update(cx, |app, cx| {
-                        app.updates_state.updating = None;
-                        app.updates_state.result_version += 1;
-                        if errors.is_empty() {
-                            app.add_toast(ToastLevel::Success, format!("Updated {count} packages"));
-                        } else {
-                            for err in &errors {
+                        app.manifest_state.applying = false;
+                        let ended = match result {
+                            Ok(Ok(report)) => {
+                                app.manifest_state.last_report = Some(report);
+                                let msg = format!(
+                                    "Manifest applied: {} installed, {} updated, {} removed",
+                                    report.installed, report.updated, report.removed
+                                );
+                                if report.failed > 0 {
+                                    app.add_toast(
+                                        ToastLevel::Error,
+                                        format!("{msg}, {} failed", report.failed),
+                                    );
+                                    JobState::Failed(format!("{} failed", report.failed))
+                                } else {
+                                    app.add_toast(ToastLevel::Success, msg);
+                                    JobState::Finished
+                                }
+                            }
+                            Ok(Err(err)) => {
+                                let msg = match err {
+                                    ManifestLoadError::FileMissing => {
+                                        "Manifest file is missing".to_string()
+                                    }
+                                    ManifestLoadError::Parse(e) | ManifestLoadError::Other(e) => e,
+                                };
+                                app.manifest_state.apply_error = Some(msg.clone());
                                 app.add_toast(
                                     ToastLevel::Error,
-                                    format!("Failed to update: {err}"),
+                                    format!("Manifest apply failed: {msg}"),
                                 );
+                                JobState::Failed(msg)
                             }
-                        }
+                            Err(e) => {
+                                let msg = format!("{e}");
+                                app.manifest_state.apply_error = Some(msg.clone());
+                                app.add_toast(
+                                    ToastLevel::Error,
+                                    format!("Manifest apply failed: {msg}"),
+                                );
+                                JobState::Failed(msg)
+                            }
+                        };
+                        let mut change = history::Entry::ended(
+                            Action::Apply,
+                            &adapter_id,
+                            mode,
+                            job_outcome(&ended),
+                        );
+                        change.output = app.output_tail(&manager_key);
+                        app.journal(vec![change]);
                         app.installed_state.loaded = false;
-                        cx.notify();
+                        app.updates_state.checked = false;
+                        app.load_manifest_diff(cx);
+                        app.job_ended(job, ended, cx);
                     })
                 });
             },
@@ -5417,6 +6722,42 @@ mod tests {
         assert!(!keys.contains("soar:cat"), "a name shared says nothing");
     }
 
+    #[test]
+    fn a_wholesale_update_keeps_where_each_package_moved_from() {
+        use super::moved_between;
+        use std::collections::HashMap;
+
+        let held = |pairs: &[(&str, &str)]| -> HashMap<String, (String, String)> {
+            pairs
+                .iter()
+                .map(|(name, version)| (name.to_string(), (name.to_string(), version.to_string())))
+                .collect()
+        };
+
+        let before = held(&[("htop", "3.5.2"), ("firedragon", "12.9.1")]);
+        let after = held(&[
+            ("htop", "3.5.2"),
+            ("firedragon", "12.9.2"),
+            ("yazi", "26.1"),
+        ]);
+        let moved: Vec<_> = moved_between(&before, &after)
+            .into_iter()
+            .map(|m| (m.id, m.was, m.now))
+            .collect();
+        assert_eq!(
+            moved,
+            vec![
+                (
+                    "firedragon".to_string(),
+                    Some("12.9.1".to_string()),
+                    "12.9.2".to_string()
+                ),
+                // Pulled in by the run, so it came from nowhere.
+                ("yazi".to_string(), None, "26.1".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn a_wholesale_update_reports_what_actually_moved() {
         use super::{ToastLevel, changed_between, wholesale_outcome};
@@ -1,4 +1,4 @@
-use crate::core::{package::Package, privilege::PackageMode};
+use crate::core::{inventory::ImportPlan, package::Package, privilege::PackageMode};
 
 #[derive(Debug, Clone)]
 pub enum ConfirmAction {
@@ -16,10 +16,10 @@ pub enum ConfirmAction {
     BatchInstall(Vec<Package>, PackageMode),
     BatchRemove(Vec<Package>, PackageMode),
     BatchUpdate(Vec<Package>, PackageMode),
-    /// Remove a specific installed entry, disambiguated by unique_key.
+    /// Remove a specific installed entry. Its version tells it apart from
+    /// another install of the same package.
     RemoveInstalled {
         pkg: Package,
-        unique_key: String,
         mode: PackageMode,
     },
     /// Remove the current selection in the Installed view.
@@ -37,6 +37,16 @@ pub enum ConfirmAction {
     },
     /// Replace the manifest's packages table with the current installed set.
     ImportInstalledManifest,
+    /// Install what an exported file lists that is not here yet.
+    ImportInventory(ImportPlan),
+    /// Clear a manager's cache, outright when `all` is set.
+    CleanCache {
+        adapter_id: String,
+        adapter_name: String,
+        all: bool,
+    },
+    /// Clear the icons, registries and release notes aeris keeps.
+    ClearOwnCaches,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -0,0 +1,191 @@
+//! Looking for updates on an interval, with or without the window open, and
+//! carrying out the managers' unattended update policies.
+//!
+//! Started with `--background` aeris opens no window at all, so it can be
+//! autostarted at login and only show itself when a notification is clicked.
+//!
+//! The window and `--background` may well be running at once, so only the
+//! one [`unattended::in_charge`] does either; the other waits its turn.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use gpui::AsyncApp;
+
+use crate::{
+    app::{App, View},
+    config::AerisConfig,
+    core::{
+        adapter_manager::load_managers,
+        history,
+        privilege::PackageMode,
+        unattended::{self, Policy},
+        update_watch,
+    },
+};
+
+/// The shortest wait between two checks, whatever the settings say. Asking
+/// every manager more often than this only keeps them busy.
+const SHORTEST_WAIT: Duration = Duration::from_secs(5 * 60);
+
+/// How long to wait before reading the settings again while checking is
+/// turned off, so turning it back on needs no restart.
+const WHILE_OFF: Duration = Duration::from_secs(15 * 60);
+
+/// How often the unattended policies are looked over. A schedule is kept to
+/// within this much of the time it names.
+const POLICY_TICK: Duration = Duration::from_secs(60);
+
+/// Check for updates until aeris quits, raising a notification whenever
+/// something turns up that was not on offer at the last check. That check
+/// may have been made before aeris last started, so what was kept of it is
+/// read back rather than announcing everything still waiting again.
+///
+/// The settings are read afresh each time round, so changing the interval or
+/// turning notifications off in the Settings page takes effect at the next
+/// check rather than at the next start.
+pub fn watch_for_updates(cx: &mut gpui::App) {
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        let kept = update_watch::offered_path();
+        let mut seen = update_watch::last_offered(&kept);
+        loop {
+            let config = AerisConfig::load();
+            let wait = config.update_check_interval();
+            if !config.notifications.unwrap_or(true) || wait.is_none() || !unattended::in_charge() {
+                cx.background_executor().timer(WHILE_OFF).await;
+                continue;
+            }
+
+            // A manager with a policy of its own says what it has to say
+            // when that policy runs.
+            let planned: HashSet<String> = unattended::plans(&config)
+                .into_iter()
+                .filter(|(_, plan)| plan.policy != Policy::Never)
+                .map(|(id, _)| id)
+                .collect();
+            let (updates, names) = crate::tokio_spawn(async move {
+                let manager = load_managers(&config).manager;
+                // Both modes, since a manager that only acts system wide, apt
+                // say, has nothing to offer in the user's.
+                let mut updates = Vec::new();
+                for mode in [PackageMode::User, PackageMode::System] {
+                    let mut found = update_watch::look(&manager, mode).await;
+                    found.retain(|u| !planned.contains(&u.package.adapter_id));
+                    updates.push((mode, found));
+                }
+                let names: HashMap<String, String> = manager
+                    .enabled_adapters()
+                    .into_iter()
+                    .map(|info| (info.id.clone(), info.name.clone()))
+                    .collect();
+                (updates, names)
+            })
+            .await
+            .unwrap_or_default();
+
+            let now: HashSet<String> = updates
+                .iter()
+                .flat_map(|(mode, found)| update_watch::offered(*mode, found))
+                .collect();
+            let updates: Vec<_> = updates.into_iter().flat_map(|(_, found)| found).collect();
+            if update_watch::grew(&seen, &now) {
+                let (title, body) = update_watch::summary(&updates, |id| {
+                    names.get(id).cloned().unwrap_or_else(|| id.to_string())
+                });
+                announce(title, body, cx);
+            }
+            if now != seen
+                && let Err(e) = update_watch::keep_offered(&kept, &now)
+            {
+                log::warn!("could not keep what is on offer: {e}");
+            }
+            seen = now;
+
+            let wait = wait.unwrap_or(WHILE_OFF).max(SHORTEST_WAIT);
+            cx.background_executor().timer(wait).await;
+        }
+    })
+    .detach();
+}
+
+/// Carry out the unattended update policies until aeris quits, each on its
+/// own schedule, and raise a notification for any run a person should hear
+/// about.
+///
+/// Like the check above, the settings are read afresh each time round.
+pub fn run_unattended(cx: &mut gpui::App) {
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        loop {
+            let config = AerisConfig::load();
+            let plans = unattended::plans(&config);
+            let tell = config.notifications.unwrap_or(true);
+
+            if plans.iter().any(|(_, plan)| plan.policy != Policy::Never) && unattended::in_charge()
+            {
+                let (runs, names) = crate::tokio_spawn(async move {
+                    let manager = load_managers(&config).manager;
+                    let runs = unattended::run_due(&manager, &plans, history::now()).await;
+                    let names: HashMap<String, String> = manager
+                        .enabled_adapters()
+                        .into_iter()
+                        .map(|info| (info.id.clone(), info.name.clone()))
+                        .collect();
+                    (runs, names)
+                })
+                .await
+                .unwrap_or_default();
+
+                for run in runs.iter().filter(|run| tell && run.worth_telling()) {
+                    let name = names
+                        .get(&run.adapter_id)
+                        .cloned()
+                        .unwrap_or_else(|| run.adapter_id.clone());
+                    announce(name, run.said.clone(), cx);
+                }
+            }
+
+            cx.background_executor().timer(POLICY_TICK).await;
+        }
+    })
+    .detach();
+}
+
+/// Raise a notification that brings up the Updates view when clicked.
+///
+/// Waiting on it would hold up whatever raised it for as long as it sits
+/// unanswered, so it is waited on apart.
+fn announce(title: String, body: String, cx: &mut AsyncApp) {
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        let clicked = crate::tokio_spawn(async move {
+            tokio::task::spawn_blocking(move || update_watch::notify(&title, &body))
+                .await
+                .unwrap_or(false)
+        })
+        .await
+        .unwrap_or(false);
+        if clicked {
+            let _ = cx.update(open_updates);
+        }
+    })
+    .detach();
+}
+
+/// Bring the window up on the Updates view, opening it if there is none.
+fn open_updates(cx: &mut gpui::App) {
+    let held = cx
+        .windows()
+        .into_iter()
+        .find_map(|window| window.downcast::<App>());
+    let handle = match held {
+        Some(handle) => handle,
+        None => crate::open_window(cx),
+    };
+
+    let _ = handle.update(cx, |app, window, cx| {
+        app.current_view = View::Updates;
+        // What prompted the notification is newer than whatever was checked.
+        app.updates_state.checked = false;
+        window.activate_window();
+        cx.notify();
+    });
+}
@@ -0,0 +1,1431 @@
+//! Aeris without its window.
+//!
+//! Each subcommand drives the same adapters the window would load, read the
+//! same way and with the same managers turned off, so a script sees what the
+//! window would have shown. Tables are for reading; `--json` is for anything
+//! that has to read the answer back.
+
+use std::{
+    collections::HashSet,
+    io::{BufRead, IsTerminal},
+    sync::Arc,
+};
+
+use serde::Serialize;
+
+use crate::adapters::command;
+use crate::config::AerisConfig;
+use crate::core::{
+    adapter::{Adapter, CancelToken, ProgressEvent, ProgressSender},
+    adapter_manager::{AdapterManager, load_managers, pinned_in},
+    history,
+    inventory::{Held, ImportPlan, Inventory, pin_again},
+    package::{InstallResult, Package, failure_among},
+    privilege::PackageMode,
+    registry,
+};
+
+const USAGE: &str = "\
+Usage: aeris [<command>] [options]
+
+With no command, aeris opens its window. With --background it opens none,
+and only looks for updates, showing the window when a notification about
+them is clicked.
+
+Commands:
+  search <query>          Search every enabled manager
+  install <package>...    Install packages
+  remove <package>...     Remove installed packages
+  update [<package>...]   Update the packages named, or everything
+  list                    List what is installed
+  updates                 List the updates on offer
+  sync                    Refresh each manager's repositories and the registry
+  export [<file>]         Write down what every manager holds, to stdout when
+                          no file is named
+  import <file>           Install what an exported file lists that is missing
+  manifest upgrade <file> Rewrite an adapter manifest in the current schema,
+                          keeping its comments
+
+A package is named as <manager>:<id>, or by its id alone where only one
+manager has it.
+
+Options:
+  --mode user|system      Whose packages to work with
+  --adapter <id>          Only ask this manager (may be given more than once)
+  --limit <n>             At most this many results from each manager (search)
+  --dry-run               Say what would be installed without installing (import)
+  --json                  Print JSON instead of a table
+  -h, --help              Print this help";
+
+/// The subcommands, and what each was handed.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Search {
+        query: String,
+        limit: Option<usize>,
+    },
+    Install(Vec<String>),
+    Remove(Vec<String>),
+    Update(Vec<String>),
+    List,
+    Updates,
+    Sync,
+    /// Write down what every manager holds, to the file named or to stdout.
+    Export(Option<String>),
+    Import {
+        path: String,
+        dry_run: bool,
+    },
+    /// Rewrite the adapter manifest at this path in the current schema.
+    UpgradeManifest(String),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Invocation {
+    pub action: Action,
+    /// Left unset, the mode is the one the window would have opened in.
+    pub mode: Option<PackageMode>,
+    pub json: bool,
+    /// The managers to ask. Empty means every one that is turned on.
+    pub adapters: Vec<String>,
+}
+
+/// What the command line asks for, or `None` when it names no subcommand and
+/// the window should open as it always has.
+pub fn parse(args: &[String]) -> Result<Option<Invocation>, String> {
+    let Some(first) = args.first() else {
+        return Ok(None);
+    };
+
+    let action = match first.as_str() {
+        "search" | "install" | "remove" | "update" | "list" | "updates" | "sync" | "export"
+        | "import" | "manifest" => first.as_str(),
+        "help" | "-h" | "--help" => {
+            return Ok(Some(Invocation {
+                action: Action::Help,
+                mode: None,
+                json: false,
+                adapters: Vec::new(),
+            }));
+        }
+        // Whatever a desktop launcher might pass along is not a command.
+        _ if !first.starts_with('-') => return Err(format!("unknown command `{first}`")),
+        _ => return Ok(None),
+    };
+
+    let mut mode = None;
+    let mut json = false;
+    let mut adapters = Vec::new();
+    let mut limit = None;
+    let mut dry_run = false;
+    let mut words = Vec::new();
+
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        let (flag, attached) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |name: &str| {
+            attached
+                .clone()
+                .or_else(|| rest.next().cloned())
+                .ok_or_else(|| format!("{name} needs a value"))
+        };
+
+        match flag {
+            "--json" => json = true,
+            "--dry-run" => dry_run = true,
+            "--mode" => mode = Some(parse_mode(&value("--mode")?)?),
+            "--adapter" | "-a" => adapters.push(value("--adapter")?),
+            "--limit" => {
+                let written = value("--limit")?;
+                limit = Some(
+                    written
+                        .parse()
+                        .map_err(|_| format!("--limit takes a number, not `{written}`"))?,
+                );
+            }
+            "-h" | "--help" => {
+                return Ok(Some(Invocation {
+                    action: Action::Help,
+                    mode,
+                    json,
+                    adapters,
+                }));
+            }
+            _ if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option `{flag}`"));
+            }
+            _ => words.push(arg.clone()),
+        }
+    }
+
+    let action = match action {
+        "search" => {
+            if words.is_empty() {
+                return Err("search needs something to look for".into());
+            }
+            Action::Search {
+                query: words.join(" "),
+                limit,
+            }
+        }
+        "install" | "remove" if words.is_empty() => {
+            return Err(format!("{action} needs at least one package"));
+        }
+        "install" => Action::Install(words),
+        "export" | "import" if words.len() > 1 => {
+            return Err(format!("{action} takes one file, not {}", words.len()));
+        }
+        "export" => Action::Export(words.pop()),
+        "import" => match words.pop() {
+            Some(path) => Action::Import { path, dry_run },
+            None => return Err("import needs the file to read".into()),
+        },
+        "manifest" => match words.as_slice() {
+            [verb, path] if verb == "upgrade" => Action::UpgradeManifest(path.clone()),
+            [verb, ..] if verb == "upgrade" => {
+                return Err("manifest upgrade takes one file".into());
+            }
+            _ => return Err("manifest takes `upgrade <file>`".into()),
+        },
+        "remove" => Action::Remove(words),
+        "update" => Action::Update(words),
+        other if !words.is_empty() => {
+            return Err(format!(
+                "{other} takes no arguments, but was given `{}`",
+                words[0]
+            ));
+        }
+        "list" => Action::List,
+        "updates" => Action::Updates,
+        _ => Action::Sync,
+    };
+
+    Ok(Some(Invocation {
+        action,
+        mode,
+        json,
+        adapters,
+    }))
+}
+
+fn parse_mode(written: &str) -> Result<PackageMode, String> {
+    match written.to_ascii_lowercase().as_str() {
+        "user" => Ok(PackageMode::User),
+        "system" => Ok(PackageMode::System),
+        _ => Err(format!("--mode is user or system, not `{written}`")),
+    }
+}
+
+/// Carry out what was asked, returning the exit code.
+pub async fn run(invocation: Invocation) -> i32 {
+    if invocation.action == Action::Help {
+        println!("{USAGE}");
+        return 0;
+    }
+    // Only a file is touched, so there are no managers to load.
+    if let Action::UpgradeManifest(path) = &invocation.action {
+        return match upgrade_manifest(path) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("aeris: {e}");
+                1
+            }
+        };
+    }
+
+    let config = AerisConfig::load();
+    let manager = load_managers(&config).manager;
+    let mode = invocation.mode.unwrap_or_else(|| manager.preferred_mode());
+
+    // Both work across every manager and every scope, so neither is narrowed
+    // to the managers of one.
+    let across = match &invocation.action {
+        Action::Export(path) => Some(export(&manager, path.as_deref()).await),
+        Action::Import { path, dry_run } => {
+            let cancel = stopped_by_interrupt();
+            Some(import(&manager, path, *dry_run, &cancel, invocation.json).await)
+        }
+        _ => None,
+    };
+    if let Some(said) = across {
+        return match said {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("aeris: {e}");
+                1
+            }
+        };
+    }
+
+    let adapters = match chosen(&manager, &invocation.adapters, mode) {
+        Ok(adapters) => adapters,
+        Err(e) => {
+            eprintln!("aeris: {e}");
+            return 2;
+        }
+    };
+
+    let said = match invocation.action {
+        Action::Search { query, limit } => {
+            search(&adapters, &query, limit, mode, invocation.json).await
+        }
+        Action::Install(names) => {
+            let cancel = stopped_by_interrupt();
+            install(&manager, &adapters, &names, &cancel, mode, invocation.json).await
+        }
+        Action::Remove(names) => {
+            let cancel = stopped_by_interrupt();
+            remove(&manager, &adapters, &names, &cancel, mode, invocation.json).await
+        }
+        Action::Update(names) => {
+            let cancel = stopped_by_interrupt();
+            update(&manager, &adapters, &names, &cancel, mode, invocation.json).await
+        }
+        Action::List => list(&adapters, mode, invocation.json).await,
+        Action::Updates => updates(&adapters, mode, invocation.json).await,
+        Action::Sync => sync(&config, &adapters, invocation.json).await,
+        Action::Export(_) | Action::Import { .. } | Action::UpgradeManifest(_) | Action::Help => {
+            Ok(())
+        }
+    };
+
+    match said {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("aeris: {e}");
+            1
+        }
+    }
+}
+
+/// Rewrite a manifest in the current schema, saying what changed.
+fn upgrade_manifest(path: &str) -> Result<(), String> {
+    let changes = command::migrate::upgrade_file(std::path::Path::new(path))?;
+    if changes.is_empty() {
+        println!(
+            "{path} is already schema {}",
+            command::manifest::SCHEMA_VERSION
+        );
+        return Ok(());
+    }
+
+    println!("Upgraded {path}:");
+    for change in changes {
+        println!("  {change}");
+    }
+    Ok(())
+}
+
+/// A token cancelled by Ctrl-C, for work that changes packages.
+///
+/// A manager runs in a process group of its own, so the interrupt a terminal
+/// sends never reaches it. It is passed on as a cancellation instead, which
+/// stops the manager and everything it started. Pressing it again gives up
+/// on waiting for that.
+fn stopped_by_interrupt() -> CancelToken {
+    let cancel = CancelToken::default();
+    let cancelling = cancel.clone();
+
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        eprintln!("aeris: stopping. Press Ctrl-C again to leave without waiting");
+        cancelling.cancel();
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+
+    cancel
+}
+
+/// The managers to ask: the ones named, or every one turned on, and either
+/// way only those that work in this mode.
+///
+/// Naming one that is off or missing is refused rather than quietly ignored,
+/// since the answer would otherwise look like it had nothing to say.
+fn chosen(
+    manager: &AdapterManager,
+    named: &[String],
+    mode: PackageMode,
+) -> Result<Vec<Arc<dyn Adapter>>, String> {
+    for id in named {
+        if manager.get_adapter(id).is_none() {
+            return Err(format!("no adapter called `{id}` is loaded"));
+        }
+        if !manager.is_enabled(id) {
+            return Err(format!("{id} is turned off"));
+        }
+    }
+
+    let mut adapters: Vec<Arc<dyn Adapter>> = manager
+        .enabled_adapters()
+        .iter()
+        .filter(|info| named.is_empty() || named.contains(&info.id))
+        .filter(|info| info.capabilities.works_in(mode))
+        .filter_map(|info| manager.get_adapter(&info.id))
+        .collect();
+    adapters.sort_by(|a, b| a.info().id.cmp(&b.info().id));
+
+    if adapters.is_empty() {
+        return Err(format!("no enabled adapter works in {mode} mode"));
+    }
+    Ok(adapters)
+}
+
+/// Print what the managers say while they work, and put their questions to
+/// whoever is at the terminal.
+///
+/// Everything goes to stderr, so the answer on stdout stays readable by
+/// whatever asked for it. A question with nobody to answer it is dropped,
+/// which the manager reads as a refusal.
+fn report_progress() -> (ProgressSender, std::thread::JoinHandle<()>) {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+    let printer = std::thread::spawn(move || {
+        while let Some(event) = receiver.blocking_recv() {
+            match event {
+                ProgressEvent::Status {
+                    adapter_id,
+                    message,
+                    ..
+                } => eprintln!("[{adapter_id}] {message}"),
+                ProgressEvent::Asked {
+                    adapter_id,
+                    question,
+                    answer,
+                    ..
+                } => {
+                    if !std::io::stdin().is_terminal() {
+                        continue;
+                    }
+                    eprintln!("[{adapter_id}] {question}");
+                    let mut typed = String::new();
+                    if std::io::stdin().lock().read_line(&mut typed).is_ok() {
+                        let _ = answer.send(typed.trim_end_matches('\n').to_string());
+                    }
+                }
+                ProgressEvent::Failed {
+                    adapter_id,
+                    package_id,
+                    error,
+                } => eprintln!("[{adapter_id}] {package_id}: {error}"),
+                _ => {}
+            }
+        }
+    });
+
+    (sender, printer)
+}
+
+async fn search(
+    adapters: &[Arc<dyn Adapter>],
+    query: &str,
+    limit: Option<usize>,
+    mode: PackageMode,
+    json: bool,
+) -> Result<(), String> {
+    let mut asking = tokio::task::JoinSet::new();
+    for adapter in adapters.iter().filter(|a| a.capabilities().can_search) {
+        let adapter = adapter.clone();
+        let query = query.to_string();
+        asking.spawn(async move {
+            let id = adapter.info().id.clone();
+            (id, adapter.search(&query, limit, mode).await)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(answered) = asking.join_next().await {
+        match answered {
+            Ok((_, Ok(found))) => results.extend(found),
+            Ok((id, Err(e))) => eprintln!("aeris: search failed for {id}: {e}"),
+            Err(e) => eprintln!("aeris: search failed: {e}"),
+        }
+    }
+    crate::app::rank_results(&mut results, query);
+
+    if json {
+        return print_json(&results);
+    }
+
+    let rows = results
+        .iter()
+        .map(|p| {
+            vec![
+                qualified(p),
+                p.version.clone(),
+                if p.installed { "installed" } else { "" }.to_string(),
+                p.description.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_table(&["PACKAGE", "VERSION", "STATE", "DESCRIPTION"], rows);
+    Ok(())
+}
+
+async fn list(adapters: &[Arc<dyn Adapter>], mode: PackageMode, json: bool) -> Result<(), String> {
+    let mut installed = Vec::new();
+    for adapter in adapters.iter().filter(|a| a.capabilities().can_list) {
+        match adapter.list_installed(mode).await {
+            Ok(held) => installed.extend(held),
+            Err(e) => eprintln!(
+                "aeris: {} could not list its packages: {e}",
+                adapter.info().id
+            ),
+        }
+    }
+
+    if json {
+        return print_json(&installed);
+    }
+
+    let rows = installed
+        .iter()
+        .map(|held| {
+            vec![
+                qualified(&held.package),
+                held.package.version.clone(),
+                match held.install_size {
+                    0 => String::new(),
+                    size => crate::views::browse::format_bytes_pub(size),
+                },
+                held.profile.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_table(&["PACKAGE", "VERSION", "SIZE", "PROFILE"], rows);
+    Ok(())
+}
+
+async fn updates(
+    adapters: &[Arc<dyn Adapter>],
+    mode: PackageMode,
+    json: bool,
+) -> Result<(), String> {
+    let mut offered = Vec::new();
+    for adapter in adapters
+        .iter()
+        .filter(|a| a.capabilities().can_list_updates)
+    {
+        match adapter.list_updates(mode).await {
+            Ok(found) => offered.extend(found),
+            Err(e) => eprintln!(
+                "aeris: {} could not list its updates: {e}",
+                adapter.info().id
+            ),
+        }
+    }
+
+    crate::core::package::security_first(&mut offered);
+    if json {
+        return print_json(&offered);
+    }
+
+    let rows = offered
+        .iter()
+        .map(|update| {
+            let security = match (update.is_security, update.severity) {
+                (true, Some(severity)) => severity.label().to_lowercase(),
+                (true, None) => "yes".to_string(),
+                (false, _) => String::new(),
+            };
+            vec![
+                qualified(&update.package),
+                update.current_version.clone(),
+                update.new_version.clone(),
+                security,
+            ]
+        })
+        .collect();
+    print_table(&["PACKAGE", "CURRENT", "NEW", "SECURITY"], rows);
+    Ok(())
+}
+
+async fn install(
+    manager: &AdapterManager,
+    adapters: &[Arc<dyn Adapter>],
+    names: &[String],
+    cancel: &CancelToken,
+    mode: PackageMode,
+    json: bool,
+) -> Result<(), String> {
+    let installing: Vec<&Arc<dyn Adapter>> = adapters
+        .iter()
+        .filter(|a| a.capabilities().can_install)
+        .collect();
+
+    let mut packages = Vec::new();
+    for name in names {
+        let (adapter_id, id) = split_name(name);
+        let mut found = Vec::new();
+        for adapter in installing
+            .iter()
+            .filter(|a| adapter_id.is_none_or(|wanted| a.info().id == wanted))
+        {
+            match adapter.search(id, None, mode).await {
+                Ok(results) => {
+                    found.extend(results.into_iter().filter(|p| p.id == id || p.name == id))
+                }
+                Err(e) => eprintln!("aeris: search failed for {}: {e}", adapter.info().id),
+            }
+        }
+        packages.push(one_of(name, found)?);
+    }
+
+    let (progress, printer) = report_progress();
+    let results = manager
+        .install(&packages, Some(progress), cancel, mode)
+        .await
+        .map_err(|e| e.to_string());
+    let _ = printer.join();
+    journal(history::Action::Install, &packages, &results, cancel, mode);
+
+    report_results("Installed", &packages, results?, json)
+}
+
+async fn remove(
+    manager: &AdapterManager,
+    adapters: &[Arc<dyn Adapter>],
+    names: &[String],
+    cancel: &CancelToken,
+    mode: PackageMode,
+    json: bool,
+) -> Result<(), String> {
+    let packages = installed_named(adapters, names, mode, |caps| caps.can_remove).await?;
+
+    // There is no question to put, so what would be left broken is said
+    // before it goes rather than found out after.
+    let needed_by: Vec<String> = manager
+        .needed_by(&packages, mode)
+        .await
+        .into_iter()
+        .map(|p| qualified(&p))
+        .collect();
+    if !needed_by.is_empty() {
+        eprintln!(
+            "aeris: {} need what is being removed and may stop working",
+            needed_by.join(", ")
+        );
+    }
+
+    let (progress, printer) = report_progress();
+    let results = manager
+        .remove(&packages, Some(progress), cancel, mode)
+        .await
+        .map_err(|e| e.to_string());
+    let _ = printer.join();
+    journal(history::Action::Remove, &packages, &results, cancel, mode);
+
+    report_results("Removed", &packages, results?, json)
+}
+
+async fn update(
+    manager: &AdapterManager,
+    adapters: &[Arc<dyn Adapter>],
+    names: &[String],
+    cancel: &CancelToken,
+    mode: PackageMode,
+    json: bool,
+) -> Result<(), String> {
+    if names.is_empty() {
+        return update_everything(adapters, cancel, mode, json).await;
+    }
+
+    let packages = installed_named(adapters, names, mode, |caps| {
+        caps.can_update && caps.can_update_one
+    })
+    .await?;
+
+    let (progress, printer) = report_progress();
+    let updated = manager
+        .update(&packages, Some(progress), cancel, mode)
+        .await
+        .map_err(|e| e.to_string());
+    let _ = printer.join();
+    let updated = updated.inspect_err(|e| {
+        journal(
+            history::Action::Update,
+            &packages,
+            &Err(e.clone()),
+            cancel,
+            mode,
+        );
+    })?;
+
+    // Held back is not failed, so it says so on the side and costs nothing
+    // in the exit code.
+    for pkg in &updated.held_back {
+        eprintln!("Held back {}, as pinned", pkg.name);
+    }
+    let asked: Vec<Package> = packages
+        .into_iter()
+        .filter(|pkg| {
+            !updated
+                .held_back
+                .iter()
+                .any(|held| held.adapter_id == pkg.adapter_id && held.id == pkg.id)
+        })
+        .collect();
+    let results = Ok(updated.results);
+    journal(history::Action::Update, &asked, &results, cancel, mode);
+    report_results("Updated", &asked, results?, json)
+}
+
+/// Update everything each manager offers, the way each can: by naming what
+/// it listed where it can, and wholesale where that is all it knows.
+async fn update_everything(
+    adapters: &[Arc<dyn Adapter>],
+    cancel: &CancelToken,
+    mode: PackageMode,
+    json: bool,
+) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Outcome {
+        adapter_id: String,
+        success: bool,
+        error: Option<String>,
+        results: Vec<InstallResult>,
+        /// Packages on offer that were left alone because they are pinned.
+        held_back: Vec<String>,
+    }
+
+    let (progress, printer) = report_progress();
+    let mut outcomes = Vec::new();
+
+    for adapter in adapters.iter().filter(|a| a.capabilities().can_update) {
+        let caps = adapter.capabilities();
+        let id = adapter.info().id.clone();
+        let mut held_back = Vec::new();
+
+        let outcome = if caps.can_list_updates && caps.can_update_one {
+            let listed = adapter.list_updates(mode).await;
+            match listed {
+                Ok(listed) if listed.is_empty() => continue,
+                Ok(listed) => {
+                    let pinned = pinned_in(adapter.as_ref(), mode).await;
+                    let (held, offered): (Vec<_>, Vec<_>) = listed.into_iter().partition(|u| {
+                        pinned.contains(&crate::core::adapter::package_key(
+                            &u.package.adapter_id,
+                            &u.package.id,
+                        ))
+                    });
+                    held_back = held.into_iter().map(|u| u.package.name).collect();
+                    if offered.is_empty() {
+                        Ok(Vec::new())
+                    } else {
+                        let packages: Vec<Package> =
+                            offered.iter().map(|u| u.package.clone()).collect();
+                        let updated = adapter
+                            .update(&packages, Some(progress.clone()), cancel, mode)
+                            .await
+                            .map_err(|e| e.to_string());
+                        // Kept against the version each moved from, which
+                        // is the one an update reports as current.
+                        let was: Vec<Package> = offered
+                            .into_iter()
+                            .map(|u| Package {
+                                version: u.current_version,
+                                ..u.package
+                            })
+                            .collect();
+                        journal(history::Action::Update, &was, &updated, cancel, mode);
+                        updated.map_err(crate::core::adapter::AdapterError::Other)
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        } else if caps.can_update_all {
+            let ended = adapter
+                .update_all(Some(progress.clone()), cancel, mode)
+                .await;
+            // Nothing here says which packages moved, so the change is kept
+            // against the manager as a whole.
+            let outcome = match &ended {
+                Ok(()) => history::Outcome::Succeeded,
+                Err(_) if cancel.is_cancelled() => history::Outcome::Cancelled,
+                Err(e) => history::Outcome::Failed(e.to_string()),
+            };
+            history::record(&[history::Entry::ended(
+                history::Action::UpdateAll,
+                &id,
+                mode,
+                outcome,
+            )]);
+            ended.map(|()| Vec::new())
+        } else {
+            continue;
+        };
+
+        outcomes.push(match outcome {
+            Ok(results) => Outcome {
+                success: results.iter().all(|r| r.success),
+                error: failure_among(&results),
+                adapter_id: id,
+                results,
+                held_back,
+            },
+            Err(e) => Outcome {
+                adapter_id: id,
+                success: false,
+                error: Some(e.to_string()),
+                results: Vec::new(),
+                held_back,
+            },
+        });
+    }
+    drop(progress);
+    let _ = printer.join();
+
+    if json {
+        print_json(&outcomes)?;
+    } else if outcomes.is_empty() {
+        println!("Nothing to update");
+    } else {
+        for outcome in &outcomes {
+            match &outcome.error {
+                None => println!("{}: updated", outcome.adapter_id),
+                Some(why) => println!("{}: failed: {why}", outcome.adapter_id),
+            }
+            if !outcome.held_back.is_empty() {
+                println!(
+                    "{}: held back, as pinned: {}",
+                    outcome.adapter_id,
+                    outcome.held_back.join(", ")
+                );
+            }
+        }
+    }
+
+    match outcomes.iter().filter(|o| !o.success).count() {
+        0 => Ok(()),
+        failed => Err(format!(
+            "{failed} of {} managers failed to update",
+            outcomes.len()
+        )),
+    }
+}
+
+async fn sync(
+    config: &AerisConfig,
+    adapters: &[Arc<dyn Adapter>],
+    json: bool,
+) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Synced {
+        source: String,
+        success: bool,
+        error: Option<String>,
+    }
+
+    let (progress, printer) = report_progress();
+    let mut synced = Vec::new();
+    for adapter in adapters.iter().filter(|a| a.capabilities().can_sync) {
+        let outcome = adapter.sync(Some(progress.clone())).await;
+        synced.push(Synced {
+            source: adapter.info().id.clone(),
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+    drop(progress);
+    let _ = printer.join();
+
+    // The registry is read the way the window reads it, from every source in
+    // the order the settings trust them, so the copy it keeps is current.
+    let sources = config.registries();
+    let (_, errors) = tokio::task::spawn_blocking(move || registry::fetch_all(&sources))
+        .await
+        .map_err(|e| e.to_string())?;
+    synced.push(Synced {
+        source: "registry".into(),
+        success: errors.is_empty(),
+        error: (!errors.is_empty()).then(|| errors.join("; ")),
+    });
+
+    if json {
+        print_json(&synced)?;
+    } else {
+        for one in &synced {
+            match &one.error {
+                None => println!("{}: synced", one.source),
+                Some(why) => println!("{}: failed: {why}", one.source),
+            }
+        }
+    }
+
+    match synced.iter().filter(|s| !s.success).count() {
+        0 => Ok(()),
+        failed => Err(format!("{failed} of {} could not be synced", synced.len())),
+    }
+}
+
+async fn export(manager: &AdapterManager, path: Option<&str>) -> Result<(), String> {
+    let (inventory, problems) = Inventory::gather(manager).await;
+    for problem in &problems {
+        eprintln!("aeris: left out {problem}");
+    }
+
+    match path {
+        Some(path) => {
+            inventory.write(std::path::Path::new(path))?;
+            eprintln!("Wrote {} packages to {path}", inventory.packages.len());
+        }
+        None => print!("{}", inventory.to_toml()?),
+    }
+    Ok(())
+}
+
+/// Put back what an exported file lists, after saying what that will mean.
+async fn import(
+    manager: &AdapterManager,
+    path: &str,
+    dry_run: bool,
+    cancel: &CancelToken,
+    json: bool,
+) -> Result<(), String> {
+    let inventory = Inventory::read(std::path::Path::new(path))?;
+    let plan = ImportPlan::for_inventory(&inventory, manager).await;
+
+    if json && dry_run {
+        #[derive(Serialize)]
+        struct Planned<'a> {
+            install: Vec<&'a Package>,
+            pin: Vec<&'a Package>,
+            present: &'a [Held],
+            missing: Vec<Missing<'a>>,
+            unkept: Vec<Missing<'a>>,
+        }
+        #[derive(Serialize)]
+        struct Missing<'a> {
+            #[serde(flatten)]
+            held: &'a Held,
+            reason: &'a str,
+        }
+        return print_json(&Planned {
+            install: plan.install.iter().map(|(package, _)| package).collect(),
+            pin: plan.pin.iter().map(|(package, _)| package).collect(),
+            present: &plan.present,
+            missing: plan
+                .missing
+                .iter()
+                .map(|(held, reason)| Missing { held, reason })
+                .collect(),
+            unkept: plan
+                .unkept
+                .iter()
+                .map(|(held, reason)| Missing { held, reason })
+                .collect(),
+        });
+    }
+
+    // Said on stderr, so the results on stdout read the way an install's do.
+    for (held, why) in &plan.missing {
+        eprintln!(
+            "aeris: cannot put back {}:{}: {why}",
+            held.adapter_id, held.id
+        );
+    }
+    for (held, why) in &plan.unkept {
+        eprintln!("aeris: {}:{} {why}", held.adapter_id, held.id);
+    }
+    eprintln!(
+        "{} to install, {} already here, {} cannot be put back",
+        plan.install.len(),
+        plan.present.len(),
+        plan.missing.len()
+    );
+    if dry_run {
+        let rows = plan
+            .install
+            .iter()
+            .map(|(package, mode)| {
+                vec![
+                    qualified(package),
+                    package.version.clone(),
+                    mode.to_string(),
+                ]
+            })
+            .collect();
+        print_table(&["PACKAGE", "VERSION", "MODE"], rows);
+        return Ok(());
+    }
+
+    let mut failed = Vec::new();
+    for (mode, packages) in plan.batches() {
+        let (progress, printer) = report_progress();
+        let results = manager
+            .install(&packages, Some(progress), cancel, mode)
+            .await
+            .map_err(|e| e.to_string());
+        let _ = printer.join();
+        journal(history::Action::Install, &packages, &results, cancel, mode);
+
+        let pinned = match &results {
+            Ok(installed) => {
+                let pins = plan.pins(mode);
+                let went: Vec<Package> = pins
+                    .iter()
+                    .filter(|package| {
+                        installed.iter().any(|r| {
+                            r.success
+                                && r.adapter_id == package.adapter_id
+                                && r.package_id == package.id
+                        })
+                    })
+                    .cloned()
+                    .collect();
+                pin_again(manager, &pins, installed, mode)
+                    .await
+                    .map(|pinned| (went, pinned))
+            }
+            Err(_) => None,
+        };
+        if let Err(e) =
+            results.and_then(|results| report_results("Installed", &packages, results, json))
+        {
+            failed.push(e);
+        }
+        if let Some((went, pinned)) = pinned {
+            failed.extend(pinned_again(&went, pinned, cancel, mode));
+        }
+    }
+    for (mode, packages) in plan.repins() {
+        let pinned = manager.pin(&packages, mode).await;
+        failed.extend(pinned_again(&packages, pinned, cancel, mode));
+    }
+
+    match (failed.is_empty(), plan.missing.len()) {
+        (true, 0) => Ok(()),
+        (true, missing) => Err(format!("{missing} packages could not be put back")),
+        (false, _) => Err(failed.join("; ")),
+    }
+}
+
+/// Say which of `tried` were pinned again, on stderr like the plan so the
+/// results read the same, and keep it in the history. What stopped any of
+/// them is given back.
+fn pinned_again(
+    tried: &[Package],
+    pinned: crate::core::adapter::Result<Vec<InstallResult>>,
+    cancel: &CancelToken,
+    mode: PackageMode,
+) -> Option<String> {
+    let pinned = pinned.map_err(|e| e.to_string());
+    if let Ok(results) = &pinned {
+        for result in results.iter().filter(|r| r.success) {
+            eprintln!("Pinned {} again", result.package_name);
+        }
+    }
+    journal(history::Action::Pin, tried, &pinned, cancel, mode);
+
+    let why = match &pinned {
+        Ok(results) => failure_among(results),
+        Err(e) => Some(e.clone()),
+    };
+    why.map(|why| format!("could not pin again: {why}"))
+}
+
+/// Keep how each package asked about fared in the history, as the window
+/// does, so a change made from a terminal can be looked back on and undone
+/// there too. One nothing came back for ended with whatever stopped the rest.
+fn journal(
+    action: history::Action,
+    asked: &[Package],
+    results: &Result<Vec<InstallResult>, String>,
+    cancel: &CancelToken,
+    mode: PackageMode,
+) {
+    let (results, failure) = match results {
+        Ok(results) => (results.as_slice(), failure_among(results)),
+        Err(e) => (&[][..], Some(e.clone())),
+    };
+
+    let entries: Vec<history::Entry> = asked
+        .iter()
+        .map(|package| {
+            let result = results
+                .iter()
+                .find(|r| r.adapter_id == package.adapter_id && r.package_id == package.id);
+            let outcome = match result {
+                Some(result) if result.success => history::Outcome::Succeeded,
+                _ if cancel.is_cancelled() => history::Outcome::Cancelled,
+                Some(result) => history::Outcome::Failed(
+                    result
+                        .error
+                        .clone()
+                        .unwrap_or_else(|| "it gave no reason".to_string()),
+                ),
+                None => history::Outcome::Failed(
+                    failure
+                        .clone()
+                        .unwrap_or_else(|| format!("{} did not report on it", package.adapter_id)),
+                ),
+            };
+
+            let went = outcome == history::Outcome::Succeeded;
+            let reported = result
+                .map(|result| result.version.clone())
+                .filter(|version| !version.is_empty());
+            let held = Some(package.version.clone());
+            let (before, after) = match action {
+                history::Action::Install => (None, went.then(|| reported.or(held)).flatten()),
+                history::Action::Remove => (held.clone(), if went { None } else { held }),
+                history::Action::Update => (held.clone(), if went { reported } else { held }),
+                _ => (None, None),
+            };
+            history::Entry::ended(action, &package.adapter_id, mode, outcome)
+                .of(&package.id, &package.name)
+                .moved(before, after)
+        })
+        .collect();
+    history::record(&entries);
+}
+
+/// The installed packages the names refer to, among the managers that can do
+/// what is about to be asked of them.
+async fn installed_named(
+    adapters: &[Arc<dyn Adapter>],
+    names: &[String],
+    mode: PackageMode,
+    able: impl Fn(&crate::core::capabilities::Capabilities) -> bool,
+) -> Result<Vec<Package>, String> {
+    let mut held: Vec<Package> = Vec::new();
+    for adapter in adapters
+        .iter()
+        .filter(|a| a.capabilities().can_list && able(a.capabilities()))
+    {
+        match adapter.list_installed(mode).await {
+            Ok(installed) => held.extend(installed.into_iter().map(|i| i.package)),
+            Err(e) => eprintln!(
+                "aeris: {} could not list its packages: {e}",
+                adapter.info().id
+            ),
+        }
+    }
+
+    names
+        .iter()
+        .map(|name| {
+            let (adapter_id, id) = split_name(name);
+            let found = held
+                .iter()
+                .filter(|p| adapter_id.is_none_or(|wanted| p.adapter_id == wanted))
+                .filter(|p| p.id == id || p.name == id)
+                .cloned()
+                .collect();
+            one_of(name, found)
+        })
+        .collect()
+}
+
+/// Split `manager:id` into its parts. A name without a manager is left whole.
+fn split_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((adapter, id)) if !adapter.is_empty() && !id.is_empty() => (Some(adapter), id),
+        _ => (None, name),
+    }
+}
+
+/// The one package a name means.
+///
+/// Two managers answering to the same name is a question only the person
+/// asking can settle, so they are named and nothing is guessed.
+fn one_of(name: &str, mut found: Vec<Package>) -> Result<Package, String> {
+    // The same package can come back from more than one search, and not
+    // necessarily side by side.
+    let mut kept = HashSet::new();
+    found.retain(|p| kept.insert(qualified(p)));
+
+    let mut managers: Vec<&str> = found.iter().map(|p| p.adapter_id.as_str()).collect();
+    managers.sort();
+    managers.dedup();
+
+    match (found.len(), managers.as_slice()) {
+        (0, _) => Err(format!("no package called `{name}` was found")),
+        (1, _) => Ok(found.remove(0)),
+        (_, [only]) => Err(format!(
+            "{only} has more than one package called `{name}`; name it by its id"
+        )),
+        (_, several) => Err(format!(
+            "`{name}` is offered by {}; name one, as in {}:{name}",
+            several.join(", "),
+            several[0]
+        )),
+    }
+}
+
+fn qualified(pkg: &Package) -> String {
+    crate::core::adapter::package_key(&pkg.adapter_id, &pkg.id)
+}
+
+/// Say how each package went, failing if any of them did.
+///
+/// A package the manager never reported on counts as failed: it was asked
+/// for and nothing says it happened.
+fn report_results(
+    done: &str,
+    asked: &[Package],
+    results: Vec<InstallResult>,
+    json: bool,
+) -> Result<(), String> {
+    // Keyed by manager as well, since two asked at once can each have a
+    // package by the same id.
+    let reported: HashSet<String> = results
+        .iter()
+        .map(|r| crate::core::adapter::package_key(&r.adapter_id, &r.package_id))
+        .collect();
+
+    let unreported: Vec<InstallResult> = asked
+        .iter()
+        .filter(|pkg| !reported.contains(&qualified(pkg)))
+        .map(|pkg| InstallResult {
+            package_name: pkg.name.clone(),
+            adapter_id: pkg.adapter_id.clone(),
+            package_id: pkg.id.clone(),
+            version: pkg.version.clone(),
+            success: false,
+            error: Some(format!("{} did not report on it", pkg.adapter_id)),
+        })
+        .collect();
+    let all: Vec<&InstallResult> = results.iter().chain(&unreported).collect();
+
+    if json {
+        print_json(&all)?;
+    } else {
+        for result in &all {
+            match (&result.error, result.success) {
+                (_, true) if result.version.is_empty() => {
+                    println!("{done} {}", result.package_name)
+                }
+                (_, true) => println!("{done} {} {}", result.package_name, result.version),
+                (Some(why), false) => println!("Failed {}: {why}", result.package_name),
+                (None, false) => println!("Failed {}", result.package_name),
+            }
+        }
+    }
+
+    match all.iter().filter(|r| !r.success).count() {
+        0 => Ok(()),
+        failed => Err(format!("{failed} of {} packages failed", all.len())),
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{text}");
+    Ok(())
+}
+
+/// Lay rows out in columns as wide as their widest cell, the last one left
+/// ragged so a long description does not pad every line to its length.
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let width = |cell: &str| cell.chars().count();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| width(h)).collect();
+    for row in &rows {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(width(cell));
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        let last = cells.len().saturating_sub(1);
+        let mut out = String::new();
+        for (column, cell) in cells.into_iter().enumerate() {
+            out.push_str(cell);
+            if column < last {
+                out.push_str(&" ".repeat(widths[column] - width(cell) + 2));
+            }
+        }
+        out.trim_end().to_string()
+    };
+
+    let mut lines = vec![line(headers.to_vec())];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(String::as_str).collect())),
+    );
+    lines.join("\n")
+}
+
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        println!("Nothing found");
+        return;
+    }
+    println!("{}", table(headers, rows));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn no_command_opens_the_window() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(parse(&args("--some-toolkit-flag")), Ok(None));
+    }
+
+    #[test]
+    fn options_are_read_wherever_they_are_written() {
+        let read = parse(&args("install --mode system soar:htop --json -a soar fd"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            read,
+            Invocation {
+                action: Action::Install(vec!["soar:htop".into(), "fd".into()]),
+                mode: Some(PackageMode::System),
+                json: true,
+                adapters: vec!["soar".into()],
+            }
+        );
+
+        let read = parse(&args("search --limit=5 ripgrep all"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            read.action,
+            Action::Search {
+                query: "ripgrep all".into(),
+                limit: Some(5)
+            }
+        );
+    }
+
+    #[test]
+    fn a_command_missing_what_it_needs_is_refused() {
+        assert!(parse(&args("install")).is_err());
+        assert!(parse(&args("search")).is_err());
+        assert!(parse(&args("list htop")).is_err());
+        assert!(parse(&args("list --mode everyone")).is_err());
+        assert!(parse(&args("frobnicate")).is_err());
+        assert!(parse(&args("import")).is_err());
+        assert!(parse(&args("export one.toml two.toml")).is_err());
+        // Update with nothing named means everything.
+        assert_eq!(
+            parse(&args("update")).unwrap().unwrap().action,
+            Action::Update(Vec::new())
+        );
+    }
+
+    #[test]
+    fn export_writes_to_stdout_unless_given_a_file() {
+        assert_eq!(
+            parse(&args("export")).unwrap().unwrap().action,
+            Action::Export(None)
+        );
+        assert_eq!(
+            parse(&args("import --dry-run laptop.toml"))
+                .unwrap()
+                .unwrap()
+                .action,
+            Action::Import {
+                path: "laptop.toml".into(),
+                dry_run: true
+            }
+        );
+    }
+
+    #[test]
+    fn only_upgrade_is_asked_of_a_manifest_and_of_one_file() {
+        assert_eq!(
+            parse(&args("manifest upgrade adapters/apt.toml"))
+                .unwrap()
+                .unwrap()
+                .action,
+            Action::UpgradeManifest("adapters/apt.toml".into())
+        );
+        assert!(parse(&args("manifest upgrade")).is_err());
+        assert!(parse(&args("manifest upgrade one.toml two.toml")).is_err());
+        assert!(parse(&args("manifest check apt.toml")).is_err());
+    }
+
+    #[test]
+    fn a_name_is_split_only_where_it_names_a_manager() {
+        assert_eq!(split_name("soar:htop"), (Some("soar"), "htop"));
+        assert_eq!(split_name("htop"), (None, "htop"));
+        assert_eq!(split_name(":htop"), (None, ":htop"));
+    }
+
+    #[test]
+    fn a_name_two_managers_answer_to_is_not_guessed() {
+        let pkg = |adapter: &str| Package {
+            id: "htop".into(),
+            name: "htop".into(),
+            version: "3.3".into(),
+            adapter_id: adapter.into(),
+            description: None,
+            size: None,
+            homepage: None,
+            license: None,
+            installed: false,
+            update_available: false,
+            category: None,
+            tags: Vec::new(),
+            icon_url: None,
+        };
+
+        assert_eq!(
+            one_of("htop", vec![pkg("soar")]).unwrap().adapter_id,
+            "soar"
+        );
+        assert!(one_of("htop", Vec::new()).is_err());
+        let twice = one_of("htop", vec![pkg("soar"), pkg("pacstall"), pkg("soar")]).unwrap_err();
+        assert!(twice.contains("offered by pacstall, soar;"), "{twice}");
+        assert_eq!(
+            one_of("htop", vec![pkg("soar"), pkg("soar")])
+                .unwrap()
+                .adapter_id,
+            "soar"
+        );
+
+        let why = one_of("htop", vec![pkg("soar"), pkg("pacstall")]).unwrap_err();
+        assert!(why.contains("pacstall, soar"), "{why}");
+        assert!(why.contains("pacstall:htop"), "{why}");
+    }
+
+    #[test]
+    fn a_result_is_matched_to_the_manager_it_came_from() {
+        let soar = Package::named("soar", "htop", "htop", "3.3");
+        let pacstall = Package::named("pacstall", "htop", "htop", "3.3");
+        let went = InstallResult {
+            package_name: "htop".into(),
+            adapter_id: "soar".into(),
+            package_id: "htop".into(),
+            version: "3.3".into(),
+            success: true,
+            error: None,
+        };
+
+        // pacstall said nothing, and soar's answer is not taken for its.
+        let why = report_results("Installed", &[soar, pacstall], vec![went], true).unwrap_err();
+        assert_eq!(why, "1 of 2 packages failed");
+    }
+
+    #[test]
+    fn columns_are_as_wide_as_their_widest_cell() {
+        let laid_out = table(
+            &["PACKAGE", "VERSION"],
+            vec![
+                vec!["soar:htop".into(), "3.3".into()],
+                vec!["am:a".into(), "12.9.1".into()],
+            ],
+        );
+        assert_eq!(
+            laid_out,
+            "PACKAGE    VERSION\nsoar:htop  3.3\nam:a       12.9.1"
+        );
+    }
+}
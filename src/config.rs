@@ -10,8 +10,23 @@ use crate::core::registry::Source;
 /// How often to read the registry again when nothing says otherwise.
 const DEFAULT_REGISTRY_SYNC: Duration = Duration::from_secs(3 * 60 * 60);
 
+/// How often to look for updates in the background when nothing says
+/// otherwise.
+const DEFAULT_UPDATE_CHECK: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Read an interval in the words soar uses, falling back where it says
+/// nothing or nonsense. `None` is `never`.
+fn read_interval(written: Option<&str>, fallback: Duration) -> Option<Duration> {
+    match written.unwrap_or("auto").trim() {
+        "always" => Some(Duration::ZERO),
+        "never" => None,
+        "auto" => Some(fallback),
+        written => parse_interval(written).or(Some(fallback)),
+    }
+}
+
 /// Read a duration written the way a person would: `30m`, `3h`, `1d`.
-fn parse_interval(written: &str) -> Option<Duration> {
+pub(crate) fn parse_interval(written: &str) -> Option<Duration> {
     let (count, unit) = written.split_at(written.len().checked_sub(1)?);
     let count: u64 = count.trim().parse().ok()?;
 
@@ -44,6 +59,9 @@ pub struct AerisConfig {
     /// words soar uses: `always`, `never`, `auto`, or a duration such as
     /// `3h` or `1d`.
     pub registry_sync_interval: Option<String>,
+    /// How often every manager is asked for updates in the background, in
+    /// the same words as `registry_sync_interval`.
+    pub update_check_interval: Option<String>,
     /// Where the map from a package's name to the application it is comes
     /// from. An HTTP(S) URL or a local path. Unset means the published one.
     pub icon_map_url: Option<String>,
@@ -85,17 +103,19 @@ impl AerisConfig {
     /// `None` means never look again on its own, which leaves refreshing to
     /// whoever asks for it.
     pub fn registry_sync_interval(&self) -> Option<Duration> {
-        match self
-            .registry_sync_interval
-            .as_deref()
-            .unwrap_or("auto")
-            .trim()
-        {
-            "always" => Some(Duration::ZERO),
-            "never" => None,
-            "auto" => Some(DEFAULT_REGISTRY_SYNC),
-            written => parse_interval(written).or(Some(DEFAULT_REGISTRY_SYNC)),
-        }
+        read_interval(
+            self.registry_sync_interval.as_deref(),
+            DEFAULT_REGISTRY_SYNC,
+        )
+    }
+
+    /// How long to wait between looking for updates in the background.
+    ///
+    /// `None` means not to look at all. `always` is as often as the checker
+    /// allows, since asking every manager without a pause would do nothing
+    /// but keep them busy.
+    pub fn update_check_interval(&self) -> Option<Duration> {
+        read_interval(self.update_check_interval.as_deref(), DEFAULT_UPDATE_CHECK)
     }
 
     /// The registries to read, in the order they are trusted.
@@ -166,7 +186,7 @@ impl AerisConfig {
 
 #[cfg(test)]
 mod tests {
-    use super::{AerisConfig, DEFAULT_REGISTRY_SYNC, parse_interval};
+    use super::{AerisConfig, DEFAULT_REGISTRY_SYNC, DEFAULT_UPDATE_CHECK, parse_interval};
     use std::time::Duration;
 
     fn with_interval(written: &str) -> AerisConfig {
@@ -210,4 +230,21 @@ mod tests {
             Some(DEFAULT_REGISTRY_SYNC)
         );
     }
+
+    #[test]
+    fn update_checks_read_their_interval_the_same_way() {
+        let checking = |written: &str| AerisConfig {
+            update_check_interval: Some(written.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            checking("30m").update_check_interval(),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(checking("never").update_check_interval(), None);
+        assert_eq!(
+            AerisConfig::default().update_check_interval(),
+            Some(DEFAULT_UPDATE_CHECK)
+        );
+    }
 }
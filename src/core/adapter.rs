@@ -1,11 +1,19 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 use crate::views::manifest::{ManifestApplyReport, ManifestDiff};
 
 use super::{
     capabilities::Capabilities,
     config::{AdapterConfig, ConfigSchema},
-    package::{InstallResult, InstalledPackage, Package, PackageDetail, Update},
+    doctor::Fault,
+    package::{InstallResult, InstalledPackage, Package, PackageDetail, ReleaseNote, Update},
     privilege::PackageMode,
     profile::Profile,
     repository::Repository,
@@ -14,6 +22,30 @@ use super::{
 pub type AdapterId = String;
 pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<ProgressEvent>;
 
+/// Asks work that is under way to stop.
+///
+/// Clones share the one flag, so whoever started the work keeps a copy to
+/// cancel with while the adapter watches another. Cancelling is a request:
+/// whatever the manager finished before it noticed stays done.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Whether the two were cloned from the same token, and so cancel
+    /// together.
+    pub fn same_as(&self, other: &CancelToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ProgressEvent {
     Download {
@@ -54,6 +86,12 @@ pub enum ProgressEvent {
         package_id: String,
         error: String,
     },
+    /// The work was stopped on request before it finished, or before it
+    /// started at all for a package later in the batch.
+    Cancelled {
+        adapter_id: AdapterId,
+        package_id: String,
+    },
     BatchProgress {
         adapter_id: AdapterId,
         completed: u32,
@@ -96,6 +134,8 @@ pub enum AdapterError {
     ParseError(String),
     #[error("Operation not supported")]
     NotSupported,
+    #[error("Cancelled")]
+    Cancelled,
     #[error("Plugin error: {0}")]
     PluginError(String),
     #[error("IO error: {0}")]
@@ -122,6 +162,12 @@ pub struct AdapterInfo {
     pub plugin_path: Option<PathBuf>,
     pub description: String,
     pub icon: Option<String>,
+    /// What had to change to read a manifest written for an older schema,
+    /// for whoever looks after it to bring up to date.
+    pub deprecations: Vec<String>,
+    /// The fields its manifest took from the one it extends, each with the
+    /// id of the manifest that wrote it.
+    pub inherited: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
@@ -149,24 +195,122 @@ pub trait Adapter: Send + Sync {
         Err(AdapterError::NotSupported)
     }
 
+    /// What the package needs.
+    async fn depends(&self, _package_id: &str, _mode: PackageMode) -> Result<Vec<Package>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// What installed packages need this one, so that removing it can say
+    /// what it would leave broken.
+    async fn required_by(&self, _package_id: &str, _mode: PackageMode) -> Result<Vec<Package>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// The release notes for a package from one version up to another.
+    async fn changelog(
+        &self,
+        _package: &Package,
+        _from: &str,
+        _to: &str,
+        _mode: PackageMode,
+    ) -> Result<Vec<ReleaseNote>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Fetch what updating the packages would install, installing nothing.
+    async fn download(
+        &self,
+        _packages: &[Package],
+        _progress: Option<ProgressSender>,
+        _cancel: &CancelToken,
+        _mode: PackageMode,
+    ) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Keep the packages where they are, so an update passes them by.
+    async fn pin(&self, _packages: &[Package], _mode: PackageMode) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
+    async fn unpin(&self, _packages: &[Package], _mode: PackageMode) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Every file the package put down, as the manager recorded them.
+    async fn files(&self, _package_id: &str, _mode: PackageMode) -> Result<Vec<String>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// What the manager finds wrong when it checks its installed packages.
+    async fn verify(&self, _mode: PackageMode) -> Result<Vec<Fault>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Put the packages right, in whatever way the manager does that.
+    async fn repair(
+        &self,
+        _packages: &[Package],
+        _progress: Option<ProgressSender>,
+        _cancel: &CancelToken,
+        _mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Which packages the manager says a file belongs to.
+    async fn owner(&self, _path: &str, _mode: PackageMode) -> Result<Vec<Package>> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Install the packages, stopping when `cancel` is.
+    ///
+    /// The work that changes packages is the work that can run long, so it is
+    /// the work that can be stopped. A cancelled run answers
+    /// [`AdapterError::Cancelled`] for whatever it did not finish.
     async fn install(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<Vec<InstallResult>>;
 
+    /// Install each package at the version it carries rather than the
+    /// newest, for putting back what an update or a removal took away.
+    async fn install_version(
+        &self,
+        _packages: &[Package],
+        _progress: Option<ProgressSender>,
+        _cancel: &CancelToken,
+        _mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        Err(AdapterError::NotSupported)
+    }
+
     async fn remove(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<()>;
 
+    /// Remove every orphan at once, through the manager's own command for it.
+    async fn remove_orphans(
+        &self,
+        _progress: Option<ProgressSender>,
+        _cancel: &CancelToken,
+        _mode: PackageMode,
+    ) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
     async fn update(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<Vec<InstallResult>>;
 
@@ -177,6 +321,7 @@ pub trait Adapter: Send + Sync {
     async fn update_all(
         &self,
         _progress: Option<ProgressSender>,
+        _cancel: &CancelToken,
         _mode: PackageMode,
     ) -> Result<()> {
         Err(AdapterError::NotSupported)
@@ -184,6 +329,12 @@ pub trait Adapter: Send + Sync {
 
     async fn list_installed(&self, mode: PackageMode) -> Result<Vec<InstalledPackage>>;
 
+    /// The installed packages that were pulled in for others and that
+    /// nothing installed needs any more.
+    async fn list_orphans(&self, _mode: PackageMode) -> Result<Vec<Package>> {
+        Err(AdapterError::NotSupported)
+    }
+
     async fn list_updates(&self, mode: PackageMode) -> Result<Vec<Update>>;
 
     async fn sync(&self, _progress: Option<ProgressSender>) -> Result<()> {
@@ -202,6 +353,16 @@ pub trait Adapter: Send + Sync {
         Err(AdapterError::NotSupported)
     }
 
+    async fn create_profile(&self, _profile_id: &str) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Delete a profile. The active one is refused, since the manager would
+    /// be left pointing at nothing.
+    async fn delete_profile(&self, _profile_id: &str) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
     async fn list_repositories(&self) -> Result<Vec<Repository>> {
         Err(AdapterError::NotSupported)
     }
@@ -251,6 +412,17 @@ pub trait Adapter: Send + Sync {
         Ok(HealthStatus::default())
     }
 
+    /// How many bytes the manager's cache takes.
+    async fn cache_size(&self) -> Result<u64> {
+        Err(AdapterError::NotSupported)
+    }
+
+    /// Clear the manager's cache: what it no longer needs, or everything in
+    /// it when `all` is set.
+    async fn clean_cache(&self, _all: bool, _progress: Option<ProgressSender>) -> Result<()> {
+        Err(AdapterError::NotSupported)
+    }
+
     /// Where this manager keeps the files a frontend may read or edit, keyed
     /// by what each one is. A file soar owns is soar's to define, so this
     /// reports the locations rather than trying to describe the contents.
@@ -3,12 +3,41 @@ use std::{
     sync::Arc,
 };
 
+use crate::adapters::command::{self, CommandAdapter};
+use crate::config::AerisConfig;
+
 use super::{
-    adapter::{Adapter, AdapterId, AdapterInfo, ProgressSender, Result},
+    adapter::{
+        Adapter, AdapterError, AdapterId, AdapterInfo, CancelToken, ProgressSender, Result,
+        package_key,
+    },
     package::{InstallResult, Package},
     privilege::PackageMode,
 };
 
+/// What an update came to: how each package passed on to its manager went,
+/// and which were left alone because they are pinned. A package held back
+/// did not fail, so it has no result of its own.
+#[derive(Debug, Default)]
+pub struct Updated {
+    pub results: Vec<InstallResult>,
+    pub held_back: Vec<Package>,
+}
+
+/// What is being done to the packages handed to each manager.
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    Install,
+    /// Install at the version each package carries.
+    InstallVersion,
+    Remove,
+    Update,
+    Pin,
+    Unpin,
+    Repair,
+}
+
+#[derive(Clone)]
 pub struct AdapterManager {
     adapters: HashMap<AdapterId, Arc<dyn Adapter>>,
     disabled: HashSet<String>,
@@ -27,6 +56,21 @@ impl AdapterManager {
         self.adapters.insert(id, adapter);
     }
 
+    /// Register an adapter unless one already answers to its id.
+    ///
+    /// Ids decide where settings are kept, so the one registered first keeps
+    /// the name rather than being replaced by whatever was discovered later.
+    pub fn register_new(&mut self, adapter: Arc<dyn Adapter>) {
+        let id = adapter.info().id.clone();
+
+        if self.get_adapter(&id).is_some() {
+            log::warn!("Ignoring a second adapter named {id}");
+            return;
+        }
+
+        self.register(adapter);
+    }
+
     pub fn unregister(&mut self, id: &str) {
         self.adapters.remove(id);
         self.disabled.remove(id);
@@ -93,6 +137,29 @@ impl AdapterManager {
         names
     }
 
+    /// The scope to work in when nobody has said which.
+    ///
+    /// Whichever scope the adapters actually work in. A manager that only
+    /// ever acts system wide would otherwise have its packages counted and
+    /// labelled as the user's, and one that is turned off should have no say
+    /// at all: what is left on decides.
+    pub fn preferred_mode(&self) -> PackageMode {
+        let enabled = self.enabled_adapters();
+        if enabled
+            .iter()
+            .any(|info| info.capabilities.supports_user_packages)
+        {
+            PackageMode::User
+        } else if enabled
+            .iter()
+            .any(|info| info.capabilities.supports_system_packages)
+        {
+            PackageMode::System
+        } else {
+            PackageMode::User
+        }
+    }
+
     pub fn get_adapter(&self, id: &str) -> Option<Arc<dyn Adapter>> {
         self.adapters.get(id).cloned()
     }
@@ -101,70 +168,212 @@ impl AdapterManager {
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
     ) -> Result<Vec<InstallResult>> {
-        let mut by_adapter: HashMap<&str, Vec<&Package>> = HashMap::new();
-        for pkg in packages {
-            by_adapter.entry(&pkg.adapter_id).or_default().push(pkg);
-        }
+        Ok(self
+            .across(Change::Install, packages, progress, cancel, mode)
+            .await)
+    }
 
-        let mut results = Vec::new();
-        for (adapter_id, pkgs) in by_adapter {
-            if let Some(adapter) = self.adapters.get(adapter_id) {
-                let owned: Vec<Package> = pkgs.into_iter().cloned().collect();
-                match adapter.install(&owned, progress.clone(), mode).await {
-                    Ok(r) => results.extend(r),
-                    Err(e) => log::error!("Install failed for {adapter_id}: {e}"),
-                }
-            }
-        }
-        Ok(results)
+    pub async fn install_version(
+        &self,
+        packages: &[Package],
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        Ok(self
+            .across(Change::InstallVersion, packages, progress, cancel, mode)
+            .await)
     }
 
     pub async fn remove(
         &self,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
-    ) -> Result<()> {
-        let mut by_adapter: HashMap<&str, Vec<&Package>> = HashMap::new();
+    ) -> Result<Vec<InstallResult>> {
+        Ok(self
+            .across(Change::Remove, packages, progress, cancel, mode)
+            .await)
+    }
+
+    /// Update the packages, holding back any that are pinned.
+    ///
+    /// A manager handed a pinned package by name will often update it anyway,
+    /// taking the name as overriding the pin, so the pin is honoured here
+    /// rather than left to the manager, and what it holds back is answered
+    /// for apart from what it updated.
+    pub async fn update(
+        &self,
+        packages: &[Package],
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<Updated> {
+        let pinned = self.pinned_among(packages, mode).await;
+        let (held_back, free): (Vec<Package>, Vec<Package>) = packages
+            .iter()
+            .cloned()
+            .partition(|pkg| pinned.contains(&package_key(&pkg.adapter_id, &pkg.id)));
+
+        let results = self
+            .across(Change::Update, &free, progress, cancel, mode)
+            .await;
+        Ok(Updated { results, held_back })
+    }
+
+    pub async fn pin(&self, packages: &[Package], mode: PackageMode) -> Result<Vec<InstallResult>> {
+        Ok(self
+            .across(Change::Pin, packages, None, &CancelToken::default(), mode)
+            .await)
+    }
+
+    pub async fn unpin(
+        &self,
+        packages: &[Package],
+        mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        Ok(self
+            .across(Change::Unpin, packages, None, &CancelToken::default(), mode)
+            .await)
+    }
+
+    pub async fn repair(
+        &self,
+        packages: &[Package],
+        progress: Option<ProgressSender>,
+        cancel: &CancelToken,
+        mode: PackageMode,
+    ) -> Result<Vec<InstallResult>> {
+        Ok(self
+            .across(Change::Repair, packages, progress, cancel, mode)
+            .await)
+    }
+
+    /// The installed packages that need any of these, each once, leaving out
+    /// those going along with them. A manager that cannot say, or fails to,
+    /// has nothing to add, since this only warns.
+    pub async fn needed_by(&self, packages: &[Package], mode: PackageMode) -> Vec<Package> {
+        let going: HashSet<String> = packages
+            .iter()
+            .map(|p| package_key(&p.adapter_id, &p.id))
+            .collect();
+        let mut seen = HashSet::new();
+        let mut needed_by = Vec::new();
         for pkg in packages {
-            by_adapter.entry(&pkg.adapter_id).or_default().push(pkg);
+            let Some(adapter) = self
+                .get_adapter(&pkg.adapter_id)
+                .filter(|a| a.capabilities().has_dependencies)
+            else {
+                continue;
+            };
+            let Ok(needing) = adapter.required_by(&pkg.id, mode).await else {
+                continue;
+            };
+            for needer in needing {
+                let key = package_key(&needer.adapter_id, &needer.id);
+                if !going.contains(&key) && seen.insert(key) {
+                    needed_by.push(needer);
+                }
+            }
         }
+        needed_by
+    }
 
-        for (adapter_id, pkgs) in by_adapter {
-            if let Some(adapter) = self.adapters.get(adapter_id) {
-                let owned: Vec<Package> = pkgs.into_iter().cloned().collect();
-                if let Err(e) = adapter.remove(&owned, progress.clone(), mode).await {
-                    log::error!("Remove failed for {adapter_id}: {e}");
-                }
+    /// The [`package_key`] of each of these packages its manager has pinned.
+    pub async fn pinned_among(&self, packages: &[Package], mode: PackageMode) -> HashSet<String> {
+        let mut asked = HashSet::new();
+        let mut pinned = HashSet::new();
+        for pkg in packages {
+            if !asked.insert(pkg.adapter_id.as_str()) {
+                continue;
+            }
+            if let Some(adapter) = self.adapters.get(&pkg.adapter_id) {
+                pinned.extend(pinned_in(adapter.as_ref(), mode).await);
             }
         }
-        Ok(())
+        pinned
     }
 
-    pub async fn update(
+    /// Hand each manager its share of the packages, all of them at once.
+    ///
+    /// Managers have nothing to do with each other, so a slow one holds up
+    /// only its own packages. Whatever a manager cannot do together with
+    /// itself, it serialises on its own. A manager that fails outright, or
+    /// that is gone, fails every package it was handed rather than dropping
+    /// them from the results, which would read as though they were never
+    /// asked for.
+    async fn across(
         &self,
+        change: Change,
         packages: &[Package],
         progress: Option<ProgressSender>,
+        cancel: &CancelToken,
         mode: PackageMode,
-    ) -> Result<Vec<InstallResult>> {
-        let mut by_adapter: HashMap<&str, Vec<&Package>> = HashMap::new();
+    ) -> Vec<InstallResult> {
+        let mut groups: Vec<(AdapterId, Vec<Package>)> = Vec::new();
         for pkg in packages {
-            by_adapter.entry(&pkg.adapter_id).or_default().push(pkg);
+            match groups.iter_mut().find(|(id, _)| *id == pkg.adapter_id) {
+                Some((_, group)) => group.push(pkg.clone()),
+                None => groups.push((pkg.adapter_id.clone(), vec![pkg.clone()])),
+            }
         }
 
+        let running: Vec<_> = groups
+            .into_iter()
+            .map(|(adapter_id, group)| {
+                let adapter = self.adapters.get(&adapter_id).cloned();
+                let progress = progress.clone();
+                let cancel = cancel.clone();
+                let handed = group.clone();
+                let task = tokio::spawn(async move {
+                    let Some(adapter) = adapter else {
+                        return Err(AdapterError::NotFound(adapter_id));
+                    };
+                    match change {
+                        Change::Install => adapter.install(&handed, progress, &cancel, mode).await,
+                        Change::InstallVersion => {
+                            adapter
+                                .install_version(&handed, progress, &cancel, mode)
+                                .await
+                        }
+                        Change::Update => adapter.update(&handed, progress, &cancel, mode).await,
+                        Change::Remove => adapter
+                            .remove(&handed, progress, &cancel, mode)
+                            .await
+                            .map(|()| handed.iter().map(|pkg| outcome(pkg, None)).collect()),
+                        Change::Pin => adapter
+                            .pin(&handed, mode)
+                            .await
+                            .map(|()| handed.iter().map(|pkg| outcome(pkg, None)).collect()),
+                        Change::Unpin => adapter
+                            .unpin(&handed, mode)
+                            .await
+                            .map(|()| handed.iter().map(|pkg| outcome(pkg, None)).collect()),
+                        Change::Repair => adapter.repair(&handed, progress, &cancel, mode).await,
+                    }
+                });
+                (group, task)
+            })
+            .collect();
+
         let mut results = Vec::new();
-        for (adapter_id, pkgs) in by_adapter {
-            if let Some(adapter) = self.adapters.get(adapter_id) {
-                let owned: Vec<Package> = pkgs.into_iter().cloned().collect();
-                match adapter.update(&owned, progress.clone(), mode).await {
-                    Ok(r) => results.extend(r),
-                    Err(e) => log::error!("Update failed for {adapter_id}: {e}"),
+        for (group, task) in running {
+            let error = match task.await {
+                Ok(Ok(done)) => {
+                    results.extend(done);
+                    continue;
                 }
-            }
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => format!("the work stopped before it finished: {e}"),
+            };
+            log::error!("{change:?} failed for {}: {error}", group[0].adapter_id);
+            results.extend(group.iter().map(|pkg| outcome(pkg, Some(error.clone()))));
         }
-        Ok(results)
+        results
     }
 
     pub async fn sync_all(
@@ -180,3 +389,361 @@ impl AdapterManager {
         results
     }
 }
+
+/// Every manager aeris drives, loaded once for the window, the command line
+/// and the background checks alike, so the three never disagree on what is
+/// there.
+pub struct Loaded {
+    pub manager: AdapterManager,
+    /// Soar, registered among the rest and kept to hand for what only it
+    /// answers: where its files are, and its own settings.
+    pub soar: Option<Arc<CommandAdapter>>,
+    /// Why soar could not be driven, when it was looked for and not found.
+    pub soar_problem: Option<String>,
+}
+
+/// Load soar and every manifest adapter, with the ones turned off in the
+/// settings off here too.
+pub fn load_managers(config: &AerisConfig) -> Loaded {
+    let mut manager = AdapterManager::new();
+    let mut soar_problem = None;
+
+    // Turned off means left alone: no looking for it, and nothing said about
+    // not finding it.
+    let soar = if config.is_adapter_disabled(crate::app::SOAR_ID) {
+        None
+    } else {
+        // Soar describes itself, so aeris drives whichever one is installed
+        // rather than the one it was built against.
+        match CommandAdapter::from_command(crate::app::SOAR_ID, command::DESCRIBE_ARGS)
+            .map(CommandAdapter::as_builtin)
+        {
+            Ok(soar) => {
+                log::info!("Driving soar {}", soar.info().version);
+                let soar = Arc::new(soar);
+                manager.register(soar.clone());
+                Some(soar)
+            }
+            Err(e) => {
+                log::warn!("Soar is unavailable: {e}");
+                soar_problem = Some(match &e {
+                    AdapterError::PluginError(said) => said.clone(),
+                    other => other.to_string(),
+                });
+                None
+            }
+        }
+    };
+
+    for result in command::load_all() {
+        match result {
+            Ok(adapter) => {
+                log::info!("Loaded adapter: {}", adapter.info().id);
+                manager.register_new(Arc::new(adapter));
+            }
+            Err(e) => log::warn!("Failed to load adapter: {e}"),
+        }
+    }
+
+    manager.set_disabled(config.disabled_adapters.iter().cloned().collect());
+    Loaded {
+        manager,
+        soar,
+        soar_problem,
+    }
+}
+
+/// The [`package_key`] of every package a manager holds pinned. A manager
+/// that cannot list what it holds, or fails to, pins nothing as far as this
+/// can tell.
+pub async fn pinned_in(adapter: &dyn Adapter, mode: PackageMode) -> HashSet<String> {
+    if !adapter.capabilities().can_list {
+        return HashSet::new();
+    }
+    match adapter.list_installed(mode).await {
+        Ok(held) => held
+            .iter()
+            .filter(|held| held.pinned)
+            .map(|held| package_key(&held.package.adapter_id, &held.package.id))
+            .collect(),
+        Err(e) => {
+            log::warn!(
+                "{} could not say what it has pinned: {e}",
+                adapter.info().id
+            );
+            HashSet::new()
+        }
+    }
+}
+
+/// How one package went, failed when there is an error to say why.
+fn outcome(pkg: &Package, error: Option<String>) -> InstallResult {
+    InstallResult {
+        package_name: pkg.name.clone(),
+        adapter_id: pkg.adapter_id.clone(),
+        package_id: pkg.id.clone(),
+        version: pkg.version.clone(),
+        success: error.is_none(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::sync::Barrier;
+
+    use super::AdapterManager;
+    use crate::core::{
+        adapter::{Adapter, AdapterError, AdapterInfo, CancelToken, ProgressSender, Result},
+        capabilities::Capabilities,
+        package::{InstallResult, InstalledPackage, Package, Update},
+        privilege::PackageMode,
+    };
+
+    /// A manager that waits at the barrier before installing anything, or
+    /// refuses outright when it has nothing to wait at.
+    struct Waiting {
+        info: AdapterInfo,
+        barrier: Option<Arc<Barrier>>,
+        /// What it reports holding pinned.
+        pinned: Vec<&'static str>,
+    }
+
+    fn waiting(id: &str, barrier: Option<Arc<Barrier>>) -> Arc<dyn Adapter> {
+        Arc::new(Waiting {
+            info: AdapterInfo {
+                id: id.to_string(),
+                name: id.to_string(),
+                version: "1.0".into(),
+                manifest_version: "1".into(),
+                capabilities: Capabilities::default(),
+                enabled: true,
+                is_builtin: false,
+                plugin_path: None,
+                description: String::new(),
+                icon: None,
+                deprecations: Vec::new(),
+                inherited: Vec::new(),
+            },
+            barrier,
+            pinned: Vec::new(),
+        })
+    }
+
+    #[async_trait::async_trait]
+    impl Adapter for Waiting {
+        fn info(&self) -> &AdapterInfo {
+            &self.info
+        }
+
+        fn capabilities(&self) -> &Capabilities {
+            &self.info.capabilities
+        }
+
+        async fn search(&self, _: &str, _: Option<usize>, _: PackageMode) -> Result<Vec<Package>> {
+            Ok(Vec::new())
+        }
+
+        async fn install(
+            &self,
+            packages: &[Package],
+            _: Option<ProgressSender>,
+            _: &CancelToken,
+            _: PackageMode,
+        ) -> Result<Vec<InstallResult>> {
+            let Some(barrier) = &self.barrier else {
+                return Err(AdapterError::Other("the lock is held".into()));
+            };
+            barrier.wait().await;
+            Ok(packages.iter().map(|p| super::outcome(p, None)).collect())
+        }
+
+        async fn remove(
+            &self,
+            _: &[Package],
+            _: Option<ProgressSender>,
+            _: &CancelToken,
+            _: PackageMode,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn update(
+            &self,
+            packages: &[Package],
+            _: Option<ProgressSender>,
+            _: &CancelToken,
+            _: PackageMode,
+        ) -> Result<Vec<InstallResult>> {
+            Ok(packages.iter().map(|p| super::outcome(p, None)).collect())
+        }
+
+        async fn list_installed(&self, _: PackageMode) -> Result<Vec<InstalledPackage>> {
+            Ok(self
+                .pinned
+                .iter()
+                .map(|id| InstalledPackage {
+                    package: package(&self.info.id, id),
+                    installed_at: String::new(),
+                    install_size: 0,
+                    install_path: None,
+                    pinned: true,
+                    auto_installed: false,
+                    is_healthy: true,
+                    profile: None,
+                })
+                .collect())
+        }
+
+        async fn list_updates(&self, _: PackageMode) -> Result<Vec<Update>> {
+            Ok(Vec::new())
+        }
+
+        /// `app` needs `libfoo` and `tool`, and `tool` needs `libfoo`.
+        async fn required_by(&self, id: &str, _: PackageMode) -> Result<Vec<Package>> {
+            let needing: &[&str] = match id {
+                "libfoo" => &["app", "tool"],
+                "tool" => &["app"],
+                _ => &[],
+            };
+            Ok(needing
+                .iter()
+                .map(|needer| package(&self.info.id, needer))
+                .collect())
+        }
+    }
+
+    fn package(adapter_id: &str, id: &str) -> Package {
+        Package {
+            id: id.to_string(),
+            name: id.to_string(),
+            version: "1.0".into(),
+            adapter_id: adapter_id.to_string(),
+            description: None,
+            size: None,
+            homepage: None,
+            license: None,
+            installed: false,
+            update_available: false,
+            category: None,
+            tags: Vec::new(),
+            icon_url: None,
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .expect("should build a runtime")
+            .block_on(future)
+    }
+
+    #[test]
+    fn managers_are_handed_their_packages_at_the_same_time() {
+        // Neither can finish until the other has started, so handing them
+        // over one after the other would never return.
+        let barrier = Arc::new(Barrier::new(2));
+        let mut manager = AdapterManager::new();
+        manager.register(waiting("one", Some(barrier.clone())));
+        manager.register(waiting("two", Some(barrier)));
+
+        let (done, finished) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let packages = [package("one", "fd"), package("two", "jq")];
+            let _ = done.send(block_on(manager.install(
+                &packages,
+                None,
+                &CancelToken::default(),
+                PackageMode::User,
+            )));
+        });
+        let results = finished
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("should not wait on one manager to start the other")
+            .expect("should answer");
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.success));
+    }
+
+    #[test]
+    fn what_needs_a_batch_is_named_once_leaving_out_what_goes_with_it() {
+        let mut linked = Waiting {
+            info: waiting("deps", None).info().clone(),
+            barrier: None,
+            pinned: Vec::new(),
+        };
+        linked.info.capabilities.has_dependencies = true;
+        let mut manager = AdapterManager::new();
+        manager.register(Arc::new(linked));
+        manager.register(waiting("flat", None));
+
+        let needed_by = |ids: &[(&str, &str)]| {
+            let packages: Vec<Package> = ids.iter().map(|(a, id)| package(a, id)).collect();
+            block_on(manager.needed_by(&packages, PackageMode::User))
+                .into_iter()
+                .map(|p| p.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(needed_by(&[("deps", "libfoo")]), vec!["app", "tool"]);
+        assert_eq!(
+            needed_by(&[("deps", "libfoo"), ("deps", "tool")]),
+            vec!["app"]
+        );
+        assert!(needed_by(&[("deps", "app"), ("flat", "libfoo")]).is_empty());
+    }
+
+    #[test]
+    fn a_pinned_package_is_held_back_from_an_update() {
+        let mut pinning = Waiting {
+            info: waiting("pins", None).info().clone(),
+            barrier: None,
+            pinned: vec!["fd"],
+        };
+        pinning.info.capabilities.can_list = true;
+        let mut manager = AdapterManager::new();
+        manager.register(Arc::new(pinning));
+
+        let packages = [package("pins", "fd"), package("pins", "jq")];
+        let updated =
+            block_on(manager.update(&packages, None, &CancelToken::default(), PackageMode::User))
+                .expect("should answer");
+
+        assert_eq!(updated.held_back.len(), 1);
+        assert_eq!(updated.held_back[0].id, "fd");
+        assert!(updated.results.iter().all(|r| r.package_id != "fd"));
+        let jq = updated
+            .results
+            .iter()
+            .find(|r| r.package_id == "jq")
+            .unwrap();
+        assert!(jq.success);
+        assert_eq!(crate::core::package::failure_among(&updated.results), None);
+    }
+
+    #[test]
+    fn a_manager_failing_outright_fails_each_package_it_was_handed() {
+        let mut manager = AdapterManager::new();
+        manager.register(waiting("locked", None));
+
+        let packages = [
+            package("locked", "fd"),
+            package("locked", "jq"),
+            package("gone", "htop"),
+        ];
+        let results =
+            block_on(manager.install(&packages, None, &CancelToken::default(), PackageMode::User))
+                .expect("should answer");
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| !r.success));
+        assert_eq!(results[0].error.as_deref(), Some("the lock is held"));
+        assert_eq!(results[1].package_id, "jq");
+        assert_eq!(results[2].error.as_deref(), Some("Adapter not found: gone"));
+    }
+}
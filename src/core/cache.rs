@@ -0,0 +1,109 @@
+//! How much room caches take, and getting it back.
+//!
+//! A manager's cache is the manager's to clear, through its own command, since
+//! only it knows what it still needs. Aeris's own caches are copies of
+//! something fetchable, so clearing them costs nothing but fetching again.
+
+use std::path::{Path, PathBuf};
+
+/// One of aeris's own caches, and what it takes up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnCache {
+    pub name: &'static str,
+    pub size: u64,
+}
+
+/// Every file and directory aeris keeps as a cache, by what it is for.
+fn own_caches() -> Vec<(&'static str, Vec<PathBuf>)> {
+    vec![
+        ("Icons", super::icons::cached_paths()),
+        ("Registries", super::registry::cached_paths()),
+        ("Release notes", vec![super::changelog::cache_dir()]),
+    ]
+}
+
+/// What each of aeris's own caches takes up now.
+pub fn measure_own() -> Vec<OwnCache> {
+    own_caches()
+        .into_iter()
+        .map(|(name, paths)| OwnCache {
+            name,
+            size: paths.iter().map(|path| size_of(path)).sum(),
+        })
+        .collect()
+}
+
+/// Clear every one of aeris's own caches, answering how much room that gave
+/// back. Whatever could not be removed is named in the error, after the rest
+/// has gone.
+pub fn clear_own() -> Result<u64, String> {
+    let mut freed = 0;
+    let mut failed = Vec::new();
+    for path in own_caches().into_iter().flat_map(|(_, paths)| paths) {
+        let size = size_of(&path);
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match removed {
+            Ok(()) => freed += size,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => failed.push(format!("{}: {e}", path.display())),
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(freed)
+    } else {
+        Err(failed.join("; "))
+    }
+}
+
+/// The bytes a file takes, or everything under a directory. Links are
+/// counted as themselves rather than followed, so a cache linking into the
+/// rest of the disk is not charged for it. What cannot be read counts as
+/// nothing.
+pub fn size_of(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| size_of(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_directory_is_measured_through_but_not_past_its_links() {
+        let dir = std::env::temp_dir().join(format!("aeris-cache-{}", std::process::id()));
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join("a"), [0u8; 100]).unwrap();
+        std::fs::write(nested.join("b"), [0u8; 50]).unwrap();
+
+        let elsewhere = std::env::temp_dir().join(format!("aeris-big-{}", std::process::id()));
+        std::fs::write(&elsewhere, [0u8; 4096]).unwrap();
+        std::os::unix::fs::symlink(&elsewhere, dir.join("link")).unwrap();
+
+        let measured = size_of(&dir);
+        let link = std::fs::symlink_metadata(dir.join("link")).unwrap().len();
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::remove_file(&elsewhere).ok();
+
+        assert_eq!(measured, 150 + link);
+        assert_eq!(size_of(&dir), 0);
+    }
+}
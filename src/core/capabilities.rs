@@ -7,7 +7,16 @@ use super::privilege::PackageMode;
 pub struct Capabilities {
     pub can_search: bool,
     pub can_install: bool,
+    /// Whether the manager can be told which version of a package to
+    /// install, which is what putting back an older one needs.
+    pub can_install_version: bool,
     pub can_remove: bool,
+    /// Whether the manager can say which packages were only pulled in for
+    /// others and are no longer needed by anything.
+    pub can_list_orphans: bool,
+    /// Whether the manager has a command of its own for removing those, which
+    /// knows better than a removal by name what is safe to take.
+    pub can_remove_orphans: bool,
     pub can_update: bool,
     /// Whether an update can be pointed at one package.
     ///
@@ -19,6 +28,11 @@ pub struct Capabilities {
     pub can_update_all: bool,
     pub can_list: bool,
     pub can_list_updates: bool,
+    /// Whether the manager can fetch an update ahead of applying it.
+    pub can_download: bool,
+    /// Whether the manager can be told to keep a package where it is, and
+    /// to let it go again.
+    pub can_pin: bool,
     pub can_sync: bool,
     pub can_run: bool,
 
@@ -27,14 +41,44 @@ pub struct Capabilities {
     pub can_list_repos: bool,
 
     pub has_profiles: bool,
+    /// Whether profiles can be made and done away with here, rather than
+    /// only chosen between.
+    pub can_manage_profiles: bool,
 
     pub has_size_info: bool,
     pub has_package_detail: bool,
+    /// Whether the manager can say what a package needs, or what needs it.
+    pub has_dependencies: bool,
+    /// Whether the manager can say what changed between two versions of a
+    /// package.
+    pub has_changelog: bool,
+    /// Whether the manager keeps a list of the files each package put down.
+    pub can_list_files: bool,
+    /// Whether the manager can check its installed packages over and say
+    /// what is wrong with each.
+    pub can_verify: bool,
+    /// Whether the manager can be told to put a broken package right.
+    pub can_repair: bool,
+    /// Whether the manager can be asked which package a file belongs to.
+    /// One that cannot is answered for from where it installed things.
+    pub can_find_owner: bool,
+    /// Whether the manager can say how much room its cache takes.
+    pub has_cache_size: bool,
+    /// Whether the manager can be told to clear what its cache no longer
+    /// needs.
+    pub can_clean_cache: bool,
+    /// Whether the manager can be told to clear its cache outright, keeping
+    /// nothing back.
+    pub can_clean_whole_cache: bool,
 
     pub supports_declarative: bool,
 
     pub supports_user_packages: bool,
     pub supports_system_packages: bool,
+
+    /// Whether the manager has to be left to finish one change before it is
+    /// handed the next. Different managers never wait on each other.
+    pub exclusive: bool,
 }
 
 impl Capabilities {
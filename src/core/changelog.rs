@@ -0,0 +1,118 @@
+//! What changed in an update, asked of the manager once and kept.
+//!
+//! The notes between two versions do not change once both are out, so they
+//! are kept on disk beside the other caches and the manager is only asked the
+//! first time an update is opened. Losing them costs one more question.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use super::{
+    adapter::{Adapter, package_key},
+    package::{ReleaseNote, Update},
+    privilege::PackageMode,
+};
+
+/// The notes for an update, from the cache where they were read before.
+pub async fn notes_for(
+    adapter: &dyn Adapter,
+    update: &Update,
+    mode: PackageMode,
+) -> Result<Vec<ReleaseNote>, String> {
+    let path = cache_path(&cache_dir(), update);
+    if let Some(kept) = read(&path) {
+        return Ok(kept);
+    }
+
+    let notes = adapter
+        .changelog(
+            &update.package,
+            &update.current_version,
+            &update.new_version,
+            mode,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = write(&path, &notes) {
+        log::warn!("Could not keep the notes for {}: {e}", update.package.name);
+    }
+    Ok(notes)
+}
+
+pub(crate) fn cache_dir() -> PathBuf {
+    crate::xdg::cache_home().join("aeris").join("changelogs")
+}
+
+/// Where the notes for an update are kept: one file for each package and the
+/// versions either side, named by a digest since neither a package id nor a
+/// version is safe to put in a path as it comes.
+fn cache_path(dir: &Path, update: &Update) -> PathBuf {
+    let key = format!(
+        "{}@{}..{}",
+        package_key(&update.package.adapter_id, &update.package.id),
+        update.current_version,
+        update.new_version
+    );
+    let mut named = String::new();
+    for byte in Sha256::digest(key.as_bytes()).iter().take(12) {
+        let _ = write!(named, "{byte:02x}");
+    }
+    dir.join(format!("{named}.json"))
+}
+
+fn read(path: &Path) -> Option<Vec<ReleaseNote>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn write(path: &Path, notes: &[ReleaseNote]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string(notes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::package::Package;
+
+    fn update(id: &str, from: &str, to: &str) -> Update {
+        Update {
+            package: Package::named("demo", id, id, from),
+            current_version: from.into(),
+            new_version: to.into(),
+            download_size: None,
+            is_security: false,
+            severity: None,
+            advisories: Vec::new(),
+            changelog_url: None,
+        }
+    }
+
+    #[test]
+    fn notes_are_kept_apart_for_each_package_and_version() {
+        let dir = std::env::temp_dir().join(format!("aeris-notes-{}", std::process::id()));
+        let fd = update("fd", "9.0", "10.0");
+        let notes = vec![ReleaseNote {
+            version: Some("10.0".into()),
+            date: None,
+            text: "Faster".into(),
+        }];
+
+        let path = cache_path(&dir, &fd);
+        write(&path, &notes).unwrap();
+        let kept = read(&path);
+        let elsewhere = read(&cache_path(&dir, &update("fd", "9.0", "10.1")));
+        let other = read(&cache_path(&dir, &update("rg", "9.0", "10.0")));
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(kept, Some(notes));
+        assert_eq!(elsewhere, None);
+        assert_eq!(other, None);
+    }
+}
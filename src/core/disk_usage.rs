@@ -0,0 +1,312 @@
+//! Where the disk went: what every manager's packages take, broken down by
+//! manager, scope and profile, beside the caches kept alongside them.
+//!
+//! Sizes are what each manager reports. A manager that reports none, or
+//! reports nothing but zero, is not taken at its word: those packages are
+//! flagged as unknown, and can be measured from where they were installed.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{
+    adapter_manager::AdapterManager,
+    cache::{self, OwnCache},
+    privilege::PackageMode,
+};
+
+/// One installed package and the room it takes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageUsage {
+    pub adapter_id: String,
+    pub package_id: String,
+    pub name: String,
+    pub version: String,
+    pub mode: PackageMode,
+    pub profile: Option<String>,
+    pub install_path: Option<String>,
+    /// Nothing when neither the manager nor a measurement could say.
+    pub size: Option<u64>,
+    /// Set when the size was measured here rather than reported.
+    pub measured: bool,
+}
+
+impl PackageUsage {
+    pub fn bytes(&self) -> u64 {
+        self.size.unwrap_or(0)
+    }
+}
+
+/// A part of the whole: what it is, what it takes, and how much of it is
+/// not accounted for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slice {
+    pub label: String,
+    pub size: u64,
+    pub packages: usize,
+    /// Packages in it whose size is not known, so the size is a floor.
+    pub unknown: usize,
+}
+
+/// Everything measured at one time.
+#[derive(Debug, Clone, Default)]
+pub struct Usage {
+    pub packages: Vec<PackageUsage>,
+    /// Each manager's cache, by adapter id.
+    pub caches: Vec<(String, u64)>,
+    pub own_caches: Vec<OwnCache>,
+    /// Managers that could not say what they hold or keep.
+    pub problems: Vec<String>,
+}
+
+/// The one thing a breakdown can be narrowed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum By {
+    Manager,
+    Mode,
+    Profile,
+}
+
+impl Usage {
+    /// Ask every enabled manager what it holds in each scope it works in, and
+    /// what its cache takes. A manager that fails is named among the problems
+    /// rather than costing the rest.
+    pub async fn gather(manager: &AdapterManager) -> Self {
+        let mut ids: Vec<String> = manager
+            .enabled_adapters()
+            .iter()
+            .map(|info| info.id.clone())
+            .collect();
+        ids.sort();
+
+        let mut usage = Self::default();
+        for id in ids {
+            let Some(adapter) = manager.get_adapter(&id) else {
+                continue;
+            };
+            let caps = *adapter.capabilities();
+
+            if caps.can_list {
+                for mode in [PackageMode::User, PackageMode::System] {
+                    if !caps.works_in(mode) {
+                        continue;
+                    }
+                    match adapter.list_installed(mode).await {
+                        Ok(installed) => {
+                            usage
+                                .packages
+                                .extend(installed.into_iter().map(|held| PackageUsage {
+                                    adapter_id: id.clone(),
+                                    package_id: held.package.id,
+                                    name: held.package.name,
+                                    version: held.package.version,
+                                    mode,
+                                    profile: held.profile,
+                                    install_path: held.install_path,
+                                    // Zero is what a manager with no idea
+                                    // writes, not what a package takes.
+                                    size: (held.install_size > 0).then_some(held.install_size),
+                                    measured: false,
+                                }))
+                        }
+                        Err(e) => usage.problems.push(format!("{id} ({mode}): {e}")),
+                    }
+                }
+            }
+
+            if caps.has_cache_size {
+                match adapter.cache_size().await {
+                    Ok(size) => usage.caches.push((id.clone(), size)),
+                    Err(e) => usage.problems.push(format!("{id} cache: {e}")),
+                }
+            }
+        }
+
+        usage.own_caches = tokio::task::spawn_blocking(cache::measure_own)
+            .await
+            .unwrap_or_default();
+        usage
+    }
+
+    /// Measure, from where it was installed, every package whose size is not
+    /// known, answering how many were. Blocks for as long as the walk takes.
+    pub fn measure_unknown(&mut self) -> usize {
+        let mut measured = 0;
+        for package in &mut self.packages {
+            if package.size.is_some() {
+                continue;
+            }
+            let Some(ref path) = package.install_path else {
+                continue;
+            };
+            let path = Path::new(path);
+            if !path.exists() {
+                continue;
+            }
+            package.size = Some(cache::size_of(path));
+            package.measured = true;
+            measured += 1;
+        }
+        measured
+    }
+
+    /// Everything counted: packages, the managers' caches and aeris's own.
+    pub fn total(&self) -> u64 {
+        self.packages.iter().map(PackageUsage::bytes).sum::<u64>()
+            + self.caches.iter().map(|(_, size)| size).sum::<u64>()
+            + self.own_caches.iter().map(|cache| cache.size).sum::<u64>()
+    }
+
+    /// The packages a manager holds, or every package when none is named.
+    pub fn within<'a>(
+        &'a self,
+        adapter_id: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a PackageUsage> + 'a {
+        self.packages
+            .iter()
+            .filter(move |p| adapter_id.is_none_or(|id| p.adapter_id == id))
+    }
+
+    /// The packages split one way, largest first. Packages with no profile
+    /// are put together under `none`.
+    pub fn breakdown(&self, by: By, adapter_id: Option<&str>) -> Vec<Slice> {
+        let mut slices: BTreeMap<String, Slice> = BTreeMap::new();
+        for package in self.within(adapter_id) {
+            let label = match by {
+                By::Manager => package.adapter_id.clone(),
+                By::Mode => package.mode.to_string(),
+                By::Profile => package.profile.clone().unwrap_or_else(|| "none".into()),
+            };
+            let slice = slices.entry(label.clone()).or_insert(Slice {
+                label,
+                size: 0,
+                packages: 0,
+                unknown: 0,
+            });
+            slice.size += package.bytes();
+            slice.packages += 1;
+            if package.size.is_none() {
+                slice.unknown += 1;
+            }
+        }
+
+        let mut slices: Vec<Slice> = slices.into_values().collect();
+        slices.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.label.cmp(&b.label)));
+        slices
+    }
+
+    /// The largest packages, across every manager or within one.
+    pub fn largest<'a>(
+        &'a self,
+        adapter_id: Option<&'a str>,
+        count: usize,
+    ) -> Vec<&'a PackageUsage> {
+        let mut sized: Vec<&PackageUsage> = self
+            .within(adapter_id)
+            .filter(|p| p.size.is_some())
+            .collect();
+        sized.sort_by(|a, b| b.bytes().cmp(&a.bytes()).then_with(|| a.name.cmp(&b.name)));
+        sized.truncate(count);
+        sized
+    }
+
+    /// The packages whose size nobody could say.
+    pub fn unknown<'a>(&'a self, adapter_id: Option<&'a str>) -> Vec<&'a PackageUsage> {
+        self.within(adapter_id)
+            .filter(|p| p.size.is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn held(
+        adapter: &str,
+        name: &str,
+        mode: PackageMode,
+        profile: Option<&str>,
+        size: Option<u64>,
+    ) -> PackageUsage {
+        PackageUsage {
+            adapter_id: adapter.into(),
+            package_id: name.into(),
+            name: name.into(),
+            version: "1.0".into(),
+            mode,
+            profile: profile.map(Into::into),
+            install_path: None,
+            size,
+            measured: false,
+        }
+    }
+
+    #[test]
+    fn usage_is_split_each_way_largest_first_with_the_unknown_counted() {
+        let usage = Usage {
+            packages: vec![
+                held("soar", "fd", PackageMode::User, Some("default"), Some(300)),
+                held("soar", "rg", PackageMode::User, Some("work"), Some(500)),
+                held("soar", "bat", PackageMode::User, Some("work"), None),
+                held("apt", "curl", PackageMode::System, None, Some(200)),
+            ],
+            caches: vec![("apt".into(), 1000)],
+            own_caches: vec![OwnCache {
+                name: "Icons",
+                size: 10,
+            }],
+            problems: Vec::new(),
+        };
+
+        let managers = usage.breakdown(By::Manager, None);
+        assert_eq!(managers[0].label, "soar");
+        assert_eq!(
+            (managers[0].size, managers[0].packages, managers[0].unknown),
+            (800, 3, 1)
+        );
+        assert_eq!(managers[1].label, "apt");
+
+        let profiles = usage.breakdown(By::Profile, Some("soar"));
+        assert_eq!(
+            profiles
+                .iter()
+                .map(|s| (s.label.as_str(), s.size))
+                .collect::<Vec<_>>(),
+            vec![("work", 500), ("default", 300)]
+        );
+        assert_eq!(usage.breakdown(By::Mode, None)[1].label, "System");
+
+        let largest: Vec<&str> = usage
+            .largest(None, 2)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(largest, vec!["rg", "fd"]);
+        assert_eq!(usage.unknown(None)[0].name, "bat");
+        assert_eq!(usage.total(), 1000 + 1000 + 10);
+    }
+
+    #[test]
+    fn an_unknown_size_is_measured_from_where_the_package_went() {
+        let dir = std::env::temp_dir().join(format!("aeris-usage-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("fd"), [0u8; 64]).unwrap();
+
+        let mut unknown = held("soar", "fd", PackageMode::User, None, None);
+        unknown.install_path = Some(dir.display().to_string());
+        let mut gone = held("soar", "rg", PackageMode::User, None, None);
+        gone.install_path = Some(dir.join("nowhere").display().to_string());
+        let mut usage = Usage {
+            packages: vec![unknown, gone],
+            ..Default::default()
+        };
+
+        let measured = usage.measure_unknown();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(measured, 1);
+        assert_eq!(usage.packages[0].size, Some(64));
+        assert!(usage.packages[0].measured);
+        assert_eq!(usage.packages[1].size, None);
+    }
+}
@@ -0,0 +1,249 @@
+//! What is wrong with what the managers have installed.
+//!
+//! A manager that can check its own packages is asked to. Every manager is
+//! also looked over from outside, for what needs no help from it to notice:
+//! an install directory that has gone, and a command linked into its `bin`
+//! that leads nowhere.
+
+use std::path::{Path, PathBuf};
+
+use super::{adapter_manager::AdapterManager, package::Package, privilege::PackageMode};
+
+/// One thing a manager found wrong with one of its packages when asked to
+/// check them.
+#[derive(Debug, Clone)]
+pub struct Fault {
+    pub package: Package,
+    pub problem: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ailment {
+    /// What the manager said when it checked.
+    Reported(String),
+    /// Listed as unhealthy, with nothing said about why.
+    Unhealthy,
+    /// The directory the package was installed to is not there.
+    MissingInstall(PathBuf),
+    /// A command in the manager's `bin` linking to something gone.
+    DanglingLink { link: PathBuf, target: PathBuf },
+}
+
+impl Ailment {
+    pub fn describe(&self) -> String {
+        match self {
+            Ailment::Reported(problem) => problem.clone(),
+            Ailment::Unhealthy => "Its manager lists it as broken".into(),
+            Ailment::MissingInstall(path) => {
+                format!("{} is missing", path.display())
+            }
+            Ailment::DanglingLink { link, target } => {
+                format!(
+                    "{} leads to {}, which is gone",
+                    link.display(),
+                    target.display()
+                )
+            }
+        }
+    }
+}
+
+/// Something wrong, where it was found, and with what if that can be told.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub adapter_id: String,
+    pub mode: PackageMode,
+    pub package: Option<Package>,
+    pub ailment: Ailment,
+}
+
+/// Look every enabled manager over. A manager that cannot say what it holds
+/// is named among the problems with looking rather than costing the rest.
+pub async fn examine(manager: &AdapterManager) -> (Vec<Problem>, Vec<String>) {
+    let mut ids: Vec<String> = manager
+        .enabled_adapters()
+        .iter()
+        .filter(|info| info.capabilities.can_list)
+        .map(|info| info.id.clone())
+        .collect();
+    ids.sort();
+
+    let mut found = Vec::new();
+    let mut problems = Vec::new();
+    for id in ids {
+        let Some(adapter) = manager.get_adapter(&id) else {
+            continue;
+        };
+        let caps = *adapter.capabilities();
+        // Where each package went, so a broken link can be laid at the door
+        // of the package it led into.
+        let mut placed: Vec<(PathBuf, Package, PackageMode)> = Vec::new();
+
+        for mode in [PackageMode::User, PackageMode::System] {
+            if !caps.works_in(mode) {
+                continue;
+            }
+            let installed = match adapter.list_installed(mode).await {
+                Ok(installed) => installed,
+                Err(e) => {
+                    problems.push(format!("{id} ({mode}): {e}"));
+                    continue;
+                }
+            };
+
+            let mut reported = Vec::new();
+            if caps.can_verify {
+                match adapter.verify(mode).await {
+                    Ok(faults) => {
+                        for fault in faults {
+                            reported.push(fault.package.id.clone());
+                            found.push(Problem {
+                                adapter_id: id.clone(),
+                                mode,
+                                package: Some(fault.package),
+                                ailment: Ailment::Reported(fault.problem),
+                            });
+                        }
+                    }
+                    Err(e) => problems.push(format!("{id} ({mode}) could not check: {e}")),
+                }
+            }
+
+            for held in installed {
+                // What the manager already said covers it better than a flag.
+                if !held.is_healthy && !reported.contains(&held.package.id) {
+                    found.push(Problem {
+                        adapter_id: id.clone(),
+                        mode,
+                        package: Some(held.package.clone()),
+                        ailment: Ailment::Unhealthy,
+                    });
+                }
+                let Some(path) = held.install_path.map(PathBuf::from) else {
+                    continue;
+                };
+                if path.symlink_metadata().is_err() {
+                    found.push(Problem {
+                        adapter_id: id.clone(),
+                        mode,
+                        package: Some(held.package.clone()),
+                        ailment: Ailment::MissingInstall(path.clone()),
+                    });
+                }
+                placed.push((path, held.package, mode));
+            }
+        }
+
+        let Some(bin) = adapter
+            .paths()
+            .await
+            .ok()
+            .and_then(|paths| paths.get("bin").map(PathBuf::from))
+        else {
+            continue;
+        };
+        // A link with no owner still belongs to this manager's directory, so
+        // it is put under the scope the manager works in first.
+        let fallback = if caps.works_in(PackageMode::User) {
+            PackageMode::User
+        } else {
+            PackageMode::System
+        };
+        for (link, target) in dangling_links(&bin) {
+            let owner = placed.iter().find(|(path, ..)| target.starts_with(path));
+            found.push(Problem {
+                adapter_id: id.clone(),
+                mode: owner.map_or(fallback, |(_, _, mode)| *mode),
+                package: owner.map(|(_, package, _)| package.clone()),
+                ailment: Ailment::DanglingLink { link, target },
+            });
+        }
+    }
+
+    (found, problems)
+}
+
+/// Every link directly in `dir` whose target is not there, with where it
+/// leads. A relative target is read from `dir`, the way following it would
+/// be.
+pub fn dangling_links(dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut dangling: Vec<(PathBuf, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.symlink_metadata()
+                .is_ok_and(|meta| meta.file_type().is_symlink())
+                && std::fs::metadata(path).is_err()
+        })
+        .filter_map(|link| {
+            let target = std::fs::read_link(&link).ok()?;
+            Some((link, tidied(&dir.join(target))))
+        })
+        .collect();
+    dangling.sort();
+    dangling
+}
+
+/// The path with `.` and `..` worked out by name alone. What they would be
+/// resolved through is gone, so nothing on disk can be asked.
+fn tidied(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut tidy = PathBuf::new();
+    for part in path.components() {
+        match part {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                tidy.pop();
+            }
+            other => tidy.push(other),
+        }
+    }
+    tidy
+}
+
+/// Remove a link found leading nowhere, provided it still does: something
+/// may have put its target back since it was found.
+pub fn remove_dangling(link: &Path) -> Result<(), String> {
+    let is_link = link
+        .symlink_metadata()
+        .is_ok_and(|meta| meta.file_type().is_symlink());
+    if !is_link {
+        return Err(format!("{} is no longer a link", link.display()));
+    }
+    if std::fs::metadata(link).is_ok() {
+        return Err(format!("{} leads somewhere again", link.display()));
+    }
+    std::fs::remove_file(link).map_err(|e| format!("{}: {e}", link.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_links_leading_nowhere_are_dangling_and_only_those_are_removed() {
+        let dir = std::env::temp_dir().join(format!("aeris-doctor-{}", std::process::id()));
+        let bin = dir.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(dir.join("fd"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("fd"), bin.join("fd")).unwrap();
+        std::os::unix::fs::symlink("../rg", bin.join("rg")).unwrap();
+        std::fs::write(bin.join("plain"), "").unwrap();
+
+        let dangling = dangling_links(&bin);
+        let removed_live = remove_dangling(&bin.join("fd"));
+        let removed_dead = remove_dangling(&bin.join("rg"));
+        let left = dangling_links(&bin);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(dangling, vec![(bin.join("rg"), dir.join("rg"))]);
+        assert!(removed_live.is_err());
+        assert!(removed_dead.is_ok());
+        assert!(left.is_empty());
+    }
+}
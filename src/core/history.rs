@@ -0,0 +1,439 @@
+//! A record of every change aeris made, kept so that what changed on this
+//! machine last Tuesday has an answer.
+//!
+//! One JSON object a line, appended as each change ends. Appending never
+//! rewrites what is already there, so a crash costs at most the line being
+//! written, and a line that cannot be read is skipped rather than costing
+//! every line after it.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{adapter::AdapterId, privilege::PackageMode};
+
+/// How many of the last lines a manager wrote are kept with each change.
+pub const OUTPUT_KEPT: usize = 20;
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Install,
+    Remove,
+    Update,
+    /// Everything one manager holds, in one go.
+    UpdateAll,
+    /// A manager brought in line with its declarative file.
+    Apply,
+    /// A package put back to a version it had before.
+    Revert,
+    Pin,
+    Unpin,
+    /// A broken package put right by its manager.
+    Repair,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Install => write!(f, "Install"),
+            Action::Remove => write!(f, "Remove"),
+            Action::Update => write!(f, "Update"),
+            Action::UpdateAll => write!(f, "Update all"),
+            Action::Apply => write!(f, "Apply"),
+            Action::Revert => write!(f, "Revert"),
+            Action::Pin => write!(f, "Pin"),
+            Action::Unpin => write!(f, "Unpin"),
+            Action::Repair => write!(f, "Repair"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Succeeded,
+    Failed(String),
+    Cancelled,
+}
+
+impl Outcome {
+    pub fn label(&self) -> &str {
+        match self {
+            Outcome::Succeeded => "Succeeded",
+            Outcome::Failed(_) => "Failed",
+            Outcome::Cancelled => "Cancelled",
+        }
+    }
+}
+
+/// One change to one package, or to a manager as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the epoch when the change ended.
+    pub at: u64,
+    pub action: Action,
+    pub adapter_id: AdapterId,
+    /// Which package, or nothing for a change made to the manager as a whole
+    /// that moved no package anyone could name.
+    pub package_id: Option<String>,
+    pub package_name: Option<String>,
+    pub version_before: Option<String>,
+    pub version_after: Option<String>,
+    pub mode: PackageMode,
+    pub outcome: Outcome,
+    /// The last lines the manager wrote while it worked.
+    #[serde(default)]
+    pub output: Vec<String>,
+}
+
+impl Entry {
+    /// A change that ended just now, made to the manager as a whole until a
+    /// package is named.
+    pub fn ended(action: Action, adapter_id: &str, mode: PackageMode, outcome: Outcome) -> Self {
+        Self {
+            at: now(),
+            action,
+            adapter_id: adapter_id.to_string(),
+            package_id: None,
+            package_name: None,
+            version_before: None,
+            version_after: None,
+            mode,
+            outcome,
+            output: Vec::new(),
+        }
+    }
+
+    pub fn of(mut self, package_id: &str, package_name: &str) -> Self {
+        self.package_id = Some(package_id.to_string());
+        self.package_name = Some(package_name.to_string());
+        self
+    }
+
+    /// The version this change took away, for one that can be undone by
+    /// putting it back: an update or a removal that went through, of a
+    /// package whose earlier version is known.
+    pub fn reverts_to(&self) -> Option<&str> {
+        if !matches!(
+            self.action,
+            Action::Update | Action::UpdateAll | Action::Remove
+        ) || self.outcome != Outcome::Succeeded
+            || self.package_id.is_none()
+        {
+            return None;
+        }
+
+        let before = self.version_before.as_deref()?;
+        (self.version_after.as_deref() != Some(before)).then_some(before)
+    }
+
+    /// The versions either side. An empty one is a version nobody said, not
+    /// a version called nothing.
+    pub fn moved(mut self, before: Option<String>, after: Option<String>) -> Self {
+        self.version_before = before.filter(|v| !v.is_empty());
+        self.version_after = after.filter(|v| !v.is_empty());
+        self
+    }
+}
+
+/// The file changes are appended to.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn default_path() -> PathBuf {
+        crate::xdg::data_home().join("aeris").join("history.jsonl")
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, entries: &[Entry]) -> Result<(), String> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut text = String::new();
+        for entry in entries {
+            text.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+            text.push('\n');
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // One write for the lot, so entries for one change are not split by
+        // another writing at the same time.
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(text.as_bytes()))
+            .map_err(|e| format!("{}: {e}", self.path.display()))
+    }
+
+    /// Every entry that can be read, oldest first. No journal yet is no
+    /// history rather than an error.
+    pub fn read(&self) -> Vec<Entry> {
+        let Ok(text) = std::fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("skipping a history line that could not be read: {e}");
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Seconds since the epoch, now.
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+/// The day a moment falls on, written `YYYY-MM-DD`, in UTC.
+pub fn day_of(at: u64) -> String {
+    let (year, month, day) = civil_from_days((at / DAY) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// A moment to the minute, in UTC.
+pub fn moment_of(at: u64) -> String {
+    let into = at % DAY;
+    format!(
+        "{} {:02}:{:02}",
+        day_of(at),
+        into / (60 * 60),
+        into / 60 % 60
+    )
+}
+
+/// When a day written `YYYY-MM-DD` starts, in UTC. Anything else, a day
+/// that does not exist included, is nothing.
+pub fn parse_day(written: &str) -> Option<u64> {
+    let mut parts = written.trim().splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // The thirty first of February comes back as a day in March.
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+
+    u64::try_from(days).ok().map(|days| days * DAY)
+}
+
+/// Days since the epoch of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let of_era = year - era * 400;
+    let from_march = (i64::from(month) + 9) % 12;
+    let of_year = (153 * from_march + 2) / 5 + i64::from(day) - 1;
+    let of_cycle = of_era * 365 + of_era / 4 - of_era / 100 + of_year;
+    era * 146_097 + of_cycle - 719_468
+}
+
+/// The date a count of days since the epoch falls on.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let of_cycle = days - era * 146_097;
+    let of_era = (of_cycle - of_cycle / 1460 + of_cycle / 36_524 - of_cycle / 146_096) / 365;
+    let of_year = of_cycle - (365 * of_era + of_era / 4 - of_era / 100);
+    let from_march = (5 * of_year + 2) / 153;
+    let day = (of_year - (153 * from_march + 2) / 5 + 1) as u32;
+    let month = if from_march < 10 {
+        from_march + 3
+    } else {
+        from_march - 9
+    } as u32;
+    let year = of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Which entries to show.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// The managers to show. None named means every one.
+    pub managers: HashSet<String>,
+    /// Part of a package's name or id, in any case.
+    pub package: String,
+    /// The first day shown, and the last, both whole.
+    pub from: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if !self.managers.is_empty() && !self.managers.contains(&entry.adapter_id) {
+            return false;
+        }
+
+        let wanted = self.package.trim().to_lowercase();
+        if !wanted.is_empty() {
+            let named = [&entry.package_name, &entry.package_id]
+                .into_iter()
+                .flatten()
+                .any(|name| name.to_lowercase().contains(&wanted));
+            if !named {
+                return false;
+            }
+        }
+
+        self.from.is_none_or(|from| entry.at >= from)
+            && self.until.is_none_or(|until| entry.at < until + DAY)
+    }
+}
+
+/// Add to the history kept where the window reads it, whichever of the
+/// window, the command line or an unattended run made the change. One that
+/// cannot be written is only logged: the change happened either way.
+pub fn record(entries: &[Entry]) {
+    if let Err(e) = Journal::at(Journal::default_path()).append(entries) {
+        log::warn!("could not add to the history: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(adapter_id: &str, name: &str, at: u64) -> Entry {
+        Entry {
+            at,
+            ..Entry::ended(
+                Action::Install,
+                adapter_id,
+                PackageMode::User,
+                Outcome::Succeeded,
+            )
+            .of(name, name)
+            .moved(Some(String::new()), Some("1.0".into()))
+        }
+    }
+
+    #[test]
+    fn a_day_is_written_and_read_the_same_way() {
+        assert_eq!(day_of(0), "1970-01-01");
+        assert_eq!(day_of(951_782_400), "2000-02-29");
+        assert_eq!(moment_of(1_700_000_000), "2023-11-14 22:13");
+
+        assert_eq!(parse_day("2000-02-29"), Some(951_782_400));
+        assert_eq!(parse_day(" 1970-01-01 "), Some(0));
+        assert_eq!(parse_day("2023-02-29"), None);
+        assert_eq!(parse_day("2023-13-01"), None);
+        assert_eq!(parse_day("last tuesday"), None);
+        assert_eq!(parse_day("1969-12-31"), None);
+
+        for at in [0, 86_399, 86_400, 1_700_000_000, 4_102_444_800] {
+            assert_eq!(parse_day(&day_of(at)), Some(at / DAY * DAY));
+        }
+    }
+
+    #[test]
+    fn entries_are_filtered_by_manager_package_and_day() {
+        let tuesday = parse_day("2024-05-14").unwrap();
+        let fd = entry("soar", "fd", tuesday + 3600);
+        let firefox = entry("apt", "firefox-esr", tuesday + DAY + 60);
+
+        let mut filter = Filter::default();
+        assert!(filter.matches(&fd) && filter.matches(&firefox));
+
+        filter.managers.insert("apt".into());
+        assert!(!filter.matches(&fd) && filter.matches(&firefox));
+
+        filter.managers.clear();
+        filter.package = "FIRE".into();
+        assert!(!filter.matches(&fd) && filter.matches(&firefox));
+
+        // The last day named is shown whole.
+        let filter = Filter {
+            from: Some(tuesday),
+            until: Some(tuesday),
+            ..Filter::default()
+        };
+        assert!(filter.matches(&fd) && !filter.matches(&firefox));
+    }
+
+    #[test]
+    fn only_a_change_that_took_a_known_version_away_can_be_undone() {
+        let updated = |before: Option<&str>, after: Option<&str>| Entry {
+            action: Action::Update,
+            ..entry("apt", "firefox-esr", 0).moved(before.map(Into::into), after.map(Into::into))
+        };
+
+        assert_eq!(
+            updated(Some("115.1"), Some("115.2")).reverts_to(),
+            Some("115.1")
+        );
+        // Nothing moved, or nobody said from where.
+        assert_eq!(updated(Some("115.1"), Some("115.1")).reverts_to(), None);
+        assert_eq!(updated(None, Some("115.2")).reverts_to(), None);
+
+        let removed = Entry {
+            action: Action::Remove,
+            ..entry("apt", "firefox-esr", 0).moved(Some("115.1".into()), None)
+        };
+        assert_eq!(removed.reverts_to(), Some("115.1"));
+
+        let failed = Entry {
+            outcome: Outcome::Failed("held by another process".into()),
+            ..removed.clone()
+        };
+        assert_eq!(failed.reverts_to(), None);
+
+        // An install took nothing away.
+        assert_eq!(entry("apt", "firefox-esr", 0).reverts_to(), None);
+    }
+
+    #[test]
+    fn the_journal_keeps_what_is_appended_and_skips_what_it_cannot_read() {
+        let dir = std::env::temp_dir().join(format!("aeris-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let journal = Journal::at(dir.join("history.jsonl"));
+
+        assert!(journal.read().is_empty());
+        journal
+            .append(&[entry("soar", "fd", 1), entry("soar", "jq", 2)])
+            .expect("should append");
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.join("history.jsonl"))
+            .and_then(|mut file| file.write_all(b"{\"half\":\n"))
+            .expect("should write");
+        journal
+            .append(&[entry("apt", "rg", 3)])
+            .expect("should append");
+
+        let read: Vec<_> = journal
+            .read()
+            .into_iter()
+            .filter_map(|e| e.package_name)
+            .collect();
+        assert_eq!(read, vec!["fd", "jq", "rg"]);
+        // An empty version is one nobody said.
+        assert_eq!(journal.read()[0].version_before, None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
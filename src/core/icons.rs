@@ -286,6 +286,13 @@ fn cache_dir() -> PathBuf {
     crate::xdg::cache_home().join("aeris")
 }
 
+/// Everything kept here, for measuring and clearing. The map and the index
+/// go with the icons, since an index with no icons behind it would only be
+/// trusted to be complete.
+pub fn cached_paths() -> Vec<PathBuf> {
+    vec![icon_dir(), map_path(), index_path()]
+}
+
 /// Whether what is on disk is old enough to be worth reading again.
 pub fn is_stale(within: std::time::Duration) -> bool {
     let Ok(written) = std::fs::metadata(index_path()).and_then(|m| m.modified()) else {
@@ -0,0 +1,690 @@
+//! Everything installed through every manager, written down so it can be put
+//! back on another machine.
+//!
+//! The file is TOML, one `[[package]]` table a package, so it can be read and
+//! trimmed by hand before it is taken anywhere. Putting it back is planned
+//! before anything runs: each package is matched to a manager that is here,
+//! and what cannot be matched is said rather than left to fail at install.
+//! A package that was pinned is pinned again once it is in, and one that was
+//! kept in a profile other than the one active here is said to be going into
+//! the active one instead.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    adapter::{self, AdapterId},
+    adapter_manager::AdapterManager,
+    package::{InstallResult, Package},
+    privilege::PackageMode,
+};
+
+/// The layout this build writes. A file declaring a newer one is refused
+/// rather than half read.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    pub version: u32,
+    /// Seconds since the epoch when it was written.
+    #[serde(default)]
+    pub exported_at: u64,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<Held>,
+}
+
+/// One package as it was held where the inventory was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Held {
+    pub adapter_id: AdapterId,
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    pub mode: PackageMode,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl Inventory {
+    /// What every enabled manager holds, in every scope it works in. A
+    /// manager that cannot list what it holds is named among the problems
+    /// rather than costing the rest.
+    pub async fn gather(manager: &AdapterManager) -> (Self, Vec<String>) {
+        let mut ids: Vec<String> = manager
+            .enabled_adapters()
+            .iter()
+            .filter(|info| info.capabilities.can_list)
+            .map(|info| info.id.clone())
+            .collect();
+        ids.sort();
+
+        let mut packages = Vec::new();
+        let mut problems = Vec::new();
+        for id in ids {
+            let Some(adapter) = manager.get_adapter(&id) else {
+                continue;
+            };
+            for mode in [PackageMode::User, PackageMode::System] {
+                if !adapter.capabilities().works_in(mode) {
+                    continue;
+                }
+                match adapter.list_installed(mode).await {
+                    Ok(installed) => packages.extend(installed.into_iter().map(|held| Held {
+                        adapter_id: id.clone(),
+                        id: held.package.id,
+                        name: held.package.name,
+                        version: held.package.version,
+                        mode,
+                        pinned: held.pinned,
+                        profile: held.profile,
+                    })),
+                    Err(e) => problems.push(format!("{id} ({mode}): {e}")),
+                }
+            }
+        }
+
+        let inventory = Self {
+            version: FORMAT_VERSION,
+            exported_at: super::history::now(),
+            packages,
+        };
+        (inventory, problems)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let inventory: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        if inventory.version > FORMAT_VERSION {
+            return Err(format!(
+                "this file is version {}, newer than the {FORMAT_VERSION} this aeris reads",
+                inventory.version
+            ));
+        }
+        Ok(inventory)
+    }
+
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_toml()?).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// What putting an inventory back would do here.
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    /// What would be installed, found with the manager it was held by.
+    pub install: Vec<(Package, PackageMode)>,
+    /// What is held here already.
+    pub present: Vec<Held>,
+    /// What cannot be put back here, each with why.
+    pub missing: Vec<(Held, String)>,
+    /// What is to be pinned again, as it was pinned where the inventory was
+    /// taken: once installed, or as it is when it is here already.
+    pub pin: Vec<(Package, PackageMode)>,
+    /// What is installed, but not quite as it was held, each with why.
+    pub unkept: Vec<(Held, String)>,
+}
+
+impl ImportPlan {
+    /// Match each package to a manager that is here and can install it.
+    ///
+    /// A manager that can search is asked whether it still has the package,
+    /// so a name gone from its repositories is found out now rather than at
+    /// install. One that cannot search is taken at its word.
+    pub async fn for_inventory(inventory: &Inventory, manager: &AdapterManager) -> Self {
+        let mut plan = Self::default();
+        let mut held_here: HashMap<(String, PackageMode), Result<HashSet<String>, String>> =
+            HashMap::new();
+        let mut active_profiles: HashMap<String, Option<String>> = HashMap::new();
+        let mut seen = HashSet::new();
+
+        for held in &inventory.packages {
+            if !seen.insert((held.adapter_id.clone(), held.id.clone(), held.mode)) {
+                continue;
+            }
+
+            let adapter = match manager.get_adapter(&held.adapter_id) {
+                Some(adapter) if manager.is_enabled(&held.adapter_id) => adapter,
+                Some(_) => {
+                    plan.missing
+                        .push((held.clone(), format!("{} is turned off", held.adapter_id)));
+                    continue;
+                }
+                None => {
+                    plan.missing.push((
+                        held.clone(),
+                        format!("no manager called {} is here", held.adapter_id),
+                    ));
+                    continue;
+                }
+            };
+            let capabilities = adapter.capabilities();
+            let manager_name = &adapter.info().name;
+            if !capabilities.can_install {
+                plan.missing
+                    .push((held.clone(), format!("{manager_name} cannot install")));
+                continue;
+            }
+            if !capabilities.works_in(held.mode) {
+                plan.missing.push((
+                    held.clone(),
+                    format!("{manager_name} does not work with {} packages", held.mode),
+                ));
+                continue;
+            }
+
+            let key = (held.adapter_id.clone(), held.mode);
+            if capabilities.can_list && !held_here.contains_key(&key) {
+                let ids = adapter
+                    .list_installed(held.mode)
+                    .await
+                    .map(|installed| installed.into_iter().map(|i| i.package.id).collect())
+                    .map_err(|e| e.to_string());
+                held_here.insert(key.clone(), ids);
+            }
+            match held_here.get(&key) {
+                // Not knowing what is here is not knowing what to install,
+                // and installing it all again is no way to find out.
+                Some(Err(e)) => {
+                    plan.missing.push((
+                        held.clone(),
+                        format!("{manager_name} could not say what is installed: {e}"),
+                    ));
+                    continue;
+                }
+                Some(Ok(ids)) if ids.contains(&held.id) => {
+                    if held.pinned && capabilities.can_pin {
+                        let package = Package::named(&held.adapter_id, &held.id, &held.name, "");
+                        plan.pin.push((package, held.mode));
+                    }
+                    plan.present.push(held.clone());
+                    continue;
+                }
+                _ => {}
+            }
+
+            let package = if !capabilities.can_search {
+                Package::named(&held.adapter_id, &held.id, &held.name, "")
+            } else {
+                match adapter.search(&held.name, None, held.mode).await {
+                    Ok(found) => match found.into_iter().find(|p| p.id == held.id) {
+                        Some(package) => package,
+                        None => {
+                            plan.missing.push((
+                                held.clone(),
+                                format!("{manager_name} has no package called {}", held.id),
+                            ));
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        plan.missing.push((
+                            held.clone(),
+                            format!("{manager_name} could not be searched: {e}"),
+                        ));
+                        continue;
+                    }
+                }
+            };
+
+            if held.pinned {
+                match capabilities.can_pin {
+                    true => plan.pin.push((package.clone(), held.mode)),
+                    false => plan.unkept.push((
+                        held.clone(),
+                        format!("was pinned, which {manager_name} cannot do"),
+                    )),
+                }
+            }
+            // Installing goes into whichever profile is active, so one kept
+            // in another is said rather than quietly moved.
+            if let Some(profile) = &held.profile {
+                if !active_profiles.contains_key(&held.adapter_id) {
+                    let active = adapter.active_profile().await.ok().map(|p| p.id);
+                    active_profiles.insert(held.adapter_id.clone(), active);
+                }
+                match &active_profiles[&held.adapter_id] {
+                    Some(active) if active == profile => {}
+                    Some(active) => plan.unkept.push((
+                        held.clone(),
+                        format!("was in the {profile} profile, and goes into {active} instead"),
+                    )),
+                    None => plan.unkept.push((
+                        held.clone(),
+                        format!("was in the {profile} profile, and goes into the active one"),
+                    )),
+                }
+            }
+            plan.install.push((package, held.mode));
+        }
+
+        plan
+    }
+
+    /// What to pin again once installed in this scope.
+    pub fn pins(&self, mode: PackageMode) -> Vec<Package> {
+        self.pin
+            .iter()
+            .filter(|(package, held_in)| *held_in == mode && !self.is_present(package, mode))
+            .map(|(package, _)| package.clone())
+            .collect()
+    }
+
+    /// What is here already and only wants pinning again, a batch for each
+    /// scope. Nothing is installed first, so nothing waits on an install.
+    pub fn repins(&self) -> Vec<(PackageMode, Vec<Package>)> {
+        [PackageMode::User, PackageMode::System]
+            .into_iter()
+            .map(|mode| {
+                let packages: Vec<Package> = self
+                    .pin
+                    .iter()
+                    .filter(|(package, held_in)| *held_in == mode && self.is_present(package, mode))
+                    .map(|(package, _)| package.clone())
+                    .collect();
+                (mode, packages)
+            })
+            .filter(|(_, packages)| !packages.is_empty())
+            .collect()
+    }
+
+    fn is_present(&self, package: &Package, mode: PackageMode) -> bool {
+        self.present.iter().any(|held| {
+            held.adapter_id == package.adapter_id && held.id == package.id && held.mode == mode
+        })
+    }
+
+    /// What to install, a batch for each scope, since a batch runs in one.
+    pub fn batches(&self) -> Vec<(PackageMode, Vec<Package>)> {
+        [PackageMode::User, PackageMode::System]
+            .into_iter()
+            .map(|mode| {
+                let packages: Vec<Package> = self
+                    .install
+                    .iter()
+                    .filter(|(_, held_in)| *held_in == mode)
+                    .map(|(package, _)| package.clone())
+                    .collect();
+                (mode, packages)
+            })
+            .filter(|(_, packages)| !packages.is_empty())
+            .collect()
+    }
+}
+
+/// Pin again those of `pin` that the install reported going in. Nothing is
+/// asked of the managers when none did.
+pub async fn pin_again(
+    manager: &AdapterManager,
+    pin: &[Package],
+    installed: &[InstallResult],
+    mode: PackageMode,
+) -> Option<adapter::Result<Vec<InstallResult>>> {
+    let went: Vec<Package> = pin
+        .iter()
+        .filter(|package| {
+            installed.iter().any(|r| {
+                r.success && r.adapter_id == package.adapter_id && r.package_id == package.id
+            })
+        })
+        .cloned()
+        .collect();
+    if went.is_empty() {
+        return None;
+    }
+    Some(manager.pin(&went, mode).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::core::{
+        adapter::{Adapter, AdapterInfo, CancelToken, ProgressSender, Result},
+        capabilities::Capabilities,
+        package::{InstallResult, InstalledPackage, Update},
+    };
+
+    /// A manager holding some packages and offering others.
+    struct Stocked {
+        info: AdapterInfo,
+        capabilities: Capabilities,
+        held: Vec<&'static str>,
+        offered: Vec<&'static str>,
+    }
+
+    fn stocked(id: &str, held: Vec<&'static str>, offered: Vec<&'static str>) -> Arc<dyn Adapter> {
+        stocked_with(id, held, offered, false)
+    }
+
+    fn stocked_with(
+        id: &str,
+        held: Vec<&'static str>,
+        offered: Vec<&'static str>,
+        can_pin: bool,
+    ) -> Arc<dyn Adapter> {
+        let capabilities = Capabilities {
+            can_search: true,
+            can_install: true,
+            can_list: true,
+            can_pin,
+            supports_user_packages: true,
+            ..Capabilities::default()
+        };
+        Arc::new(Stocked {
+            info: AdapterInfo {
+                id: id.to_string(),
+                name: id.to_string(),
+                version: "1.0".into(),
+                manifest_version: "1".into(),
+                capabilities,
+                enabled: true,
+                is_builtin: false,
+                plugin_path: None,
+                description: String::new(),
+                icon: None,
+                deprecations: Vec::new(),
+                inherited: Vec::new(),
+            },
+            capabilities,
+            held,
+            offered,
+        })
+    }
+
+    #[async_trait::async_trait]
+    impl Adapter for Stocked {
+        fn info(&self) -> &AdapterInfo {
+            &self.info
+        }
+
+        fn capabilities(&self) -> &Capabilities {
+            &self.capabilities
+        }
+
+        async fn search(
+            &self,
+            query: &str,
+            _: Option<usize>,
+            _: PackageMode,
+        ) -> Result<Vec<Package>> {
+            Ok(self
+                .offered
+                .iter()
+                .filter(|name| name.contains(query))
+                .map(|name| Package::named(&self.info.id, name, name, "2.0"))
+                .collect())
+        }
+
+        async fn install(
+            &self,
+            _: &[Package],
+            _: Option<ProgressSender>,
+            _: &CancelToken,
+            _: PackageMode,
+        ) -> Result<Vec<InstallResult>> {
+            Ok(Vec::new())
+        }
+
+        async fn pin(&self, _: &[Package], _: PackageMode) -> Result<()> {
+            Ok(())
+        }
+
+        async fn remove(
+            &self,
+            _: &[Package],
+            _: Option<ProgressSender>,
+            _: &CancelToken,
+            _: PackageMode,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn update(
+            &self,
+            _: &[Package],
+            _: Option<ProgressSender>,
+            _: &CancelToken,
+            _: PackageMode,
+        ) -> Result<Vec<InstallResult>> {
+            Ok(Vec::new())
+        }
+
+        async fn list_installed(&self, _: PackageMode) -> Result<Vec<InstalledPackage>> {
+            // A manager by this name cannot say what it holds.
+            if self.info.id == "broken" {
+                return Err(crate::core::adapter::AdapterError::Other(
+                    "the database is locked".into(),
+                ));
+            }
+            Ok(self
+                .held
+                .iter()
+                .map(|name| InstalledPackage {
+                    package: Package::named(&self.info.id, name, name, "1.0"),
+                    installed_at: String::new(),
+                    install_size: 0,
+                    install_path: None,
+                    pinned: *name == "jq",
+                    auto_installed: false,
+                    is_healthy: true,
+                    profile: Some("default".into()),
+                })
+                .collect())
+        }
+
+        async fn list_updates(&self, _: PackageMode) -> Result<Vec<Update>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("should build a runtime")
+            .block_on(future)
+    }
+
+    fn held(adapter_id: &str, id: &str, mode: PackageMode) -> Held {
+        Held {
+            adapter_id: adapter_id.into(),
+            id: id.into(),
+            name: id.into(),
+            version: "1.0".into(),
+            mode,
+            pinned: false,
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn an_inventory_keeps_what_each_manager_holds_and_reads_back_the_same() {
+        let mut manager = AdapterManager::new();
+        manager.register(stocked("soar", vec!["jq", "fd"], Vec::new()));
+
+        let (inventory, problems) = block_on(Inventory::gather(&manager));
+        assert!(problems.is_empty());
+        // Only the scope the manager works in is asked about.
+        assert_eq!(inventory.packages.len(), 2);
+        let jq = inventory.packages.iter().find(|h| h.id == "jq").unwrap();
+        assert!(jq.pinned);
+        assert_eq!(jq.profile.as_deref(), Some("default"));
+        assert_eq!(jq.mode, PackageMode::User);
+
+        let text = inventory.to_toml().expect("should write");
+        assert!(text.contains("[[package]]"));
+        let read = Inventory::parse(&text).expect("should read back");
+        assert_eq!(read.packages, inventory.packages);
+    }
+
+    #[test]
+    fn a_newer_inventory_is_refused() {
+        let error = Inventory::parse("version = 2\n").unwrap_err();
+        assert!(error.contains("newer"), "{error}");
+        assert!(
+            Inventory::parse("version = 1\n")
+                .unwrap()
+                .packages
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn importing_plans_what_to_install_and_says_what_cannot_be() {
+        let mut manager = AdapterManager::new();
+        manager.register(stocked("soar", vec!["jq"], vec!["ripgrep", "bat"]));
+
+        let inventory = Inventory {
+            version: FORMAT_VERSION,
+            exported_at: 0,
+            packages: vec![
+                held("soar", "jq", PackageMode::User),
+                held("soar", "ripgrep", PackageMode::User),
+                held("soar", "ripgrep", PackageMode::User),
+                held("soar", "gone", PackageMode::User),
+                held("soar", "bat", PackageMode::System),
+                held("flatpak", "org.gimp.GIMP", PackageMode::User),
+            ],
+        };
+        let plan = block_on(ImportPlan::for_inventory(&inventory, &manager));
+
+        assert_eq!(plan.present.len(), 1);
+        // Written twice, installed once.
+        let installing: Vec<&str> = plan.install.iter().map(|(p, _)| p.id.as_str()).collect();
+        assert_eq!(installing, vec!["ripgrep"]);
+        assert_eq!(plan.batches().len(), 1);
+
+        let why: Vec<(&str, &str)> = plan
+            .missing
+            .iter()
+            .map(|(held, why)| (held.id.as_str(), why.as_str()))
+            .collect();
+        assert_eq!(
+            why,
+            vec![
+                ("gone", "soar has no package called gone"),
+                ("bat", "soar does not work with System packages"),
+                ("org.gimp.GIMP", "no manager called flatpak is here"),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_manager_that_cannot_list_installs_nothing_again() {
+        let mut manager = AdapterManager::new();
+        manager.register(stocked("broken", vec!["jq"], vec!["jq"]));
+
+        let inventory = Inventory {
+            version: FORMAT_VERSION,
+            exported_at: 0,
+            packages: vec![held("broken", "jq", PackageMode::User)],
+        };
+        let plan = block_on(ImportPlan::for_inventory(&inventory, &manager));
+
+        assert!(plan.install.is_empty());
+        assert!(plan.present.is_empty());
+        let why: Vec<&str> = plan.missing.iter().map(|(_, why)| why.as_str()).collect();
+        assert_eq!(
+            why,
+            vec!["broken could not say what is installed: the database is locked"]
+        );
+    }
+
+    #[test]
+    fn what_was_pinned_is_pinned_again_and_what_cannot_be_kept_is_said() {
+        let mut manager = AdapterManager::new();
+        manager.register(stocked_with("soar", vec!["bat"], vec!["jq", "fd"], true));
+        manager.register(stocked("cargo", Vec::new(), vec!["rg"]));
+
+        let pinned = |adapter_id, id| Held {
+            pinned: true,
+            ..held(adapter_id, id, PackageMode::User)
+        };
+        let inventory = Inventory {
+            version: FORMAT_VERSION,
+            exported_at: 0,
+            packages: vec![
+                pinned("soar", "jq"),
+                Held {
+                    profile: Some("work".into()),
+                    ..held("soar", "fd", PackageMode::User)
+                },
+                pinned("cargo", "rg"),
+                pinned("soar", "bat"),
+            ],
+        };
+        let plan = block_on(ImportPlan::for_inventory(&inventory, &manager));
+
+        assert_eq!(plan.install.len(), 3);
+        // Here already, so pinned without waiting on an install.
+        assert_eq!(plan.present.len(), 1);
+        let repins: Vec<(PackageMode, Vec<String>)> = plan
+            .repins()
+            .into_iter()
+            .map(|(mode, packages)| (mode, packages.into_iter().map(|p| p.id).collect()))
+            .collect();
+        assert_eq!(repins, vec![(PackageMode::User, vec!["bat".to_string()])]);
+        let pins: Vec<String> = plan
+            .pins(PackageMode::User)
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(pins, vec!["jq"]);
+        assert!(plan.pins(PackageMode::System).is_empty());
+        let why: Vec<(&str, &str)> = plan
+            .unkept
+            .iter()
+            .map(|(held, why)| (held.id.as_str(), why.as_str()))
+            .collect();
+        assert_eq!(
+            why,
+            vec![
+                (
+                    "fd",
+                    "was in the work profile, and goes into the active one"
+                ),
+                ("rg", "was pinned, which cargo cannot do"),
+            ]
+        );
+
+        let installed = |id: &str, success| InstallResult {
+            package_name: id.into(),
+            adapter_id: "soar".into(),
+            package_id: id.into(),
+            version: "2.0".into(),
+            success,
+            error: None,
+        };
+        let jq = plan.pins(PackageMode::User);
+        let after_failure = block_on(pin_again(
+            &manager,
+            &jq,
+            &[installed("jq", false)],
+            PackageMode::User,
+        ));
+        assert!(after_failure.is_none());
+        let repinned = block_on(pin_again(
+            &manager,
+            &jq,
+            &[installed("jq", true), installed("fd", true)],
+            PackageMode::User,
+        ))
+        .expect("should pin what went in")
+        .expect("should answer");
+        assert_eq!(repinned.len(), 1);
+        assert!(repinned[0].success);
+    }
+}
@@ -1,11 +1,22 @@
 pub mod adapter;
 pub mod adapter_manager;
+pub mod cache;
 pub mod capabilities;
+pub mod changelog;
 pub mod config;
 pub mod desktop;
+pub mod disk_usage;
+pub mod doctor;
+pub mod history;
 pub mod icons;
+pub mod inventory;
+pub mod ownership;
 pub mod package;
 pub mod privilege;
 pub mod profile;
+pub mod queue;
 pub mod registry;
 pub mod repository;
+pub mod shadowing;
+pub mod unattended;
+pub mod update_watch;
@@ -0,0 +1,375 @@
+//! Which package a file belongs to, and which files a package put down.
+//!
+//! A manager that keeps a list of files answers both itself. One that does
+//! not still says where it installed each package and where it links the
+//! commands, which is enough to answer for anything under those. The answer is
+//! narrower, since a file put elsewhere goes unclaimed, so each claim says how
+//! it was reached.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::{
+    adapter::Adapter,
+    adapter_manager::AdapterManager,
+    package::{InstalledPackage, Package},
+    privilege::PackageMode,
+    shadowing::list_package_binaries,
+};
+
+/// How many files are read out of an install directory before giving up, so
+/// a package holding a whole toolchain does not stall the panel.
+const FILES_WALKED: usize = 20_000;
+
+/// How a package came to be named as a file's owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum How {
+    /// Its manager said so.
+    Reported,
+    /// The file is inside where the package was installed.
+    Installed,
+    /// The file is a command the manager linked to the package.
+    Linked,
+}
+
+impl How {
+    pub fn label(&self) -> &'static str {
+        match self {
+            How::Reported => "its manager says so",
+            How::Installed => "inside where it was installed",
+            How::Linked => "a command linked to it",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Owner {
+    pub package: Package,
+    pub mode: PackageMode,
+    pub how: How,
+}
+
+/// Every package any enabled manager claims `path` for.
+///
+/// A manager asked outright usually fails for a file it does not own rather
+/// than answering with nothing, so a failure there is taken as not its file.
+/// A manager that cannot list what it holds is named among the problems.
+pub async fn owners_of(manager: &AdapterManager, path: &Path) -> (Vec<Owner>, Vec<String>) {
+    let mut ids: Vec<String> = manager
+        .enabled_adapters()
+        .iter()
+        .filter(|info| info.capabilities.can_find_owner || info.capabilities.can_list)
+        .map(|info| info.id.clone())
+        .collect();
+    ids.sort();
+
+    let asked = path.to_string_lossy().into_owned();
+    let mut owners = Vec::new();
+    let mut problems = Vec::new();
+    for id in ids {
+        let Some(adapter) = manager.get_adapter(&id) else {
+            continue;
+        };
+        let caps = *adapter.capabilities();
+        let bin = if caps.can_find_owner {
+            None
+        } else {
+            bin_of(adapter.as_ref()).await
+        };
+
+        for mode in [PackageMode::User, PackageMode::System] {
+            if !caps.works_in(mode) {
+                continue;
+            }
+            if caps.can_find_owner {
+                match adapter.owner(&asked, mode).await {
+                    Ok(found) => owners.extend(found.into_iter().map(|package| Owner {
+                        package,
+                        mode,
+                        how: How::Reported,
+                    })),
+                    Err(e) => log::debug!("{id} ({mode}) does not own {asked}: {e}"),
+                }
+                continue;
+            }
+
+            match adapter.list_installed(mode).await {
+                Ok(installed) => owners.extend(installed.into_iter().filter_map(|held| {
+                    let install_path = held.install_path.as_deref()?;
+                    let how = held_under(path, Path::new(install_path), bin.as_deref())?;
+                    Some(Owner {
+                        package: held.package,
+                        mode,
+                        how,
+                    })
+                })),
+                Err(e) => problems.push(format!("{id} ({mode}): {e}")),
+            }
+        }
+    }
+
+    (owners, problems)
+}
+
+/// Every file a package put down, sorted.
+///
+/// Without a list from its manager this is what is under the package's
+/// install directory, with the commands linked to it.
+pub async fn files_of(
+    adapter: &dyn Adapter,
+    held: &InstalledPackage,
+    mode: PackageMode,
+) -> Result<Vec<String>, String> {
+    let mut files = if adapter.capabilities().can_list_files {
+        adapter
+            .files(&held.package.id, mode)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        let Some(install_path) = held.install_path.as_deref() else {
+            return Err(format!(
+                "{} keeps no list of files, and did not say where {} went",
+                adapter.info().name,
+                held.package.name
+            ));
+        };
+        let install_path = Path::new(install_path);
+        let mut files = walk(install_path);
+        if let Some(bin) = bin_of(adapter).await {
+            files.extend(
+                list_package_binaries(install_path, &bin)
+                    .into_iter()
+                    .map(|path| path.to_string_lossy().into_owned()),
+            );
+        }
+        files
+    };
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Where the manager links the commands it installs.
+async fn bin_of(adapter: &dyn Adapter) -> Option<PathBuf> {
+    adapter
+        .paths()
+        .await
+        .ok()
+        .and_then(|paths| paths.get("bin").map(PathBuf::from))
+}
+
+/// Whether `path` belongs to a package installed at `install_path`, either by
+/// being a command linked into `bin` from there or by being under it.
+fn held_under(path: &Path, install_path: &Path, bin: Option<&Path>) -> Option<How> {
+    let install_path = std::fs::canonicalize(install_path).ok()?;
+
+    // A link is judged by where it sits before where it leads, so a command
+    // is claimed as one even though its target is inside the install too.
+    let linked = bin.is_some_and(|bin| {
+        path.symlink_metadata()
+            .is_ok_and(|meta| meta.file_type().is_symlink())
+            && path
+                .parent()
+                .and_then(|dir| std::fs::canonicalize(dir).ok())
+                == std::fs::canonicalize(bin).ok()
+    });
+    let target = std::fs::canonicalize(path).ok()?;
+    if !target.starts_with(&install_path) {
+        return None;
+    }
+    Some(if linked { How::Linked } else { How::Installed })
+}
+
+/// The files under a directory, without following links out of it.
+fn walk(root: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(read) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read.flatten() {
+            if files.len() >= FILES_WALKED {
+                return files;
+            }
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => pending.push(path),
+                Ok(_) => files.push(path.to_string_lossy().into_owned()),
+                Err(_) => continue,
+            }
+        }
+    }
+    files
+}
+
+/// One line of a file tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// The whole path, which is what a directory is folded by.
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub dir: bool,
+}
+
+/// Lay a list of files out as a tree, leaving out what is under a folded
+/// directory.
+///
+/// A manager listing its files often lists the directories as well; one that
+/// has anything under it is shown as a directory rather than twice.
+pub fn tree(files: &[String], folded: &HashSet<String>) -> Vec<Row> {
+    let mut sorted: Vec<&str> = files
+        .iter()
+        .map(|file| file.trim_end_matches('/'))
+        .filter(|file| !file.is_empty())
+        .collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let rooted = sorted.first().is_some_and(|file| file.starts_with('/'));
+    let joined = |parts: &[String]| {
+        let joined = parts.join("/");
+        if rooted { format!("/{joined}") } else { joined }
+    };
+
+    let mut rows = Vec::new();
+    let mut open: Vec<String> = Vec::new();
+    for (idx, file) in sorted.iter().enumerate() {
+        let is_parent = sorted
+            .get(idx + 1)
+            .is_some_and(|next| next.strip_prefix(file).is_some_and(|r| r.starts_with('/')));
+        if is_parent {
+            continue;
+        }
+
+        let parts: Vec<&str> = file.split('/').filter(|p| !p.is_empty()).collect();
+        let Some((name, dirs)) = parts.split_last() else {
+            continue;
+        };
+        let shared = open
+            .iter()
+            .zip(dirs)
+            .take_while(|(open, dir)| open.as_str() == **dir)
+            .count();
+        open.truncate(shared);
+        for dir in &dirs[shared..] {
+            let depth = open.len();
+            open.push(dir.to_string());
+            rows.push(Row {
+                path: joined(&open),
+                name: dir.to_string(),
+                depth,
+                dir: true,
+            });
+        }
+        rows.push(Row {
+            path: file.to_string(),
+            name: name.to_string(),
+            depth: dirs.len(),
+            dir: false,
+        });
+    }
+
+    // Rows come out depth first, so everything under a folded directory
+    // follows it without a break.
+    let mut hidden: Option<String> = None;
+    rows.retain(|row| {
+        if let Some(ref under) = hidden {
+            if row
+                .path
+                .strip_prefix(under.as_str())
+                .is_some_and(|r| r.starts_with('/'))
+            {
+                return false;
+            }
+            hidden = None;
+        }
+        if row.dir && folded.contains(&row.path) {
+            hidden = Some(row.path.clone());
+        }
+        true
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn files_are_laid_out_under_their_directories() {
+        let files = paths(&[
+            "/usr/share/man/man1/fd.1",
+            "/usr/bin/",
+            "/usr/bin/fd",
+            "/usr/bin/fdfind",
+        ]);
+        let shown: Vec<String> = tree(&files, &HashSet::new())
+            .iter()
+            .map(|row| {
+                let slash = if row.dir { "/" } else { "" };
+                format!("{}{}{slash}", "  ".repeat(row.depth), row.name)
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                "usr/",
+                "  bin/",
+                "    fd",
+                "    fdfind",
+                "  share/",
+                "    man/",
+                "      man1/",
+                "        fd.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn a_folded_directory_hides_only_what_is_under_it() {
+        let files = paths(&["/usr/bin/fd", "/usr/bin2/x", "/usr/lib/libfd.so"]);
+        let folded = HashSet::from(["/usr/bin".to_string()]);
+        let shown: Vec<String> = tree(&files, &folded).into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            shown,
+            [
+                "/usr",
+                "/usr/bin",
+                "/usr/bin2",
+                "/usr/bin2/x",
+                "/usr/lib",
+                "/usr/lib/libfd.so"
+            ]
+        );
+    }
+
+    #[test]
+    fn a_file_is_claimed_by_where_it_sits_and_where_it_leads() {
+        let root = std::env::temp_dir().join(format!("aeris-owner-{}", std::process::id()));
+        let install = root.join("packages/fd");
+        let bin = root.join("bin");
+        std::fs::create_dir_all(&install).unwrap();
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(install.join("fd"), "").unwrap();
+        std::os::unix::fs::symlink(install.join("fd"), bin.join("fd")).unwrap();
+        std::fs::write(bin.join("other"), "").unwrap();
+
+        let inside = held_under(&install.join("fd"), &install, Some(&bin));
+        let linked = held_under(&bin.join("fd"), &install, Some(&bin));
+        let elsewhere = held_under(&bin.join("other"), &install, Some(&bin));
+        let walked = walk(&install);
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(inside, Some(How::Installed));
+        assert_eq!(linked, Some(How::Linked));
+        assert_eq!(elsewhere, None);
+        assert_eq!(walked.len(), 1);
+    }
+}
@@ -21,6 +21,28 @@ pub struct Package {
     pub icon_url: Option<String>,
 }
 
+impl Package {
+    /// A package known only by what a record of it kept, with nothing else
+    /// said about it.
+    pub fn named(adapter_id: &str, id: &str, name: &str, version: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            adapter_id: adapter_id.to_string(),
+            description: None,
+            size: None,
+            homepage: None,
+            license: None,
+            installed: false,
+            update_available: false,
+            category: None,
+            tags: Vec::new(),
+            icon_url: None,
+        }
+    }
+}
+
 /// What a manager knows about one package beyond what a listing carries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageDetail {
@@ -35,6 +57,14 @@ pub struct PackageDetail {
     /// these without knowing what any of them mean.
     #[serde(default)]
     pub extra: Vec<(String, String)>,
+    /// What the package needs, as the manager names it. A version here is
+    /// whatever the manager said, which may be a constraint rather than one
+    /// version.
+    #[serde(default)]
+    pub depends: Vec<Package>,
+    /// What installed packages need this one.
+    #[serde(default)]
+    pub required_by: Vec<Package>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,12 +97,82 @@ pub struct Update {
     pub new_version: String,
     pub download_size: Option<u64>,
     pub is_security: bool,
+    /// How bad the worst thing it fixes is, where the manager or an advisory
+    /// says.
+    #[serde(default)]
+    pub severity: Option<Severity>,
+    /// The advisories this update fixes.
+    #[serde(default)]
+    pub advisories: Vec<Advisory>,
     pub changelog_url: Option<String>,
 }
 
+/// How serious a security fix is, in the grades advisories commonly use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Read a grade the way a manager might spell it. Anything unrecognised is
+    /// nothing rather than a guess.
+    pub fn parse(said: &str) -> Option<Self> {
+        match said.trim().to_ascii_lowercase().as_str() {
+            "low" | "negligible" | "minor" => Some(Severity::Low),
+            "medium" | "moderate" => Some(Severity::Medium),
+            "high" | "important" => Some(Severity::High),
+            "critical" | "urgent" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+            Severity::Critical => "Critical",
+        }
+    }
+}
+
+/// A published security advisory, and the version of a package that fixes it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    pub package_id: PackageId,
+    /// The first version without the problem. Nothing when the advisory does
+    /// not say, in which case any update to the package is taken as the fix.
+    pub fixed_version: Option<String>,
+    pub severity: Option<Severity>,
+}
+
+/// One entry of a package's release notes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseNote {
+    /// The version the entry is about, for notes a manager splits by version.
+    pub version: Option<String>,
+    pub date: Option<String>,
+    pub text: String,
+}
+
+/// Put security fixes first, the worst of them at the top, keeping the order
+/// within each grade as it was.
+pub fn security_first(updates: &mut [Update]) {
+    updates.sort_by_key(|u| (!u.is_security, std::cmp::Reverse(u.severity)));
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallResult {
     pub package_name: String,
+    /// The manager that answered, since two can each have a package by the
+    /// same id.
+    #[serde(default)]
+    pub adapter_id: AdapterId,
     pub package_id: String,
     pub version: String,
     pub success: bool,
@@ -109,11 +209,12 @@ pub fn failure_among(results: &[InstallResult]) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{InstallResult, failure_among};
+    use super::{InstallResult, Package, Severity, Update, failure_among, security_first};
 
     fn result(name: &str, success: bool, error: Option<&str>) -> InstallResult {
         InstallResult {
             package_name: name.to_string(),
+            adapter_id: "soar".into(),
             package_id: name.to_string(),
             version: "1.0".into(),
             success,
@@ -148,4 +249,30 @@ mod tests {
             Some("2 of 3 failed. firefox-bin: no")
         );
     }
+
+    #[test]
+    fn security_fixes_come_first_and_the_worst_leads() {
+        let update = |id: &str, is_security: bool, severity: Option<Severity>| Update {
+            package: Package::named("demo", id, id, "1.0"),
+            current_version: "1.0".into(),
+            new_version: "1.1".into(),
+            download_size: None,
+            is_security,
+            severity,
+            advisories: Vec::new(),
+            changelog_url: None,
+        };
+        let mut updates = vec![
+            update("routine", false, None),
+            update("graded", true, Some(Severity::Low)),
+            update("ungraded", true, None),
+            update("worst", true, Some(Severity::Critical)),
+        ];
+        security_first(&mut updates);
+
+        let order: Vec<&str> = updates.iter().map(|u| u.package.id.as_str()).collect();
+        assert_eq!(order, ["worst", "graded", "ungraded", "routine"]);
+        assert_eq!(Severity::parse(" Moderate"), Some(Severity::Medium));
+        assert_eq!(Severity::parse("spicy"), None);
+    }
 }
@@ -8,8 +8,10 @@
 
 use std::process::{Command, ExitStatus};
 
+use serde::{Deserialize, Serialize};
+
 /// Whose packages an operation is about.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum PackageMode {
     #[default]
     User,
@@ -0,0 +1,595 @@
+//! Changes waiting their turn with the package managers, and what became of
+//! them.
+//!
+//! Every change aeris makes goes through here rather than straight to a
+//! manager. Starting a second install while one runs lines it up instead of
+//! racing it, and what was waiting is still waiting after a restart. A manager
+//! that cannot run twice at once is handed one job at a time, the rest a few,
+//! and different managers never hold each other up.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    adapter::{AdapterId, manager_progress_key, progress_key},
+    package::Package,
+    privilege::PackageMode,
+};
+
+pub type JobId = u64;
+
+/// How many jobs a manager is handed at once when nothing says it has to take
+/// them one at a time.
+pub const JOBS_PER_MANAGER: usize = 2;
+
+/// How many jobs that have ended are kept. Enough to see what just happened
+/// and retry it, bounded so the file does not grow for ever.
+const ENDED_KEPT: usize = 50;
+
+/// What a job that was running when aeris last closed is left saying.
+const INTERRUPTED: &str = "Aeris closed before this finished";
+
+/// What a job does, with everything needed to do it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JobKind {
+    Install(Vec<Package>),
+    /// Install what an inventory lists, then pin again those of `pin` that
+    /// went in, as they were pinned where it was taken.
+    Import {
+        packages: Vec<Package>,
+        pin: Vec<Package>,
+    },
+    Remove(Vec<Package>),
+    Update(Vec<Package>),
+    /// Put each package back at the version it carries.
+    Revert(Vec<Package>),
+    Pin(Vec<Package>),
+    Unpin(Vec<Package>),
+    /// Have each package's manager put it right.
+    Repair(Vec<Package>),
+    /// Everything one manager holds, for a manager that cannot be pointed at
+    /// a single package.
+    UpdateAll {
+        adapter_id: AdapterId,
+        adapter_name: String,
+    },
+    /// Read the repositories of each of these managers again.
+    Sync(Vec<AdapterId>),
+    /// Clear a manager's cache, outright when `all` is set.
+    Clean {
+        adapter_id: AdapterId,
+        adapter_name: String,
+        all: bool,
+    },
+    /// Bring a manager in line with its declarative file, removing what the
+    /// file leaves out when `prune` is set.
+    Apply {
+        adapter_id: AdapterId,
+        prune: bool,
+    },
+}
+
+impl JobKind {
+    /// Every manager the job needs, each once, in the order it names them.
+    pub fn managers(&self) -> Vec<&str> {
+        let mut named: Vec<&str> = match self {
+            JobKind::Install(packages)
+            | JobKind::Import { packages, .. }
+            | JobKind::Remove(packages)
+            | JobKind::Update(packages)
+            | JobKind::Revert(packages)
+            | JobKind::Pin(packages)
+            | JobKind::Unpin(packages)
+            | JobKind::Repair(packages) => packages.iter().map(|p| p.adapter_id.as_str()).collect(),
+            JobKind::UpdateAll { adapter_id, .. }
+            | JobKind::Apply { adapter_id, .. }
+            | JobKind::Clean { adapter_id, .. } => vec![adapter_id.as_str()],
+            JobKind::Sync(adapters) => adapters.iter().map(String::as_str).collect(),
+        };
+
+        let mut seen = HashSet::new();
+        named.retain(|id| seen.insert(*id));
+        named
+    }
+
+    /// The keys progress about this job is reported under, so a card can say
+    /// its package is waiting before the manager has said anything.
+    pub fn progress_keys(&self) -> Vec<String> {
+        match self {
+            JobKind::Install(packages)
+            | JobKind::Import { packages, .. }
+            | JobKind::Remove(packages)
+            | JobKind::Update(packages)
+            | JobKind::Revert(packages)
+            | JobKind::Pin(packages)
+            | JobKind::Unpin(packages)
+            | JobKind::Repair(packages) => packages
+                .iter()
+                .map(|p| progress_key(&p.adapter_id, &p.id))
+                .collect(),
+            JobKind::UpdateAll { adapter_id, .. } => vec![manager_progress_key(adapter_id)],
+            JobKind::Sync(_) | JobKind::Apply { .. } | JobKind::Clean { .. } => Vec::new(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            JobKind::Install(packages) => format!("Install {}", named(packages)),
+            JobKind::Import { packages, .. } => format!("Put back {}", named(packages)),
+            JobKind::Remove(packages) => format!("Remove {}", named(packages)),
+            JobKind::Update(packages) => format!("Update {}", named(packages)),
+            JobKind::Revert(packages) => match packages.as_slice() {
+                [one] => format!("Put {} back to {}", one.name, one.version),
+                many => format!("Put {} packages back", many.len()),
+            },
+            JobKind::Pin(packages) => format!("Pin {}", named(packages)),
+            JobKind::Unpin(packages) => format!("Unpin {}", named(packages)),
+            JobKind::Repair(packages) => format!("Repair {}", named(packages)),
+            JobKind::UpdateAll { adapter_name, .. } => {
+                format!("Update everything in {adapter_name}")
+            }
+            JobKind::Sync(_) => "Sync repositories".into(),
+            JobKind::Clean {
+                adapter_name,
+                all: false,
+                ..
+            } => format!("Clean the {adapter_name} cache"),
+            JobKind::Clean {
+                adapter_name,
+                all: true,
+                ..
+            } => format!("Clear the whole {adapter_name} cache"),
+            JobKind::Apply { prune: false, .. } => "Apply the manifest".into(),
+            JobKind::Apply { prune: true, .. } => {
+                "Apply the manifest and remove what it leaves out".into()
+            }
+        }
+    }
+}
+
+/// The package by name when there is one, otherwise how many there are.
+pub fn named(packages: &[Package]) -> String {
+    match packages {
+        [one] => one.name.clone(),
+        many => format!("{} packages", many.len()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Finished,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobState {
+    pub fn label(&self) -> String {
+        match self {
+            JobState::Queued => "Queued".into(),
+            JobState::Running => "Running".into(),
+            JobState::Finished => "Finished".into(),
+            JobState::Failed(why) => format!("Failed: {why}"),
+            JobState::Cancelled => "Cancelled".into(),
+        }
+    }
+
+    pub fn has_ended(&self) -> bool {
+        matches!(
+            self,
+            JobState::Finished | JobState::Failed(_) | JobState::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub mode: PackageMode,
+    pub state: JobState,
+}
+
+impl Job {
+    /// Whether running it again makes sense. Only work that did not go
+    /// through is offered, since repeating what worked would do nothing.
+    pub fn can_retry(&self) -> bool {
+        matches!(self.state, JobState::Failed(_) | JobState::Cancelled)
+    }
+}
+
+/// Every job aeris knows of, in the order they were lined up.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct JobQueue {
+    next_id: JobId,
+    jobs: Vec<Job>,
+    /// Where the queue is kept. Nothing means it lives only as long as this
+    /// value does.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl JobQueue {
+    pub fn default_path() -> PathBuf {
+        crate::xdg::data_home().join("aeris").join("jobs.json")
+    }
+
+    /// The queue as it was left, kept at `path` from here on.
+    ///
+    /// Nothing on disk, or nothing readable, starts an empty queue rather than
+    /// refusing to start. A job that was running when aeris closed cannot be
+    /// picked up where it stopped, so it is left failed, to be retried by hand
+    /// rather than run again unasked.
+    pub fn open(path: PathBuf) -> Self {
+        let mut queue: JobQueue = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                log::warn!("could not read the job queue at {}: {e}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+
+        for job in &mut queue.jobs {
+            if job.state == JobState::Running {
+                job.state = JobState::Failed(INTERRUPTED.into());
+            }
+        }
+
+        queue.path = Some(path);
+        queue
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    /// How many jobs are under way and how many are waiting.
+    pub fn counts(&self) -> (usize, usize) {
+        let running = self
+            .jobs
+            .iter()
+            .filter(|job| job.state == JobState::Running)
+            .count();
+        let queued = self
+            .jobs
+            .iter()
+            .filter(|job| job.state == JobState::Queued)
+            .count();
+        (running, queued)
+    }
+
+    pub fn push(&mut self, kind: JobKind, mode: PackageMode) -> JobId {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.jobs.push(Job {
+            id,
+            kind,
+            mode,
+            state: JobState::Queued,
+        });
+        self.save();
+        id
+    }
+
+    /// The jobs that can start now, marked as running.
+    ///
+    /// Jobs are taken in order. One waits while any manager it needs already
+    /// has as many running as `room` allows it, and every later job for that
+    /// manager waits behind it, so the order of the queue decides what goes
+    /// first rather than whatever happens to fit.
+    pub fn take_ready(&mut self, room: impl Fn(&str) -> usize) -> Vec<Job> {
+        let mut busy: HashMap<String, usize> = HashMap::new();
+        for job in self.jobs.iter().filter(|j| j.state == JobState::Running) {
+            for manager in job.kind.managers() {
+                *busy.entry(manager.to_string()).or_default() += 1;
+            }
+        }
+
+        let mut held: HashSet<String> = HashSet::new();
+        let mut ready = Vec::new();
+        for job in &mut self.jobs {
+            if job.state != JobState::Queued {
+                continue;
+            }
+
+            let managers: Vec<String> = job
+                .kind
+                .managers()
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            let fits = managers.iter().all(|manager| {
+                !held.contains(manager)
+                    && busy.get(manager).copied().unwrap_or(0) < room(manager).max(1)
+            });
+
+            if fits {
+                for manager in managers {
+                    *busy.entry(manager).or_default() += 1;
+                }
+                job.state = JobState::Running;
+                ready.push(job.clone());
+            } else {
+                held.extend(managers);
+            }
+        }
+
+        if !ready.is_empty() {
+            self.save();
+        }
+        ready
+    }
+
+    /// Record how a job ended.
+    pub fn end(&mut self, id: JobId, state: JobState) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            job.state = state;
+        }
+        self.trim();
+        self.save();
+    }
+
+    /// Move a waiting job one place earlier or later among the others
+    /// waiting. Anything else stays where it is.
+    pub fn shift(&mut self, id: JobId, earlier: bool) -> bool {
+        let waiting: Vec<usize> = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.state == JobState::Queued)
+            .map(|(at, _)| at)
+            .collect();
+        let Some(place) = waiting.iter().position(|at| self.jobs[*at].id == id) else {
+            return false;
+        };
+
+        let other = if earlier {
+            place.checked_sub(1)
+        } else {
+            Some(place + 1).filter(|next| *next < waiting.len())
+        };
+        let Some(other) = other else {
+            return false;
+        };
+
+        self.jobs.swap(waiting[place], waiting[other]);
+        self.save();
+        true
+    }
+
+    /// Forget a job that has not started. One under way has to be cancelled
+    /// instead, since dropping it here would not stop the manager.
+    pub fn discard(&mut self, id: JobId) -> bool {
+        let before = self.jobs.len();
+        self.jobs
+            .retain(|job| job.id != id || job.state != JobState::Queued);
+        if self.jobs.len() == before {
+            return false;
+        }
+
+        self.save();
+        true
+    }
+
+    /// Line a job that did not go through up again, at the back, with the
+    /// same packages and scope it had.
+    pub fn retry(&mut self, id: JobId) -> bool {
+        let Some(at) = self
+            .jobs
+            .iter()
+            .position(|job| job.id == id && job.can_retry())
+        else {
+            return false;
+        };
+
+        let mut job = self.jobs.remove(at);
+        job.state = JobState::Queued;
+        self.jobs.push(job);
+        self.save();
+        true
+    }
+
+    /// Forget every job that has ended.
+    pub fn clear_ended(&mut self) {
+        self.jobs.retain(|job| !job.state.has_ended());
+        self.save();
+    }
+
+    /// Drop the oldest ended jobs beyond what is kept.
+    fn trim(&mut self) {
+        let ended = self.jobs.iter().filter(|job| job.state.has_ended()).count();
+        let mut over = ended.saturating_sub(ENDED_KEPT);
+        self.jobs.retain(|job| {
+            if over > 0 && job.state.has_ended() {
+                over -= 1;
+                return false;
+            }
+            true
+        });
+    }
+
+    /// Write the queue where it is kept, through a file alongside so a crash
+    /// part way leaves the last whole copy rather than half of a new one.
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let partial = path.with_extension("part");
+        let wrote = serde_json::to_string_pretty(self)
+            .map_err(std::io::Error::other)
+            .and_then(|text| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&partial, text)?;
+                std::fs::rename(&partial, path)
+            });
+
+        if let Err(e) = wrote {
+            // The work itself goes on; only surviving a restart is lost.
+            log::warn!("could not keep the job queue at {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(adapter_id: &str, name: &str) -> Package {
+        Package {
+            id: name.into(),
+            name: name.into(),
+            version: "1.0".into(),
+            adapter_id: adapter_id.into(),
+            description: None,
+            size: None,
+            homepage: None,
+            license: None,
+            installed: false,
+            update_available: false,
+            category: None,
+            tags: Vec::new(),
+            icon_url: None,
+        }
+    }
+
+    fn install(adapter_id: &str, name: &str) -> JobKind {
+        JobKind::Install(vec![package(adapter_id, name)])
+    }
+
+    fn ids(jobs: &[Job]) -> Vec<JobId> {
+        jobs.iter().map(|job| job.id).collect()
+    }
+
+    #[test]
+    fn a_manager_is_handed_no_more_jobs_than_it_has_room_for() {
+        let mut queue = JobQueue::default();
+        let first = queue.push(install("apt", "fd"), PackageMode::System);
+        let second = queue.push(install("apt", "jq"), PackageMode::System);
+        let elsewhere = queue.push(install("soar", "bat"), PackageMode::User);
+
+        let room = |manager: &str| if manager == "apt" { 1 } else { 2 };
+
+        // The second apt job waits; soar is not held up by apt being busy.
+        assert_eq!(ids(&queue.take_ready(room)), vec![first, elsewhere]);
+        assert!(queue.take_ready(room).is_empty());
+
+        queue.end(first, JobState::Finished);
+        assert_eq!(ids(&queue.take_ready(room)), vec![second]);
+    }
+
+    #[test]
+    fn a_job_held_back_holds_back_later_ones_for_the_same_manager() {
+        let mut queue = JobQueue::default();
+        let running = queue.push(install("apt", "fd"), PackageMode::System);
+        let both = queue.push(
+            JobKind::Install(vec![package("apt", "jq"), package("soar", "bat")]),
+            PackageMode::System,
+        );
+        let behind = queue.push(install("soar", "rg"), PackageMode::System);
+
+        let room = |_: &str| 1;
+        assert_eq!(ids(&queue.take_ready(room)), vec![running]);
+
+        // Soar is free, but the job before this one is waiting for it too.
+        queue.end(running, JobState::Finished);
+        assert_eq!(ids(&queue.take_ready(room)), vec![both]);
+
+        queue.end(both, JobState::Finished);
+        assert_eq!(ids(&queue.take_ready(room)), vec![behind]);
+    }
+
+    #[test]
+    fn reordering_decides_which_waiting_job_goes_first() {
+        let mut queue = JobQueue::default();
+        let running = queue.push(install("apt", "fd"), PackageMode::System);
+        let a = queue.push(install("apt", "jq"), PackageMode::System);
+        let b = queue.push(install("apt", "rg"), PackageMode::System);
+        queue.take_ready(|_| 1);
+
+        // A running job is not among the waiting, so there is nothing
+        // before the first waiting job to move past.
+        assert!(!queue.shift(a, true));
+        assert!(!queue.shift(running, false));
+        assert!(queue.shift(b, true));
+
+        queue.end(running, JobState::Finished);
+        assert_eq!(ids(&queue.take_ready(|_| 1)), vec![b]);
+        assert_eq!(queue.get(a).map(|job| &job.state), Some(&JobState::Queued));
+    }
+
+    #[test]
+    fn only_waiting_jobs_are_dropped_and_only_failed_ones_retried() {
+        let mut queue = JobQueue::default();
+        let started = queue.push(install("apt", "fd"), PackageMode::System);
+        let waiting = queue.push(install("apt", "jq"), PackageMode::System);
+        queue.take_ready(|_| 1);
+
+        assert!(!queue.discard(started));
+        assert!(!queue.retry(started));
+        assert!(queue.discard(waiting));
+        assert!(queue.get(waiting).is_none());
+
+        queue.end(started, JobState::Failed("no network".into()));
+        let later = queue.push(install("apt", "rg"), PackageMode::System);
+        assert!(queue.retry(started));
+
+        // Back of the line, with what it had the first time.
+        let job = queue.jobs().last().expect("should be queued again");
+        assert_eq!(job.id, started);
+        assert_eq!(job.state, JobState::Queued);
+        assert_eq!(job.mode, PackageMode::System);
+        assert_eq!(ids(&queue.take_ready(|_| 1)), vec![later]);
+    }
+
+    #[test]
+    fn what_was_waiting_survives_a_restart_and_what_was_running_is_failed() {
+        let dir = std::env::temp_dir().join(format!("aeris-jobs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("jobs.json");
+
+        let mut queue = JobQueue::open(path.clone());
+        let started = queue.push(install("apt", "fd"), PackageMode::System);
+        let waiting = queue.push(install("apt", "jq"), PackageMode::User);
+        queue.take_ready(|_| 1);
+        drop(queue);
+
+        let mut reopened = JobQueue::open(path);
+        assert_eq!(
+            reopened.get(started).map(|job| &job.state),
+            Some(&JobState::Failed(INTERRUPTED.into()))
+        );
+        assert_eq!(ids(&reopened.take_ready(|_| 1)), vec![waiting]);
+        assert_eq!(
+            reopened.get(waiting).map(|job| job.mode),
+            Some(PackageMode::User)
+        );
+
+        // Numbering carries on, so a new job is not mistaken for an old one.
+        assert!(reopened.push(install("apt", "rg"), PackageMode::User) > waiting);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn only_so_many_ended_jobs_are_kept() {
+        let mut queue = JobQueue::default();
+        for at in 0..ENDED_KEPT + 5 {
+            let id = queue.push(install("apt", &format!("p{at}")), PackageMode::User);
+            queue.take_ready(|_| 1);
+            queue.end(id, JobState::Finished);
+        }
+
+        assert_eq!(queue.jobs().len(), ENDED_KEPT);
+        assert_eq!(queue.jobs()[0].id, 6);
+    }
+}
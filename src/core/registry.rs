@@ -124,6 +124,22 @@ fn cache_path(source: &str) -> PathBuf {
         .join(format!("registry-{named}.toml"))
 }
 
+/// Every registry copy kept, whichever source it was read from.
+pub fn cached_paths() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(crate::xdg::cache_home().join("aeris")) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("registry-") && name.ends_with(".toml"))
+        })
+        .collect()
+}
+
 /// The registry as it was last read, and when that was.
 ///
 /// A listing from yesterday beats an empty page, so long as it is clear it
@@ -264,19 +280,27 @@ pub fn merge(listings: Vec<Vec<PluginEntry>>) -> Vec<PluginEntry> {
 /// A manifest is read before it is kept, so a broken one is refused here
 /// rather than at the next start. The manifest URL may point at the network
 /// or at a local file.
-pub fn download_plugin(entry: &PluginEntry) -> Result<PathBuf, String> {
-    if entry.manifest_url.is_empty() {
-        return Err(format!("{} offers no manifest", entry.id));
-    }
-
-    let manifest = read_bytes(&entry.manifest_url)?;
-    if !entry.manifest_checksum_sha256.is_empty() {
-        verify_checksum(&manifest, &entry.manifest_checksum_sha256)?;
-    }
-
-    let text = String::from_utf8(manifest)
-        .map_err(|_| format!("{} sent a manifest that is not text", entry.id))?;
-    let parsed = crate::adapters::command::manifest::parse(&text)
+///
+/// A manifest that `extends` another is read with it, from disk where it is
+/// there and otherwise from what `offered` lists. A parent fetched that way
+/// is kept too, since the child cannot be read again without it.
+pub fn download_plugin(entry: &PluginEntry, offered: &[PluginEntry]) -> Result<PathBuf, String> {
+    let text = fetch_manifest(entry)?;
+
+    let fetched: std::cell::RefCell<Vec<(String, String)>> = Default::default();
+    let parent = |id: &str| -> Result<String, String> {
+        if let Some(text) = crate::adapters::command::manifest::find(id) {
+            return Ok(text);
+        }
+        let listed = offered
+            .iter()
+            .find(|listed| listed.id == id)
+            .ok_or_else(|| format!("{id} is neither installed nor offered"))?;
+        let text = fetch_manifest(listed)?;
+        fetched.borrow_mut().push((id.to_string(), text.clone()));
+        Ok(text)
+    };
+    let parsed = crate::adapters::command::manifest::parse_extending(&text, &parent)
         .map_err(|e| format!("{}: {e}", entry.id))?;
 
     if parsed.id != entry.id {
@@ -292,10 +316,30 @@ pub fn download_plugin(entry: &PluginEntry) -> Result<PathBuf, String> {
             .map_err(|e| format!("Failed to create adapter dir: {e}"))?;
     }
     std::fs::write(&path, &text).map_err(|e| format!("Failed to write manifest: {e}"))?;
+    for (id, text) in fetched.into_inner() {
+        std::fs::write(adapter_path(&id), text)
+            .map_err(|e| format!("Failed to write the manifest for {id}: {e}"))?;
+    }
 
     Ok(path)
 }
 
+/// The text of the manifest an entry offers, checked against its checksum
+/// where the registry gives one.
+fn fetch_manifest(entry: &PluginEntry) -> Result<String, String> {
+    if entry.manifest_url.is_empty() {
+        return Err(format!("{} offers no manifest", entry.id));
+    }
+
+    let manifest = read_bytes(&entry.manifest_url)?;
+    if !entry.manifest_checksum_sha256.is_empty() {
+        verify_checksum(&manifest, &entry.manifest_checksum_sha256)?;
+    }
+
+    String::from_utf8(manifest)
+        .map_err(|_| format!("{} sent a manifest that is not text", entry.id))
+}
+
 pub fn remove_plugin(id: &str) -> Result<(), String> {
     let path = adapter_path(id);
     if !path.exists() {
@@ -521,6 +565,41 @@ command = "true"
         let _ = std::fs::remove_file(adapter_path(id));
     }
 
+    #[test]
+    fn a_manifest_is_fetched_with_the_one_it_extends() {
+        let dir = std::env::temp_dir().join(format!("aeris-extends-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (parent, child) = ("extends-parent-test", "extends-child-test");
+        let parent_url = dir.join("parent.toml");
+        let child_url = dir.join("child.toml");
+        std::fs::write(&parent_url, manifest_saying(parent, "1.0")).unwrap();
+        std::fs::write(
+            &child_url,
+            format!(
+                "schema_version = 2\nid = \"{child}\"\nname = \"Child\"\nextends = \"{parent}\"\n"
+            ),
+        )
+        .unwrap();
+
+        let listed = |id: &str, url: &std::path::Path| PluginEntry {
+            manifest_url: url.display().to_string(),
+            ..offered(id, "1.0")
+        };
+        let listing = vec![listed(parent, &parent_url), listed(child, &child_url)];
+        let downloaded = download_plugin(&listing[1], &listing);
+        let kept = (adapter_path(parent).exists(), adapter_path(child).exists());
+        let alone = download_plugin(&listing[1], &[]);
+
+        let _ = std::fs::remove_file(adapter_path(parent));
+        let _ = std::fs::remove_file(adapter_path(child));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        downloaded.expect("should fetch both");
+        assert_eq!(kept, (true, true));
+        // Once the parent is on disk it is read from there.
+        alone.expect("should find the parent installed");
+    }
+
     #[test]
     fn http_is_remote_and_a_path_is_not() {
         assert!(is_remote("https://example.com/registry.toml"));
@@ -0,0 +1,358 @@
+//! Commands that more than one manager puts on `PATH`, and whose copy runs.
+//!
+//! A shell runs the first match it finds walking `PATH`, so with soar, cargo
+//! and the distribution each installing `fd` only one of them is ever run and
+//! which one depends on the order of a variable nobody looks at. This works
+//! out that order the way the shell would, and says which copy wins and which
+//! are only taking up room.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use super::{adapter_manager::AdapterManager, package::InstalledPackage, privilege::PackageMode};
+
+/// Whether a copy of a command is the one that runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standing {
+    /// Found first on `PATH`, so this is what typing the name runs.
+    Wins,
+    /// On `PATH`, behind one found earlier.
+    Shadowed,
+    /// Linked where its manager puts commands, which is not on `PATH` at all.
+    OffPath,
+}
+
+/// The package a copy came from, for a copy a manager aeris drives put there.
+#[derive(Debug, Clone)]
+pub struct Owner {
+    pub package: InstalledPackage,
+    pub mode: PackageMode,
+}
+
+/// One copy of a command.
+#[derive(Debug, Clone)]
+pub struct Binary {
+    pub path: PathBuf,
+    pub standing: Standing,
+    /// Nothing for a copy no manager here owns up to, which is usually the
+    /// distribution's own.
+    pub owner: Option<Owner>,
+}
+
+/// A command name more than one copy answers to.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub command: String,
+    /// The copies on `PATH` in the order the shell tries them, then those off
+    /// it.
+    pub binaries: Vec<Binary>,
+}
+
+impl Conflict {
+    /// Copies a manager here put in that never run, each package once.
+    pub fn losers(&self) -> Vec<&Owner> {
+        let mut seen = HashSet::new();
+        self.binaries
+            .iter()
+            .filter(|b| b.standing != Standing::Wins)
+            .filter_map(|b| b.owner.as_ref())
+            .filter(|owner| seen.insert((owner.package.unique_key(), owner.mode)))
+            .collect()
+    }
+}
+
+/// Find user-runnable binaries for a package: symlinks in `bin_path` whose
+/// canonicalized target lives inside `install_path`. Avoids launching
+/// internal helpers/libraries inside the install dir that happen to be
+/// marked executable but aren't meant to be invoked directly.
+pub fn list_package_binaries(install_path: &Path, bin_path: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let canonical_install = match std::fs::canonicalize(install_path) {
+        Ok(p) => p,
+        Err(_) => return out,
+    };
+    let read = match std::fs::read_dir(bin_path) {
+        Ok(r) => r,
+        Err(_) => return out,
+    };
+    for entry in read.flatten() {
+        let path = entry.path();
+        let symlink_meta = match path.symlink_metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        if !symlink_meta.file_type().is_symlink() {
+            continue;
+        }
+        let canonical_target = match std::fs::canonicalize(&path) {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        if canonical_target.starts_with(&canonical_install) {
+            out.push(path);
+        }
+    }
+    out.sort();
+    out
+}
+
+/// The directories the shell searches, in its order.
+pub fn search_path() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+/// Every command the enabled managers link, resolved against `PATH`.
+///
+/// Only a manager that says where it links commands, and a package that says
+/// where it was installed, can be placed. A manager that cannot list what it
+/// holds is named among the problems rather than costing the rest.
+pub async fn gather(manager: &AdapterManager) -> (Vec<Conflict>, Vec<String>) {
+    let mut ids: Vec<String> = manager
+        .enabled_adapters()
+        .iter()
+        .filter(|info| info.capabilities.can_list)
+        .map(|info| info.id.clone())
+        .collect();
+    ids.sort();
+
+    let mut linked = Vec::new();
+    let mut problems = Vec::new();
+    for id in ids {
+        let Some(adapter) = manager.get_adapter(&id) else {
+            continue;
+        };
+        let Some(bin) = adapter
+            .paths()
+            .await
+            .ok()
+            .and_then(|paths| paths.get("bin").map(PathBuf::from))
+        else {
+            continue;
+        };
+        for mode in [PackageMode::User, PackageMode::System] {
+            if !adapter.capabilities().works_in(mode) {
+                continue;
+            }
+            let installed = match adapter.list_installed(mode).await {
+                Ok(installed) => installed,
+                Err(e) => {
+                    problems.push(format!("{id} ({mode}): {e}"));
+                    continue;
+                }
+            };
+            for package in installed {
+                let Some(install_path) = package.install_path.clone() else {
+                    continue;
+                };
+                for path in list_package_binaries(Path::new(&install_path), &bin) {
+                    let owner = Owner {
+                        package: package.clone(),
+                        mode,
+                    };
+                    linked.push((path, owner));
+                }
+            }
+        }
+    }
+
+    (resolve(&search_path(), linked), problems)
+}
+
+/// Which copy of each linked command runs, given the directories searched.
+///
+/// Every directory on `search` is looked in for each name, so a copy nobody
+/// here owns still counts when it stands in front. A name only one copy
+/// answers to is no conflict and is left out.
+pub fn resolve(search: &[PathBuf], linked: Vec<(PathBuf, Owner)>) -> Vec<Conflict> {
+    // The same directory can be on PATH twice, or under two names where /bin
+    // is a link to /usr/bin; the shell would find the same file again.
+    let mut seen = HashSet::new();
+    let search: Vec<PathBuf> = search
+        .iter()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .filter(|dir| seen.insert(canonical(dir)))
+        .cloned()
+        .collect();
+
+    let mut by_command: BTreeMap<String, Vec<(PathBuf, Owner)>> = BTreeMap::new();
+    for (path, owner) in linked {
+        let Some(command) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        by_command
+            .entry(command.to_string())
+            .or_default()
+            .push((canonical_parent(&path), owner));
+    }
+
+    let mut conflicts = Vec::new();
+    for (command, copies) in by_command {
+        let mut owned: HashMap<PathBuf, Vec<Owner>> = HashMap::new();
+        for (dir, owner) in copies {
+            owned.entry(dir).or_default().push(owner);
+        }
+
+        let mut binaries = Vec::new();
+        for dir in &search {
+            let path = dir.join(&command);
+            if !is_executable(&path) {
+                continue;
+            }
+            let standing = if binaries.is_empty() {
+                Standing::Wins
+            } else {
+                Standing::Shadowed
+            };
+            match owned.remove(&canonical(dir)) {
+                Some(owners) => binaries.extend(owners.into_iter().map(|owner| Binary {
+                    path: path.clone(),
+                    standing,
+                    owner: Some(owner),
+                })),
+                None => binaries.push(Binary {
+                    path,
+                    standing,
+                    owner: None,
+                }),
+            }
+        }
+
+        let mut off_path: Vec<Binary> = owned
+            .into_iter()
+            .flat_map(|(dir, owners)| {
+                let path = dir.join(&command);
+                owners.into_iter().map(move |owner| Binary {
+                    path: path.clone(),
+                    standing: Standing::OffPath,
+                    owner: Some(owner),
+                })
+            })
+            .collect();
+        off_path.sort_by(|a, b| a.path.cmp(&b.path));
+        binaries.extend(off_path);
+
+        if binaries.len() > 1 {
+            conflicts.push(Conflict { command, binaries });
+        }
+    }
+    conflicts
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The directory a link sits in, without following the link itself.
+fn canonical_parent(path: &Path) -> PathBuf {
+    path.parent().map(canonical).unwrap_or_default()
+}
+
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::package::Package;
+
+    fn owner(adapter: &str, name: &str) -> Owner {
+        Owner {
+            package: InstalledPackage {
+                package: Package::named(adapter, name, name, "1.0"),
+                installed_at: String::new(),
+                install_size: 0,
+                install_path: None,
+                pinned: false,
+                auto_installed: false,
+                is_healthy: true,
+                profile: None,
+            },
+            mode: PackageMode::User,
+        }
+    }
+
+    fn command(dir: &Path, name: &str) -> PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn the_first_copy_on_path_wins_and_the_rest_are_shadowed() {
+        let root = std::env::temp_dir().join(format!("aeris-shadow-{}", std::process::id()));
+        let (soar, cargo, distro, hidden) = (
+            root.join("soar"),
+            root.join("cargo"),
+            root.join("usr"),
+            root.join("hidden"),
+        );
+        let linked = vec![
+            (command(&cargo, "fd"), owner("cargo", "fd-find")),
+            (command(&soar, "fd"), owner("soar", "fd")),
+            (command(&hidden, "fd"), owner("pipx", "fd")),
+            (command(&soar, "jq"), owner("soar", "jq")),
+        ];
+        command(&distro, "fd");
+        command(&distro, "jq");
+        // Not executable, so the shell walks past it.
+        std::fs::write(cargo.join("jq"), "").unwrap();
+
+        let search = vec![soar.clone(), distro.clone(), cargo.clone(), soar.clone()];
+        let conflicts = resolve(&search, linked);
+        std::fs::remove_dir_all(&root).ok();
+
+        let names: Vec<&str> = conflicts.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(names, ["fd", "jq"]);
+
+        let fd = &conflicts[0];
+        let standings: Vec<(Option<&str>, Standing)> = fd
+            .binaries
+            .iter()
+            .map(|b| {
+                let by = b
+                    .owner
+                    .as_ref()
+                    .map(|o| o.package.package.adapter_id.as_str());
+                (by, b.standing)
+            })
+            .collect();
+        assert_eq!(
+            standings,
+            [
+                (Some("soar"), Standing::Wins),
+                (None, Standing::Shadowed),
+                (Some("cargo"), Standing::Shadowed),
+                (Some("pipx"), Standing::OffPath),
+            ]
+        );
+        let losers: Vec<&str> = fd
+            .losers()
+            .iter()
+            .map(|o| o.package.package.adapter_id.as_str())
+            .collect();
+        assert_eq!(losers, ["cargo", "pipx"]);
+
+        // The distribution's copy is nobody's to remove, and soar's one wins.
+        assert!(conflicts[1].losers().is_empty());
+    }
+
+    #[test]
+    fn a_command_only_one_copy_answers_to_is_no_conflict() {
+        let root = std::env::temp_dir().join(format!("aeris-alone-{}", std::process::id()));
+        let soar = root.join("soar");
+        let linked = vec![(command(&soar, "rg"), owner("soar", "ripgrep"))];
+
+        let conflicts = resolve(std::slice::from_ref(&soar), linked);
+        std::fs::remove_dir_all(&root).ok();
+
+        assert!(conflicts.is_empty());
+    }
+}
@@ -0,0 +1,715 @@
+//! Updates a manager takes on its own, on a schedule of its own.
+//!
+//! Not every manager is trusted the same way. Applying whatever soar offers
+//! for one user overnight costs little if it goes wrong; a system manager is
+//! better left to a person, who only wants to hear that something is waiting.
+//! So each manager can be given a policy in its settings:
+//!
+//! ```toml
+//! [adapters.soar]
+//! update_policy = "apply"      # never, notify, download-only, apply
+//! update_schedule = "03:00"    # a time of day on the local clock, or an interval
+//! update_cooldown = "2d"       # how long a version is on offer before it is taken
+//! ```
+//!
+//! Every run, whatever became of it, is appended to a log the Updates view
+//! reads back, and what it changed goes into the history like any other
+//! change.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    adapter::{CancelToken, package_key},
+    adapter_manager::AdapterManager,
+    history::{self, Action, Outcome},
+    package::{Update, failure_among},
+    privilege::PackageMode,
+};
+use crate::config::AerisConfig;
+
+pub const POLICY_KEY: &str = "update_policy";
+pub const SCHEDULE_KEY: &str = "update_schedule";
+pub const COOLDOWN_KEY: &str = "update_cooldown";
+/// Which packages the policy is for, where a manager works for both.
+pub const MODE_KEY: &str = "update_mode";
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// When a policy names no schedule: the middle of the night.
+const DEFAULT_SCHEDULE: Schedule = Schedule::DailyAt(3 * 60 * 60);
+
+/// The shortest interval a schedule may name. Anything shorter would have
+/// the manager doing nothing but looking.
+const SHORTEST_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// What a manager does about its updates with nobody watching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    Never,
+    /// Say what is on offer and touch nothing.
+    Notify,
+    /// Fetch what is on offer so applying it later is quick, and say so.
+    DownloadOnly,
+    Apply,
+}
+
+impl Policy {
+    pub fn parse(written: &str) -> Option<Self> {
+        match written.trim() {
+            "never" => Some(Policy::Never),
+            "notify" => Some(Policy::Notify),
+            "download-only" | "download" => Some(Policy::DownloadOnly),
+            "apply" => Some(Policy::Apply),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Policy::Never => "Never",
+            Policy::Notify => "Notify",
+            Policy::DownloadOnly => "Download only",
+            Policy::Apply => "Apply",
+        }
+    }
+}
+
+/// When a policy is carried out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Once a day at this many seconds past midnight, on the local clock,
+    /// since `03:00` is written by someone who means their own night.
+    DailyAt(u64),
+    Every(Duration),
+}
+
+impl Schedule {
+    /// A time of day written `HH:MM`, or an interval the way the other
+    /// settings write one.
+    pub fn parse(written: &str) -> Option<Self> {
+        let written = written.trim();
+        if let Some((hours, minutes)) = written.split_once(':') {
+            let hours: u64 = hours.parse().ok()?;
+            let minutes: u64 = minutes.parse().ok()?;
+            return (hours < 24 && minutes < 60)
+                .then_some(Schedule::DailyAt(hours * 60 * 60 + minutes * 60));
+        }
+        crate::config::parse_interval(written)
+            .map(|every| Schedule::Every(every.max(SHORTEST_INTERVAL)))
+    }
+
+    /// Whether a run is owed, given when the last one was.
+    pub fn is_due(&self, last: u64, now: u64) -> bool {
+        self.is_due_where(last, now, utc_offset)
+    }
+
+    /// Whether a run is owed where the clock is `offset` seconds ahead of
+    /// UTC at a given moment.
+    fn is_due_where(&self, last: u64, now: u64, offset: impl Fn(u64) -> i64) -> bool {
+        match *self {
+            Schedule::Every(every) => now >= last + every.as_secs(),
+            Schedule::DailyAt(into) => {
+                let ahead = offset(now);
+                let local = now.saturating_add_signed(ahead);
+                let today = local - local % DAY + into;
+                let latest = if local >= today { today } else { today - DAY };
+                last < latest.saturating_add_signed(-ahead)
+            }
+        }
+    }
+}
+
+/// How far the local clock is ahead of UTC at a moment, as the system's time
+/// zone has it. Asked at each moment rather than once, since summer time
+/// moves it; a moment the zone cannot place is taken as UTC.
+fn utc_offset(at: u64) -> i64 {
+    let Ok(at) = libc::time_t::try_from(at) else {
+        return 0;
+    };
+    // SAFETY: `tm` is plain data, so all zeroes is a valid one, and
+    // localtime_r writes only to the one handed to it, which outlives the
+    // call.
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    let filled = unsafe { libc::localtime_r(&at, &mut local) };
+    if filled.is_null() {
+        0
+    } else {
+        local.tm_gmtoff as i64
+    }
+}
+
+/// A manager's policy, read from its settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub policy: Policy,
+    pub schedule: Schedule,
+    /// How long a version has to have been on offer before it is taken, so a
+    /// release pulled within a day of going out is never installed here.
+    pub cooldown: Option<Duration>,
+    pub mode: Option<PackageMode>,
+}
+
+impl Plan {
+    /// The plan a manager's settings describe, or nothing when they name no
+    /// policy. A value that cannot be read is logged and left at its default
+    /// rather than costing the whole plan.
+    pub fn from_settings(adapter_id: &str, settings: &HashMap<String, String>) -> Option<Self> {
+        let written = settings.get(POLICY_KEY)?;
+        let Some(policy) = Policy::parse(written) else {
+            log::warn!("{adapter_id}: `{written}` is not an update policy");
+            return None;
+        };
+
+        let schedule = match settings.get(SCHEDULE_KEY) {
+            Some(written) => Schedule::parse(written).unwrap_or_else(|| {
+                log::warn!("{adapter_id}: `{written}` is not a schedule");
+                DEFAULT_SCHEDULE
+            }),
+            None => DEFAULT_SCHEDULE,
+        };
+        let cooldown = settings
+            .get(COOLDOWN_KEY)
+            .and_then(|written| crate::config::parse_interval(written.trim()));
+        let mode = match settings.get(MODE_KEY).map(|m| m.trim()) {
+            Some("user") => Some(PackageMode::User),
+            Some("system") => Some(PackageMode::System),
+            _ => None,
+        };
+
+        Some(Self {
+            policy,
+            schedule,
+            cooldown,
+            mode,
+        })
+    }
+}
+
+/// Every manager given a policy, `never` included, by adapter id.
+pub fn plans(config: &AerisConfig) -> Vec<(String, Plan)> {
+    let mut plans: Vec<(String, Plan)> = config
+        .adapters
+        .iter()
+        .filter_map(|(id, settings)| Some((id.clone(), Plan::from_settings(id, settings)?)))
+        .collect();
+    plans.sort_by(|a, b| a.0.cmp(&b.0));
+    plans
+}
+
+/// What has to outlive one run: when each manager last ran, and when each
+/// version on offer was first seen, for the cooldown.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    #[serde(default)]
+    last_run: HashMap<String, u64>,
+    /// By adapter id, then by [`offered_key`].
+    #[serde(default)]
+    first_seen: HashMap<String, HashMap<String, u64>>,
+}
+
+impl State {
+    fn path() -> PathBuf {
+        crate::xdg::data_home()
+            .join("aeris")
+            .join("unattended.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+fn offered_key(update: &Update) -> String {
+    format!(
+        "{}@{}",
+        package_key(&update.package.adapter_id, &update.package.id),
+        update.new_version
+    )
+}
+
+/// Split what is on offer into what has waited out the cooldown and what is
+/// still waiting, noting when each version was first seen. A version no
+/// longer on offer is forgotten, so one offered again starts over.
+pub fn cooled(
+    updates: Vec<Update>,
+    cooldown: Option<Duration>,
+    first_seen: &mut HashMap<String, u64>,
+    now: u64,
+) -> (Vec<Update>, Vec<Update>) {
+    let seen: HashMap<String, u64> = updates
+        .iter()
+        .map(|update| {
+            let key = offered_key(update);
+            let at = first_seen.get(&key).copied().unwrap_or(now);
+            (key, at)
+        })
+        .collect();
+    *first_seen = seen;
+
+    let Some(cooldown) = cooldown else {
+        return (updates, Vec::new());
+    };
+    updates
+        .into_iter()
+        .partition(|update| now >= first_seen[&offered_key(update)] + cooldown.as_secs())
+}
+
+/// One run of a policy, as the log keeps it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub at: u64,
+    pub adapter_id: String,
+    pub policy: Policy,
+    pub mode: PackageMode,
+    pub outcome: Outcome,
+    /// What happened, in a sentence.
+    pub said: String,
+    /// The packages it was about.
+    #[serde(default)]
+    pub packages: Vec<String>,
+}
+
+impl Run {
+    /// Whether a person ought to hear about this run: something on offer
+    /// they asked to be told of, or something that went wrong.
+    pub fn worth_telling(&self) -> bool {
+        match self.outcome {
+            Outcome::Succeeded => self.policy != Policy::Apply && !self.packages.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+/// The file runs are appended to, one JSON object a line.
+pub fn log_path() -> PathBuf {
+    crate::xdg::data_home()
+        .join("aeris")
+        .join("unattended.jsonl")
+}
+
+fn append(runs: &[Run]) -> Result<(), String> {
+    let mut text = String::new();
+    for run in runs {
+        text.push_str(&serde_json::to_string(run).map_err(|e| e.to_string())?);
+        text.push('\n');
+    }
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Every run the log holds, newest first. A line that cannot be read is
+/// skipped.
+pub fn read_log() -> Vec<Run> {
+    let Ok(text) = std::fs::read_to_string(log_path()) else {
+        return Vec::new();
+    };
+    let mut runs: Vec<Run> = text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    runs.reverse();
+    runs
+}
+
+/// The lock this process holds while it is the one looking after updates.
+static IN_CHARGE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Whether this process is the one to look after updates: to carry out the
+/// policies and to announce what is on offer.
+///
+/// The window and `aeris --background` can both be running, and each would
+/// otherwise apply the same plan and raise the same notification. Whichever
+/// asks first takes a lock on `unattended.lock` and keeps it until it quits;
+/// the other asks again each time round, so it takes over once the first has
+/// gone.
+pub fn in_charge() -> bool {
+    let mut held = IN_CHARGE.lock().unwrap_or_else(|e| e.into_inner());
+    if held.is_some() {
+        return true;
+    }
+
+    let path = crate::xdg::data_home()
+        .join("aeris")
+        .join("unattended.lock");
+    if let Some(dir) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        log::warn!("Could not make {}: {e}", dir.display());
+        return false;
+    }
+    let file = match File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+    {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!("Could not open {}: {e}", path.display());
+            return false;
+        }
+    };
+    match file.try_lock() {
+        Ok(()) => {
+            *held = Some(file);
+            true
+        }
+        Err(std::fs::TryLockError::WouldBlock) => false,
+        Err(std::fs::TryLockError::Error(e)) => {
+            log::warn!("Could not lock {}: {e}", path.display());
+            false
+        }
+    }
+}
+
+/// Carry out every plan that is due, logging each run.
+///
+/// A plan seen for the first time starts its clock rather than running at
+/// once, so setting a policy at noon does not apply updates there and then.
+///
+/// When each run started is kept before any is carried out, so one that
+/// takes long, or never finishes, is not started again by the next tick.
+pub async fn run_due(manager: &AdapterManager, plans: &[(String, Plan)], now: u64) -> Vec<Run> {
+    let mut state = State::load();
+    let mut due = Vec::new();
+    for (adapter_id, plan) in plans {
+        if plan.policy == Policy::Never || !manager.is_enabled(adapter_id) {
+            continue;
+        }
+        let Some(&last) = state.last_run.get(adapter_id) else {
+            state.last_run.insert(adapter_id.clone(), now);
+            continue;
+        };
+        if !plan.schedule.is_due(last, now) {
+            continue;
+        }
+
+        state.last_run.insert(adapter_id.clone(), now);
+        due.push((adapter_id, plan));
+    }
+    if let Err(e) = state.save() {
+        log::warn!("Could not keep when updates were last looked at: {e}");
+    }
+
+    let mut runs = Vec::new();
+    for (adapter_id, plan) in due {
+        let first_seen = state.first_seen.entry(adapter_id.clone()).or_default();
+        runs.push(carry_out(manager, adapter_id, plan, first_seen, now).await);
+    }
+
+    if let Err(e) = state.save() {
+        log::warn!("Could not keep when updates were last looked at: {e}");
+    }
+    if let Err(e) = append(&runs) {
+        log::warn!("Could not log the unattended updates: {e}");
+    }
+    runs
+}
+
+/// Carry out one manager's plan now.
+async fn carry_out(
+    manager: &AdapterManager,
+    adapter_id: &str,
+    plan: &Plan,
+    first_seen: &mut HashMap<String, u64>,
+    now: u64,
+) -> Run {
+    let mut run = Run {
+        at: now,
+        adapter_id: adapter_id.to_string(),
+        policy: plan.policy,
+        mode: plan.mode.unwrap_or(PackageMode::User),
+        outcome: Outcome::Succeeded,
+        said: String::new(),
+        packages: Vec::new(),
+    };
+    let failed = |mut run: Run, why: String| {
+        run.said = why.clone();
+        run.outcome = Outcome::Failed(why);
+        run
+    };
+
+    let Some(adapter) = manager.get_adapter(adapter_id) else {
+        return failed(run, "is not loaded".into());
+    };
+    let caps = *adapter.capabilities();
+    run.mode = plan.mode.unwrap_or(if caps.works_in(PackageMode::User) {
+        PackageMode::User
+    } else {
+        PackageMode::System
+    });
+    let mode = run.mode;
+    if !caps.works_in(mode) {
+        return failed(run, format!("does not work with {mode} packages"));
+    }
+
+    // Managers that cannot list their updates can only be updated wholesale,
+    // which there is no holding back a cooldown on.
+    if !caps.can_list_updates {
+        return match plan.policy {
+            Policy::Apply if caps.can_update_all && plan.cooldown.is_none() => {
+                let ended = adapter
+                    .update_all(None, &CancelToken::default(), mode)
+                    .await;
+                let outcome = match ended {
+                    Ok(()) => Outcome::Succeeded,
+                    Err(e) => Outcome::Failed(e.to_string()),
+                };
+                history::record(&[history::Entry::ended(
+                    Action::UpdateAll,
+                    adapter_id,
+                    mode,
+                    outcome.clone(),
+                )]);
+                run.said = match &outcome {
+                    Outcome::Failed(why) => why.clone(),
+                    _ => "Updated everything".into(),
+                };
+                run.outcome = outcome;
+                run
+            }
+            Policy::Apply if caps.can_update_all => failed(
+                run,
+                "cannot say what it offers, so there is no waiting out a cooldown".into(),
+            ),
+            _ => failed(run, "cannot say what updates it has".into()),
+        };
+    }
+
+    let offered = match adapter.list_updates(mode).await {
+        Ok(offered) => offered,
+        Err(e) => return failed(run, e.to_string()),
+    };
+    let (ready, cooling) = cooled(offered, plan.cooldown, first_seen, now);
+    let waiting = match cooling.len() {
+        0 => String::new(),
+        n => format!("; {n} still cooling down"),
+    };
+    run.packages = ready.iter().map(|u| u.package.name.clone()).collect();
+
+    if ready.is_empty() {
+        run.said = format!("Nothing ready{waiting}");
+        return run;
+    }
+    let packages: Vec<_> = ready.iter().map(|u| u.package.clone()).collect();
+    let on_offer = |n: usize| match n {
+        1 => "1 update".to_string(),
+        n => format!("{n} updates"),
+    };
+
+    match plan.policy {
+        Policy::Never => run,
+        Policy::DownloadOnly if caps.can_download => {
+            match adapter
+                .download(&packages, None, &CancelToken::default(), mode)
+                .await
+            {
+                Ok(()) => {
+                    run.said = format!("Fetched {}{waiting}", on_offer(packages.len()));
+                    run
+                }
+                Err(e) => failed(run, e.to_string()),
+            }
+        }
+        Policy::Notify | Policy::DownloadOnly => {
+            run.said = format!("{} on offer{waiting}", on_offer(packages.len()));
+            if plan.policy == Policy::DownloadOnly {
+                run.said.push_str(", which it cannot fetch ahead");
+            }
+            run
+        }
+        Policy::Apply if !caps.can_update_one => {
+            failed(run, "cannot update one package at a time".into())
+        }
+        Policy::Apply => {
+            let (results, held_back) = match manager
+                .update(&packages, None, &CancelToken::default(), mode)
+                .await
+            {
+                Ok(updated) => (updated.results, updated.held_back),
+                Err(e) => return failed(run, e.to_string()),
+            };
+            let waiting = match held_back.len() {
+                0 => waiting,
+                n => format!("{waiting}; {n} held back, as pinned"),
+            };
+
+            let entries: Vec<history::Entry> = ready
+                .iter()
+                .filter_map(|update| {
+                    let result = results.iter().find(|r| r.package_id == update.package.id)?;
+                    let (outcome, after) = match (result.success, &result.error) {
+                        (true, _) => (Outcome::Succeeded, update.new_version.clone()),
+                        (false, why) => (
+                            Outcome::Failed(why.clone().unwrap_or_default()),
+                            update.current_version.clone(),
+                        ),
+                    };
+                    Some(
+                        history::Entry::ended(Action::Update, adapter_id, mode, outcome)
+                            .of(&update.package.id, &update.package.name)
+                            .moved(Some(update.current_version.clone()), Some(after)),
+                    )
+                })
+                .collect();
+            history::record(&entries);
+
+            let went = results.iter().filter(|r| r.success).count();
+            match failure_among(&results) {
+                Some(why) if went == 0 => failed(run, why),
+                Some(why) => {
+                    run.said = format!("Updated {went} of {}: {why}", results.len());
+                    run.outcome = Outcome::Failed(why);
+                    run
+                }
+                None => {
+                    run.said = format!("Updated {}{waiting}", on_offer(went));
+                    run
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::package::Package;
+
+    fn update(id: &str, to: &str) -> Update {
+        Update {
+            package: Package::named("soar", id, id, "1.0"),
+            current_version: "1.0".into(),
+            new_version: to.into(),
+            download_size: None,
+            is_security: false,
+            severity: None,
+            advisories: Vec::new(),
+            changelog_url: None,
+        }
+    }
+
+    #[test]
+    fn a_plan_is_read_from_a_manager_s_settings() {
+        let settings = HashMap::from([
+            (POLICY_KEY.to_string(), "download-only".to_string()),
+            (SCHEDULE_KEY.to_string(), "02:30".to_string()),
+            (COOLDOWN_KEY.to_string(), "2d".to_string()),
+            (MODE_KEY.to_string(), "system".to_string()),
+        ]);
+        let plan = Plan::from_settings("apt", &settings).expect("should read");
+        assert_eq!(plan.policy, Policy::DownloadOnly);
+        assert_eq!(plan.schedule, Schedule::DailyAt(2 * 3600 + 30 * 60));
+        assert_eq!(plan.cooldown, Some(Duration::from_secs(2 * DAY)));
+        assert_eq!(plan.mode, Some(PackageMode::System));
+
+        let sloppy = HashMap::from([
+            (POLICY_KEY.to_string(), "apply".to_string()),
+            (SCHEDULE_KEY.to_string(), "25:00".to_string()),
+        ]);
+        let plan = Plan::from_settings("soar", &sloppy).expect("should read");
+        assert_eq!(plan.schedule, DEFAULT_SCHEDULE);
+        assert_eq!(plan.cooldown, None);
+
+        assert!(Plan::from_settings("soar", &HashMap::new()).is_none());
+        assert_eq!(
+            Schedule::parse("1m"),
+            Some(Schedule::Every(SHORTEST_INTERVAL))
+        );
+    }
+
+    #[test]
+    fn a_daily_run_is_owed_once_its_time_has_passed_since_the_last() {
+        let at_three = Schedule::DailyAt(3 * 3600);
+        let monday = 20 * DAY;
+
+        let due = |last, now| at_three.is_due_where(last, now, |_| 0);
+
+        // Ran on Monday at 03:00; nothing more is owed until Tuesday's.
+        assert!(!due(monday + 3 * 3600, monday + 23 * 3600));
+        assert!(due(monday + 3 * 3600, monday + DAY + 3 * 3600));
+        // Asleep through Tuesday night, it catches up on waking.
+        assert!(due(monday + 3 * 3600, monday + 2 * DAY + 12 * 3600));
+        // Set up Monday at noon; the first run is that night, not at once.
+        assert!(!due(monday + 12 * 3600, monday + 20 * 3600));
+
+        let hourly = Schedule::Every(Duration::from_secs(3600));
+        assert!(!hourly.is_due(monday, monday + 1800));
+        assert!(hourly.is_due(monday, monday + 3600));
+    }
+
+    #[test]
+    fn a_time_of_day_is_read_on_the_local_clock() {
+        let at_three = Schedule::DailyAt(3 * 3600);
+        let monday = 20 * DAY;
+        let utc = |_| 0;
+        let berlin_summer = |_| 2 * 3600;
+        let new_york = |_| -5 * 3600;
+
+        // 03:00 in Berlin is 01:00 UTC, and in New York 08:00 UTC.
+        assert!(!at_three.is_due_where(monday, monday + 3600 - 1, berlin_summer));
+        assert!(at_three.is_due_where(monday, monday + 3600, berlin_summer));
+        assert!(!at_three.is_due_where(monday, monday + 8 * 3600 - 1, new_york));
+        assert!(at_three.is_due_where(monday, monday + 8 * 3600, new_york));
+
+        // Ran at New York's 03:00; the next is owed a day later, not at
+        // UTC's 03:00 in between.
+        let ran = monday + 8 * 3600;
+        assert!(!at_three.is_due_where(ran, ran + 20 * 3600, new_york));
+        assert!(at_three.is_due_where(ran, ran + DAY, new_york));
+        assert!(at_three.is_due_where(monday, monday + 3 * 3600, utc));
+    }
+
+    #[test]
+    fn a_version_is_only_taken_once_it_has_waited_out_the_cooldown() {
+        let mut seen = HashMap::new();
+        let cooldown = Some(Duration::from_secs(DAY));
+
+        let (ready, cooling) = cooled(vec![update("fd", "2.0")], cooldown, &mut seen, 100);
+        assert!(ready.is_empty());
+        assert_eq!(cooling.len(), 1);
+
+        // A day on, fd has waited long enough; rg only just turned up.
+        let (ready, cooling) = cooled(
+            vec![update("fd", "2.0"), update("rg", "14.0")],
+            cooldown,
+            &mut seen,
+            100 + DAY,
+        );
+        assert_eq!(ready[0].package.id, "fd");
+        assert_eq!(cooling[0].package.id, "rg");
+
+        // A newer fd starts over.
+        let (ready, _) = cooled(vec![update("fd", "2.1")], cooldown, &mut seen, 100 + DAY);
+        assert!(ready.is_empty());
+        assert_eq!(seen.len(), 1);
+    }
+}
@@ -0,0 +1,228 @@
+//! Looking for updates while nobody is watching, and saying when there are
+//! new ones.
+//!
+//! The Updates view only knows what was on offer when it was last opened.
+//! This asks every enabled manager on an interval and raises a desktop
+//! notification when something turns up that was not on offer the last time,
+//! so the same updates are not announced over and over while they wait.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{
+    adapter::package_key, adapter_manager::AdapterManager, package::Update, privilege::PackageMode,
+};
+
+/// The action a notification offers, named the way the notification service
+/// reports it back when it is clicked.
+const OPEN_ACTION: &str = "default";
+
+/// Every update the enabled managers offer in this mode. A manager that fails
+/// to answer is left out rather than costing the rest.
+pub async fn look(manager: &AdapterManager, mode: PackageMode) -> Vec<Update> {
+    let mut ids: Vec<String> = manager
+        .enabled_adapters()
+        .iter()
+        .filter(|info| info.capabilities.can_list_updates && info.capabilities.works_in(mode))
+        .map(|info| info.id.clone())
+        .collect();
+    ids.sort();
+
+    let mut offered = Vec::new();
+    for id in ids {
+        let Some(adapter) = manager.get_adapter(&id) else {
+            continue;
+        };
+        match adapter.list_updates(mode).await {
+            Ok(updates) => offered.extend(updates),
+            Err(e) => log::warn!("{id} could not list its updates: {e}"),
+        }
+    }
+    offered
+}
+
+/// What is on offer in this mode, each update once for the version it
+/// offers, so a newer version of something already announced counts as new.
+/// The mode is part of each, since a manager that works in both can offer
+/// the same package to the user and to the system as two separate updates.
+pub fn offered(mode: PackageMode, updates: &[Update]) -> HashSet<String> {
+    updates
+        .iter()
+        .map(|u| {
+            format!(
+                "{mode}:{}@{}",
+                package_key(&u.package.adapter_id, &u.package.id),
+                u.new_version
+            )
+        })
+        .collect()
+}
+
+/// Where what was on offer at the last check is kept. Only held in memory,
+/// everything waiting would be news again after every login.
+pub fn offered_path() -> PathBuf {
+    crate::xdg::data_home().join("aeris").join("offered.json")
+}
+
+/// What was on offer at the last check, whether the window or
+/// `--background` made it. Nothing kept yet is nothing seen, so the first
+/// check there ever is announces what it finds.
+pub fn last_offered(path: &Path) -> HashSet<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn keep_offered(path: &Path, offered: &HashSet<String>) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut sorted: Vec<&String> = offered.iter().collect();
+    sorted.sort();
+    let text = serde_json::to_string(&sorted).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Whether anything on offer now was not on offer before.
+pub fn grew(before: &HashSet<String>, now: &HashSet<String>) -> bool {
+    now.difference(before).next().is_some()
+}
+
+/// The title and body of a notification about these updates: how many in
+/// all, and how many from each manager, most first.
+pub fn summary(updates: &[Update], name_of: impl Fn(&str) -> String) -> (String, String) {
+    let mut by_manager: BTreeMap<String, usize> = BTreeMap::new();
+    for update in updates {
+        *by_manager
+            .entry(name_of(&update.package.adapter_id))
+            .or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = by_manager.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let title = match updates.len() {
+        1 => "1 update available".to_string(),
+        n => format!("{n} updates available"),
+    };
+    let body = counts
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (title, body)
+}
+
+/// Raise a notification through `org.freedesktop.Notifications` and wait for
+/// it to be clicked or dismissed, answering whether it was clicked.
+///
+/// This speaks to the service through `notify-send`, which every desktop that
+/// runs one ships. One too old to offer actions is asked again without, so
+/// the notification still shows even though clicking it does nothing.
+pub fn notify(title: &str, body: &str) -> bool {
+    let raise = |with_action: bool| {
+        let mut command = Command::new("notify-send");
+        command.args([
+            "--app-name",
+            crate::app::APP_NAME,
+            "--icon",
+            crate::app::APP_ID,
+        ]);
+        if with_action {
+            command
+                .arg(format!("--action={OPEN_ACTION}=Show updates"))
+                .arg("--wait");
+        }
+        command.arg(title).arg(body).output()
+    };
+
+    match raise(true) {
+        Ok(out) if out.status.success() => {
+            String::from_utf8_lossy(&out.stdout).trim() == OPEN_ACTION
+        }
+        Ok(_) => {
+            if let Err(e) = raise(false) {
+                log::warn!("Could not raise a notification: {e}");
+            }
+            false
+        }
+        Err(e) => {
+            log::warn!("Could not raise a notification: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::package::Package;
+
+    fn update(adapter: &str, id: &str, to: &str) -> Update {
+        Update {
+            package: Package::named(adapter, id, id, "1.0"),
+            current_version: "1.0".into(),
+            new_version: to.into(),
+            download_size: None,
+            is_security: false,
+            severity: None,
+            advisories: Vec::new(),
+            changelog_url: None,
+        }
+    }
+
+    #[test]
+    fn what_was_on_offer_is_kept_across_a_restart() {
+        let dir = std::env::temp_dir().join(format!("aeris-offered-{}", std::process::id()));
+        let path = dir.join("offered.json");
+        assert!(last_offered(&path).is_empty());
+
+        let offered = super::offered(PackageMode::User, &[update("soar", "jq", "1.8")]);
+        keep_offered(&path, &offered).expect("should keep");
+        let read = last_offered(&path);
+        assert_eq!(read, offered);
+        assert!(!grew(&read, &offered));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn only_something_not_on_offer_before_is_news() {
+        let user = PackageMode::User;
+        let before = offered(
+            user,
+            &[update("soar", "fd", "2.0"), update("cargo", "rg", "14.0")],
+        );
+
+        let fewer = offered(user, &[update("soar", "fd", "2.0")]);
+        let newer = offered(user, &[update("soar", "fd", "2.1")]);
+        let more = offered(
+            user,
+            &[
+                update("soar", "fd", "2.0"),
+                update("cargo", "rg", "14.0"),
+                update("cargo", "bat", "1.0"),
+            ],
+        );
+        let elsewhere = offered(PackageMode::System, &[update("soar", "fd", "2.0")]);
+
+        assert!(!grew(&before, &fewer));
+        assert!(!grew(&before, &before));
+        assert!(grew(&before, &newer));
+        assert!(grew(&before, &more));
+        assert!(grew(&before, &elsewhere));
+    }
+
+    #[test]
+    fn the_summary_counts_each_manager_most_first() {
+        let updates = [
+            update("soar", "fd", "2.0"),
+            update("cargo", "rg", "14.0"),
+            update("cargo", "bat", "1.0"),
+        ];
+        let (title, body) = summary(&updates, |id| id.to_uppercase());
+        assert_eq!(title, "3 updates available");
+        assert_eq!(body, "CARGO: 2\nSOAR: 1");
+    }
+}
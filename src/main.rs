@@ -1,6 +1,8 @@
 mod adapters;
 mod app;
 mod assets;
+mod background;
+mod cli;
 mod components;
 mod config;
 mod core;
@@ -11,7 +13,9 @@ mod views;
 mod xdg;
 
 use app::App;
-use gpui::{AppContext as _, Application, WindowDecorations, WindowOptions, px, size};
+use gpui::{
+    AppContext as _, Application, WindowDecorations, WindowHandle, WindowOptions, px, size,
+};
 
 static TOKIO_RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
 
@@ -26,13 +30,49 @@ where
         .spawn(f)
 }
 
+/// Open the window, the way aeris always has.
+pub fn open_window(cx: &mut gpui::App) -> WindowHandle<App> {
+    // Asked for outright, because a compositor offering no decoration
+    // protocol at all is reported as decorating the window itself. GNOME
+    // offers none, so the window came back with nothing drawn and no way
+    // to move, resize or close it.
+    let options = WindowOptions {
+        app_id: Some(app::APP_ID.into()),
+        window_min_size: Some(size(px(900.0), px(600.0))),
+        window_decorations: Some(WindowDecorations::Client),
+        ..Default::default()
+    };
+
+    cx.open_window(options, |window, cx| {
+        window.set_window_title(app::APP_NAME);
+        cx.new(|cx| App::new(window, cx))
+    })
+    .unwrap()
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Only watching for updates, with a window only once a notification is
+    // clicked.
+    let background = args.iter().any(|arg| arg == "--background");
+    let command = match cli::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("aeris: {e}\nRun `aeris --help` to see what it takes.");
+            std::process::exit(2);
+        }
+    };
+
+    // A command's answer is what it prints, so only trouble is logged over it.
+    let level = if command.is_some() {
+        log::LevelFilter::Warn
+    } else {
+        log::LevelFilter::Info
+    };
     env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
+        .filter_level(level)
         .init();
 
-    log::info!("Starting {}", app::APP_NAME);
-
     // Start a background Tokio runtime for adapters that need it
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -40,34 +80,34 @@ fn main() {
         .expect("Failed to create Tokio runtime");
     TOKIO_RUNTIME.set(tokio_rt).expect("Runtime already set");
 
-    Application::new().with_assets(assets::Assets).run(|cx| {
-        components::text_input::bind_text_input_keys(cx);
-        app::bind_app_keys(cx);
+    if let Some(command) = command {
+        let runtime = TOKIO_RUNTIME.get().expect("Tokio runtime not initialized");
+        std::process::exit(runtime.block_on(cli::run(command)));
+    }
 
-        // Asked for outright, because a compositor offering no decoration
-        // protocol at all is reported as decorating the window itself. GNOME
-        // offers none, so the window came back with nothing drawn and no way
-        // to move, resize or close it.
-        let options = WindowOptions {
-            app_id: Some(app::APP_ID.into()),
-            window_min_size: Some(size(px(900.0), px(600.0))),
-            window_decorations: Some(WindowDecorations::Client),
-            ..Default::default()
-        };
+    log::info!("Starting {}", app::APP_NAME);
 
-        cx.open_window(options, |window, cx| {
-            window.set_window_title(app::APP_NAME);
-            cx.new(|cx| App::new(window, cx))
-        })
-        .unwrap();
+    Application::new()
+        .with_assets(assets::Assets)
+        .run(move |cx| {
+            components::text_input::bind_text_input_keys(cx);
+            app::bind_app_keys(cx);
 
-        // Aeris is its window. Without saying so, closing it leaves the work
-        // behind it running, still asking for frames of a window that has gone.
-        cx.on_window_closed(|cx| {
-            if cx.windows().is_empty() {
-                cx.quit();
+            if !background {
+                open_window(cx);
             }
-        })
-        .detach();
-    });
+            background::watch_for_updates(cx);
+            background::run_unattended(cx);
+
+            // Aeris is its window. Without saying so, closing it leaves the work
+            // behind it running, still asking for frames of a window that has gone.
+            // In the background it was never only its window, so it stays to
+            // keep watching.
+            cx.on_window_closed(move |cx| {
+                if cx.windows().is_empty() && !background {
+                    cx.quit();
+                }
+            })
+            .detach();
+        });
 }
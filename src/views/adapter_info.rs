@@ -1,7 +1,7 @@
 use gpui::*;
 
 use crate::{
-    app::App,
+    app::{App, ProfileChange},
     core::{
         adapter::AdapterInfo, capabilities::Capabilities, privilege::PackageMode,
         registry::PluginEntry,
@@ -70,6 +70,9 @@ impl App {
         for (at, (info, enabled)) in adapters.iter().enumerate() {
             let has_repos = info.capabilities.can_list_repos && *enabled;
             let has_profiles = info.capabilities.has_profiles && *enabled;
+            let caps = &info.capabilities;
+            let has_cache = *enabled
+                && (caps.has_cache_size || caps.can_clean_cache || caps.can_clean_whole_cache);
 
             if at > 0 {
                 content = content.child(div().w_full().h(px(1.0)).bg(border));
@@ -84,6 +87,10 @@ impl App {
             if has_repos {
                 content = content.child(self.render_repos_section(&info.id, theme, cx));
             }
+
+            if has_cache {
+                content = content.child(self.render_cache_section(info, theme, cx));
+            }
         }
 
         if !unusable.is_empty() {
@@ -94,6 +101,9 @@ impl App {
             content = content.child(self.render_unusable_card(info, reason, theme, cx));
         }
 
+        content = content.child(div().w_full().h(px(1.0)).bg(border));
+        content = content.child(self.render_own_caches(theme, cx));
+
         // Available plugins section
         let refresh_plugins = cx.listener(|app, _: &ClickEvent, _window, cx| {
             app.fetch_registry(cx);
@@ -420,10 +430,271 @@ impl App {
             .gap(px(styles::spacing::SM))
             .child(header_row)
             .child(desc)
+            .children(self.render_deprecations(info, theme))
+            .children(self.render_inherited(info, theme))
             .child(caps_view)
             .child(actions)
     }
 
+    /// Which fields the manifest took from the ones it extends, grouped by
+    /// the manifest each came from. Whatever is not listed it wrote itself.
+    fn render_inherited(&self, info: &AdapterInfo, theme: &theme::Theme) -> Option<Div> {
+        if info.inherited.is_empty() {
+            return None;
+        }
+
+        let mut by_source: std::collections::BTreeMap<&str, Vec<&str>> = Default::default();
+        for (field, from) in &info.inherited {
+            by_source.entry(from).or_default().push(field);
+        }
+
+        let mut lines = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XXXS))
+            .text_size(px(styles::font_size::CAPTION))
+            .text_color(theme.text_muted);
+        for (from, fields) in by_source {
+            lines = lines.child(format!("From {from}: {}", fields.join(", ")));
+        }
+        Some(lines)
+    }
+
+    /// What had to change to read the manifest, and how to stop it having
+    /// to. Nothing for a manifest that is already current.
+    fn render_deprecations(&self, info: &AdapterInfo, theme: &theme::Theme) -> Option<Div> {
+        if info.deprecations.is_empty() {
+            return None;
+        }
+
+        let mut notes = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XXXS))
+            .px(px(styles::spacing::SM))
+            .py(px(styles::spacing::XS))
+            .rounded(px(styles::radius::MD))
+            .bg(theme.warning.opacity(0.1))
+            .border_1()
+            .border_color(theme.warning.opacity(0.4))
+            .text_size(px(styles::font_size::SMALL))
+            .child(
+                div()
+                    .text_color(theme.warning)
+                    .font_weight(FontWeight::MEDIUM)
+                    .child("Deprecated manifest"),
+            );
+        for note in &info.deprecations {
+            notes = notes.child(div().child(note.clone()));
+        }
+
+        // A manifest from the registry is better updated than rewritten,
+        // but only one on disk can be either.
+        if let Some(path) = &info.plugin_path {
+            notes = notes.child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(theme.text_muted)
+                    .child(format!("aeris manifest upgrade {}", path.display())),
+            );
+        }
+        Some(notes)
+    }
+
+    /// How much room a manager's cache takes, and the ways it offers to clear
+    /// it. Either asks first, since what goes has to be downloaded again.
+    fn render_cache_section(
+        &mut self,
+        info: &AdapterInfo,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let surface = theme.surface;
+        let border = theme.border;
+        let hover = theme.hover;
+        let text_muted = theme.text_muted;
+        let caps = info.capabilities;
+
+        if caps.has_cache_size
+            && !self.adapter_view.cache_sizes.contains_key(&info.id)
+            && !self.adapter_view.cache_measuring.contains(&info.id)
+        {
+            self.measure_cache(&info.id, cx);
+        }
+
+        let said = match self.adapter_view.cache_sizes.get(&info.id) {
+            _ if !caps.has_cache_size => "Its size is not reported".to_string(),
+            _ if self.adapter_view.cache_measuring.contains(&info.id) => "Measuring...".to_string(),
+            Some(Ok(size)) => crate::views::browse::format_bytes_pub(*size),
+            Some(Err(e)) => format!("Could not measure: {e}"),
+            None => String::new(),
+        };
+        let cleaning = self.adapter_view.cleaning.contains(&info.id);
+
+        let mut row = div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap(px(styles::spacing::SM))
+            .w_full()
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(said),
+            );
+
+        let button = |id: String, label: &'static str| {
+            div()
+                .id(SharedString::from(id))
+                .flex_shrink_0()
+                .px(px(14.0))
+                .py(px(styles::spacing::XS))
+                .rounded(px(styles::radius::MD))
+                .bg(surface)
+                .border_1()
+                .border_color(border)
+                .cursor_pointer()
+                .text_size(px(styles::font_size::SMALL))
+                .hover(move |s| s.bg(hover))
+                .child(label)
+        };
+
+        if cleaning {
+            row = row.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child("Cleaning..."),
+            );
+        } else {
+            for (all, offered, label) in [
+                (false, caps.can_clean_cache, "Clean"),
+                (true, caps.can_clean_whole_cache, "Clear All"),
+            ] {
+                if !offered {
+                    continue;
+                }
+                let adapter_id = info.id.clone();
+                let adapter_name = info.name.clone();
+                let ask = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.confirm_dialog = Some(crate::app::ConfirmAction::CleanCache {
+                        adapter_id: adapter_id.clone(),
+                        adapter_name: adapter_name.clone(),
+                        all,
+                    });
+                    cx.notify();
+                });
+                row = row
+                    .child(button(format!("clean-cache-{}-{all}", info.id), label).on_click(ask));
+            }
+        }
+
+        div()
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .w_full()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .child(
+                div()
+                    .text_size(px(styles::font_size::HEADING))
+                    .child("Cache"),
+            )
+            .child(row)
+    }
+
+    /// What aeris keeps for itself: icons, registry copies and release notes.
+    fn render_own_caches(
+        &mut self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let surface = theme.surface;
+        let border = theme.border;
+        let hover = theme.hover;
+        let text_muted = theme.text_muted;
+
+        let mut list = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XS))
+            .w_full();
+
+        match &self.adapter_view.own_caches {
+            None => {
+                // Marked as measured before it is, so the frames in between
+                // do not each ask again.
+                self.adapter_view.own_caches = Some(Vec::new());
+                self.measure_own_caches(cx);
+            }
+            Some(caches) => {
+                for cache in caches {
+                    list = list.child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .justify_between()
+                            .text_size(px(styles::font_size::SMALL))
+                            .child(cache.name)
+                            .child(
+                                div()
+                                    .text_color(text_muted)
+                                    .child(crate::views::browse::format_bytes_pub(cache.size)),
+                            ),
+                    );
+                }
+            }
+        }
+
+        let clear = cx.listener(|app, _: &ClickEvent, _window, cx| {
+            app.confirm_dialog = Some(crate::app::ConfirmAction::ClearOwnCaches);
+            cx.notify();
+        });
+        let clearing = self.adapter_view.own_caches_clearing;
+        let mut clear_button = div()
+            .id("clear-own-caches")
+            .px(px(14.0))
+            .py(px(styles::spacing::XS))
+            .rounded(px(styles::radius::MD))
+            .bg(surface)
+            .border_1()
+            .border_color(border)
+            .text_size(px(styles::font_size::SMALL))
+            .child(if clearing { "Clearing..." } else { "Clear" });
+        if !clearing {
+            clear_button = clear_button
+                .cursor_pointer()
+                .hover(move |s| s.bg(hover))
+                .on_click(clear);
+        }
+
+        div()
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .w_full()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::HEADING))
+                            .child("Aeris Caches"),
+                    )
+                    .child(clear_button),
+            )
+            .child(list)
+    }
+
     fn render_profiles_section(
         &self,
         adapter_id: &str,
@@ -445,7 +716,11 @@ impl App {
             .copied()
             .unwrap_or(false);
         let load_error = self.adapter_view.profiles_error.get(adapter_id);
-        let switching = &self.adapter_view.switching_profile;
+        let changing = &self.adapter_view.changing_profile;
+        let can_manage = self
+            .adapter_manager
+            .get_adapter(adapter_id)
+            .is_some_and(|a| a.capabilities().can_manage_profiles);
 
         // Trigger load on first render
         if profiles.is_none() && !is_loading && load_error.is_none() {
@@ -499,7 +774,11 @@ impl App {
                     let pid = profile.id.clone();
                     let aid = adapter_id.to_string();
                     let is_active = profile.is_active;
-                    let is_switching = switching.as_deref() == Some(profile.id.as_str());
+                    let doing = changing
+                        .as_ref()
+                        .filter(|(id, _)| *id == profile.id)
+                        .map(|(_, change)| change.doing());
+                    let is_switching = doing.is_some();
                     let listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
                         app.switch_to_profile(&aid, &pid, cx);
                     });
@@ -513,13 +792,18 @@ impl App {
                     } else {
                         border
                     };
-                    let label = if is_switching {
-                        format!("{} (switching...)", profile.name)
+                    let mut label = if let Some(doing) = doing {
+                        format!("{} ({doing})", profile.name)
                     } else if is_active {
                         format!("{} (active)", profile.name)
                     } else {
                         profile.name.clone()
                     };
+                    label = match profile.package_count {
+                        0 => label,
+                        1 => format!("{label} \u{b7} 1 package"),
+                        n => format!("{label} \u{b7} {n} packages"),
+                    };
                     let mut row = div()
                         .id(SharedString::from(format!("profile-{adapter_id}-{idx}")))
                         .flex()
@@ -547,12 +831,76 @@ impl App {
                                 .text_color(success)
                                 .child("\u{2713}"),
                         );
+                    } else if can_manage && changing.is_none() {
+                        let aid = adapter_id.to_string();
+                        let pid = profile.id.clone();
+                        let delete = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                            cx.stop_propagation();
+                            app.delete_profile(&aid, &pid, cx);
+                        });
+                        row = row.child(
+                            div()
+                                .id(SharedString::from(format!(
+                                    "delete-profile-{adapter_id}-{idx}"
+                                )))
+                                .px(px(styles::spacing::SM))
+                                .rounded(px(styles::radius::SM))
+                                .text_size(px(styles::font_size::CAPTION))
+                                .text_color(text_muted)
+                                .cursor_pointer()
+                                .hover(move |s| s.bg(hover))
+                                .on_click(delete)
+                                .child("Delete"),
+                        );
                     }
                     list = list.child(row);
                 }
             }
         }
 
+        if can_manage {
+            let aid = adapter_id.to_string();
+            let create = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                app.create_profile(&aid, cx);
+            });
+            let creating = changing
+                .as_ref()
+                .is_some_and(|(_, change)| *change == ProfileChange::Create);
+            list = list.child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(px(styles::spacing::SM))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .px(px(styles::spacing::MD))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .border_1()
+                            .border_color(border)
+                            .text_size(px(styles::font_size::SMALL))
+                            .child(self.profile_name_input.clone()),
+                    )
+                    .child(
+                        div()
+                            .id(SharedString::from(format!("create-profile-{adapter_id}")))
+                            .px(px(14.0))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .border_1()
+                            .border_color(border)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(create)
+                            .child(if creating { "Creating..." } else { "Create" }),
+                    ),
+            );
+        }
+
         div()
             .px(px(styles::spacing::LG))
             .py(px(styles::spacing::MD))
@@ -1001,6 +1349,8 @@ impl App {
                     .text_size(px(styles::font_size::BODY))
                     .child(info.description.clone()),
             )
+            .children(self.render_deprecations(info, theme))
+            .children(self.render_inherited(info, theme))
             .child({
                 // Turned off is a choice; missing is a problem. They should
                 // not read the same.
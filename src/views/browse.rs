@@ -841,6 +841,16 @@ impl App {
             for (label, value) in &detail.extra {
                 content = content.child(self.detail_row(label, value, theme));
             }
+
+            content = content
+                .child(self.related_packages("Needs", "browse-depends", &detail.depends, theme, cx))
+                .child(self.related_packages(
+                    "Needed by",
+                    "browse-required-by",
+                    &detail.required_by,
+                    theme,
+                    cx,
+                ));
         }
 
         // Separator
@@ -939,7 +949,76 @@ impl App {
         content
     }
 
-    fn detail_row(&self, label: &str, value: &str, _theme: &theme::Theme) -> impl IntoElement {
+    /// Packages named alongside the one open, each of which opens in turn.
+    /// Nothing at all when there are none, since a manager that said nothing
+    /// and one that found nothing read the same.
+    pub(crate) fn related_packages(
+        &self,
+        label: &str,
+        id_prefix: &str,
+        packages: &[Package],
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let border = theme.border;
+        let hover = theme.hover;
+        if packages.is_empty() {
+            return div();
+        }
+
+        let mut chips = div()
+            .flex_1()
+            .min_w(px(0.0))
+            .flex()
+            .flex_wrap()
+            .gap(px(styles::spacing::XXS));
+        for (idx, pkg) in packages.iter().enumerate() {
+            let open = pkg.clone();
+            let listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                app.open_related(open.clone(), cx);
+                cx.notify();
+            });
+            let shown = if pkg.version.is_empty() {
+                pkg.name.clone()
+            } else {
+                format!("{} {}", pkg.name, pkg.version)
+            };
+            chips = chips.child(
+                div()
+                    .id(SharedString::from(format!("{id_prefix}-{idx}")))
+                    .px(px(styles::spacing::XS))
+                    .py(px(styles::spacing::XXXS))
+                    .rounded(px(styles::radius::SM))
+                    .border_1()
+                    .border_color(border)
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .hover(move |s| s.bg(hover))
+                    .on_click(listener)
+                    .child(shown),
+            );
+        }
+
+        div()
+            .flex()
+            .flex_row()
+            .gap(px(styles::spacing::SM))
+            .child(
+                div()
+                    .flex_shrink_0()
+                    .text_size(px(styles::font_size::SMALL))
+                    .w(px(100.0))
+                    .child(label.to_string()),
+            )
+            .child(chips)
+    }
+
+    pub(crate) fn detail_row(
+        &self,
+        label: &str,
+        value: &str,
+        _theme: &theme::Theme,
+    ) -> impl IntoElement {
         div()
             .flex()
             .flex_row()
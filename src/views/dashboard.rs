@@ -9,6 +9,8 @@ use crate::{
 pub struct DashboardStats {
     pub installed_count: usize,
     pub update_count: usize,
+    /// How many of those updates fix a security problem.
+    pub security_count: usize,
     pub updates_checked: bool,
     pub current_mode: PackageMode,
     pub unhealthy_count: usize,
@@ -44,6 +46,8 @@ impl App {
         let update_value = if stats.updates_checked {
             if stats.update_count == 0 {
                 "Up to date".to_string()
+            } else if stats.security_count > 0 {
+                format!("{} ({} security)", stats.update_count, stats.security_count)
             } else {
                 stats.update_count.to_string()
             }
@@ -78,8 +82,8 @@ impl App {
         let text_muted = theme.text_muted;
         let hover = theme.hover;
         let danger = theme.danger;
-        let nav_installed_view = cx.listener(|app, _: &ClickEvent, _window, _cx| {
-            app.current_view = View::Installed;
+        let nav_doctor = cx.listener(|app, _: &ClickEvent, _window, _cx| {
+            app.current_view = View::Doctor;
         });
 
         let mut content = div()
@@ -206,8 +210,8 @@ impl App {
                             .cursor_pointer()
                             .text_size(px(styles::font_size::SMALL))
                             .hover(move |s| s.bg(hover))
-                            .on_click(nav_installed_view)
-                            .child("View"),
+                            .on_click(nav_doctor)
+                            .child("Open Doctor"),
                     ),
             );
         }
@@ -328,6 +332,12 @@ impl App {
         DashboardStats {
             installed_count: self.installed_state.packages.len(),
             update_count: self.updates_state.updates.len(),
+            security_count: self
+                .updates_state
+                .updates
+                .iter()
+                .filter(|u| u.is_security)
+                .count(),
             updates_checked: self.updates_state.checked,
             current_mode: self.current_mode,
             unhealthy_count: self
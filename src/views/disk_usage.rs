@@ -0,0 +1,521 @@
+//! Where the disk went, added up across every manager.
+//!
+//! Measured the first time it is shown and again on asking, since listing
+//! every manager's packages is not free. Picking a manager narrows the page
+//! to it and splits what it holds by scope and profile.
+
+use gpui::*;
+
+use crate::{
+    app::{App, ToastLevel},
+    core::disk_usage::{By, PackageUsage, Slice, Usage},
+    styles, theme,
+    views::browse::format_bytes_pub as format_bytes,
+};
+
+/// How many of the largest packages are listed.
+const LARGEST: usize = 25;
+
+/// How many packages of unknown size are listed before the rest are counted.
+const UNKNOWN_SHOWN: usize = 50;
+
+#[derive(Debug, Default)]
+pub struct DiskUsageState {
+    pub usage: Usage,
+    pub loading: bool,
+    pub loaded: bool,
+    /// Set while unknown sizes are being measured from disk.
+    pub measuring: bool,
+    /// The manager drilled into, if any.
+    pub focus: Option<String>,
+}
+
+impl App {
+    pub(crate) fn load_disk_usage(&mut self, cx: &mut Context<Self>) {
+        if self.disk_usage_state.loading {
+            return;
+        }
+        self.disk_usage_state.loading = true;
+        cx.notify();
+
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let usage = crate::tokio_spawn(async move { Usage::gather(&manager).await }).await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        let state = &mut app.disk_usage_state;
+                        state.loading = false;
+                        state.loaded = true;
+                        match usage {
+                            Ok(usage) => {
+                                // A manager that has gone since is no place
+                                // to stay drilled into.
+                                if state.focus.as_ref().is_some_and(|id| {
+                                    !usage.packages.iter().any(|p| &p.adapter_id == id)
+                                }) {
+                                    state.focus = None;
+                                }
+                                state.usage = usage;
+                            }
+                            Err(e) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Could not measure disk usage: {e}"),
+                            ),
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    /// Walk the install path of every package no manager gave a size for.
+    fn measure_unknown_sizes(&mut self, cx: &mut Context<Self>) {
+        if self.disk_usage_state.measuring {
+            return;
+        }
+        self.disk_usage_state.measuring = true;
+        cx.notify();
+
+        let mut usage = self.disk_usage_state.usage.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let measured = crate::tokio_spawn(async move {
+                    tokio::task::spawn_blocking(move || {
+                        let count = usage.measure_unknown();
+                        (usage, count)
+                    })
+                    .await
+                })
+                .await
+                .and_then(|measured| measured);
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.disk_usage_state.measuring = false;
+                        match measured {
+                            Ok((usage, count)) => {
+                                app.disk_usage_state.usage = usage;
+                                app.add_toast(
+                                    ToastLevel::Success,
+                                    match count {
+                                        0 => "Nothing more could be measured".to_string(),
+                                        1 => "Measured 1 package".to_string(),
+                                        count => format!("Measured {count} packages"),
+                                    },
+                                );
+                            }
+                            Err(e) => {
+                                app.add_toast(ToastLevel::Error, format!("Measuring failed: {e}"))
+                            }
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    fn focus_disk_usage(&mut self, adapter_id: Option<String>, cx: &mut Context<Self>) {
+        self.disk_usage_state.focus = adapter_id;
+        cx.notify();
+    }
+
+    pub fn render_disk_usage(
+        &mut self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let state = &self.disk_usage_state;
+        if !state.loaded && !state.loading {
+            self.load_disk_usage(cx);
+        }
+
+        let state = &self.disk_usage_state;
+        let usage = &state.usage;
+        let focus = state.focus.as_deref();
+        let text_muted = theme.text_muted;
+        let hover = theme.hover;
+
+        let subtitle = if state.loading && !state.loaded {
+            "Asking every manager what it holds...".to_string()
+        } else {
+            match focus {
+                Some(id) => {
+                    let held: u64 = usage.within(Some(id)).map(PackageUsage::bytes).sum();
+                    format!("{} in {id}'s packages", format_bytes(held))
+                }
+                None => format!("{} across every manager", format_bytes(usage.total())),
+            }
+        };
+
+        let measurable = usage
+            .unknown(focus)
+            .iter()
+            .filter(|p| p.install_path.is_some())
+            .count();
+
+        let mut actions = div()
+            .flex()
+            .flex_row()
+            .flex_shrink_0()
+            .gap(px(styles::spacing::SM))
+            .child(button(
+                "disk-usage-refresh",
+                if state.loading {
+                    "Measuring..."
+                } else {
+                    "Refresh"
+                },
+                theme,
+                cx.listener(|app, _: &ClickEvent, _window, cx| app.load_disk_usage(cx)),
+            ));
+        if measurable > 0 {
+            actions = actions.child(button(
+                "disk-usage-measure",
+                &if state.measuring {
+                    "Measuring...".to_string()
+                } else {
+                    format!("Measure {measurable} unknown")
+                },
+                theme,
+                cx.listener(|app, _: &ClickEvent, _window, cx| app.measure_unknown_sizes(cx)),
+            ));
+        }
+
+        let header = div()
+            .flex()
+            .flex_row()
+            .justify_between()
+            .items_center()
+            .gap(px(styles::spacing::MD))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(px(styles::spacing::XXS))
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::TITLE))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child(match focus {
+                                Some(id) => format!("Disk Usage \u{00b7} {id}"),
+                                None => "Disk Usage".to_string(),
+                            }),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::SMALL))
+                            .text_color(text_muted)
+                            .child(subtitle),
+                    ),
+            )
+            .child(actions);
+
+        let mut body = div()
+            .p(px(styles::spacing::XL))
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::LG))
+            .w_full()
+            .min_w_0()
+            .child(header);
+
+        for problem in &usage.problems {
+            body = body.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(theme.danger)
+                    .child(problem.clone()),
+            );
+        }
+
+        match focus {
+            None => {
+                let managers = usage.breakdown(By::Manager, None);
+                let mut rows = section("By manager", theme);
+                let largest = managers.first().map_or(0, |slice| slice.size);
+                for slice in &managers {
+                    let id = slice.label.clone();
+                    let drill = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                        app.focus_disk_usage(Some(id.clone()), cx);
+                    });
+                    rows = rows.child(
+                        slice_row(slice, largest, Self::adapter_color(&slice.label), theme)
+                            .id(SharedString::from(format!("disk-usage-{}", slice.label)))
+                            .cursor_pointer()
+                            .hover(move |s| s.bg(hover))
+                            .on_click(drill),
+                    );
+                }
+                if managers.is_empty() && state.loaded {
+                    rows = rows.child(
+                        div()
+                            .text_size(px(styles::font_size::SMALL))
+                            .text_color(text_muted)
+                            .child("No manager listed anything installed."),
+                    );
+                }
+                body = body.child(rows);
+            }
+            Some(id) => {
+                body = body.child(button(
+                    "disk-usage-back",
+                    "All managers",
+                    theme,
+                    cx.listener(|app, _: &ClickEvent, _window, cx| {
+                        app.focus_disk_usage(None, cx);
+                    }),
+                ));
+
+                let color = Self::adapter_color(id);
+                for (title, by) in [("By scope", By::Mode), ("By profile", By::Profile)] {
+                    let slices = usage.breakdown(by, Some(id));
+                    // A manager without profiles has everything under none,
+                    // which says nothing.
+                    if by == By::Profile && usage.within(Some(id)).all(|p| p.profile.is_none()) {
+                        continue;
+                    }
+                    let largest = slices.first().map_or(0, |slice| slice.size);
+                    let mut rows = section(title, theme);
+                    for slice in &slices {
+                        rows = rows.child(slice_row(slice, largest, color, theme));
+                    }
+                    body = body.child(rows);
+                }
+            }
+        }
+
+        let mut largest = section("Largest packages", theme);
+        for package in usage.largest(focus, LARGEST) {
+            largest = largest.child(package_row(package, theme));
+        }
+        body = body.child(largest);
+
+        let unknown = usage.unknown(focus);
+        if !unknown.is_empty() {
+            let mut rows = section(&format!("Size unknown ({})", unknown.len()), theme).child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(text_muted)
+                    .child(
+                        "Their manager gave no size, so the totals above leave them out. \
+                         Those with an install path can be measured.",
+                    ),
+            );
+            for package in unknown.iter().take(UNKNOWN_SHOWN) {
+                rows = rows.child(package_row(package, theme));
+            }
+            if unknown.len() > UNKNOWN_SHOWN {
+                rows = rows.child(
+                    div()
+                        .text_size(px(styles::font_size::CAPTION))
+                        .text_color(text_muted)
+                        .child(format!("and {} more", unknown.len() - UNKNOWN_SHOWN)),
+                );
+            }
+            body = body.child(rows);
+        }
+
+        let mut caches = section("Caches", theme);
+        let mut any_cache = false;
+        for (adapter_id, size) in &usage.caches {
+            if focus.is_some_and(|id| id != adapter_id) {
+                continue;
+            }
+            any_cache = true;
+            caches = caches.child(line(adapter_id, *size, theme));
+        }
+        if focus.is_none() {
+            for cache in &usage.own_caches {
+                any_cache = true;
+                caches = caches.child(line(&format!("aeris: {}", cache.name), cache.size, theme));
+            }
+        }
+        if any_cache {
+            body = body.child(caches);
+        }
+
+        div()
+            .id("disk-usage-scroll")
+            .flex_1()
+            .min_h_0()
+            .min_w_0()
+            .w_full()
+            .overflow_y_scroll()
+            .child(body)
+    }
+}
+
+fn section(title: &str, theme: &theme::Theme) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap(px(styles::spacing::XS))
+        .w_full()
+        .px(px(styles::spacing::LG))
+        .py(px(styles::spacing::MD))
+        .rounded(px(styles::radius::LG))
+        .bg(theme.surface)
+        .border_1()
+        .border_color(theme.border)
+        .child(
+            div()
+                .pb(px(styles::spacing::XS))
+                .font_weight(FontWeight::SEMIBOLD)
+                .child(title.to_string()),
+        )
+}
+
+fn button(
+    id: &'static str,
+    label: &str,
+    theme: &theme::Theme,
+    on_click: impl Fn(&ClickEvent, &mut Window, &mut gpui::App) + 'static,
+) -> Stateful<Div> {
+    let hover = theme.hover;
+    div()
+        .id(id)
+        .flex_shrink_0()
+        .px(px(styles::spacing::MD))
+        .py(px(styles::spacing::XS))
+        .rounded(px(styles::radius::MD))
+        .border_1()
+        .border_color(theme.border)
+        .cursor_pointer()
+        .text_size(px(styles::font_size::SMALL))
+        .hover(move |s| s.bg(hover))
+        .on_click(on_click)
+        .child(label.to_string())
+}
+
+/// A slice with a bar as long as its share of the largest beside it.
+fn slice_row(slice: &Slice, largest: u64, color: Hsla, theme: &theme::Theme) -> Div {
+    let share = if largest == 0 {
+        0.0
+    } else {
+        slice.size as f32 / largest as f32
+    };
+    let mut counted = match slice.packages {
+        1 => "1 package".to_string(),
+        count => format!("{count} packages"),
+    };
+    if slice.unknown > 0 {
+        counted.push_str(&format!(", {} of unknown size", slice.unknown));
+    }
+
+    div()
+        .flex()
+        .flex_col()
+        .gap(px(styles::spacing::XXXS))
+        .w_full()
+        .px(px(styles::spacing::SM))
+        .py(px(styles::spacing::XS))
+        .rounded(px(styles::radius::MD))
+        .child(
+            div()
+                .flex()
+                .flex_row()
+                .justify_between()
+                .gap(px(styles::spacing::MD))
+                .child(div().truncate().min_w_0().child(slice.label.clone()))
+                .child(
+                    div()
+                        .flex_shrink_0()
+                        .text_size(px(styles::font_size::SMALL))
+                        .child(format_bytes(slice.size)),
+                ),
+        )
+        .child(
+            div()
+                .w_full()
+                .h(px(6.0))
+                .rounded(px(3.0))
+                .bg(theme.border)
+                .child(
+                    div()
+                        .h(px(6.0))
+                        .rounded(px(3.0))
+                        .bg(color)
+                        .w(relative(share)),
+                ),
+        )
+        .child(
+            div()
+                .text_size(px(styles::font_size::CAPTION))
+                .text_color(theme.text_muted)
+                .child(counted),
+        )
+}
+
+fn package_row(package: &PackageUsage, theme: &theme::Theme) -> Div {
+    let size = match package.size {
+        Some(size) if package.measured => format!("{} (measured)", format_bytes(size)),
+        Some(size) => format_bytes(size),
+        None if package.install_path.is_some() => "unknown".to_string(),
+        None => "unknown, no install path".to_string(),
+    };
+    let mut said = vec![package.adapter_id.clone(), package.mode.to_string()];
+    if let Some(profile) = &package.profile {
+        said.push(profile.clone());
+    }
+
+    div()
+        .flex()
+        .flex_row()
+        .justify_between()
+        .items_center()
+        .gap(px(styles::spacing::MD))
+        .w_full()
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .min_w_0()
+                .child(
+                    div()
+                        .truncate()
+                        .child(format!("{} {}", package.name, package.version)),
+                )
+                .child(
+                    div()
+                        .truncate()
+                        .text_size(px(styles::font_size::CAPTION))
+                        .text_color(theme.text_muted)
+                        .child(said.join(" \u{00b7} ")),
+                ),
+        )
+        .child(
+            div()
+                .flex_shrink_0()
+                .text_size(px(styles::font_size::SMALL))
+                .text_color(if package.size.is_some() {
+                    theme.text
+                } else {
+                    theme.text_muted
+                })
+                .child(size),
+        )
+}
+
+fn line(label: &str, size: u64, theme: &theme::Theme) -> Div {
+    div()
+        .flex()
+        .flex_row()
+        .justify_between()
+        .gap(px(styles::spacing::MD))
+        .w_full()
+        .text_size(px(styles::font_size::SMALL))
+        .child(
+            div()
+                .truncate()
+                .min_w_0()
+                .text_color(theme.text_muted)
+                .child(label.to_string()),
+        )
+        .child(div().flex_shrink_0().child(format_bytes(size)))
+}
@@ -0,0 +1,304 @@
+//! Everything found wrong with what is installed, and a way to put each right.
+//!
+//! Looked for the first time the page is shown and again after every repair,
+//! since whether a repair took is only known by looking again.
+
+use std::collections::HashSet;
+
+use gpui::*;
+
+use crate::{
+    app::{App, ToastLevel},
+    core::{
+        adapter::progress_key,
+        doctor::{self, Ailment, Problem},
+        queue::JobKind,
+    },
+    styles, theme,
+};
+
+#[derive(Debug, Default)]
+pub struct DoctorState {
+    pub problems: Vec<Problem>,
+    /// Managers that could not be looked over, and why.
+    pub unexamined: Vec<String>,
+    pub loading: bool,
+    pub loaded: bool,
+    /// The [`progress_key`] of every package waiting on or under repair.
+    pub repairing: HashSet<String>,
+}
+
+impl App {
+    pub(crate) fn load_doctor(&mut self, cx: &mut Context<Self>) {
+        if self.doctor_state.loading {
+            return;
+        }
+        self.doctor_state.loading = true;
+        cx.notify();
+
+        let manager = self.adapter_manager.clone();
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let examined =
+                    crate::tokio_spawn(async move { doctor::examine(&manager).await }).await;
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        let state = &mut app.doctor_state;
+                        state.loading = false;
+                        state.loaded = true;
+                        match examined {
+                            Ok((problems, unexamined)) => {
+                                state.problems = problems;
+                                state.unexamined = unexamined;
+                            }
+                            Err(e) => app.add_toast(
+                                ToastLevel::Error,
+                                format!("Could not look the packages over: {e}"),
+                            ),
+                        }
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    fn repair_problem(&mut self, problem: &Problem, cx: &mut Context<Self>) {
+        let Some(package) = problem.package.clone() else {
+            return;
+        };
+        self.enqueue(JobKind::Repair(vec![package]), problem.mode, cx);
+    }
+
+    /// A link leading nowhere is taken away here rather than by the manager,
+    /// which has no command for what it no longer knows it made.
+    fn remove_dangling_link(&mut self, link: std::path::PathBuf, cx: &mut Context<Self>) {
+        match doctor::remove_dangling(&link) {
+            Ok(()) => {
+                self.doctor_state
+                    .problems
+                    .retain(|problem| match &problem.ailment {
+                        Ailment::DanglingLink { link: gone, .. } => *gone != link,
+                        _ => true,
+                    });
+                self.add_toast(ToastLevel::Success, format!("Removed {}", link.display()));
+            }
+            Err(e) => self.add_toast(ToastLevel::Error, format!("Could not remove it: {e}")),
+        }
+        cx.notify();
+    }
+
+    pub fn render_doctor(
+        &mut self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        if !self.doctor_state.loaded && !self.doctor_state.loading {
+            self.load_doctor(cx);
+        }
+
+        let state = &self.doctor_state;
+        let text_muted = theme.text_muted;
+        let hover = theme.hover;
+
+        let subtitle = if state.loading {
+            "Looking every manager over...".to_string()
+        } else {
+            match state.problems.len() {
+                0 => "Nothing wrong was found".to_string(),
+                1 => "1 problem".to_string(),
+                count => format!("{count} problems"),
+            }
+        };
+
+        let look_again = cx.listener(|app, _: &ClickEvent, _window, cx| app.load_doctor(cx));
+        let header = div()
+            .flex()
+            .flex_row()
+            .justify_between()
+            .items_center()
+            .gap(px(styles::spacing::MD))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(px(styles::spacing::XXS))
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::TITLE))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child("Doctor"),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::SMALL))
+                            .text_color(text_muted)
+                            .child(subtitle),
+                    ),
+            )
+            .child(
+                div()
+                    .id("doctor-check")
+                    .flex_shrink_0()
+                    .px(px(styles::spacing::MD))
+                    .py(px(styles::spacing::XS))
+                    .rounded(px(styles::radius::MD))
+                    .border_1()
+                    .border_color(theme.border)
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::SMALL))
+                    .hover(move |s| s.bg(hover))
+                    .on_click(look_again)
+                    .child(if state.loading {
+                        "Checking..."
+                    } else {
+                        "Check Again"
+                    }),
+            );
+
+        let mut body = div()
+            .p(px(styles::spacing::XL))
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .w_full()
+            .min_w_0()
+            .child(header);
+
+        for unexamined in &state.unexamined {
+            body = body.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(theme.danger)
+                    .child(unexamined.clone()),
+            );
+        }
+
+        for (idx, problem) in state.problems.iter().enumerate() {
+            body = body.child(self.render_problem(idx, problem, theme, cx));
+        }
+
+        div()
+            .id("doctor-scroll")
+            .flex_1()
+            .min_h_0()
+            .min_w_0()
+            .w_full()
+            .overflow_y_scroll()
+            .child(body)
+    }
+
+    fn render_problem(
+        &self,
+        idx: usize,
+        problem: &Problem,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let hover = theme.hover;
+        let caps = self
+            .adapter_manager
+            .get_adapter(&problem.adapter_id)
+            .map(|adapter| *adapter.capabilities())
+            .unwrap_or_default();
+
+        let what = match (&problem.package, &problem.ailment) {
+            (Some(package), _) => package.name.clone(),
+            (None, Ailment::DanglingLink { link, .. }) => link.file_name().map_or_else(
+                || link.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            ),
+            (None, _) => problem.adapter_id.clone(),
+        };
+        let said = format!("{} \u{00b7} {}", problem.adapter_id, problem.mode);
+
+        let action = |id: &'static str, label: &str| {
+            div()
+                .id((id, idx))
+                .flex_shrink_0()
+                .px(px(styles::spacing::SM))
+                .py(px(styles::spacing::XXS))
+                .rounded(px(styles::radius::MD))
+                .border_1()
+                .border_color(theme.border)
+                .cursor_pointer()
+                .text_size(px(styles::font_size::SMALL))
+                .hover(move |s| s.bg(hover))
+                .child(label.to_string())
+        };
+
+        // The manager's repair is preferred wherever there is a package to
+        // hand it; a link with no owner is all that aeris sees to itself.
+        let repairing = problem.package.as_ref().is_some_and(|package| {
+            self.doctor_state
+                .repairing
+                .contains(&progress_key(&package.adapter_id, &package.id))
+        });
+        let remedy = match (&problem.package, &problem.ailment) {
+            _ if repairing => div()
+                .text_size(px(styles::font_size::SMALL))
+                .text_color(theme.text_muted)
+                .child("Repairing...")
+                .into_any_element(),
+            (Some(_), _) if caps.can_repair => {
+                let problem = problem.clone();
+                action("doctor-repair", "Repair")
+                    .on_click(cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                        app.repair_problem(&problem, cx);
+                    }))
+                    .into_any_element()
+            }
+            (_, Ailment::DanglingLink { link, .. }) => {
+                let link = link.clone();
+                action("doctor-unlink", "Remove Link")
+                    .on_click(cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                        app.remove_dangling_link(link.clone(), cx);
+                    }))
+                    .into_any_element()
+            }
+            _ => div()
+                .text_size(px(styles::font_size::CAPTION))
+                .text_color(theme.text_muted)
+                .child(format!("{} offers no repair", problem.adapter_id))
+                .into_any_element(),
+        };
+
+        div()
+            .w_full()
+            .min_w_0()
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .rounded(px(styles::radius::LG))
+            .bg(theme.surface)
+            .border_1()
+            .border_l(px(4.0))
+            .border_color(theme.danger)
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .gap(px(styles::spacing::MD))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .min_w_0()
+                    .gap(px(styles::spacing::XXXS))
+                    .child(div().truncate().font_weight(FontWeight::MEDIUM).child(what))
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::SMALL))
+                            .child(problem.ailment.describe()),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::CAPTION))
+                            .text_color(theme.text_muted)
+                            .child(said),
+                    ),
+            )
+            .child(remedy)
+    }
+}
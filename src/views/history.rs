@@ -0,0 +1,459 @@
+//! Every change aeris has made on this machine, newest first.
+//!
+//! Read back from the journal the first time it is shown and added to as
+//! changes end, narrowed by manager, package and the days between two dates.
+
+use std::collections::HashSet;
+
+use gpui::*;
+
+use crate::{
+    app::App,
+    core::history::{self, Entry, Filter, Outcome},
+    styles, theme,
+};
+
+/// How many entries are drawn at once. A journal grows for as long as aeris
+/// is used, and the filter is the way to reach further back.
+const SHOWN: usize = 500;
+
+#[derive(Debug, Default)]
+pub struct HistoryState {
+    /// Every change read back, oldest first.
+    pub entries: Vec<Entry>,
+    pub loading: bool,
+    pub loaded: bool,
+    /// The managers shown. None picked means every one.
+    pub managers: HashSet<String>,
+    /// The entry opened to show what its manager said, by its place in
+    /// `entries`.
+    pub open: Option<usize>,
+}
+
+impl App {
+    fn load_history(&mut self, cx: &mut Context<Self>) {
+        self.history_state.loading = true;
+
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut gpui::AsyncApp| {
+                let entries = crate::tokio_spawn(async move {
+                    history::Journal::at(history::Journal::default_path()).read()
+                })
+                .await
+                .unwrap_or_default();
+
+                let _ = cx.update(|cx| {
+                    this.update(cx, |app, cx| {
+                        app.history_state.loading = false;
+                        app.history_state.loaded = true;
+                        // Anything that ended while this was reading is
+                        // already in the file, so what was read replaces it.
+                        app.history_state.entries = entries;
+                        app.history_state.open = None;
+                        cx.notify();
+                    })
+                });
+            },
+        )
+        .detach();
+    }
+
+    fn toggle_history_manager(&mut self, adapter_id: &str, cx: &mut Context<Self>) {
+        let managers = &mut self.history_state.managers;
+        if !managers.remove(adapter_id) {
+            managers.insert(adapter_id.to_string());
+        }
+        cx.notify();
+    }
+
+    fn toggle_history_entry(&mut self, at: usize, cx: &mut Context<Self>) {
+        self.history_state.open = match self.history_state.open {
+            Some(open) if open == at => None,
+            _ => Some(at),
+        };
+        cx.notify();
+    }
+
+    pub fn render_history(
+        &mut self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        if !self.history_state.loaded && !self.history_state.loading {
+            self.load_history(cx);
+        }
+
+        let text_muted = theme.text_muted;
+
+        // A date that cannot be read narrows nothing, and says so.
+        let from_text = self.history_from_input.read(cx).content().to_string();
+        let until_text = self.history_until_input.read(cx).content().to_string();
+        let from = history::parse_day(&from_text);
+        let until = history::parse_day(&until_text);
+        let unreadable = (!from_text.trim().is_empty() && from.is_none())
+            || (!until_text.trim().is_empty() && until.is_none());
+
+        let filter = Filter {
+            managers: self.history_state.managers.clone(),
+            package: self.history_package_input.read(cx).content().to_string(),
+            from,
+            until,
+        };
+
+        let matching: Vec<usize> = (0..self.history_state.entries.len())
+            .rev()
+            .filter(|&at| filter.matches(&self.history_state.entries[at]))
+            .collect();
+
+        let subtitle = if self.history_state.loading {
+            "Reading the history...".to_string()
+        } else {
+            match matching.len() {
+                1 => "1 change".to_string(),
+                count => format!("{count} changes"),
+            }
+        };
+
+        let header = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XXS))
+            .child(
+                div()
+                    .text_size(px(styles::font_size::TITLE))
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .child("History"),
+            )
+            .child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(subtitle),
+            );
+
+        let mut filters = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .w_full()
+            .child(self.render_history_managers(theme, cx))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(px(styles::spacing::SM))
+                    .w_full()
+                    .child(history_input(self.history_package_input.clone(), theme))
+                    .child(history_input(self.history_from_input.clone(), theme))
+                    .child(history_input(self.history_until_input.clone(), theme)),
+            );
+        if unreadable {
+            filters = filters.child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(theme.danger)
+                    .child("Dates are written YYYY-MM-DD, and are read in UTC."),
+            );
+        }
+
+        let mut list = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .w_full();
+        for &at in matching.iter().take(SHOWN) {
+            list = list.child(self.render_history_entry(at, theme, cx));
+        }
+
+        if matching.len() > SHOWN {
+            list = list.child(
+                div()
+                    .py(px(styles::spacing::MD))
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(format!(
+                        "Showing the newest {SHOWN}. Narrow the filter to reach older changes."
+                    )),
+            );
+        }
+        if matching.is_empty() && self.history_state.loaded {
+            list = list.child(
+                div()
+                    .py(px(styles::spacing::XXL))
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(if self.history_state.entries.is_empty() {
+                        "Installs, removals, updates and applies are kept here once they end."
+                    } else {
+                        "No change matches the filter."
+                    }),
+            );
+        }
+
+        div()
+            .id("history-scroll")
+            .flex_1()
+            .min_h_0()
+            .min_w_0()
+            .w_full()
+            .overflow_y_scroll()
+            .child(
+                div()
+                    .p(px(styles::spacing::XL))
+                    .flex()
+                    .flex_col()
+                    .gap(px(styles::spacing::LG))
+                    .w_full()
+                    .min_w_0()
+                    .child(header)
+                    .child(filters)
+                    .child(list),
+            )
+    }
+
+    /// A chip for every manager named in the history, including ones no
+    /// longer loaded, since their changes happened all the same.
+    fn render_history_managers(
+        &self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let mut named: Vec<String> = self
+            .history_state
+            .entries
+            .iter()
+            .map(|entry| entry.adapter_id.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        named.sort();
+
+        let picked = &self.history_state.managers;
+        let border = theme.border;
+        let text_muted = theme.text_muted;
+        let hover = theme.hover;
+
+        let mut row = div()
+            .flex()
+            .flex_row()
+            .flex_wrap()
+            .items_center()
+            .gap(px(styles::spacing::XS));
+
+        for adapter_id in named {
+            let showing = picked.is_empty() || picked.contains(&adapter_id);
+            let color = Self::adapter_color(&adapter_id);
+            let id = adapter_id.clone();
+            let toggle = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                app.toggle_history_manager(&id, cx);
+            });
+
+            let chip = div()
+                .id(SharedString::from(format!("history-filter-{adapter_id}")))
+                .flex_shrink_0()
+                .px(px(styles::spacing::SM))
+                .py(px(styles::spacing::XXS))
+                .rounded(px(styles::radius::FULL))
+                .border_1()
+                .cursor_pointer()
+                .text_size(px(styles::font_size::CAPTION))
+                .on_click(toggle)
+                .child(adapter_id);
+
+            row = row.child(if showing {
+                chip.bg(color.opacity(0.2))
+                    .border_color(color.opacity(0.4))
+                    .text_color(color)
+                    .hover(move |s| s.bg(color.opacity(0.3)))
+            } else {
+                chip.border_color(border)
+                    .text_color(text_muted)
+                    .hover(move |s| s.bg(hover))
+            });
+        }
+
+        row
+    }
+
+    fn render_history_entry(
+        &self,
+        at: usize,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let entry = &self.history_state.entries[at];
+        let open = self.history_state.open == Some(at);
+        let hover = theme.hover;
+        let accent = match entry.outcome {
+            Outcome::Succeeded => theme.success,
+            Outcome::Failed(_) => theme.danger,
+            Outcome::Cancelled => theme.border,
+        };
+
+        let what = match &entry.package_name {
+            Some(name) => format!("{} {name}", entry.action),
+            None => format!("{} in {}", entry.action, entry.adapter_id),
+        };
+        let mut said = vec![entry.adapter_id.clone(), entry.mode.to_string()];
+        if let Some(versions) = versions(entry) {
+            said.push(versions);
+        }
+        said.push(format!("{} UTC", history::moment_of(entry.at)));
+
+        let toggle = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+            app.toggle_history_entry(at, cx);
+        });
+
+        let mut ending = div()
+            .flex()
+            .flex_row()
+            .flex_shrink_0()
+            .items_center()
+            .gap(px(styles::spacing::SM))
+            .child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(accent)
+                    .child(entry.outcome.label().to_string()),
+            );
+        if let Some(version) = entry.reverts_to() {
+            ending = ending.child(match self.revert_blocker(entry) {
+                Some(why) => div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(theme.text_muted)
+                    .child(format!("Cannot revert: {why}"))
+                    .into_any_element(),
+                None => {
+                    let revert = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                        cx.stop_propagation();
+                        if let Some(entry) = app.history_state.entries.get(at).cloned() {
+                            app.revert(&entry, cx);
+                        }
+                    });
+                    div()
+                        .id(SharedString::from(format!("history-revert-{at}")))
+                        .px(px(styles::spacing::SM))
+                        .py(px(styles::spacing::XXS))
+                        .rounded(px(styles::radius::MD))
+                        .border_1()
+                        .border_color(theme.border)
+                        .cursor_pointer()
+                        .text_size(px(styles::font_size::SMALL))
+                        .hover(move |s| s.bg(hover))
+                        .on_click(revert)
+                        .child(format!("Revert to {version}"))
+                        .into_any_element()
+                }
+            });
+        }
+
+        let mut card = div()
+            .id(SharedString::from(format!("history-{at}")))
+            .w_full()
+            .min_w_0()
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .rounded(px(styles::radius::LG))
+            .bg(theme.surface)
+            .border_1()
+            .border_l(px(4.0))
+            .border_color(accent)
+            .cursor_pointer()
+            .hover(move |s| s.bg(hover))
+            .on_click(toggle)
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XXXS))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .gap(px(styles::spacing::MD))
+                    .child(
+                        div()
+                            .truncate()
+                            .min_w_0()
+                            .font_weight(FontWeight::MEDIUM)
+                            .child(what),
+                    )
+                    .child(ending),
+            )
+            .child(
+                div()
+                    .truncate()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(theme.text_muted)
+                    .child(said.join(" \u{00b7} ")),
+            );
+
+        if !open {
+            return card.into_any_element();
+        }
+
+        if let Outcome::Failed(why) = &entry.outcome {
+            card = card.child(
+                div()
+                    .pt(px(styles::spacing::XS))
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(theme.danger)
+                    .child(why.clone()),
+            );
+        }
+
+        let mut lines = div()
+            .pt(px(styles::spacing::XS))
+            .flex()
+            .flex_col()
+            .w_full()
+            .min_w_0();
+        if entry.output.is_empty() {
+            lines = lines.child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(theme.text_muted)
+                    .child("The manager wrote nothing."),
+            );
+        }
+        for line in &entry.output {
+            lines = lines.child(
+                div()
+                    .w_full()
+                    .min_w_0()
+                    .font_family("monospace")
+                    .text_size(px(styles::font_size::CAPTION))
+                    .child(line.clone()),
+            );
+        }
+
+        card.child(lines).into_any_element()
+    }
+}
+
+/// Where a package's version went, or nothing when neither side is known.
+fn versions(entry: &Entry) -> Option<String> {
+    match (&entry.version_before, &entry.version_after) {
+        (Some(was), Some(now)) if was == now => Some(format!("stayed at {now}")),
+        (Some(was), Some(now)) => Some(format!("{was} \u{2192} {now}")),
+        (None, Some(now)) => Some(now.clone()),
+        (Some(was), None) => Some(format!("was {was}")),
+        (None, None) => None,
+    }
+}
+
+fn history_input(
+    input: Entity<crate::components::TextInput>,
+    theme: &theme::Theme,
+) -> impl IntoElement {
+    div()
+        .flex_1()
+        .min_w_0()
+        .px(px(styles::spacing::MD))
+        .py(px(styles::spacing::XS))
+        .rounded(px(styles::radius::MD))
+        .bg(theme.surface)
+        .border_1()
+        .border_color(theme.border)
+        .text_size(px(styles::font_size::SMALL))
+        .child(input)
+}
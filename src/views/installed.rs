@@ -4,10 +4,33 @@ use gpui::*;
 
 use crate::{
     app::{App, OperationStatus},
-    core::{package::InstalledPackage, privilege::PackageMode},
+    core::{
+        ownership::{self, Owner},
+        package::{InstalledPackage, Package},
+        privilege::PackageMode,
+        queue::JobKind,
+        shadowing::{Conflict, Standing},
+    },
     styles, theme,
 };
 
+/// An installed package open beside the list, with what it needs and what
+/// needs it once its manager has said.
+#[derive(Debug)]
+pub struct InstalledDetail {
+    pub package: InstalledPackage,
+    pub depends: Vec<Package>,
+    pub required_by: Vec<Package>,
+    pub loading: bool,
+    pub error: Option<String>,
+    /// The files it put down, once asked for. Read only on request, since
+    /// walking an install directory can take a while.
+    pub files: Option<Result<Vec<String>, String>>,
+    pub files_loading: bool,
+    /// Directories in the file tree folded shut, by path.
+    pub folded: HashSet<String>,
+}
+
 #[derive(Debug, Default)]
 pub struct InstalledState {
     pub packages: Vec<InstalledPackage>,
@@ -31,6 +54,49 @@ pub struct InstalledState {
     pub held: HashSet<String>,
     pub selected: HashSet<String>,
     pub package_progress: HashMap<String, OperationStatus>,
+    /// Set while every manager is listed for an export.
+    pub exporting: bool,
+    /// Set while an exported file is matched against the managers here.
+    pub importing: bool,
+    /// Commands more than one copy answers to on `PATH`, once looked for.
+    pub conflicts: Vec<Conflict>,
+    /// Managers that could not say what they hold while looking.
+    pub conflict_problems: Vec<String>,
+    pub conflicts_checking: bool,
+    /// Set once conflicts have been looked for, after which they are looked
+    /// for again whenever the list is.
+    pub conflicts_checked: bool,
+    pub detail: Option<InstalledDetail>,
+    /// The path last asked about, and what every manager said owns it.
+    pub owner_asked: Option<String>,
+    pub owners: Vec<Owner>,
+    pub owner_problems: Vec<String>,
+    pub owner_searching: bool,
+    /// The profile the list is narrowed to, or every package when unset.
+    pub profile_filter: Option<String>,
+    /// What the managers that can tell say nothing needs any more, as they
+    /// named them, which is what removing them hands back.
+    pub orphans: Vec<Package>,
+    pub held_filter: HeldFilter,
+}
+
+/// Which installed packages the list shows, by how they came to be there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeldFilter {
+    #[default]
+    All,
+    /// Pulled in for another package rather than asked for.
+    Automatic,
+    /// Pulled in for another package that no longer needs them.
+    Orphaned,
+}
+
+impl InstalledState {
+    fn is_orphan(&self, pkg: &InstalledPackage) -> bool {
+        self.orphans
+            .iter()
+            .any(|o| o.adapter_id == pkg.package.adapter_id && o.id == pkg.package.id)
+    }
 }
 
 impl App {
@@ -67,6 +133,22 @@ impl App {
         });
         let syncing = self.adapter_view.syncing.is_some();
         let sync_label = if syncing { "Syncing..." } else { "Sync" };
+        let export_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
+            app.export_installed(cx);
+        });
+        let import_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
+            app.import_installed(cx);
+        });
+        let export_label = if self.installed_state.exporting {
+            "Exporting..."
+        } else {
+            "Export..."
+        };
+        let import_label = if self.installed_state.importing {
+            "Reading..."
+        } else {
+            "Import..."
+        };
 
         let mut title_block = div()
             .flex()
@@ -106,6 +188,36 @@ impl App {
                     .flex()
                     .flex_row()
                     .gap(px(styles::spacing::SM))
+                    .child(
+                        div()
+                            .id("installed-export")
+                            .px(px(14.0))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .bg(surface)
+                            .border_1()
+                            .border_color(border)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(export_listener)
+                            .child(export_label),
+                    )
+                    .child(
+                        div()
+                            .id("installed-import")
+                            .px(px(14.0))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .bg(surface)
+                            .border_1()
+                            .border_color(border)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(import_listener)
+                            .child(import_label),
+                    )
                     .child(
                         div()
                             .id("installed-sync")
@@ -139,6 +251,24 @@ impl App {
             );
 
         let has_packages = !self.installed_state.packages.is_empty();
+        // Where each row of the list is in `packages`, once narrowed to the
+        // profile asked for.
+        let shown: Vec<usize> = self
+            .installed_state
+            .packages
+            .iter()
+            .enumerate()
+            .filter(|(_, pkg)| match &self.installed_state.profile_filter {
+                Some(profile) => pkg.profile.as_ref() == Some(profile),
+                None => true,
+            })
+            .filter(|(_, pkg)| match self.installed_state.held_filter {
+                HeldFilter::All => true,
+                HeldFilter::Automatic => pkg.auto_installed,
+                HeldFilter::Orphaned => self.installed_state.is_orphan(pkg),
+            })
+            .map(|(idx, _)| idx)
+            .collect();
         let content = if self.installed_state.loading && !has_packages {
             // Only show loading placeholder on first load
             div().flex_1().flex().items_center().justify_center().child(
@@ -152,11 +282,17 @@ impl App {
                     .text_size(px(styles::font_size::BODY))
                     .child(format!("Failed to load: {err}")),
             )
-        } else if !has_packages {
-            let msg = if self.installed_state.loaded {
-                "No packages installed"
+        } else if shown.is_empty() {
+            let msg = if !self.installed_state.loaded {
+                "Loading...".to_string()
+            } else if self.installed_state.held_filter == HeldFilter::Orphaned {
+                "Nothing installed is left over".to_string()
+            } else if self.installed_state.held_filter == HeldFilter::Automatic {
+                "Nothing here was installed automatically".to_string()
+            } else if let Some(ref profile) = self.installed_state.profile_filter {
+                format!("Nothing is installed in the {profile} profile")
             } else {
-                "Loading..."
+                "No packages installed".to_string()
             };
             div()
                 .flex_1()
@@ -167,7 +303,7 @@ impl App {
         } else {
             // Only what is on screen is built, so a machine holding hundreds
             // of packages costs the same to scroll as one holding ten.
-            let held_count = self.installed_state.packages.len();
+            let held_count = shown.len();
             if self.installed_list_version != self.installed_state.result_version {
                 self.installed_list.reset(held_count);
                 self.installed_list_version = self.installed_state.result_version;
@@ -176,8 +312,11 @@ impl App {
             let held = cx.entity();
             let theme = theme.clone();
             div().flex_1().min_h_0().w_full().child(
-                list(self.installed_list.clone(), move |idx, _window, cx| {
+                list(self.installed_list.clone(), move |row, _window, cx| {
                     held.update(cx, |app, cx| {
+                        let Some(&idx) = shown.get(row) else {
+                            return div().into_any_element();
+                        };
                         let Some(pkg) = app.installed_state.packages.get(idx).cloned() else {
                             return div().into_any_element();
                         };
@@ -212,15 +351,17 @@ impl App {
             .gap(px(styles::spacing::MD))
             .w_full()
             .child(header)
+            .child(self.render_profile_filter(theme, cx))
+            .child(self.render_held_filter(theme, cx))
+            .child(self.render_conflicts(theme, cx))
+            .child(self.render_owner_search(theme, cx))
             .child(content);
 
         // Floating action bar for batch removal
         if !self.installed_state.selected.is_empty() {
             let count = self.installed_state.selected.len();
             let remove_selected = cx.listener(|app, _: &ClickEvent, _window, cx| {
-                let count = app.installed_state.selected.len();
-                app.confirm_dialog =
-                    Some(crate::app::ConfirmAction::BatchRemoveInstalled { count });
+                app.ask_to_remove_selected(cx);
                 cx.notify();
             });
             let clear_selection = cx.listener(|app, _: &ClickEvent, _window, _cx| {
@@ -239,7 +380,7 @@ impl App {
             ));
         }
 
-        div()
+        let page = div()
             .flex_1()
             .min_h_0()
             .min_w_0()
@@ -247,7 +388,733 @@ impl App {
             .p(px(styles::spacing::XL))
             .flex()
             .flex_col()
-            .child(main_col)
+            .child(main_col);
+
+        match self.installed_state.detail {
+            Some(ref detail) => div()
+                .flex_1()
+                .min_h_0()
+                .min_w_0()
+                .flex()
+                .flex_row()
+                .child(page)
+                .child(div().w(px(1.0)).h_full().bg(border))
+                .child(self.render_installed_detail(detail, theme, cx)),
+            None => page,
+        }
+    }
+
+    /// What aeris knows about one installed package, and what its manager
+    /// says it needs and is needed by.
+    fn render_installed_detail(
+        &self,
+        detail: &InstalledDetail,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let surface = theme.surface;
+        let border = theme.border;
+        let text_muted = theme.text_muted;
+        let hover = theme.hover;
+        let held = &detail.package;
+
+        let close_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
+            app.installed_state.detail = None;
+            cx.notify();
+        });
+
+        let mut content = div()
+            .id("installed-detail-scroll")
+            .flex_shrink()
+            .w(px(320.0))
+            .min_w(px(220.0))
+            .h_full()
+            .min_h_0()
+            .overflow_y_scroll()
+            .bg(surface)
+            .p(px(styles::spacing::XL))
+            .flex()
+            .flex_col()
+            .gap(px(10.0))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::TITLE))
+                            .child(held.package.name.clone()),
+                    )
+                    .child(
+                        div()
+                            .id("close-installed-detail")
+                            .px(px(styles::spacing::SM))
+                            .py(px(styles::spacing::XXS))
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::TITLE))
+                            .hover(move |s| s.bg(hover))
+                            .rounded(px(styles::radius::SM))
+                            .on_click(close_listener)
+                            .child("\u{00d7}"),
+                    ),
+            );
+
+        if let Some(ref description) = held.package.description {
+            content = content.child(
+                div()
+                    .text_size(px(styles::font_size::BODY))
+                    .child(description.clone()),
+            );
+        }
+        content = content.child(div().w_full().h(px(1.0)).bg(border));
+
+        content = content.child(self.detail_row("Manager", &held.package.adapter_id, theme));
+        if !held.package.version.is_empty() {
+            content = content.child(self.detail_row("Version", &held.package.version, theme));
+        }
+        if !held.installed_at.is_empty() {
+            content = content.child(self.detail_row("Installed", &held.installed_at, theme));
+        }
+        if held.install_size > 0 {
+            content = content.child(self.detail_row(
+                "Size",
+                &crate::views::browse::format_bytes_pub(held.install_size),
+                theme,
+            ));
+        }
+        if let Some(ref path) = held.install_path {
+            content = content.child(self.detail_row("Path", path, theme));
+        }
+        if let Some(ref profile) = held.profile {
+            content = content.child(self.detail_row("Profile", profile, theme));
+        }
+
+        if detail.loading {
+            content = content.child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(text_muted)
+                    .child("Asking what it needs..."),
+            );
+        } else if let Some(ref err) = detail.error {
+            content = content.child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(text_muted)
+                    .child(err.clone()),
+            );
+        } else {
+            content = content
+                .child(self.related_packages(
+                    "Needs",
+                    "installed-depends",
+                    &detail.depends,
+                    theme,
+                    cx,
+                ))
+                .child(self.related_packages(
+                    "Needed by",
+                    "installed-required-by",
+                    &detail.required_by,
+                    theme,
+                    cx,
+                ));
+        }
+
+        content = content
+            .child(div().w_full().h(px(1.0)).bg(border))
+            .child(self.render_file_tree(detail, theme, cx));
+
+        content
+    }
+
+    /// The files an installed package put down, folded by directory.
+    fn render_file_tree(
+        &self,
+        detail: &InstalledDetail,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        const ROWS_SHOWN: usize = 400;
+
+        let border = theme.border;
+        let hover = theme.hover;
+        let text_muted = theme.text_muted;
+
+        let mut section = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XXXS))
+            .child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .child("Files"),
+            );
+
+        let files = match detail.files {
+            None if detail.files_loading => {
+                return section.child(
+                    div()
+                        .text_size(px(styles::font_size::CAPTION))
+                        .text_color(text_muted)
+                        .child("Reading files..."),
+                );
+            }
+            None => {
+                let show_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
+                    app.load_installed_files(cx);
+                    cx.notify();
+                });
+                return section.child(
+                    div().flex().child(
+                        div()
+                            .id("installed-show-files")
+                            .px(px(styles::spacing::SM))
+                            .py(px(styles::spacing::XXXS))
+                            .rounded(px(styles::radius::SM))
+                            .border_1()
+                            .border_color(border)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(show_listener)
+                            .child("Show files"),
+                    ),
+                );
+            }
+            Some(Err(ref e)) => {
+                return section.child(
+                    div()
+                        .text_size(px(styles::font_size::CAPTION))
+                        .text_color(text_muted)
+                        .child(e.clone()),
+                );
+            }
+            Some(Ok(ref files)) => files,
+        };
+
+        section = section.child(
+            div()
+                .text_size(px(styles::font_size::CAPTION))
+                .text_color(text_muted)
+                .child(match files.len() {
+                    1 => "1 file".to_string(),
+                    n => format!("{n} files"),
+                }),
+        );
+
+        let rows = ownership::tree(files, &detail.folded);
+        for (idx, row) in rows.iter().take(ROWS_SHOWN).enumerate() {
+            let indent = px(styles::spacing::MD * row.depth as f32);
+            let line = div()
+                .id(("installed-file", idx))
+                .pl(indent)
+                .text_size(px(styles::font_size::CAPTION))
+                .truncate();
+            section = section.child(if row.dir {
+                let path = row.path.clone();
+                let fold_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.toggle_file_fold(&path, cx);
+                });
+                let marker = if detail.folded.contains(&row.path) {
+                    "\u{25b8}"
+                } else {
+                    "\u{25be}"
+                };
+                line.cursor_pointer()
+                    .hover(move |s| s.bg(hover))
+                    .on_click(fold_listener)
+                    .child(format!("{marker} {}/", row.name))
+            } else {
+                line.text_color(text_muted).child(row.name.clone())
+            });
+        }
+        if rows.len() > ROWS_SHOWN {
+            section = section.child(
+                div()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(text_muted)
+                    .child(format!(
+                        "and {} more; fold a directory to see past it",
+                        rows.len() - ROWS_SHOWN
+                    )),
+            );
+        }
+
+        section
+    }
+
+    /// Ask every manager which package a path belongs to.
+    /// A chip for each profile the installed packages are in, for a manager
+    /// that keeps them apart. Nothing is shown when none says.
+    fn render_profile_filter(
+        &self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let primary = theme.primary;
+        let border = theme.border;
+        let text_muted = theme.text_muted;
+        let hover = theme.hover;
+
+        let mut profiles: Vec<&String> = self
+            .installed_state
+            .packages
+            .iter()
+            .filter_map(|pkg| pkg.profile.as_ref())
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+
+        let mut row = div()
+            .flex()
+            .flex_row()
+            .flex_wrap()
+            .items_center()
+            .gap(px(styles::spacing::XS));
+        if profiles.is_empty() {
+            return row;
+        }
+
+        row = row.child(
+            div()
+                .text_size(px(styles::font_size::CAPTION))
+                .text_color(text_muted)
+                .child("Profile"),
+        );
+
+        let current = self.installed_state.profile_filter.as_ref();
+        let choices =
+            std::iter::once((None, "All".to_string())).chain(profiles.into_iter().map(|p| {
+                let named = p.clone();
+                (Some(named.clone()), named)
+            }));
+        for (idx, (choice, label)) in choices.enumerate() {
+            let chosen = current == choice.as_ref();
+            let pick = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                app.installed_state.profile_filter = choice.clone();
+                app.installed_state.result_version += 1;
+                cx.notify();
+            });
+            let chip = div()
+                .id(("installed-profile", idx))
+                .flex_shrink_0()
+                .px(px(styles::spacing::SM))
+                .py(px(styles::spacing::XXS))
+                .rounded(px(styles::radius::FULL))
+                .border_1()
+                .cursor_pointer()
+                .text_size(px(styles::font_size::CAPTION))
+                .on_click(pick)
+                .child(label);
+
+            row = row.child(if chosen {
+                chip.bg(primary.opacity(0.2))
+                    .border_color(primary.opacity(0.4))
+                    .text_color(primary)
+            } else {
+                chip.border_color(border)
+                    .text_color(text_muted)
+                    .hover(move |s| s.bg(hover))
+            });
+        }
+
+        row
+    }
+
+    /// Chips narrowing the list to what was installed automatically or is
+    /// left over, and a way to remove what is left over. Shown only once a
+    /// manager has said either.
+    fn render_held_filter(&self, theme: &theme::Theme, cx: &mut Context<Self>) -> impl IntoElement {
+        let primary = theme.primary;
+        let border = theme.border;
+        let text_muted = theme.text_muted;
+        let hover = theme.hover;
+        let state = &self.installed_state;
+
+        let automatic = state.packages.iter().filter(|p| p.auto_installed).count();
+        let orphaned = state.orphans.len();
+
+        let mut row = div()
+            .flex()
+            .flex_row()
+            .flex_wrap()
+            .items_center()
+            .gap(px(styles::spacing::XS));
+        if automatic == 0 && orphaned == 0 && state.held_filter == HeldFilter::All {
+            return row;
+        }
+
+        row = row.child(
+            div()
+                .text_size(px(styles::font_size::CAPTION))
+                .text_color(text_muted)
+                .child("Show"),
+        );
+
+        let choices = [
+            (HeldFilter::All, "All".to_string()),
+            (
+                HeldFilter::Automatic,
+                format!("Installed automatically ({automatic})"),
+            ),
+            (HeldFilter::Orphaned, format!("Orphans ({orphaned})")),
+        ];
+        for (idx, (choice, label)) in choices.into_iter().enumerate() {
+            let pick = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                app.installed_state.held_filter = choice;
+                app.installed_state.result_version += 1;
+                cx.notify();
+            });
+            let chip = div()
+                .id(("installed-held", idx))
+                .flex_shrink_0()
+                .px(px(styles::spacing::SM))
+                .py(px(styles::spacing::XXS))
+                .rounded(px(styles::radius::FULL))
+                .border_1()
+                .cursor_pointer()
+                .text_size(px(styles::font_size::CAPTION))
+                .on_click(pick)
+                .child(label);
+
+            row = row.child(if state.held_filter == choice {
+                chip.bg(primary.opacity(0.2))
+                    .border_color(primary.opacity(0.4))
+                    .text_color(primary)
+            } else {
+                chip.border_color(border)
+                    .text_color(text_muted)
+                    .hover(move |s| s.bg(hover))
+            });
+        }
+
+        if orphaned > 0 {
+            // Every orphan listed, removed by name like any other batch, so
+            // only what was shown here goes.
+            let remove = cx.listener(|app, _: &ClickEvent, _window, cx| {
+                let orphans = app.installed_state.orphans.clone();
+                app.confirm_dialog = Some(crate::app::ConfirmAction::BatchRemove(
+                    orphans,
+                    app.current_mode,
+                ));
+                cx.notify();
+            });
+            row = row.child(
+                div()
+                    .id("installed-remove-orphans")
+                    .flex_shrink_0()
+                    .ml(px(styles::spacing::SM))
+                    .px(px(styles::spacing::SM))
+                    .py(px(styles::spacing::XXS))
+                    .rounded(px(styles::radius::MD))
+                    .border_1()
+                    .border_color(theme.danger.opacity(0.4))
+                    .text_color(theme.danger)
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::CAPTION))
+                    .hover(move |s| s.bg(hover))
+                    .on_click(remove)
+                    .child("Remove orphans"),
+            );
+        }
+
+        row
+    }
+
+    fn render_owner_search(
+        &self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let surface = theme.surface;
+        let border = theme.border;
+        let hover = theme.hover;
+        let text_muted = theme.text_muted;
+        let state = &self.installed_state;
+
+        let find_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
+            app.find_owner(cx);
+            cx.notify();
+        });
+
+        let mut section = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .p(px(styles::spacing::MD))
+            .rounded(px(styles::radius::LG))
+            .bg(surface)
+            .border_1()
+            .border_color(border)
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(styles::spacing::SM))
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::BODY))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child("Owner"),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .px(px(styles::spacing::MD))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .border_1()
+                            .border_color(border)
+                            .text_size(px(styles::font_size::SMALL))
+                            .child(self.owner_path_input.clone()),
+                    )
+                    .child(
+                        div()
+                            .id("installed-find-owner")
+                            .px(px(14.0))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .border_1()
+                            .border_color(border)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(find_listener)
+                            .child(if state.owner_searching {
+                                "Looking..."
+                            } else {
+                                "Find"
+                            }),
+                    ),
+            );
+
+        let Some(ref asked) = state.owner_asked else {
+            return section;
+        };
+        if state.owner_searching {
+            return section;
+        }
+
+        if state.owners.is_empty() {
+            section = section.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(format!("No manager here claims {asked}")),
+            );
+        }
+        for (idx, owner) in state.owners.iter().enumerate() {
+            let open = owner.package.clone();
+            let open_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                app.open_related(open.clone(), cx);
+                cx.notify();
+            });
+            let named = if owner.package.version.is_empty() {
+                owner.package.name.clone()
+            } else {
+                format!("{} {}", owner.package.name, owner.package.version)
+            };
+            section = section.child(
+                div()
+                    .id(("installed-owner", idx))
+                    .flex()
+                    .gap(px(styles::spacing::SM))
+                    .px(px(styles::spacing::XS))
+                    .rounded(px(styles::radius::SM))
+                    .cursor_pointer()
+                    .hover(move |s| s.bg(hover))
+                    .text_size(px(styles::font_size::SMALL))
+                    .on_click(open_listener)
+                    .child(div().font_weight(FontWeight::MEDIUM).child(named))
+                    .child(format!("{} ({})", owner.package.adapter_id, owner.mode))
+                    .child(div().text_color(text_muted).child(owner.how.label())),
+            );
+        }
+        if !state.owner_problems.is_empty() {
+            section = section.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(format!("Left out: {}", state.owner_problems.join("; "))),
+            );
+        }
+
+        section
+    }
+
+    /// Commands several managers put on `PATH`, with which copy runs and a
+    /// way to be rid of the ones that do not.
+    fn render_conflicts(&self, theme: &theme::Theme, cx: &mut Context<Self>) -> impl IntoElement {
+        let surface = theme.surface;
+        let border = theme.border;
+        let hover = theme.hover;
+        let danger = theme.danger;
+        let success = theme.success;
+        let text_muted = theme.text_muted;
+        let state = &self.installed_state;
+
+        let check_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
+            app.check_conflicts(cx);
+            cx.notify();
+        });
+        let check_label = if state.conflicts_checking {
+            "Checking..."
+        } else if state.conflicts_checked {
+            "Check again"
+        } else {
+            "Check PATH"
+        };
+        let summary = match (state.conflicts_checked, state.conflicts.len()) {
+            (false, _) => "Find commands more than one manager installs".to_string(),
+            (true, 0) => "No command is installed twice".to_string(),
+            (true, 1) => "1 command is installed more than once".to_string(),
+            (true, n) => format!("{n} commands are installed more than once"),
+        };
+
+        let mut section = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .p(px(styles::spacing::MD))
+            .rounded(px(styles::radius::LG))
+            .bg(surface)
+            .border_1()
+            .border_color(border)
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap(px(styles::spacing::SM))
+                            .child(
+                                div()
+                                    .text_size(px(styles::font_size::BODY))
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .child("Conflicts"),
+                            )
+                            .child(
+                                div()
+                                    .text_size(px(styles::font_size::SMALL))
+                                    .text_color(text_muted)
+                                    .child(summary),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .id("installed-check-conflicts")
+                            .px(px(14.0))
+                            .py(px(styles::spacing::XS))
+                            .rounded(px(styles::radius::MD))
+                            .border_1()
+                            .border_color(border)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(check_listener)
+                            .child(check_label),
+                    ),
+            );
+
+        if !state.conflict_problems.is_empty() {
+            section = section.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child(format!("Left out: {}", state.conflict_problems.join("; "))),
+            );
+        }
+
+        for (idx, conflict) in state.conflicts.iter().enumerate() {
+            let mut row = div()
+                .flex()
+                .flex_col()
+                .gap(px(styles::spacing::XXXS))
+                .pt(px(styles::spacing::SM))
+                .border_t_1()
+                .border_color(border)
+                .child(
+                    div()
+                        .text_size(px(styles::font_size::BODY))
+                        .font_weight(FontWeight::MEDIUM)
+                        .child(conflict.command.clone()),
+                );
+
+            for binary in &conflict.binaries {
+                let by = match &binary.owner {
+                    Some(owner) => format!(
+                        "{} from {} ({})",
+                        owner.package.package.name, owner.package.package.adapter_id, owner.mode
+                    ),
+                    None => "not from a manager aeris drives".to_string(),
+                };
+                let (said, color) = match binary.standing {
+                    Standing::Wins => ("Runs", success),
+                    Standing::Shadowed => ("Shadowed", text_muted),
+                    Standing::OffPath => ("Not on PATH", text_muted),
+                };
+                row = row.child(
+                    div()
+                        .flex()
+                        .gap(px(styles::spacing::SM))
+                        .text_size(px(styles::font_size::SMALL))
+                        .child(div().w(px(90.0)).text_color(color).child(said))
+                        .child(div().child(by))
+                        .child(
+                            div()
+                                .text_color(text_muted)
+                                .child(binary.path.display().to_string()),
+                        ),
+                );
+            }
+
+            let losers = conflict.losers();
+            if !losers.is_empty() {
+                let command = conflict.command.clone();
+                let remove_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.remove_losing_copies(&command, cx);
+                    cx.notify();
+                });
+                let named: Vec<String> = losers
+                    .iter()
+                    .map(|owner| {
+                        format!(
+                            "{} ({})",
+                            owner.package.package.name, owner.package.package.adapter_id
+                        )
+                    })
+                    .collect();
+                row = row.child(
+                    div().flex().child(
+                        div()
+                            .id(("installed-remove-losers", idx))
+                            .mt(px(styles::spacing::XS))
+                            .px(px(styles::spacing::SM))
+                            .py(px(styles::spacing::XXXS))
+                            .rounded(px(styles::radius::SM))
+                            .border_1()
+                            .border_color(danger)
+                            .text_color(danger)
+                            .cursor_pointer()
+                            .text_size(px(styles::font_size::SMALL))
+                            .hover(move |s| s.bg(hover))
+                            .on_click(remove_listener)
+                            .child(format!("Remove {}", named.join(", "))),
+                    ),
+                );
+            }
+            section = section.child(row);
+        }
+
+        section
     }
 
     fn render_installed_card(
@@ -380,6 +1247,21 @@ impl App {
             );
         }
 
+        let left_over = self.installed_state.is_orphan(pkg);
+        if pkg.auto_installed || left_over {
+            info_row = info_row.child(
+                div()
+                    .px(px(styles::spacing::XS))
+                    .py(px(styles::spacing::XXXS))
+                    .rounded(px(styles::radius::SM))
+                    .border_1()
+                    .border_color(border)
+                    .text_color(text_muted)
+                    .text_size(px(styles::font_size::BADGE))
+                    .child(if left_over { "Orphan" } else { "Automatic" }),
+            );
+        }
+
         // Left column. Like the browse row, this has to be allowed to shrink
         // or a long name or install path pushes the buttons off the end.
         let mut left = div()
@@ -429,6 +1311,27 @@ impl App {
             .map(|a| *a.capabilities())
             .unwrap_or_default();
 
+        let detail_pkg = pkg.clone();
+        let detail_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+            cx.stop_propagation();
+            app.open_installed_detail(detail_pkg.clone(), cx);
+            cx.notify();
+        });
+        buttons = buttons.child(
+            div()
+                .id(SharedString::from(format!("detail-pkg-{idx}")))
+                .px(px(14.0))
+                .py(px(styles::spacing::XXS))
+                .rounded(px(styles::radius::MD))
+                .bg(surface)
+                .border_1()
+                .border_color(border)
+                .cursor_pointer()
+                .text_size(px(styles::font_size::SMALL))
+                .on_click(detail_listener)
+                .child("Details"),
+        );
+
         if caps.can_run {
             let run_installed = pkg.clone();
             let run_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
@@ -482,10 +1385,43 @@ impl App {
             }
         }
 
-        let show_update = self
-            .installed_state
-            .updatable_adapters
-            .contains(&pkg.package.adapter_id);
+        if caps.can_pin {
+            let pinned = pkg.pinned;
+            let pin_pkg = pkg.package.clone();
+            let pin_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                let packages = vec![pin_pkg.clone()];
+                let kind = if pinned {
+                    JobKind::Unpin(packages)
+                } else {
+                    JobKind::Pin(packages)
+                };
+                app.enqueue(kind, app.current_mode, cx);
+            });
+            buttons = buttons.child(
+                div()
+                    .id(SharedString::from(format!("pin-pkg-{idx}")))
+                    .px(px(14.0))
+                    .py(px(styles::spacing::XXS))
+                    .rounded(px(styles::radius::MD))
+                    .bg(surface)
+                    .border_1()
+                    .border_color(border)
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::SMALL))
+                    .hover(move |s| s.bg(hover))
+                    .on_click(pin_listener)
+                    .child(if pinned { "Unpin" } else { "Pin" }),
+            );
+        }
+
+        // A pinned package is held back from any update, so offering one
+        // would only be turned down.
+        let show_update = !pkg.pinned
+            && self
+                .installed_state
+                .updatable_adapters
+                .contains(&pkg.package.adapter_id);
 
         if show_update {
             let is_updating = self.installed_state.updating.as_deref() == Some(pkey.as_str())
@@ -537,14 +1473,9 @@ impl App {
             buttons = buttons.child(self.status_pill(&pkey, label, theme, cx));
         } else if caps.can_remove {
             let remove_pkg = pkg.package.clone();
-            let remove_unique_key = unique_key.clone();
             let remove_listener = cx.listener(move |app, _: &ClickEvent, _window, cx| {
                 cx.stop_propagation();
-                app.confirm_dialog = Some(crate::app::ConfirmAction::RemoveInstalled {
-                    pkg: remove_pkg.clone(),
-                    unique_key: remove_unique_key.clone(),
-                    mode: app.current_mode,
-                });
+                app.ask_to_remove(remove_pkg.clone(), app.current_mode, cx);
                 cx.notify();
             });
             buttons = buttons.child(
@@ -0,0 +1,245 @@
+//! Everything aeris has been asked to change, in the order it was asked.
+//!
+//! Waiting jobs can be moved or dropped before a manager sees them, a running
+//! one stopped, and one that did not go through lined up again as it was.
+
+use gpui::*;
+
+use crate::{
+    app::App,
+    core::queue::{Job, JobState},
+    styles, theme,
+};
+
+impl App {
+    pub fn render_jobs(
+        &mut self,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let text_muted = theme.text_muted;
+        let surface = theme.surface;
+        let border = theme.border;
+        let hover = theme.hover;
+
+        let (running, queued) = self.jobs.counts();
+        let subtitle = match (running, queued) {
+            (0, 0) => "Nothing is running or waiting.".to_string(),
+            (running, queued) => format!("{running} running, {queued} waiting."),
+        };
+
+        let mut header = div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .w_full()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(px(styles::spacing::XXS))
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::TITLE))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child("Jobs"),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(styles::font_size::SMALL))
+                            .text_color(text_muted)
+                            .child(subtitle),
+                    ),
+            );
+
+        if self.jobs.jobs().iter().any(|job| job.state.has_ended()) {
+            let clear = cx.listener(|app, _: &ClickEvent, _window, cx| {
+                app.clear_ended_jobs(cx);
+            });
+            header = header.child(
+                div()
+                    .id("clear-ended-jobs")
+                    .px(px(14.0))
+                    .py(px(styles::spacing::XS))
+                    .rounded(px(styles::radius::MD))
+                    .bg(surface)
+                    .border_1()
+                    .border_color(border)
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::SMALL))
+                    .hover(move |s| s.bg(hover))
+                    .on_click(clear)
+                    .child("Clear Finished"),
+            );
+        }
+
+        let jobs: Vec<Job> = self.jobs.jobs().to_vec();
+        let mut list = div()
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::SM))
+            .w_full();
+        for job in &jobs {
+            list = list.child(self.render_job(job, theme, cx));
+        }
+
+        if jobs.is_empty() {
+            list = list.child(
+                div()
+                    .py(px(styles::spacing::XXL))
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child("Installs, removals and updates show here while they wait and run."),
+            );
+        }
+
+        div()
+            .id("jobs-scroll")
+            .flex_1()
+            .min_h_0()
+            .min_w_0()
+            .w_full()
+            .overflow_y_scroll()
+            .child(
+                div()
+                    .p(px(styles::spacing::XL))
+                    .flex()
+                    .flex_col()
+                    .gap(px(styles::spacing::LG))
+                    .w_full()
+                    .min_w_0()
+                    .child(header)
+                    .child(list),
+            )
+    }
+
+    fn render_job(&self, job: &Job, theme: &theme::Theme, cx: &mut Context<Self>) -> AnyElement {
+        let id = job.id;
+        let accent = match job.state {
+            JobState::Running => theme.primary,
+            JobState::Failed(_) => theme.danger,
+            JobState::Finished => theme.success,
+            JobState::Queued | JobState::Cancelled => theme.border,
+        };
+
+        let mut buttons = div()
+            .flex()
+            .flex_row()
+            .flex_shrink_0()
+            .gap(px(styles::spacing::XS));
+
+        match job.state {
+            JobState::Queued => {
+                let earlier = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.shift_job(id, true, cx);
+                });
+                let later = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.shift_job(id, false, cx);
+                });
+                let drop = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.discard_job(id, cx);
+                });
+                buttons = buttons
+                    .child(job_button(
+                        format!("job-up-{id}"),
+                        "\u{25b2}",
+                        earlier,
+                        theme,
+                    ))
+                    .child(job_button(
+                        format!("job-down-{id}"),
+                        "\u{25bc}",
+                        later,
+                        theme,
+                    ))
+                    .child(job_button(format!("job-drop-{id}"), "Drop", drop, theme));
+            }
+            JobState::Running if self.can_cancel_job(id) => {
+                let cancel = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.cancel_job(id, cx);
+                });
+                buttons = buttons.child(job_button(
+                    format!("job-cancel-{id}"),
+                    "Cancel",
+                    cancel,
+                    theme,
+                ));
+            }
+            _ if job.can_retry() => {
+                let retry = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                    app.retry_job(id, cx);
+                });
+                buttons =
+                    buttons.child(job_button(format!("job-retry-{id}"), "Retry", retry, theme));
+            }
+            _ => {}
+        }
+
+        div()
+            .id(SharedString::from(format!("job-{id}")))
+            .w_full()
+            .min_w_0()
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .rounded(px(styles::radius::LG))
+            .bg(theme.surface)
+            .border_1()
+            .border_l(px(4.0))
+            .border_color(accent)
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .gap(px(styles::spacing::MD))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .min_w_0()
+                    .gap(px(styles::spacing::XXXS))
+                    .child(
+                        div()
+                            .truncate()
+                            .font_weight(FontWeight::MEDIUM)
+                            .child(job.kind.describe()),
+                    )
+                    .child(
+                        div()
+                            .truncate()
+                            .text_size(px(styles::font_size::CAPTION))
+                            .text_color(theme.text_muted)
+                            .child(format!(
+                                "{} \u{00b7} {} \u{00b7} {}",
+                                job.kind.managers().join(", "),
+                                job.mode,
+                                job.state.label()
+                            )),
+                    ),
+            )
+            .child(buttons)
+            .into_any_element()
+    }
+}
+
+fn job_button(
+    id: String,
+    label: &str,
+    on_click: impl Fn(&ClickEvent, &mut Window, &mut gpui::App) + 'static,
+    theme: &theme::Theme,
+) -> impl IntoElement {
+    let hover = theme.hover;
+
+    div()
+        .id(SharedString::from(id))
+        .px(px(styles::spacing::SM))
+        .py(px(styles::spacing::XXS))
+        .rounded(px(styles::radius::MD))
+        .border_1()
+        .border_color(theme.border)
+        .cursor_pointer()
+        .text_size(px(styles::font_size::SMALL))
+        .hover(move |s| s.bg(hover))
+        .on_click(on_click)
+        .child(label.to_string())
+}
@@ -1,7 +1,11 @@
 pub mod adapter_info;
 pub mod browse;
 pub mod dashboard;
+pub mod disk_usage;
+pub mod doctor;
+pub mod history;
 pub mod installed;
+pub mod jobs;
 pub mod manifest;
 pub mod output;
 pub mod settings;
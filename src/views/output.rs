@@ -22,13 +22,56 @@ impl App {
     }
 
     /// The label a card shows while it works. Once the manager has said
-    /// something, it also opens what it said.
+    /// something, it also opens what it said, and for as long as the work
+    /// can still be stopped it comes with the button that stops it.
     pub fn status_pill(
         &self,
         key: &str,
         label: String,
         theme: &theme::Theme,
         cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let pill = self.pill(key, label, theme, cx);
+        if !self.can_cancel(key) {
+            return pill;
+        }
+
+        let hover = theme.hover;
+        let cancelling = key.to_string();
+        let cancel = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+            cx.stop_propagation();
+            app.cancel_operation(&cancelling, cx);
+        });
+
+        div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap(px(styles::spacing::XS))
+            .child(pill)
+            .child(
+                div()
+                    .id(SharedString::from(format!("cancel-{key}")))
+                    .flex_shrink_0()
+                    .px(px(styles::spacing::SM))
+                    .py(px(styles::spacing::XXS))
+                    .rounded(px(styles::radius::MD))
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(theme.text_muted)
+                    .hover(move |s| s.bg(hover))
+                    .on_click(cancel)
+                    .child("Cancel"),
+            )
+            .into_any_element()
+    }
+
+    fn pill(
+        &self,
+        key: &str,
+        label: String,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
     ) -> AnyElement {
         let pill = div()
             .flex_shrink_0()
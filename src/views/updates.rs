@@ -4,7 +4,12 @@ use gpui::*;
 
 use crate::{
     app::{App, OperationStatus},
-    core::{package::Update, privilege::PackageMode},
+    core::{
+        history::{self, Outcome},
+        package::{ReleaseNote, Update},
+        privilege::PackageMode,
+        unattended::{self, Run},
+    },
     styles, theme,
 };
 
@@ -35,8 +40,26 @@ pub struct UpdatesState {
     pub limits: Vec<ManagerLimit>,
     pub selected: HashSet<String>,
     pub package_progress: HashMap<String, OperationStatus>,
+    /// The [`package_key`] of each update whose package is pinned, which is
+    /// shown but held back rather than updated.
+    ///
+    /// [`package_key`]: crate::core::adapter::package_key
+    pub pinned: HashSet<String>,
+    /// The updates whose changelog is open, by [`package_key`].
+    ///
+    /// [`package_key`]: crate::core::adapter::package_key
+    pub changelog_open: HashSet<String>,
+    /// Notes read so far, or why they could not be, by the same key.
+    pub changelogs: HashMap<String, Result<Vec<ReleaseNote>, String>>,
+    pub changelog_loading: HashSet<String>,
+    pub unattended_open: bool,
+    /// What the unattended update policies did, newest first.
+    pub unattended_runs: Vec<Run>,
 }
 
+/// How many unattended runs the log panel shows; the file keeps the rest.
+const RUNS_SHOWN: usize = 50;
+
 impl App {
     pub fn render_updates(
         &mut self,
@@ -110,6 +133,33 @@ impl App {
                     .on_click(update_all_listener)
                     .child("Update All"),
             );
+
+            let security = self
+                .updates_state
+                .updates
+                .iter()
+                .filter(|u| u.is_security)
+                .count();
+            if security > 0 {
+                let update_security = cx.listener(|app, _: &ClickEvent, _window, cx| {
+                    app.update_security(cx);
+                });
+                header_buttons = header_buttons.child(
+                    div()
+                        .id("update-security-btn")
+                        .px(px(14.0))
+                        .py(px(styles::spacing::XS))
+                        .rounded(px(styles::radius::MD))
+                        .bg(surface)
+                        .border_1()
+                        .border_color(border)
+                        .cursor_pointer()
+                        .text_size(px(styles::font_size::SMALL))
+                        .hover(move |s| s.bg(hover))
+                        .on_click(update_security)
+                        .child(format!("Security Only ({security})")),
+                );
+            }
         }
 
         if !is_busy {
@@ -133,6 +183,33 @@ impl App {
             );
         }
 
+        // Only worth a button once some manager has a policy to log.
+        if !unattended::plans(&self.aeris_config).is_empty() || self.updates_state.unattended_open {
+            let toggle_log = cx.listener(|app, _: &ClickEvent, _window, cx| {
+                app.toggle_unattended_log(cx);
+            });
+            let label = if self.updates_state.unattended_open {
+                "Hide Unattended Log"
+            } else {
+                "Unattended Log"
+            };
+            header_buttons = header_buttons.child(
+                div()
+                    .id("unattended-log-btn")
+                    .px(px(14.0))
+                    .py(px(styles::spacing::XS))
+                    .rounded(px(styles::radius::MD))
+                    .bg(surface)
+                    .border_1()
+                    .border_color(border)
+                    .cursor_pointer()
+                    .text_size(px(styles::font_size::SMALL))
+                    .hover(move |s| s.bg(hover))
+                    .on_click(toggle_log)
+                    .child(label),
+            );
+        }
+
         let sync_listener = cx.listener(|app, _: &ClickEvent, _window, cx| {
             app.sync_all_repos(cx);
         });
@@ -354,6 +431,9 @@ impl App {
         if has_notes {
             pinned = pinned.child(notes_col);
         }
+        if self.updates_state.unattended_open {
+            pinned = pinned.child(self.render_unattended_log(theme));
+        }
 
         let mut main_col = div()
             .flex_1()
@@ -424,13 +504,9 @@ impl App {
         let warning = theme.warning;
         let text_muted = theme.text_muted;
 
-        let is_selected = self
-            .updates_state
-            .selected
-            .contains(&crate::core::adapter::package_key(
-                &update.package.adapter_id,
-                &update.package.id,
-            ));
+        let key = crate::core::adapter::package_key(&update.package.adapter_id, &update.package.id);
+        let is_selected = self.updates_state.selected.contains(&key);
+        let is_pinned = self.updates_state.pinned.contains(&key);
         let pkey =
             crate::core::adapter::progress_key(&update.package.adapter_id, &update.package.id);
         let pkg_status = self.updates_state.package_progress.get(&pkey);
@@ -502,6 +578,10 @@ impl App {
         }
 
         if update.is_security {
+            let label = match update.severity {
+                Some(severity) => format!("Security: {}", severity.label()),
+                None => "Security".to_string(),
+            };
             info_row = info_row.child(
                 div()
                     .px(px(styles::spacing::XS))
@@ -513,7 +593,54 @@ impl App {
                     .text_size(px(styles::font_size::BADGE))
                     .text_color(warning)
                     .font_weight(FontWeight::MEDIUM)
-                    .child("Security"),
+                    .child(label),
+            );
+
+            if !update.advisories.is_empty() {
+                let ids: Vec<&str> = update.advisories.iter().map(|a| a.id.as_str()).collect();
+                info_row = info_row.child(
+                    div()
+                        .text_size(px(styles::font_size::CAPTION))
+                        .text_color(text_muted)
+                        .truncate()
+                        .child(format!("Fixes {}", ids.join(", "))),
+                );
+            }
+        }
+
+        let has_changelog = self
+            .adapter_manager
+            .get_adapter(&update.package.adapter_id)
+            .is_some_and(|a| a.capabilities().has_changelog);
+        if has_changelog || update.changelog_url.is_some() {
+            let open = self.updates_state.changelog_open.contains(&key);
+            let for_notes = update.clone();
+            let toggle = cx.listener(move |app, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                app.toggle_changelog(&for_notes, cx);
+            });
+            info_row = info_row.child(
+                div()
+                    .id(SharedString::from(format!("update-notes-{idx}")))
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(primary)
+                    .cursor_pointer()
+                    .on_click(toggle)
+                    .child(if open { "Hide changelog" } else { "Changelog" }),
+            );
+        }
+
+        if is_pinned {
+            info_row = info_row.child(
+                div()
+                    .px(px(styles::spacing::XS))
+                    .py(px(styles::spacing::XXXS))
+                    .rounded(px(styles::radius::SM))
+                    .bg(primary.opacity(0.2))
+                    .border_1()
+                    .border_color(primary.opacity(0.4))
+                    .text_size(px(styles::font_size::BADGE))
+                    .child("Pinned"),
             );
         }
 
@@ -526,6 +653,12 @@ impl App {
 
         let update_btn: AnyElement = if !can_update_one {
             div().into_any_element()
+        } else if is_pinned {
+            div()
+                .text_size(px(styles::font_size::SMALL))
+                .text_color(text_muted)
+                .child("Held back")
+                .into_any_element()
         } else if is_updating_this || is_updating_all || is_updating_batch {
             let label = pkg_status
                 .map(|s| s.label())
@@ -638,6 +771,10 @@ impl App {
 
         let mut row = div().w_full().min_w_0().flex().flex_col().child(card);
 
+        if self.updates_state.changelog_open.contains(&key) {
+            row = row.child(self.render_changelog(update, &key, theme, cx));
+        }
+
         if self.output_is_open(&pkey) {
             let titled = format!("{} · {}", update.package.adapter_id, update.package.name);
             row = row.child(self.render_output_log(&pkey, &titled, theme, cx));
@@ -645,4 +782,173 @@ impl App {
 
         row
     }
+
+    /// What changed between the version held and the one on offer, below the
+    /// update it belongs to.
+    /// The unattended runs, one line each: when, which manager, under which
+    /// policy, and what came of it.
+    fn render_unattended_log(&self, theme: &theme::Theme) -> impl IntoElement {
+        let text_muted = theme.text_muted;
+        let text = theme.text;
+        let danger = theme.danger;
+
+        let mut pane = div()
+            .id("unattended-log")
+            .max_h(px(220.0))
+            .overflow_y_scroll()
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .rounded(px(styles::radius::MD))
+            .bg(theme.surface)
+            .border_1()
+            .border_color(theme.border)
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XS))
+            .w_full();
+
+        let runs = &self.updates_state.unattended_runs;
+        if runs.is_empty() {
+            return pane.child(
+                div()
+                    .text_size(px(styles::font_size::SMALL))
+                    .text_color(text_muted)
+                    .child("Nothing has run unattended yet."),
+            );
+        }
+
+        for run in runs.iter().take(RUNS_SHOWN) {
+            let name = self
+                .adapter_manager
+                .get_adapter(&run.adapter_id)
+                .map(|a| a.info().name.clone())
+                .unwrap_or_else(|| run.adapter_id.clone());
+            let failed = matches!(run.outcome, Outcome::Failed(_));
+            let mut said = run.said.clone();
+            if !run.packages.is_empty() && !failed {
+                said = format!("{said}: {}", run.packages.join(", "));
+            }
+
+            pane = pane.child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(px(styles::spacing::MD))
+                    .text_size(px(styles::font_size::SMALL))
+                    .child(
+                        div()
+                            .flex_shrink_0()
+                            .text_color(text_muted)
+                            .child(format!("{} UTC", history::moment_of(run.at))),
+                    )
+                    .child(
+                        div()
+                            .flex_shrink_0()
+                            .font_weight(FontWeight::MEDIUM)
+                            .child(format!("{name} ({})", run.policy.label())),
+                    )
+                    .child(
+                        div()
+                            .min_w_0()
+                            .text_color(if failed { danger } else { text })
+                            .child(said),
+                    ),
+            );
+        }
+        pane
+    }
+
+    fn render_changelog(
+        &self,
+        update: &Update,
+        key: &str,
+        theme: &theme::Theme,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let surface = theme.surface;
+        let border = theme.border;
+        let primary = theme.primary;
+        let text_muted = theme.text_muted;
+
+        let mut pane = div()
+            .mt(px(styles::spacing::XXS))
+            .px(px(styles::spacing::LG))
+            .py(px(styles::spacing::MD))
+            .rounded(px(styles::radius::MD))
+            .bg(surface)
+            .border_1()
+            .border_color(border)
+            .flex()
+            .flex_col()
+            .gap(px(styles::spacing::XS))
+            .w_full()
+            .min_w_0();
+
+        let said = |text: String| {
+            div()
+                .text_size(px(styles::font_size::SMALL))
+                .text_color(text_muted)
+                .child(text)
+        };
+
+        match self.updates_state.changelogs.get(key) {
+            _ if self.updates_state.changelog_loading.contains(key) => {
+                pane = pane.child(said("Reading the changelog...".into()));
+            }
+            Some(Ok(notes)) if notes.is_empty() => {
+                pane = pane.child(said(format!(
+                    "{} says nothing changed between {} and {}.",
+                    update.package.adapter_id, update.current_version, update.new_version
+                )));
+            }
+            Some(Ok(notes)) => {
+                for note in notes {
+                    if let Some(ref version) = note.version {
+                        let heading = match note.date {
+                            Some(ref date) => format!("{version} ({date})"),
+                            None => version.clone(),
+                        };
+                        pane = pane.child(
+                            div()
+                                .pt(px(styles::spacing::XXS))
+                                .text_size(px(styles::font_size::SMALL))
+                                .font_weight(FontWeight::SEMIBOLD)
+                                .child(heading),
+                        );
+                    }
+                    if !note.text.trim().is_empty() {
+                        pane = pane.child(
+                            div()
+                                .text_size(px(styles::font_size::SMALL))
+                                .child(note.text.clone()),
+                        );
+                    }
+                }
+            }
+            Some(Err(why)) => {
+                pane = pane.child(said(format!("Could not read the changelog: {why}")));
+            }
+            None => {}
+        }
+
+        if let Some(ref url) = update.changelog_url {
+            let url = url.clone();
+            let shown = url.clone();
+            pane = pane.child(
+                div()
+                    .id(SharedString::from(format!("update-notes-url-{key}")))
+                    .text_size(px(styles::font_size::CAPTION))
+                    .text_color(primary)
+                    .cursor_pointer()
+                    .truncate()
+                    .on_click(cx.listener(move |_app, _: &ClickEvent, _window, cx| {
+                        cx.stop_propagation();
+                        cx.open_url(&url);
+                    }))
+                    .child(format!("Release notes: {shown}")),
+            );
+        }
+
+        pane
+    }
 }